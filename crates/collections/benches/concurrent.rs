@@ -0,0 +1,164 @@
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use std::{
+    collections::{HashMap, VecDeque},
+    hint::black_box,
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use emixcollections::concurrent::{ObjectPool, RingBuffer, ShardedMap};
+
+const KEYS: u64 = 10_000;
+const OPS_PER_THREAD: u64 = 20_000;
+
+/// Runs `op` on `threads` threads at once, each with its thread number.
+fn run_threads(threads: u64, op: impl Fn(u64) + Send + Sync + 'static) {
+    let op = Arc::new(op);
+    let handles: Vec<_> = (0..threads)
+        .map(|t| {
+            let op = op.clone();
+            thread::spawn(move || op(t))
+        })
+        .collect();
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+}
+
+/// A mixed workload of nine reads to one write per thread.
+fn bench_maps(c: &mut Criterion) {
+    let mut group = c.benchmark_group("map_90_read");
+
+    for threads in [1, 4, 8] {
+        group.bench_with_input(
+            BenchmarkId::new("Mutex<HashMap>", threads),
+            &threads,
+            |b, &threads| {
+                let map = Arc::new(Mutex::new(
+                    (0..KEYS).map(|k| (k, k)).collect::<HashMap<_, _>>(),
+                ));
+                b.iter(|| {
+                    let map = map.clone();
+                    run_threads(threads, move |t| {
+                        for i in 0..OPS_PER_THREAD {
+                            let key = (i * 31 + t * 7) % KEYS;
+
+                            if i % 10 == 0 {
+                                map.lock().unwrap().insert(key, i);
+                            } else {
+                                black_box(map.lock().unwrap().get(&key).copied());
+                            }
+                        }
+                    });
+                });
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("ShardedMap", threads),
+            &threads,
+            |b, &threads| {
+                let map = Arc::new((0..KEYS).map(|k| (k, k)).collect::<ShardedMap<_, _>>());
+                b.iter(|| {
+                    let map = map.clone();
+                    run_threads(threads, move |t| {
+                        for i in 0..OPS_PER_THREAD {
+                            let key = (i * 31 + t * 7) % KEYS;
+
+                            if i % 10 == 0 {
+                                map.insert(key, i);
+                            } else {
+                                black_box(map.get(&key));
+                            }
+                        }
+                    });
+                });
+            },
+        );
+    }
+
+    group.finish();
+}
+
+/// Producers and consumers moving items through a bounded queue.
+fn bench_queues(c: &mut Criterion) {
+    let mut group = c.benchmark_group("queue");
+    const CAPACITY: usize = 1024;
+
+    for threads in [1, 4] {
+        group.bench_with_input(
+            BenchmarkId::new("Mutex<VecDeque>", threads),
+            &threads,
+            |b, &threads| {
+                let queue = Arc::new(Mutex::new(VecDeque::with_capacity(CAPACITY)));
+                b.iter(|| {
+                    let queue = queue.clone();
+                    run_threads(threads * 2, move |t| {
+                        for i in 0..OPS_PER_THREAD {
+                            if t % 2 == 0 {
+                                loop {
+                                    let mut queue = queue.lock().unwrap();
+
+                                    if queue.len() < CAPACITY {
+                                        queue.push_back(i);
+                                        break;
+                                    }
+                                }
+                            } else {
+                                while queue.lock().unwrap().pop_front().is_none() {}
+                            }
+                        }
+                    });
+                });
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("RingBuffer", threads),
+            &threads,
+            |b, &threads| {
+                let queue = Arc::new(RingBuffer::new(CAPACITY));
+                b.iter(|| {
+                    let queue = queue.clone();
+                    run_threads(threads * 2, move |t| {
+                        for i in 0..OPS_PER_THREAD {
+                            if t % 2 == 0 {
+                                let mut item = i;
+
+                                while let Err(rejected) = queue.push(item) {
+                                    item = rejected;
+                                }
+                            } else {
+                                while queue.pop().is_none() {}
+                            }
+                        }
+                    });
+                });
+            },
+        );
+    }
+
+    group.finish();
+}
+
+fn bench_pool(c: &mut Criterion) {
+    let mut group = c.benchmark_group("buffer_4k");
+    group.bench_function("allocate", |b| {
+        b.iter(|| {
+            let mut buffer = Vec::<u8>::with_capacity(4096);
+            buffer.extend_from_slice(b"payload");
+            black_box(buffer.len())
+        })
+    });
+    group.bench_function("ObjectPool", |b| {
+        let pool = ObjectPool::with_reset(16, || Vec::<u8>::with_capacity(4096), Vec::clear);
+        b.iter(|| {
+            let mut buffer = pool.get();
+            buffer.extend_from_slice(b"payload");
+            black_box(buffer.len())
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_maps, bench_queues, bench_pool);
+criterion_main!(benches);
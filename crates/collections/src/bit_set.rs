@@ -0,0 +1,517 @@
+use std::ops::{
+    BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not, Sub, SubAssign,
+};
+
+use crate::range::Range;
+
+const WORD_BITS: usize = 32;
+
+/// An owned, growable array of bits.
+///
+/// The set has a logical length in bits. Setting or toggling a bit past the end grows it,
+/// while reading or clearing past the end sees an unset bit. Bits are stored in `u32`
+/// words, the same layout `BitHelper` works on, so arrays can move between the two with
+/// `words` and `from_words`.
+///
+/// Two sets are equal when they have the same length and the same bits set.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "BitSetRepr", try_from = "BitSetRepr")
+)]
+pub struct BitSet {
+    words: Vec<u32>,
+    len: usize,
+}
+
+impl BitSet {
+    /// Creates an empty set of length 0.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a set of `len` unset bits.
+    pub fn with_len(len: usize) -> Self {
+        Self {
+            words: vec![0; len.div_ceil(WORD_BITS)],
+            len,
+        }
+    }
+
+    /// Creates a set of `len` bits that are all set.
+    pub fn ones(len: usize) -> Self {
+        let mut set = Self {
+            words: vec![u32::MAX; len.div_ceil(WORD_BITS)],
+            len,
+        };
+        set.mask_tail();
+        set
+    }
+
+    /// Creates a set over `u32` words, such as an array filled through `BitHelper`.
+    /// Words past `len` bits are dropped and missing words are filled with zeros.
+    pub fn from_words(mut words: Vec<u32>, len: usize) -> Self {
+        words.resize(len.div_ceil(WORD_BITS), 0);
+        let mut set = Self { words, len };
+        set.mask_tail();
+        set
+    }
+
+    /// Creates a set from bytes, with bit `i` at bit `i % 8` of byte `i / 8`. The length
+    /// is `bytes.len() * 8`.
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        let mut words = vec![0u32; bytes.len().div_ceil(4)];
+
+        for (i, &byte) in bytes.iter().enumerate() {
+            words[i / 4] |= (byte as u32) << ((i % 4) * 8);
+        }
+
+        Self {
+            words,
+            len: bytes.len() * 8,
+        }
+    }
+
+    /// Returns the bits as bytes in the layout read by `from_bytes`. The unused high bits
+    /// of the last byte are zero.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        (0..self.len.div_ceil(8))
+            .map(|i| (self.words[i / 4] >> ((i % 4) * 8)) as u8)
+            .collect()
+    }
+
+    /// Returns the underlying words. Bits past the length are always zero.
+    pub fn words(&self) -> &[u32] {
+        &self.words
+    }
+
+    /// Returns the length in bits.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the length is 0.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Changes the length, dropping bits past the new length or adding unset bits.
+    pub fn resize(&mut self, len: usize) {
+        self.words.resize(len.div_ceil(WORD_BITS), 0);
+        self.len = len;
+        self.mask_tail();
+    }
+
+    /// Returns true if the bit at `index` is set.
+    pub fn contains(&self, index: usize) -> bool {
+        index < self.len && self.words[index / WORD_BITS] & bit(index) != 0
+    }
+
+    /// Sets the bit at `index`, growing the set if needed. Returns the previous value.
+    pub fn set(&mut self, index: usize) -> bool {
+        self.grow_to(index);
+        let previous = self.contains(index);
+        self.words[index / WORD_BITS] |= bit(index);
+        previous
+    }
+
+    /// Clears the bit at `index`. Returns the previous value.
+    pub fn clear(&mut self, index: usize) -> bool {
+        let previous = self.contains(index);
+
+        if previous {
+            self.words[index / WORD_BITS] &= !bit(index);
+        }
+
+        previous
+    }
+
+    /// Flips the bit at `index`, growing the set if needed. Returns the previous value.
+    pub fn toggle(&mut self, index: usize) -> bool {
+        self.grow_to(index);
+        let previous = self.contains(index);
+        self.words[index / WORD_BITS] ^= bit(index);
+        previous
+    }
+
+    /// Sets or clears every bit in `range`. Setting grows the set to cover the range.
+    pub fn fill(&mut self, range: &Range<usize>, value: bool) {
+        if value {
+            self.grow_to(range.max);
+            self.apply_range(range, |word, mask| *word |= mask);
+        } else {
+            self.apply_range(range, |word, mask| *word &= !mask);
+        }
+    }
+
+    /// Flips every bit in `range`, growing the set to cover it.
+    pub fn toggle_range(&mut self, range: &Range<usize>) {
+        self.grow_to(range.max);
+        self.apply_range(range, |word, mask| *word ^= mask);
+    }
+
+    /// Sets every bit.
+    pub fn set_all(&mut self) {
+        self.words.fill(u32::MAX);
+        self.mask_tail();
+    }
+
+    /// Clears every bit, keeping the length.
+    pub fn clear_all(&mut self) {
+        self.words.fill(0);
+    }
+
+    /// Flips every bit.
+    pub fn toggle_all(&mut self) {
+        for word in &mut self.words {
+            *word = !*word;
+        }
+
+        self.mask_tail();
+    }
+
+    /// Returns the number of set bits.
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// Returns the number of unset bits.
+    pub fn count_zeros(&self) -> usize {
+        self.len - self.count_ones()
+    }
+
+    /// Returns true if any bit is set.
+    pub fn any(&self) -> bool {
+        self.words.iter().any(|&w| w != 0)
+    }
+
+    /// Returns the index of the lowest set bit.
+    pub fn first_set(&self) -> Option<usize> {
+        self.next_set(0)
+    }
+
+    /// Returns the index of the highest set bit.
+    pub fn last_set(&self) -> Option<usize> {
+        self.words
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|&(_, &w)| w != 0)
+            .map(|(i, w)| i * WORD_BITS + (WORD_BITS - 1 - w.leading_zeros() as usize))
+    }
+
+    /// Returns the index of the lowest set bit at or after `from`.
+    pub fn next_set(&self, from: usize) -> Option<usize> {
+        if from >= self.len {
+            return None;
+        }
+
+        let mut index = from / WORD_BITS;
+        let mut word = self.words[index] & (u32::MAX << (from % WORD_BITS));
+
+        loop {
+            if word != 0 {
+                return Some(index * WORD_BITS + word.trailing_zeros() as usize);
+            }
+
+            index += 1;
+            word = *self.words.get(index)?;
+        }
+    }
+
+    /// Returns the index of the lowest unset bit at or after `from`, within the length.
+    pub fn next_clear(&self, from: usize) -> Option<usize> {
+        if from >= self.len {
+            return None;
+        }
+
+        let mut index = from / WORD_BITS;
+        let mut word = !self.words[index] & (u32::MAX << (from % WORD_BITS));
+
+        loop {
+            if word != 0 {
+                let found = index * WORD_BITS + word.trailing_zeros() as usize;
+                return (found < self.len).then_some(found);
+            }
+
+            index += 1;
+            word = !*self.words.get(index)?;
+        }
+    }
+
+    /// Returns the number of set bits before `index`.
+    pub fn rank(&self, index: usize) -> usize {
+        let index = index.min(self.len);
+        let full = index / WORD_BITS;
+        let mut count = self.words[..full]
+            .iter()
+            .map(|w| w.count_ones() as usize)
+            .sum();
+
+        if !index.is_multiple_of(WORD_BITS) {
+            count += (self.words[full] & (bit(index) - 1)).count_ones() as usize;
+        }
+
+        count
+    }
+
+    /// Returns the index of the set bit with rank `n`, that is the `n`th set bit counting
+    /// from 0.
+    pub fn select(&self, n: usize) -> Option<usize> {
+        let mut remaining = n;
+
+        for (index, &word) in self.words.iter().enumerate() {
+            let ones = word.count_ones() as usize;
+
+            if remaining < ones {
+                let mut word = word;
+
+                for _ in 0..remaining {
+                    word &= word - 1;
+                }
+
+                return Some(index * WORD_BITS + word.trailing_zeros() as usize);
+            }
+
+            remaining -= ones;
+        }
+
+        None
+    }
+
+    /// Returns an iterator over the indexes of the set bits in ascending order.
+    pub fn iter(&self) -> BitSetIter<'_> {
+        BitSetIter {
+            words: &self.words,
+            index: 0,
+            current: self.words.first().copied().unwrap_or(0),
+        }
+    }
+
+    /// Sets every bit that is set in `other`. The result is as long as the longer set.
+    pub fn union_with(&mut self, other: &BitSet) {
+        self.grow_len(other.len);
+
+        for (a, b) in self.words.iter_mut().zip(&other.words) {
+            *a |= b;
+        }
+    }
+
+    /// Clears every bit that is not set in `other`. The result is as long as the longer
+    /// set.
+    pub fn intersect_with(&mut self, other: &BitSet) {
+        self.grow_len(other.len);
+
+        for (i, a) in self.words.iter_mut().enumerate() {
+            *a &= other.words.get(i).copied().unwrap_or(0);
+        }
+    }
+
+    /// Clears every bit that is set in `other`. The result is as long as the longer set.
+    pub fn difference_with(&mut self, other: &BitSet) {
+        self.grow_len(other.len);
+
+        for (a, b) in self.words.iter_mut().zip(&other.words) {
+            *a &= !b;
+        }
+    }
+
+    /// Flips every bit that is set in `other`. The result is as long as the longer set.
+    pub fn symmetric_difference_with(&mut self, other: &BitSet) {
+        self.grow_len(other.len);
+
+        for (a, b) in self.words.iter_mut().zip(&other.words) {
+            *a ^= b;
+        }
+    }
+
+    /// Returns true if every bit set here is also set in `other`.
+    pub fn is_subset(&self, other: &BitSet) -> bool {
+        self.words
+            .iter()
+            .enumerate()
+            .all(|(i, a)| a & !other.words.get(i).copied().unwrap_or(0) == 0)
+    }
+
+    /// Returns true if every bit set in `other` is also set here.
+    pub fn is_superset(&self, other: &BitSet) -> bool {
+        other.is_subset(self)
+    }
+
+    /// Returns true if no bit is set in both sets.
+    pub fn is_disjoint(&self, other: &BitSet) -> bool {
+        self.words.iter().zip(&other.words).all(|(a, b)| a & b == 0)
+    }
+
+    fn grow_to(&mut self, index: usize) {
+        if index >= self.len {
+            self.resize(index + 1);
+        }
+    }
+
+    fn grow_len(&mut self, len: usize) {
+        if len > self.len {
+            self.resize(len);
+        }
+    }
+
+    /// Clears the bits of the last word past the length.
+    fn mask_tail(&mut self) {
+        let used = self.len % WORD_BITS;
+
+        if used != 0
+            && let Some(last) = self.words.last_mut()
+        {
+            *last &= bit(used) - 1;
+        }
+    }
+
+    /// Calls `f` with each word that overlaps `range` and the mask of its bits in the
+    /// range, skipping the part of the range past the length.
+    fn apply_range(&mut self, range: &Range<usize>, f: impl Fn(&mut u32, u32)) {
+        if range.min >= self.len {
+            return;
+        }
+
+        let max = range.max.min(self.len - 1);
+
+        for index in range.min / WORD_BITS..=max / WORD_BITS {
+            let start = range.min.max(index * WORD_BITS) - index * WORD_BITS;
+            let end = max.min(index * WORD_BITS + WORD_BITS - 1) - index * WORD_BITS;
+            let mask = (u32::MAX >> (WORD_BITS - 1 - end)) & (u32::MAX << start);
+            f(&mut self.words[index], mask);
+        }
+    }
+}
+
+/// Returns the mask of `index` within its word.
+fn bit(index: usize) -> u32 {
+    1 << (index % WORD_BITS)
+}
+
+impl FromIterator<usize> for BitSet {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut set = BitSet::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl Extend<usize> for BitSet {
+    fn extend<I: IntoIterator<Item = usize>>(&mut self, iter: I) {
+        for index in iter {
+            self.set(index);
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a BitSet {
+    type Item = usize;
+    type IntoIter = BitSetIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+macro_rules! impl_bit_op {
+    ($op:ident, $fn:ident, $assign:ident, $assign_fn:ident, $with:ident) => {
+        impl $assign<&BitSet> for BitSet {
+            fn $assign_fn(&mut self, rhs: &BitSet) {
+                self.$with(rhs);
+            }
+        }
+
+        impl $op<&BitSet> for &BitSet {
+            type Output = BitSet;
+
+            fn $fn(self, rhs: &BitSet) -> BitSet {
+                let mut result = self.clone();
+                result.$with(rhs);
+                result
+            }
+        }
+    };
+}
+
+impl_bit_op!(BitAnd, bitand, BitAndAssign, bitand_assign, intersect_with);
+impl_bit_op!(BitOr, bitor, BitOrAssign, bitor_assign, union_with);
+impl_bit_op!(
+    BitXor,
+    bitxor,
+    BitXorAssign,
+    bitxor_assign,
+    symmetric_difference_with
+);
+impl_bit_op!(Sub, sub, SubAssign, sub_assign, difference_with);
+
+impl Not for &BitSet {
+    type Output = BitSet;
+
+    fn not(self) -> BitSet {
+        let mut result = self.clone();
+        result.toggle_all();
+        result
+    }
+}
+
+/// Iterator over the indexes of the set bits of a `BitSet`.
+#[derive(Debug, Clone)]
+pub struct BitSetIter<'a> {
+    words: &'a [u32],
+    index: usize,
+    current: u32,
+}
+
+impl Iterator for BitSetIter<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        while self.current == 0 {
+            self.index += 1;
+            self.current = *self.words.get(self.index)?;
+        }
+
+        let offset = self.current.trailing_zeros() as usize;
+        self.current &= self.current - 1;
+        Some(self.index * WORD_BITS + offset)
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct BitSetRepr {
+    len: usize,
+    bytes: Vec<u8>,
+}
+
+#[cfg(feature = "serde")]
+impl From<BitSet> for BitSetRepr {
+    fn from(set: BitSet) -> Self {
+        Self {
+            len: set.len,
+            bytes: set.to_bytes(),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<BitSetRepr> for BitSet {
+    type Error = String;
+
+    fn try_from(repr: BitSetRepr) -> std::result::Result<Self, String> {
+        if repr.bytes.len() != repr.len.div_ceil(8) {
+            return Err(format!(
+                "expected {} bytes for {} bits, found {}",
+                repr.len.div_ceil(8),
+                repr.len,
+                repr.bytes.len()
+            ));
+        }
+
+        let mut set = BitSet::from_bytes(&repr.bytes);
+        set.resize(repr.len);
+        Ok(set)
+    }
+}
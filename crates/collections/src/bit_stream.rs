@@ -0,0 +1,514 @@
+use std::{
+    collections::VecDeque,
+    io::{ErrorKind, Read, Write},
+};
+
+use crate::{Error, Result};
+
+/// Most bits a single read or write can move.
+const MAX_BITS: u32 = 64;
+
+/// The order in which the bits of each byte are read or written.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BitOrder {
+    /// The most significant bit of a byte comes first, and values are stored high bits
+    /// first. Used by most media formats such as H.264 and MPEG.
+    #[default]
+    MsbFirst,
+    /// The least significant bit of a byte comes first, and values are stored low bits
+    /// first. Used by formats such as DEFLATE.
+    LsbFirst,
+}
+
+fn check_count(count: u32) -> Result<()> {
+    if count > MAX_BITS {
+        Err(Error::Argument(format!(
+            "Cannot move {} bits at once; the limit is {}",
+            count, MAX_BITS
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+fn mask(count: u32) -> u64 {
+    if count >= MAX_BITS {
+        u64::MAX
+    } else {
+        (1 << count) - 1
+    }
+}
+
+/// Reads bit-packed values from a byte source such as a `&[u8]` or a file.
+///
+/// Reaching the end of the input returns `Error::NotEnoughData`. Fixed-size reads and
+/// peeks either succeed or leave the reader where it was, keeping the bytes they fetched
+/// for the next read; a varint read that fails stops partway through its code.
+#[derive(Debug)]
+pub struct BitReader<R: Read> {
+    reader: R,
+    order: BitOrder,
+    /// The byte being read.
+    byte: u8,
+    /// Unread bits left in `byte`.
+    available: u32,
+    /// Bytes fetched from `reader` ahead of `byte`.
+    pending: VecDeque<u8>,
+    position: u64,
+}
+
+impl<R: Read> BitReader<R> {
+    /// Creates a reader that reads the most significant bits first.
+    pub fn new(reader: R) -> Self {
+        Self::with_order(reader, BitOrder::MsbFirst)
+    }
+
+    /// Creates a reader with the given bit order.
+    pub fn with_order(reader: R, order: BitOrder) -> Self {
+        Self {
+            reader,
+            order,
+            byte: 0,
+            available: 0,
+            pending: VecDeque::new(),
+            position: 0,
+        }
+    }
+
+    /// Returns the bit order.
+    pub fn order(&self) -> BitOrder {
+        self.order
+    }
+
+    /// Returns the number of bits read or skipped so far.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Returns true if the next bit starts a byte.
+    pub fn is_aligned(&self) -> bool {
+        self.available == 0
+    }
+
+    /// Returns the underlying reader. Bytes already fetched from it but not yet read are
+    /// lost.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Reads a single bit.
+    pub fn read_bit(&mut self) -> Result<bool> {
+        Ok(self.read_bits(1)? == 1)
+    }
+
+    /// Reads an unsigned value of `count` bits, at most 64.
+    pub fn read_bits(&mut self, count: u32) -> Result<u64> {
+        let value = self.peek_bits(count)?;
+        self.consume(count);
+        Ok(value)
+    }
+
+    /// Reads a two's complement signed value of `count` bits, at most 64.
+    pub fn read_signed(&mut self, count: u32) -> Result<i64> {
+        let value = self.read_bits(count)?;
+
+        if count == 0 {
+            return Ok(0);
+        }
+
+        let shift = MAX_BITS - count;
+        Ok(((value << shift) as i64) >> shift)
+    }
+
+    /// Reads an unsigned value of `count` bits without moving past it.
+    pub fn peek_bits(&mut self, count: u32) -> Result<u64> {
+        check_count(count)?;
+        self.fill(count)?;
+
+        let mut value = 0u64;
+        let mut done = 0;
+        let mut byte = self.byte;
+        let mut available = self.available;
+        let mut pending = self.pending.iter();
+
+        while done < count {
+            if available == 0 {
+                byte = *pending.next().expect("bits were filled");
+                available = 8;
+            }
+
+            let take = (count - done).min(available);
+            let consumed = 8 - available;
+            value = match self.order {
+                BitOrder::MsbFirst => {
+                    let chunk = (byte << consumed) >> (8 - take);
+                    (value << take) | chunk as u64
+                }
+                BitOrder::LsbFirst => {
+                    let chunk = (byte >> consumed) as u64 & mask(take);
+                    value | (chunk << done)
+                }
+            };
+            available -= take;
+            done += take;
+        }
+
+        Ok(value)
+    }
+
+    /// Moves past `count` bits. On error the reader is left at the end of the input.
+    pub fn skip_bits(&mut self, count: u64) -> Result<()> {
+        let mut remaining = count;
+
+        while remaining > 0 {
+            let step = remaining.min(MAX_BITS as u64) as u32;
+            self.fill(step)?;
+            self.consume(step);
+            remaining -= step as u64;
+        }
+
+        Ok(())
+    }
+
+    /// Skips to the start of the next byte, unless already there.
+    pub fn align(&mut self) {
+        self.consume(self.available);
+    }
+
+    /// Fills `buf` with the next bytes, which need not be aligned.
+    pub fn read_bytes(&mut self, buf: &mut [u8]) -> Result<()> {
+        self.fill(
+            u32::try_from(buf.len() * 8)
+                .map_err(|_| Error::Argument("Too many bytes to read at once".to_string()))?,
+        )?;
+
+        for byte in buf.iter_mut() {
+            *byte = self.read_bits(8)? as u8;
+        }
+
+        Ok(())
+    }
+
+    /// Reads an unsigned Exp-Golomb code, written as `ue(v)` in H.264.
+    pub fn read_exp_golomb(&mut self) -> Result<u64> {
+        let mut zeros = 0;
+
+        while !self.read_bit()? {
+            zeros += 1;
+
+            if zeros > MAX_BITS {
+                return Err(Error::Parse(
+                    "Exp-Golomb code is longer than 64 bits".to_string(),
+                ));
+            }
+        }
+
+        let rest = self.read_bits(zeros)? as u128;
+        u64::try_from(((1u128 << zeros) | rest) - 1)
+            .map_err(|_| Error::Parse("Exp-Golomb value overflows u64".to_string()))
+    }
+
+    /// Reads a signed Exp-Golomb code, written as `se(v)` in H.264.
+    pub fn read_signed_exp_golomb(&mut self) -> Result<i64> {
+        let code = self.read_exp_golomb()? as i128;
+        let value = if code % 2 == 1 {
+            (code + 1) / 2
+        } else {
+            -(code / 2)
+        };
+        i64::try_from(value).map_err(|_| Error::Parse("Exp-Golomb value overflows i64".to_string()))
+    }
+
+    /// Reads an unsigned LEB128 varint.
+    pub fn read_leb128(&mut self) -> Result<u64> {
+        let mut value = 0u64;
+        let mut shift = 0;
+
+        loop {
+            let byte = self.read_bits(8)?;
+            let low = byte & 0x7F;
+
+            if shift > 63 || (shift == 63 && low > 1) {
+                return Err(Error::Parse("LEB128 value overflows u64".to_string()));
+            }
+
+            value |= low << shift;
+
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+
+            shift += 7;
+        }
+    }
+
+    /// Reads a signed LEB128 varint.
+    pub fn read_signed_leb128(&mut self) -> Result<i64> {
+        let mut value = 0i64;
+        let mut shift = 0;
+
+        loop {
+            let byte = self.read_bits(8)?;
+            let low = byte & 0x7F;
+
+            if shift > 63 || (shift == 63 && low != 0 && low != 0x7F) {
+                return Err(Error::Parse("LEB128 value overflows i64".to_string()));
+            }
+
+            value |= (low as i64) << shift;
+            shift += 7;
+
+            if byte & 0x80 == 0 {
+                if shift < 64 && byte & 0x40 != 0 {
+                    value |= -1i64 << shift;
+                }
+
+                return Ok(value);
+            }
+        }
+    }
+
+    /// Fetches bytes until at least `count` bits are buffered.
+    fn fill(&mut self, count: u32) -> Result<()> {
+        while (self.available as usize) + self.pending.len() * 8 < count as usize {
+            let mut buf = [0u8];
+            self.reader.read_exact(&mut buf).map_err(|e| {
+                if e.kind() == ErrorKind::UnexpectedEof {
+                    Error::NotEnoughData
+                } else {
+                    Error::IO(e)
+                }
+            })?;
+            self.pending.push_back(buf[0]);
+        }
+
+        Ok(())
+    }
+
+    /// Moves past `count` buffered bits.
+    fn consume(&mut self, count: u32) {
+        let mut remaining = count;
+
+        while remaining > 0 {
+            if self.available == 0 {
+                self.byte = self.pending.pop_front().expect("bits were filled");
+                self.available = 8;
+            }
+
+            let take = remaining.min(self.available);
+            self.available -= take;
+            remaining -= take;
+        }
+
+        self.position += count as u64;
+    }
+}
+
+/// Writes bit-packed values to a byte sink such as a `Vec<u8>` or a file.
+///
+/// Whole bytes are written as soon as they are complete. Call `finish` to pad and write
+/// the last partial byte; dropping the writer discards it.
+#[derive(Debug)]
+pub struct BitWriter<W: Write> {
+    writer: W,
+    order: BitOrder,
+    /// The byte being written.
+    byte: u8,
+    /// Bits already written to `byte`.
+    used: u32,
+    position: u64,
+}
+
+impl<W: Write> BitWriter<W> {
+    /// Creates a writer that writes the most significant bits first.
+    pub fn new(writer: W) -> Self {
+        Self::with_order(writer, BitOrder::MsbFirst)
+    }
+
+    /// Creates a writer with the given bit order.
+    pub fn with_order(writer: W, order: BitOrder) -> Self {
+        Self {
+            writer,
+            order,
+            byte: 0,
+            used: 0,
+            position: 0,
+        }
+    }
+
+    /// Returns the bit order.
+    pub fn order(&self) -> BitOrder {
+        self.order
+    }
+
+    /// Returns the number of bits written so far, including padding.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Returns true if the next bit starts a byte.
+    pub fn is_aligned(&self) -> bool {
+        self.used == 0
+    }
+
+    /// Writes a single bit.
+    pub fn write_bit(&mut self, bit: bool) -> Result<()> {
+        self.write_bits(1, bit as u64)
+    }
+
+    /// Writes the low `count` bits of `value`, at most 64. Fails if `value` does not fit.
+    pub fn write_bits(&mut self, count: u32, value: u64) -> Result<()> {
+        check_count(count)?;
+
+        if value & !mask(count) != 0 {
+            return Err(Error::Argument(format!(
+                "Value {} does not fit in {} bits",
+                value, count
+            )));
+        }
+
+        let mut done = 0;
+
+        while done < count {
+            let space = 8 - self.used;
+            let take = (count - done).min(space);
+            let chunk = match self.order {
+                BitOrder::MsbFirst => {
+                    let chunk = (value >> (count - done - take)) & mask(take);
+                    chunk << (space - take)
+                }
+                BitOrder::LsbFirst => ((value >> done) & mask(take)) << self.used,
+            };
+            self.byte |= chunk as u8;
+            self.used += take;
+            done += take;
+
+            if self.used == 8 {
+                self.writer.write_all(&[self.byte])?;
+                self.byte = 0;
+                self.used = 0;
+            }
+        }
+
+        self.position += count as u64;
+        Ok(())
+    }
+
+    /// Writes `value` as a two's complement signed value of `count` bits, at most 64.
+    /// Fails if `value` does not fit.
+    pub fn write_signed(&mut self, count: u32, value: i64) -> Result<()> {
+        check_count(count)?;
+        let fits = if count == 0 {
+            value == 0
+        } else {
+            let limit = 1i128 << (count - 1);
+            (-limit..limit).contains(&(value as i128))
+        };
+
+        if !fits {
+            return Err(Error::Argument(format!(
+                "Value {} does not fit in {} signed bits",
+                value, count
+            )));
+        }
+
+        self.write_bits(count, value as u64 & mask(count))
+    }
+
+    /// Pads with zero bits to the start of the next byte, unless already there.
+    pub fn align(&mut self) -> Result<()> {
+        if self.used > 0 {
+            self.write_bits(8 - self.used, 0)?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes bytes, which need not be aligned.
+    pub fn write_bytes(&mut self, bytes: &[u8]) -> Result<()> {
+        for &byte in bytes {
+            self.write_bits(8, byte as u64)?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes an unsigned Exp-Golomb code, written as `ue(v)` in H.264.
+    pub fn write_exp_golomb(&mut self, value: u64) -> Result<()> {
+        let code = value as u128 + 1;
+        let zeros = 127 - code.leading_zeros();
+
+        for _ in 0..zeros {
+            self.write_bit(false)?;
+        }
+
+        self.write_bit(true)?;
+        self.write_bits(zeros, (code as u64) & mask(zeros))
+    }
+
+    /// Writes a signed Exp-Golomb code, written as `se(v)` in H.264. Fails for `i64::MIN`,
+    /// whose code does not fit in 64 bits.
+    pub fn write_signed_exp_golomb(&mut self, value: i64) -> Result<()> {
+        let value = value as i128;
+        let code = if value > 0 { 2 * value - 1 } else { -2 * value };
+        let code = u64::try_from(code)
+            .map_err(|_| Error::Argument(format!("Value {} is out of Exp-Golomb range", value)))?;
+        self.write_exp_golomb(code)
+    }
+
+    /// Writes an unsigned LEB128 varint.
+    pub fn write_leb128(&mut self, value: u64) -> Result<()> {
+        let mut value = value;
+
+        loop {
+            let mut byte = value & 0x7F;
+            value >>= 7;
+
+            if value != 0 {
+                byte |= 0x80;
+            }
+
+            self.write_bits(8, byte)?;
+
+            if value == 0 {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Writes a signed LEB128 varint.
+    pub fn write_signed_leb128(&mut self, value: i64) -> Result<()> {
+        let mut value = value;
+
+        loop {
+            let mut byte = (value & 0x7F) as u64;
+            value >>= 7;
+            let done = (value == 0 && byte & 0x40 == 0) || (value == -1 && byte & 0x40 != 0);
+
+            if !done {
+                byte |= 0x80;
+            }
+
+            self.write_bits(8, byte)?;
+
+            if done {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Flushes the underlying writer. The last partial byte is not written until
+    /// `finish`.
+    pub fn flush(&mut self) -> Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    /// Pads the last partial byte with zero bits, writes it, and returns the underlying
+    /// writer.
+    pub fn finish(mut self) -> Result<W> {
+        self.align()?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
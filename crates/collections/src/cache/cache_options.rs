@@ -0,0 +1,100 @@
+use std::{fmt, sync::Arc, time::Duration};
+
+/// Gives the weight of an entry, used to measure capacity by weight instead of count.
+pub type Weigher<K, V> = Arc<dyn Fn(&K, &V) -> usize + Send + Sync>;
+
+/// Called with the entries a cache drops on its own.
+pub type EvictionListener<K, V> = Arc<dyn Fn(K, V, EvictionReason) + Send + Sync>;
+
+/// Why a cache dropped an entry.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EvictionReason {
+    /// The entry was evicted to make room for another one, or is heavier than the whole
+    /// capacity.
+    Capacity,
+    /// The entry outlived its time to live.
+    Expired,
+    /// The entry was replaced by an insert with the same key.
+    Replaced,
+}
+
+/// Shortest interval a `Cleanup::Background` thread wakes up at.
+pub const CLEANUP_INTERVAL_MIN: Duration = Duration::from_millis(10);
+
+/// How a `TtlCache` removes expired entries.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Cleanup {
+    /// Expired entries are removed when they are accessed or when room is needed.
+    #[default]
+    Lazy,
+    /// A background thread also removes expired entries at this interval, raised to at
+    /// least `CLEANUP_INTERVAL_MIN`. The thread stops at its next wake-up once every
+    /// handle to the cache is dropped.
+    Background(Duration),
+}
+
+#[must_use]
+pub struct CacheOptions<K, V> {
+    /// The most entries the cache holds, or the most total weight if a weigher is set.
+    pub capacity: usize,
+    /// Gives the weight of an entry. Without one, every entry weighs 1.
+    pub weigher: Option<Weigher<K, V>>,
+    /// Called after the cache lock is released, so the listener may use the cache.
+    pub eviction_listener: Option<EvictionListener<K, V>>,
+}
+
+impl<K, V> Clone for CacheOptions<K, V> {
+    fn clone(&self) -> Self {
+        CacheOptions {
+            capacity: self.capacity,
+            weigher: self.weigher.clone(),
+            eviction_listener: self.eviction_listener.clone(),
+        }
+    }
+}
+
+impl<K, V> fmt::Debug for CacheOptions<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CacheOptions")
+            .field("capacity", &self.capacity)
+            .field("weigher", &self.weigher.is_some())
+            .field("eviction_listener", &self.eviction_listener.is_some())
+            .finish()
+    }
+}
+
+impl<K, V> CacheOptions<K, V> {
+    /// Creates options for a cache of `capacity` entries, at least 1.
+    pub fn new(capacity: usize) -> Self {
+        CacheOptions {
+            capacity: capacity.max(1),
+            weigher: None,
+            eviction_listener: None,
+        }
+    }
+
+    pub fn with_capacity(&self, capacity: usize) -> Self {
+        CacheOptions {
+            capacity: capacity.max(1),
+            ..self.clone()
+        }
+    }
+
+    /// Measures the capacity as the total weight of the entries.
+    pub fn with_weigher(&self, weigher: impl Fn(&K, &V) -> usize + Send + Sync + 'static) -> Self {
+        CacheOptions {
+            weigher: Some(Arc::new(weigher)),
+            ..self.clone()
+        }
+    }
+
+    pub fn with_eviction_listener(
+        &self,
+        listener: impl Fn(K, V, EvictionReason) + Send + Sync + 'static,
+    ) -> Self {
+        CacheOptions {
+            eviction_listener: Some(Arc::new(listener)),
+            ..self.clone()
+        }
+    }
+}
@@ -0,0 +1,27 @@
+/// Counters of a cache since it was created or its stats were last reset.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Lookups that found a live entry.
+    pub hits: u64,
+    /// Lookups that found nothing, including loads by `get_or_insert_with`.
+    pub misses: u64,
+    pub insertions: u64,
+    /// Entries dropped for capacity.
+    pub evictions: u64,
+    /// Entries dropped because they outlived their time to live.
+    pub expirations: u64,
+}
+
+impl CacheStats {
+    pub fn lookups(&self) -> u64 {
+        self.hits + self.misses
+    }
+
+    /// Returns the share of lookups that were hits, or 0 without lookups.
+    pub fn hit_rate(&self) -> f64 {
+        match self.lookups() {
+            0 => 0.0,
+            lookups => self.hits as f64 / lookups as f64,
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use std::{hash::Hash, sync::Arc};
+
+use super::{
+    CacheOptions,
+    store::{Policy, Store},
+};
+
+/// A thread-safe cache that evicts the least recently used entries when full.
+///
+/// Clones share the same entries, so a cache can be handed to several threads. Values are
+/// returned as clones; wrap large values in an `Arc`.
+pub struct LruCache<K, V> {
+    store: Arc<Store<K, V>>,
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> LruCache<K, V> {
+    /// Creates a cache of `capacity` entries.
+    pub fn new(capacity: usize) -> Self {
+        Self::with_options(CacheOptions::new(capacity))
+    }
+
+    pub fn with_options(options: CacheOptions<K, V>) -> Self {
+        LruCache {
+            store: Arc::new(Store::new(options, Policy::Lru, None)),
+        }
+    }
+}
+
+impl_cache!(LruCache);
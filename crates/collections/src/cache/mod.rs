@@ -0,0 +1,126 @@
+/// Implements the operations every cache type shares by delegating to its `store`.
+macro_rules! impl_cache {
+    ($name:ident) => {
+        impl<K: std::hash::Hash + Eq + Clone, V: Clone> $name<K, V> {
+            pub fn options(&self) -> &$crate::cache::CacheOptions<K, V> {
+                self.store.options()
+            }
+
+            /// Returns the value of `key` and marks it used, counting a hit or a miss.
+            pub fn get<Q>(&self, key: &Q) -> $crate::Result<Option<V>>
+            where
+                K: std::borrow::Borrow<Q>,
+                Q: std::hash::Hash + Eq + ?Sized,
+            {
+                self.store.get(key)
+            }
+
+            /// Returns the value of `key` without marking it used or counting the lookup.
+            pub fn peek<Q>(&self, key: &Q) -> $crate::Result<Option<V>>
+            where
+                K: std::borrow::Borrow<Q>,
+                Q: std::hash::Hash + Eq + ?Sized,
+            {
+                self.store.peek(key)
+            }
+
+            pub fn contains_key<Q>(&self, key: &Q) -> $crate::Result<bool>
+            where
+                K: std::borrow::Borrow<Q>,
+                Q: std::hash::Hash + Eq + ?Sized,
+            {
+                Ok(self.store.peek(key)?.is_some())
+            }
+
+            /// Inserts a value, evicting entries as needed to stay within the capacity.
+            pub fn insert(&self, key: K, value: V) -> $crate::Result<()> {
+                self.store.insert(key, value, self.store.ttl())
+            }
+
+            /// Removes an entry and returns its value. The eviction listener is not called.
+            pub fn remove<Q>(&self, key: &Q) -> $crate::Result<Option<V>>
+            where
+                K: std::borrow::Borrow<Q>,
+                Q: std::hash::Hash + Eq + ?Sized,
+            {
+                self.store.remove(key)
+            }
+
+            /// Returns the value of `key`, or computes it with `f` and inserts it.
+            ///
+            /// Concurrent misses on the same key compute once: the other callers wait for
+            /// the value instead of calling their own `f`. `f` must not access the same key
+            /// of this cache.
+            pub fn get_or_insert_with(&self, key: K, f: impl FnOnce() -> V) -> $crate::Result<V> {
+                self.store
+                    .try_get_or_insert_with(key, self.store.ttl(), || Ok(f()))
+            }
+
+            /// Like `get_or_insert_with`, for a fallible `f`. Errors are returned to the
+            /// caller that ran `f` and are not cached; one of the waiting callers then
+            /// computes the value again.
+            pub fn try_get_or_insert_with(
+                &self,
+                key: K,
+                f: impl FnOnce() -> $crate::Result<V>,
+            ) -> $crate::Result<V> {
+                self.store.try_get_or_insert_with(key, self.store.ttl(), f)
+            }
+
+            pub fn len(&self) -> $crate::Result<usize> {
+                self.store.len()
+            }
+
+            pub fn is_empty(&self) -> $crate::Result<bool> {
+                Ok(self.store.len()? == 0)
+            }
+
+            /// Returns the total weight of the entries, which is their count without a
+            /// weigher.
+            pub fn weight(&self) -> $crate::Result<usize> {
+                self.store.weight()
+            }
+
+            /// Removes every entry. The eviction listener is not called.
+            pub fn clear(&self) -> $crate::Result<()> {
+                self.store.clear()
+            }
+
+            pub fn stats(&self) -> $crate::Result<$crate::cache::CacheStats> {
+                self.store.stats()
+            }
+
+            pub fn reset_stats(&self) -> $crate::Result<()> {
+                self.store.reset_stats()
+            }
+        }
+
+        impl<K, V> Clone for $name<K, V> {
+            fn clone(&self) -> Self {
+                $name {
+                    store: self.store.clone(),
+                }
+            }
+        }
+
+        impl<K: std::hash::Hash + Eq + Clone, V: Clone> std::fmt::Debug for $name<K, V> {
+            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.debug_struct(stringify!($name))
+                    .field("options", self.store.options())
+                    .finish_non_exhaustive()
+            }
+        }
+    };
+}
+
+mod cache_options;
+pub use cache_options::*;
+mod cache_stats;
+pub use cache_stats::*;
+mod lfu_cache;
+pub use lfu_cache::*;
+mod lru_cache;
+pub use lru_cache::*;
+mod store;
+mod ttl_cache;
+pub use ttl_cache::*;
@@ -0,0 +1,427 @@
+use std::{
+    borrow::Borrow,
+    collections::{BTreeMap, HashMap},
+    hash::Hash,
+    sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError},
+    time::{Duration, Instant},
+};
+
+use super::{CacheOptions, CacheStats, EvictionReason};
+use crate::{Error, Result};
+
+/// Which entry a full cache evicts first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum Policy {
+    /// The least recently used.
+    Lru,
+    /// The least frequently used, then the least recently used among those.
+    Lfu,
+}
+
+struct Entry<V> {
+    value: V,
+    weight: usize,
+    /// The key of the entry in `State::order`.
+    rank: (u64, u64),
+    uses: u64,
+    expires_at: Option<Instant>,
+}
+
+impl<V> Entry<V> {
+    fn is_expired(&self, now: Instant) -> bool {
+        self.expires_at.is_some_and(|it| it <= now)
+    }
+}
+
+type Evicted<K, V> = Vec<(K, V, EvictionReason)>;
+
+struct State<K, V> {
+    entries: HashMap<K, Entry<V>>,
+    /// The keys by rank. The first key is the next to evict.
+    order: BTreeMap<(u64, u64), K>,
+    /// The loads in progress by `try_get_or_insert_with`, at most one per key.
+    loads: HashMap<K, Arc<Load>>,
+    tick: u64,
+    weight: usize,
+    stats: CacheStats,
+}
+
+#[derive(Default)]
+struct Load {
+    done: Mutex<bool>,
+    cond: Condvar,
+}
+
+impl Load {
+    fn wait(&self) -> Result<()> {
+        let mut done = Error::handle_poison_error(self.done.lock())?;
+
+        while !*done {
+            done = Error::handle_poison_error(self.cond.wait(done))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Ends a load when dropped, even if the loader failed or panicked, and wakes the threads
+/// waiting for it so they retry.
+struct LoadGuard<'a, K: Hash + Eq, V> {
+    store: &'a Store<K, V>,
+    key: &'a K,
+    load: Arc<Load>,
+}
+
+impl<K: Hash + Eq, V> Drop for LoadGuard<'_, K, V> {
+    fn drop(&mut self) {
+        let mut state = self
+            .store
+            .state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        state.loads.remove(self.key);
+        drop(state);
+
+        let mut done = self
+            .load
+            .done
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        *done = true;
+        self.load.cond.notify_all();
+    }
+}
+
+/// The thread-safe storage shared by the cache types.
+pub(super) struct Store<K, V> {
+    options: CacheOptions<K, V>,
+    policy: Policy,
+    /// The default time to live. Entries never expire without one.
+    ttl: Option<Duration>,
+    state: Mutex<State<K, V>>,
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> Store<K, V> {
+    pub fn new(options: CacheOptions<K, V>, policy: Policy, ttl: Option<Duration>) -> Self {
+        Store {
+            options,
+            policy,
+            ttl,
+            state: Mutex::new(State {
+                entries: HashMap::new(),
+                order: BTreeMap::new(),
+                loads: HashMap::new(),
+                tick: 0,
+                weight: 0,
+                stats: CacheStats::default(),
+            }),
+        }
+    }
+
+    pub fn options(&self) -> &CacheOptions<K, V> {
+        &self.options
+    }
+
+    pub fn ttl(&self) -> Option<Duration> {
+        self.ttl
+    }
+
+    fn lock(&self) -> Result<MutexGuard<'_, State<K, V>>> {
+        self.state
+            .lock()
+            .map_err(|_| Error::Poisoned("The cache lock was poisoned".to_string()))
+    }
+
+    fn notify(&self, evicted: Evicted<K, V>) {
+        if let Some(listener) = &self.options.eviction_listener {
+            for (key, value, reason) in evicted {
+                listener(key, value, reason);
+            }
+        }
+    }
+
+    fn next_rank(&self, state: &mut State<K, V>, uses: u64) -> (u64, u64) {
+        state.tick += 1;
+
+        match self.policy {
+            Policy::Lru => (0, state.tick),
+            Policy::Lfu => (uses, state.tick),
+        }
+    }
+
+    fn remove_entry<Q>(state: &mut State<K, V>, key: &Q) -> Option<(K, Entry<V>)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let (key, entry) = state.entries.remove_entry(key)?;
+        state.order.remove(&entry.rank);
+        state.weight -= entry.weight;
+        Some((key, entry))
+    }
+
+    /// Returns the value of a live entry and marks it used. An expired entry is removed.
+    fn lookup<Q>(&self, state: &mut State<K, V>, key: &Q, evicted: &mut Evicted<K, V>) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let entry = state.entries.get(key)?;
+
+        if entry.is_expired(Instant::now()) {
+            let (key, entry) = Self::remove_entry(state, key)?;
+            state.stats.expirations += 1;
+            evicted.push((key, entry.value, EvictionReason::Expired));
+            return None;
+        }
+
+        let uses = entry.uses + 1;
+        let old_rank = entry.rank;
+        let rank = self.next_rank(state, uses);
+        let owned_key = state.order.remove(&old_rank)?;
+        state.order.insert(rank, owned_key);
+
+        let entry = state.entries.get_mut(key)?;
+        entry.uses = uses;
+        entry.rank = rank;
+        Some(entry.value.clone())
+    }
+
+    fn insert_locked(
+        &self,
+        state: &mut State<K, V>,
+        key: K,
+        value: V,
+        ttl: Option<Duration>,
+        evicted: &mut Evicted<K, V>,
+    ) {
+        let weight = self
+            .options
+            .weigher
+            .as_ref()
+            .map_or(1, |weigher| weigher(&key, &value));
+
+        if let Some((old_key, old)) = Self::remove_entry(state, &key) {
+            evicted.push((old_key, old.value, EvictionReason::Replaced));
+        }
+
+        if weight > self.options.capacity {
+            state.stats.evictions += 1;
+            evicted.push((key, value, EvictionReason::Capacity));
+            return;
+        }
+
+        if self.ttl.is_some() && state.weight + weight > self.options.capacity {
+            Self::purge_locked(state, evicted);
+        }
+
+        while state.weight + weight > self.options.capacity {
+            let Some((_, victim)) = state.order.pop_first() else {
+                break;
+            };
+
+            if let Some(entry) = state.entries.remove(&victim) {
+                state.weight -= entry.weight;
+                state.stats.evictions += 1;
+                evicted.push((victim, entry.value, EvictionReason::Capacity));
+            }
+        }
+
+        let rank = self.next_rank(state, 1);
+        state.order.insert(rank, key.clone());
+        state.entries.insert(
+            key,
+            Entry {
+                value,
+                weight,
+                rank,
+                uses: 1,
+                expires_at: ttl.map(|ttl| Instant::now() + ttl),
+            },
+        );
+        state.weight += weight;
+        state.stats.insertions += 1;
+    }
+
+    fn purge_locked(state: &mut State<K, V>, evicted: &mut Evicted<K, V>) -> usize {
+        let now = Instant::now();
+        let expired: Vec<K> = state
+            .entries
+            .iter()
+            .filter(|(_, entry)| entry.is_expired(now))
+            .map(|(key, _)| key.clone())
+            .collect();
+
+        for key in &expired {
+            if let Some((key, entry)) = Self::remove_entry(state, key) {
+                evicted.push((key, entry.value, EvictionReason::Expired));
+            }
+        }
+
+        state.stats.expirations += expired.len() as u64;
+        expired.len()
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Result<Option<V>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let mut evicted = Vec::new();
+        let mut state = self.lock()?;
+        let value = self.lookup(&mut state, key, &mut evicted);
+
+        if value.is_some() {
+            state.stats.hits += 1;
+        } else {
+            state.stats.misses += 1;
+        }
+
+        drop(state);
+        self.notify(evicted);
+        Ok(value)
+    }
+
+    pub fn peek<Q>(&self, key: &Q) -> Result<Option<V>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let state = self.lock()?;
+        let now = Instant::now();
+        Ok(state
+            .entries
+            .get(key)
+            .filter(|entry| !entry.is_expired(now))
+            .map(|entry| entry.value.clone()))
+    }
+
+    pub fn time_to_live<Q>(&self, key: &Q) -> Result<Option<Duration>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let state = self.lock()?;
+        let now = Instant::now();
+        Ok(state
+            .entries
+            .get(key)
+            .filter(|entry| !entry.is_expired(now))
+            .and_then(|entry| entry.expires_at)
+            .map(|it| it - now))
+    }
+
+    pub fn insert(&self, key: K, value: V, ttl: Option<Duration>) -> Result<()> {
+        let mut evicted = Vec::new();
+        let mut state = self.lock()?;
+        self.insert_locked(&mut state, key, value, ttl, &mut evicted);
+        drop(state);
+        self.notify(evicted);
+        Ok(())
+    }
+
+    pub fn remove<Q>(&self, key: &Q) -> Result<Option<V>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let mut state = self.lock()?;
+        let Some((key, entry)) = Self::remove_entry(&mut state, key) else {
+            return Ok(None);
+        };
+
+        if !entry.is_expired(Instant::now()) {
+            return Ok(Some(entry.value));
+        }
+
+        state.stats.expirations += 1;
+        drop(state);
+        self.notify(vec![(key, entry.value, EvictionReason::Expired)]);
+        Ok(None)
+    }
+
+    /// Returns the value of `key`, or loads it with `f` and inserts it. Concurrent calls
+    /// for a key that is being loaded wait for that load instead of calling their own `f`.
+    /// If the load fails, one of the waiting calls loads it again.
+    pub fn try_get_or_insert_with(
+        &self,
+        key: K,
+        ttl: Option<Duration>,
+        f: impl FnOnce() -> Result<V>,
+    ) -> Result<V> {
+        let mut evicted = Vec::new();
+        let load = loop {
+            let waiting = {
+                let mut state = self.lock()?;
+
+                if let Some(value) = self.lookup(&mut state, &key, &mut evicted) {
+                    state.stats.hits += 1;
+                    drop(state);
+                    self.notify(evicted);
+                    return Ok(value);
+                }
+
+                match state.loads.get(&key) {
+                    Some(load) => load.clone(),
+                    None => {
+                        let load = Arc::new(Load::default());
+                        state.loads.insert(key.clone(), load.clone());
+                        state.stats.misses += 1;
+                        break load;
+                    }
+                }
+            };
+
+            self.notify(std::mem::take(&mut evicted));
+            waiting.wait()?;
+        };
+
+        self.notify(std::mem::take(&mut evicted));
+        let guard = LoadGuard {
+            store: self,
+            key: &key,
+            load,
+        };
+        let value = f()?;
+        let mut state = self.lock()?;
+        self.insert_locked(&mut state, key.clone(), value.clone(), ttl, &mut evicted);
+        drop(state);
+        drop(guard);
+        self.notify(evicted);
+        Ok(value)
+    }
+
+    pub fn len(&self) -> Result<usize> {
+        Ok(self.lock()?.entries.len())
+    }
+
+    pub fn weight(&self) -> Result<usize> {
+        Ok(self.lock()?.weight)
+    }
+
+    pub fn clear(&self) -> Result<()> {
+        let mut state = self.lock()?;
+        state.entries.clear();
+        state.order.clear();
+        state.weight = 0;
+        Ok(())
+    }
+
+    pub fn purge_expired(&self) -> Result<usize> {
+        let mut evicted = Vec::new();
+        let mut state = self.lock()?;
+        let count = Self::purge_locked(&mut state, &mut evicted);
+        drop(state);
+        self.notify(evicted);
+        Ok(count)
+    }
+
+    pub fn stats(&self) -> Result<CacheStats> {
+        Ok(self.lock()?.stats)
+    }
+
+    pub fn reset_stats(&self) -> Result<()> {
+        self.lock()?.stats = CacheStats::default();
+        Ok(())
+    }
+}
@@ -0,0 +1,81 @@
+use std::{borrow::Borrow, hash::Hash, sync::Arc, thread, time::Duration};
+
+use super::{
+    CLEANUP_INTERVAL_MIN, CacheOptions, Cleanup,
+    store::{Policy, Store},
+};
+use crate::Result;
+
+/// A thread-safe cache whose entries expire a time after they are inserted.
+///
+/// Expired entries are never returned. They are removed when accessed, before entries are
+/// evicted for room, and by a background thread with `Cleanup::Background`. Until then
+/// they still count toward `len` and `weight`. When full, the cache evicts the least
+/// recently used entries.
+pub struct TtlCache<K, V> {
+    store: Arc<Store<K, V>>,
+}
+
+impl<K, V> TtlCache<K, V>
+where
+    K: Hash + Eq + Clone + Send + 'static,
+    V: Clone + Send + 'static,
+{
+    /// Creates a cache of `capacity` entries that live for `ttl` and are removed lazily.
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self::with_options(CacheOptions::new(capacity), ttl, Cleanup::Lazy)
+    }
+
+    pub fn with_options(options: CacheOptions<K, V>, ttl: Duration, cleanup: Cleanup) -> Self {
+        let store = Arc::new(Store::new(options, Policy::Lru, Some(ttl)));
+
+        if let Cleanup::Background(interval) = cleanup {
+            let interval = interval.max(CLEANUP_INTERVAL_MIN);
+            let weak = Arc::downgrade(&store);
+            thread::spawn(move || {
+                loop {
+                    thread::sleep(interval);
+
+                    let Some(store) = weak.upgrade() else {
+                        break;
+                    };
+
+                    if store.purge_expired().is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+
+        TtlCache { store }
+    }
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> TtlCache<K, V> {
+    /// Returns the time to live of entries inserted without one.
+    pub fn ttl(&self) -> Duration {
+        self.store.ttl().unwrap_or_default()
+    }
+
+    /// Inserts a value that expires after `ttl` instead of the cache default.
+    pub fn insert_with_ttl(&self, key: K, value: V, ttl: Duration) -> Result<()> {
+        self.store.insert(key, value, Some(ttl))
+    }
+
+    /// Returns how long until the entry of `key` expires, or `None` if there is no live
+    /// entry.
+    pub fn time_to_live<Q>(&self, key: &Q) -> Result<Option<Duration>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.store.time_to_live(key)
+    }
+
+    /// Removes the expired entries now. Returns how many were removed.
+    pub fn purge_expired(&self) -> Result<usize> {
+        self.store.purge_expired()
+    }
+}
+
+impl_cache!(TtlCache);
@@ -0,0 +1,12 @@
+mod object_pool;
+pub use object_pool::*;
+mod ring_buffer;
+pub use ring_buffer::*;
+mod sharded_map;
+pub use sharded_map::*;
+
+/// Aligns a value to its own cache lines, so threads updating neighboring values do not
+/// invalidate each other's caches.
+#[derive(Debug, Default)]
+#[repr(align(128))]
+struct CachePadded<T>(T);
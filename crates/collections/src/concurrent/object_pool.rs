@@ -0,0 +1,177 @@
+use parking_lot::Mutex;
+use std::{
+    fmt,
+    ops::{Deref, DerefMut},
+    sync::Arc,
+};
+
+type Create<T> = Box<dyn Fn() -> T + Send + Sync>;
+type Reset<T> = Box<dyn Fn(&mut T) + Send + Sync>;
+
+struct PoolInner<T> {
+    idle: Mutex<Vec<T>>,
+    create: Create<T>,
+    reset: Option<Reset<T>>,
+    max_idle: usize,
+}
+
+impl<T> PoolInner<T> {
+    fn put(&self, mut value: T) {
+        if let Some(reset) = &self.reset {
+            reset(&mut value);
+        }
+
+        let mut idle = self.idle.lock();
+
+        if idle.len() < self.max_idle {
+            idle.push(value);
+        }
+    }
+}
+
+/// A thread-safe pool of reusable objects, such as buffers or connections.
+///
+/// `get` hands out an idle object, or creates one when none is idle, wrapped in a
+/// `Pooled` guard that returns it to the pool on drop. Returned objects are reset first,
+/// and dropped instead when the pool already keeps `max_idle` idle objects. Clones share
+/// the same objects.
+pub struct ObjectPool<T> {
+    inner: Arc<PoolInner<T>>,
+}
+
+impl<T> ObjectPool<T> {
+    /// Creates a pool that keeps at most `max_idle` idle objects and creates objects with
+    /// `create`.
+    pub fn new(max_idle: usize, create: impl Fn() -> T + Send + Sync + 'static) -> Self {
+        ObjectPool {
+            inner: Arc::new(PoolInner {
+                idle: Mutex::new(Vec::new()),
+                create: Box::new(create),
+                reset: None,
+                max_idle,
+            }),
+        }
+    }
+
+    /// Like `new`, and calls `reset` on every object returned to the pool, for example to
+    /// clear a buffer.
+    pub fn with_reset(
+        max_idle: usize,
+        create: impl Fn() -> T + Send + Sync + 'static,
+        reset: impl Fn(&mut T) + Send + Sync + 'static,
+    ) -> Self {
+        ObjectPool {
+            inner: Arc::new(PoolInner {
+                idle: Mutex::new(Vec::new()),
+                create: Box::new(create),
+                reset: Some(Box::new(reset)),
+                max_idle,
+            }),
+        }
+    }
+
+    pub fn max_idle(&self) -> usize {
+        self.inner.max_idle
+    }
+
+    /// Returns the number of idle objects.
+    pub fn idle(&self) -> usize {
+        self.inner.idle.lock().len()
+    }
+
+    /// Takes an idle object, or creates one if none is idle.
+    pub fn get(&self) -> Pooled<T> {
+        self.try_get().unwrap_or_else(|| Pooled {
+            value: Some((self.inner.create)()),
+            pool: self.inner.clone(),
+        })
+    }
+
+    /// Takes an idle object, or returns `None` if none is idle.
+    pub fn try_get(&self) -> Option<Pooled<T>> {
+        let value = self.inner.idle.lock().pop()?;
+        Some(Pooled {
+            value: Some(value),
+            pool: self.inner.clone(),
+        })
+    }
+
+    /// Creates objects until `count` are idle, up to `max_idle`.
+    pub fn prefill(&self, count: usize) {
+        let count = count.min(self.inner.max_idle);
+
+        while self.idle() < count {
+            let value = (self.inner.create)();
+            self.inner.put(value);
+        }
+    }
+
+    /// Drops every idle object.
+    pub fn clear(&self) {
+        self.inner.idle.lock().clear();
+    }
+}
+
+impl<T> Clone for ObjectPool<T> {
+    fn clone(&self) -> Self {
+        ObjectPool {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for ObjectPool<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ObjectPool")
+            .field("idle", &self.idle())
+            .field("max_idle", &self.inner.max_idle)
+            .finish()
+    }
+}
+
+/// An object taken from an `ObjectPool`, returned to it when dropped.
+pub struct Pooled<T> {
+    value: Option<T>,
+    pool: Arc<PoolInner<T>>,
+}
+
+impl<T> Pooled<T> {
+    /// Takes the object out of the pool for good.
+    pub fn detach(mut self) -> T {
+        self.value
+            .take()
+            .expect("Pooled object is present until dropped")
+    }
+}
+
+impl<T> Deref for Pooled<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value
+            .as_ref()
+            .expect("Pooled object is present until dropped")
+    }
+}
+
+impl<T> DerefMut for Pooled<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.value
+            .as_mut()
+            .expect("Pooled object is present until dropped")
+    }
+}
+
+impl<T> Drop for Pooled<T> {
+    fn drop(&mut self) {
+        if let Some(value) = self.value.take() {
+            self.pool.put(value);
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Pooled<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Pooled").field(&self.value).finish()
+    }
+}
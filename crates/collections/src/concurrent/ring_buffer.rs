@@ -0,0 +1,183 @@
+use std::{
+    cell::UnsafeCell,
+    fmt,
+    mem::MaybeUninit,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use super::CachePadded;
+
+struct Slot<T> {
+    /// Twice the position that may write the slot next while it is empty, and that plus
+    /// one once it holds a value. Doubling keeps a full slot distinct from an empty one
+    /// even when the capacity is 1.
+    sequence: AtomicUsize,
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+/// A fixed-capacity lock-free queue for any number of producers and consumers.
+///
+/// `push` rejects items while the buffer is full, and `push_overwrite` makes room by
+/// dropping the oldest item instead, which suits streams where only the latest items
+/// matter. The same type serves single-producer single-consumer pipelines.
+pub struct RingBuffer<T> {
+    head: CachePadded<AtomicUsize>,
+    tail: CachePadded<AtomicUsize>,
+    slots: Box<[Slot<T>]>,
+}
+
+// Items move between threads through the slots, and each slot is accessed by a single
+// thread at a time, as claimed through `head` and `tail`.
+unsafe impl<T: Send> Send for RingBuffer<T> {}
+unsafe impl<T: Send> Sync for RingBuffer<T> {}
+
+impl<T> RingBuffer<T> {
+    /// Creates a buffer of `capacity` items, at least 1.
+    pub fn new(capacity: usize) -> Self {
+        let slots = (0..capacity.max(1))
+            .map(|i| Slot {
+                sequence: AtomicUsize::new(i * 2),
+                value: UnsafeCell::new(MaybeUninit::uninit()),
+            })
+            .collect();
+
+        RingBuffer {
+            head: CachePadded(AtomicUsize::new(0)),
+            tail: CachePadded(AtomicUsize::new(0)),
+            slots,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Returns the number of items. Other threads may change it at any time.
+    pub fn len(&self) -> usize {
+        loop {
+            let tail = self.tail.0.load(Ordering::SeqCst);
+            let head = self.head.0.load(Ordering::SeqCst);
+
+            if self.tail.0.load(Ordering::SeqCst) == tail {
+                return tail.wrapping_sub(head).min(self.capacity());
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len() == self.capacity()
+    }
+
+    /// Adds an item at the back. Returns it back if the buffer is full.
+    pub fn push(&self, value: T) -> Result<(), T> {
+        let mut tail = self.tail.0.load(Ordering::Relaxed);
+
+        loop {
+            let slot = &self.slots[tail % self.slots.len()];
+            let sequence = slot.sequence.load(Ordering::Acquire);
+            let diff = sequence.wrapping_sub(tail.wrapping_mul(2)) as isize;
+
+            if diff == 0 {
+                match self.tail.0.compare_exchange_weak(
+                    tail,
+                    tail.wrapping_add(1),
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        // The successful exchange gave this thread the slot.
+                        unsafe { (*slot.value.get()).write(value) };
+                        slot.sequence
+                            .store(tail.wrapping_mul(2).wrapping_add(1), Ordering::Release);
+                        return Ok(());
+                    }
+                    Err(current) => tail = current,
+                }
+            } else if diff < 0 {
+                return Err(value);
+            } else {
+                tail = self.tail.0.load(Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Adds an item at the back, dropping the oldest items as needed to make room.
+    /// Returns the oldest item if one was displaced. Under contention from other
+    /// producers more than one item can be displaced; only the last one is returned.
+    pub fn push_overwrite(&self, value: T) -> Option<T> {
+        let mut value = value;
+        let mut displaced = None;
+
+        loop {
+            match self.push(value) {
+                Ok(()) => return displaced,
+                Err(rejected) => {
+                    value = rejected;
+
+                    if let Some(oldest) = self.pop() {
+                        displaced = Some(oldest);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Removes the item at the front, or returns `None` if the buffer is empty.
+    pub fn pop(&self) -> Option<T> {
+        let mut head = self.head.0.load(Ordering::Relaxed);
+
+        loop {
+            let slot = &self.slots[head % self.slots.len()];
+            let sequence = slot.sequence.load(Ordering::Acquire);
+            let diff = sequence.wrapping_sub(head.wrapping_mul(2).wrapping_add(1)) as isize;
+
+            if diff == 0 {
+                match self.head.0.compare_exchange_weak(
+                    head,
+                    head.wrapping_add(1),
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        // The successful exchange gave this thread the slot, which the
+                        // sequence shows was written.
+                        let value = unsafe { (*slot.value.get()).assume_init_read() };
+                        let next = head.wrapping_add(self.slots.len());
+                        slot.sequence.store(next.wrapping_mul(2), Ordering::Release);
+                        return Some(value);
+                    }
+                    Err(current) => head = current,
+                }
+            } else if diff < 0 {
+                return None;
+            } else {
+                head = self.head.0.load(Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Removes every item, oldest first, as an iterator. Items pushed while iterating are
+    /// also returned.
+    pub fn drain(&self) -> impl Iterator<Item = T> + '_ {
+        std::iter::from_fn(|| self.pop())
+    }
+}
+
+impl<T> Drop for RingBuffer<T> {
+    fn drop(&mut self) {
+        while self.pop().is_some() {}
+    }
+}
+
+impl<T> fmt::Debug for RingBuffer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RingBuffer")
+            .field("capacity", &self.capacity())
+            .field("len", &self.len())
+            .finish()
+    }
+}
@@ -0,0 +1,216 @@
+use parking_lot::RwLock;
+use std::{
+    borrow::Borrow,
+    collections::{HashMap, hash_map::RandomState},
+    fmt,
+    hash::{BuildHasher, Hash},
+};
+
+use super::CachePadded;
+
+type Shard<K, V, S> = CachePadded<RwLock<HashMap<K, V, S>>>;
+
+/// A concurrent hash map split into shards, each behind its own reader-writer lock.
+///
+/// Threads working on keys of different shards never wait for each other, so the map
+/// scales with the number of threads where a single `Mutex<HashMap>` serializes them.
+/// Closures passed to the map run while a shard lock is held and must not use the map.
+pub struct ShardedMap<K, V, S = RandomState> {
+    shards: Box<[Shard<K, V, S>]>,
+    hasher: S,
+    /// How far to shift a hash right to get the shard index.
+    shift: u32,
+}
+
+impl<K: Hash + Eq, V> ShardedMap<K, V> {
+    /// Creates a map with four shards per available core.
+    pub fn new() -> Self {
+        Self::with_shards(emixcore::system::num_cpus() * 4)
+    }
+
+    /// Creates a map with `shards` shards, rounded up to a power of two.
+    pub fn with_shards(shards: usize) -> Self {
+        Self::with_shards_and_hasher(shards, RandomState::new())
+    }
+}
+
+impl<K: Hash + Eq, V> Default for ShardedMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher + Clone> ShardedMap<K, V, S> {
+    /// Creates a map with `shards` shards, rounded up to a power of two, that hashes keys
+    /// with `hasher`.
+    pub fn with_shards_and_hasher(shards: usize, hasher: S) -> Self {
+        let shards = shards.max(1).next_power_of_two();
+        ShardedMap {
+            shards: (0..shards)
+                .map(|_| CachePadded(RwLock::new(HashMap::with_hasher(hasher.clone()))))
+                .collect(),
+            hasher,
+            shift: u64::BITS - shards.trailing_zeros(),
+        }
+    }
+
+    pub fn shard_count(&self) -> usize {
+        self.shards.len()
+    }
+
+    fn shard<Q>(&self, key: &Q) -> &RwLock<HashMap<K, V, S>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + ?Sized,
+    {
+        if self.shards.len() == 1 {
+            return &self.shards[0].0;
+        }
+
+        // hashbrown tags each entry with the top 7 bits of its hash, so the shard index
+        // comes from the bits below them. Taking it from the tag bits would give the keys
+        // of a shard similar tags and more false matches when probing.
+        let hash = self.hasher.hash_one(key) << 7;
+        &self.shards[(hash >> self.shift) as usize].0
+    }
+
+    /// Inserts a value, returning the value it replaced.
+    pub fn insert(&self, key: K, value: V) -> Option<V> {
+        self.shard(&key).write().insert(key, value)
+    }
+
+    pub fn remove<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.shard(key).write().remove(key)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.shard(key).read().contains_key(key)
+    }
+
+    /// Returns a clone of the value of `key`.
+    pub fn get<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        V: Clone,
+    {
+        self.shard(key).read().get(key).cloned()
+    }
+
+    /// Calls `f` with the value of `key` and returns its result.
+    pub fn get_with<Q, R>(&self, key: &Q, f: impl FnOnce(&V) -> R) -> Option<R>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.shard(key).read().get(key).map(f)
+    }
+
+    /// Calls `f` with the value of `key` to change it in place and returns its result.
+    pub fn update<Q, R>(&self, key: &Q, f: impl FnOnce(&mut V) -> R) -> Option<R>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.shard(key).write().get_mut(key).map(f)
+    }
+
+    /// Returns a clone of the value of `key`, inserting the result of `f` first if the key
+    /// is missing. Concurrent calls for the same key call `f` at most once.
+    pub fn get_or_insert_with(&self, key: K, f: impl FnOnce() -> V) -> V
+    where
+        V: Clone,
+    {
+        let shard = self.shard(&key);
+
+        if let Some(value) = shard.read().get(&key) {
+            return value.clone();
+        }
+
+        shard.write().entry(key).or_insert_with(f).clone()
+    }
+
+    /// Returns the number of entries, summed over the shards one at a time.
+    pub fn len(&self) -> usize {
+        self.shards.iter().map(|shard| shard.0.read().len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shards.iter().all(|shard| shard.0.read().is_empty())
+    }
+
+    pub fn clear(&self) {
+        for shard in self.shards.iter() {
+            shard.0.write().clear();
+        }
+    }
+
+    /// Keeps only the entries for which `f` returns true, one shard at a time.
+    pub fn retain(&self, mut f: impl FnMut(&K, &mut V) -> bool) {
+        for shard in self.shards.iter() {
+            shard.0.write().retain(&mut f);
+        }
+    }
+
+    /// Calls `f` with every entry, one shard at a time.
+    pub fn for_each(&self, mut f: impl FnMut(&K, &V)) {
+        for shard in self.shards.iter() {
+            for (key, value) in shard.0.read().iter() {
+                f(key, value);
+            }
+        }
+    }
+
+    /// Returns the entries as a `HashMap`, copied one shard at a time.
+    pub fn to_map(&self) -> HashMap<K, V>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        let mut map = HashMap::new();
+        self.for_each(|key, value| {
+            map.insert(key.clone(), value.clone());
+        });
+        map
+    }
+
+    /// Consumes the map and returns its entries as a `HashMap`.
+    pub fn into_map(self) -> HashMap<K, V> {
+        self.shards
+            .into_iter()
+            .flat_map(|shard| shard.0.into_inner())
+            .collect()
+    }
+}
+
+impl<K: Hash + Eq, V> FromIterator<(K, V)> for ShardedMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let map = ShardedMap::new();
+
+        for (key, value) in iter {
+            map.insert(key, value);
+        }
+
+        map
+    }
+}
+
+impl<K: Hash + Eq + fmt::Debug, V: fmt::Debug, S: BuildHasher + Clone> fmt::Debug
+    for ShardedMap<K, V, S>
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut map = f.debug_map();
+        self.for_each(|key, value| {
+            map.entry(key, value);
+        });
+        map.finish()
+    }
+}
@@ -0,0 +1,152 @@
+use std::cmp::Ordering;
+
+use super::{EdgeId, Graph, NodeId};
+use crate::bit_set::BitSet;
+
+const UNVISITED: usize = usize::MAX;
+
+/// Disjoint sets of node indexes, for growing a spanning forest.
+struct UnionFind {
+    parents: Vec<usize>,
+    sizes: Vec<usize>,
+}
+
+impl UnionFind {
+    fn new(len: usize) -> Self {
+        UnionFind {
+            parents: (0..len).collect(),
+            sizes: vec![1; len],
+        }
+    }
+
+    fn find(&mut self, mut index: usize) -> usize {
+        while self.parents[index] != index {
+            // Path halving: point every other node at its grandparent on the way up.
+            self.parents[index] = self.parents[self.parents[index]];
+            index = self.parents[index];
+        }
+
+        index
+    }
+
+    /// Joins the sets of `a` and `b`. Returns false if they were already joined.
+    fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+
+        if a == b {
+            return false;
+        }
+
+        if self.sizes[a] < self.sizes[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+
+        self.parents[b] = a;
+        self.sizes[a] += self.sizes[b];
+        true
+    }
+}
+
+impl<N, E> Graph<N, E> {
+    /// Groups the nodes into strongly connected components with Tarjan's algorithm, so
+    /// every node of a component can reach every other. Components come out in reverse
+    /// topological order: no edge leads from a component to one listed after it. In an
+    /// undirected graph these are the connected components.
+    pub fn strongly_connected_components(&self) -> Vec<Vec<NodeId>> {
+        let count = self.node_count();
+        let mut indexes = vec![UNVISITED; count];
+        let mut low_links = vec![UNVISITED; count];
+        let mut on_stack = BitSet::with_len(count);
+        let mut stack = Vec::new();
+        // The DFS path, with the index of the next edge to follow from each node.
+        let mut path: Vec<(NodeId, usize)> = Vec::new();
+        let mut components = Vec::new();
+        let mut counter = 0;
+
+        for root in self.node_ids() {
+            if indexes[root.index()] != UNVISITED {
+                continue;
+            }
+
+            indexes[root.index()] = counter;
+            low_links[root.index()] = counter;
+            counter += 1;
+            stack.push(root);
+            on_stack.set(root.index());
+            path.push((root, 0));
+
+            while let Some((node, next)) = path.last_mut() {
+                let node = *node;
+
+                if let Some(neighbor) = self.neighbor_at(node, *next) {
+                    *next += 1;
+
+                    if indexes[neighbor.index()] == UNVISITED {
+                        indexes[neighbor.index()] = counter;
+                        low_links[neighbor.index()] = counter;
+                        counter += 1;
+                        stack.push(neighbor);
+                        on_stack.set(neighbor.index());
+                        path.push((neighbor, 0));
+                    } else if on_stack.contains(neighbor.index()) {
+                        low_links[node.index()] =
+                            low_links[node.index()].min(indexes[neighbor.index()]);
+                    }
+
+                    continue;
+                }
+
+                path.pop();
+
+                if let Some((parent, _)) = path.last() {
+                    low_links[parent.index()] =
+                        low_links[parent.index()].min(low_links[node.index()]);
+                }
+
+                if low_links[node.index()] == indexes[node.index()] {
+                    let mut component = Vec::new();
+
+                    while let Some(member) = stack.pop() {
+                        on_stack.clear(member.index());
+                        component.push(member);
+
+                        if member == node {
+                            break;
+                        }
+                    }
+
+                    component.reverse();
+                    components.push(component);
+                }
+            }
+        }
+
+        components
+    }
+
+    /// Returns the edges of a minimum spanning forest, found with Kruskal's algorithm,
+    /// in order of cost. `cost` gives the cost of each edge. Edge directions are ignored,
+    /// and a graph with several connected components gets one tree for each.
+    pub fn minimum_spanning_tree<C: PartialOrd>(
+        &self,
+        mut cost: impl FnMut(&E) -> C,
+    ) -> Vec<EdgeId> {
+        let mut edges: Vec<_> = self
+            .edges()
+            .filter(|edge| edge.source != edge.target)
+            .map(|edge| (cost(edge.weight), edge))
+            .collect();
+        edges.sort_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+        let mut sets = UnionFind::new(self.node_count());
+        let mut tree = Vec::with_capacity(self.node_count().saturating_sub(1));
+
+        for (_, edge) in edges {
+            if sets.union(edge.source.index(), edge.target.index()) {
+                tree.push(edge.id);
+            }
+        }
+
+        tree
+    }
+}
@@ -0,0 +1,70 @@
+use std::fmt::{Display, Write};
+
+use super::Graph;
+
+/// Escapes a label for a double quoted DOT string.
+fn escape(label: &str) -> String {
+    let mut escaped = String::with_capacity(label.len());
+
+    for c in label.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            _ => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+impl<N, E> Graph<N, E> {
+    /// Writes the graph in the Graphviz DOT language, naming nodes by their index and
+    /// labelling nodes and edges with the given functions. Edges with an empty label get
+    /// no label attribute.
+    pub fn to_dot_with(
+        &self,
+        mut node_label: impl FnMut(&N) -> String,
+        mut edge_label: impl FnMut(&E) -> String,
+    ) -> String {
+        let (keyword, arrow) = if self.is_directed() {
+            ("digraph", "->")
+        } else {
+            ("graph", "--")
+        };
+        let mut dot = String::new();
+        // Writing to a `String` cannot fail.
+        let _ = writeln!(dot, "{} {{", keyword);
+
+        for (id, weight) in self.nodes() {
+            let _ = writeln!(
+                dot,
+                "    {} [label=\"{}\"];",
+                id,
+                escape(&node_label(weight))
+            );
+        }
+
+        for edge in self.edges() {
+            let label = edge_label(edge.weight);
+            let _ = write!(dot, "    {} {} {}", edge.source, arrow, edge.target);
+
+            if !label.is_empty() {
+                let _ = write!(dot, " [label=\"{}\"]", escape(&label));
+            }
+
+            dot.push_str(";\n");
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+impl<N: Display, E: Display> Graph<N, E> {
+    /// Writes the graph in the Graphviz DOT language, labelled with the `Display` output
+    /// of the node and edge weights.
+    pub fn to_dot(&self) -> String {
+        self.to_dot_with(|node| node.to_string(), |edge| edge.to_string())
+    }
+}
@@ -0,0 +1,8 @@
+mod components;
+mod dot;
+mod paths;
+pub use paths::*;
+mod structure;
+pub use structure::*;
+mod traversal;
+pub use traversal::*;
@@ -0,0 +1,191 @@
+use std::{cmp::Ordering, collections::BinaryHeap, ops::Add};
+
+use super::{Graph, NodeId};
+use crate::bit_set::BitSet;
+
+/// A node waiting in the priority queue, ordered so the cheapest is popped first.
+struct Candidate<C> {
+    priority: C,
+    node: NodeId,
+}
+
+impl<C: PartialOrd> PartialEq for Candidate<C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<C: PartialOrd> Eq for Candidate<C> {}
+
+impl<C: PartialOrd> PartialOrd for Candidate<C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<C: PartialOrd> Ord for Candidate<C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed to make `BinaryHeap` a min-heap. Incomparable costs such as NaN tie.
+        other
+            .priority
+            .partial_cmp(&self.priority)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| other.node.cmp(&self.node))
+    }
+}
+
+/// The cheapest paths from one node to every node reachable from it.
+#[derive(Clone, Debug)]
+pub struct ShortestPaths<C> {
+    start: NodeId,
+    distances: Vec<Option<C>>,
+    predecessors: Vec<Option<NodeId>>,
+}
+
+impl<C: Copy> ShortestPaths<C> {
+    pub fn start(&self) -> NodeId {
+        self.start
+    }
+
+    /// Returns the cost of the cheapest path to `node`, or `None` if it is unreachable.
+    pub fn distance(&self, node: NodeId) -> Option<C> {
+        self.distances.get(node.index()).copied().flatten()
+    }
+
+    /// Returns the nodes of the cheapest path from the start to `node`, both included.
+    pub fn path_to(&self, node: NodeId) -> Option<Vec<NodeId>> {
+        self.distance(node)?;
+        Some(walk_back(&self.predecessors, node))
+    }
+
+    /// Returns every reachable node with its distance.
+    pub fn reachable(&self) -> impl Iterator<Item = (NodeId, C)> {
+        self.distances
+            .iter()
+            .enumerate()
+            .filter_map(|(i, distance)| distance.map(|it| (NodeId::new(i), it)))
+    }
+}
+
+fn walk_back(predecessors: &[Option<NodeId>], node: NodeId) -> Vec<NodeId> {
+    let mut path = vec![node];
+    let mut current = node;
+
+    while let Some(previous) = predecessors[current.index()] {
+        path.push(previous);
+        current = previous;
+    }
+
+    path.reverse();
+    path
+}
+
+impl<N, E> Graph<N, E> {
+    /// Finds the cheapest paths from `start` with Dijkstra's algorithm. `cost` gives the
+    /// cost of each edge and must not return a negative value; `C::default()` is zero.
+    pub fn dijkstra<C>(&self, start: NodeId, mut cost: impl FnMut(&E) -> C) -> ShortestPaths<C>
+    where
+        C: Copy + PartialOrd + Add<Output = C> + Default,
+    {
+        let count = self.node_count();
+        let mut distances = vec![None; count];
+        let mut predecessors = vec![None; count];
+        let mut settled = BitSet::with_len(count);
+        let mut queue = BinaryHeap::new();
+        distances[start.index()] = Some(C::default());
+        queue.push(Candidate {
+            priority: C::default(),
+            node: start,
+        });
+
+        while let Some(Candidate { priority, node }) = queue.pop() {
+            if settled.set(node.index()) {
+                continue;
+            }
+
+            for edge in self.edges_of(node) {
+                let next = edge.target;
+
+                if settled.contains(next.index()) {
+                    continue;
+                }
+
+                let distance = priority + cost(edge.weight);
+
+                if distances[next.index()].is_none_or(|it| distance < it) {
+                    distances[next.index()] = Some(distance);
+                    predecessors[next.index()] = Some(node);
+                    queue.push(Candidate {
+                        priority: distance,
+                        node: next,
+                    });
+                }
+            }
+        }
+
+        ShortestPaths {
+            start,
+            distances,
+            predecessors,
+        }
+    }
+
+    /// Finds the cheapest path from `start` to `goal` with A*, returning its cost and
+    /// nodes. `heuristic` estimates the remaining cost from a node to `goal`; it must
+    /// never overestimate it, nor drop by more than an edge costs along that edge, or
+    /// the path found may not be the cheapest.
+    pub fn astar<C>(
+        &self,
+        start: NodeId,
+        goal: NodeId,
+        mut cost: impl FnMut(&E) -> C,
+        mut heuristic: impl FnMut(NodeId) -> C,
+    ) -> Option<(C, Vec<NodeId>)>
+    where
+        C: Copy + PartialOrd + Add<Output = C> + Default,
+    {
+        let count = self.node_count();
+        let mut distances: Vec<Option<C>> = vec![None; count];
+        let mut predecessors = vec![None; count];
+        let mut settled = BitSet::with_len(count);
+        let mut queue = BinaryHeap::new();
+        distances[start.index()] = Some(C::default());
+        queue.push(Candidate {
+            priority: heuristic(start),
+            node: start,
+        });
+
+        while let Some(Candidate { node, .. }) = queue.pop() {
+            if settled.set(node.index()) {
+                continue;
+            }
+
+            let distance = distances[node.index()]?;
+
+            if node == goal {
+                return Some((distance, walk_back(&predecessors, goal)));
+            }
+
+            for edge in self.edges_of(node) {
+                let next = edge.target;
+
+                if settled.contains(next.index()) {
+                    continue;
+                }
+
+                let next_distance = distance + cost(edge.weight);
+
+                if distances[next.index()].is_none_or(|it| next_distance < it) {
+                    distances[next.index()] = Some(next_distance);
+                    predecessors[next.index()] = Some(node);
+                    queue.push(Candidate {
+                        priority: next_distance + heuristic(next),
+                        node: next,
+                    });
+                }
+            }
+        }
+
+        None
+    }
+}
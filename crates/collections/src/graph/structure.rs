@@ -0,0 +1,345 @@
+use std::{
+    fmt,
+    ops::{Index, IndexMut},
+};
+
+/// Identifies a node of a `Graph`. Ids are indexes in the order nodes were added.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(usize);
+
+impl NodeId {
+    pub fn new(index: usize) -> Self {
+        NodeId(index)
+    }
+
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+impl fmt::Display for NodeId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Identifies an edge of a `Graph`. Ids are indexes in the order edges were added.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct EdgeId(usize);
+
+impl EdgeId {
+    pub fn new(index: usize) -> Self {
+        EdgeId(index)
+    }
+
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+impl fmt::Display for EdgeId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GraphKind {
+    #[default]
+    Directed,
+    Undirected,
+}
+
+/// Which edges of a node to follow in a directed graph. Undirected graphs ignore it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Outgoing,
+    Incoming,
+}
+
+/// An edge as seen from one of its nodes. For undirected edges `source` is the node it
+/// was reached from.
+#[derive(Debug, PartialEq, Eq)]
+pub struct EdgeRef<'a, E> {
+    pub id: EdgeId,
+    pub source: NodeId,
+    pub target: NodeId,
+    pub weight: &'a E,
+}
+
+impl<E> Clone for EdgeRef<'_, E> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<E> Copy for EdgeRef<'_, E> {}
+
+#[derive(Clone, Debug)]
+struct Node<N> {
+    weight: N,
+    /// Edges leaving the node, or every edge of the node in an undirected graph.
+    outgoing: Vec<EdgeId>,
+    /// Edges entering the node. Unused in an undirected graph.
+    incoming: Vec<EdgeId>,
+}
+
+#[derive(Clone, Debug)]
+struct Edge<E> {
+    source: NodeId,
+    target: NodeId,
+    weight: E,
+}
+
+/// A directed or undirected graph with node weights `N` and edge weights `E`, stored as
+/// adjacency lists.
+///
+/// Nodes and edges are never removed, so their ids stay valid for the life of the graph.
+/// Parallel edges and self-loops are allowed. Methods that take an id panic if it does
+/// not belong to the graph, except the `Option` returning lookups.
+#[derive(Clone, Debug)]
+pub struct Graph<N, E> {
+    kind: GraphKind,
+    nodes: Vec<Node<N>>,
+    edges: Vec<Edge<E>>,
+}
+
+impl<N, E> Default for Graph<N, E> {
+    fn default() -> Self {
+        Self::new(GraphKind::Directed)
+    }
+}
+
+impl<N, E> Graph<N, E> {
+    pub fn new(kind: GraphKind) -> Self {
+        Graph {
+            kind,
+            nodes: Vec::new(),
+            edges: Vec::new(),
+        }
+    }
+
+    pub fn directed() -> Self {
+        Self::new(GraphKind::Directed)
+    }
+
+    pub fn undirected() -> Self {
+        Self::new(GraphKind::Undirected)
+    }
+
+    pub fn kind(&self) -> GraphKind {
+        self.kind
+    }
+
+    pub fn is_directed(&self) -> bool {
+        self.kind == GraphKind::Directed
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn add_node(&mut self, weight: N) -> NodeId {
+        self.nodes.push(Node {
+            weight,
+            outgoing: Vec::new(),
+            incoming: Vec::new(),
+        });
+        NodeId(self.nodes.len() - 1)
+    }
+
+    /// Adds an edge from `source` to `target`, or between them in an undirected graph.
+    pub fn add_edge(&mut self, source: NodeId, target: NodeId, weight: E) -> EdgeId {
+        assert!(
+            source.0 < self.nodes.len() && target.0 < self.nodes.len(),
+            "Edge {} -> {} refers to a missing node",
+            source,
+            target
+        );
+        let id = EdgeId(self.edges.len());
+        self.edges.push(Edge {
+            source,
+            target,
+            weight,
+        });
+        self.nodes[source.0].outgoing.push(id);
+
+        match self.kind {
+            GraphKind::Directed => self.nodes[target.0].incoming.push(id),
+            GraphKind::Undirected if source != target => self.nodes[target.0].outgoing.push(id),
+            GraphKind::Undirected => {}
+        }
+
+        id
+    }
+
+    pub fn node(&self, id: NodeId) -> Option<&N> {
+        self.nodes.get(id.0).map(|node| &node.weight)
+    }
+
+    pub fn node_mut(&mut self, id: NodeId) -> Option<&mut N> {
+        self.nodes.get_mut(id.0).map(|node| &mut node.weight)
+    }
+
+    pub fn edge(&self, id: EdgeId) -> Option<&E> {
+        self.edges.get(id.0).map(|edge| &edge.weight)
+    }
+
+    pub fn edge_mut(&mut self, id: EdgeId) -> Option<&mut E> {
+        self.edges.get_mut(id.0).map(|edge| &mut edge.weight)
+    }
+
+    /// Returns the source and target of an edge, as they were added.
+    pub fn endpoints(&self, id: EdgeId) -> Option<(NodeId, NodeId)> {
+        self.edges.get(id.0).map(|edge| (edge.source, edge.target))
+    }
+
+    pub fn node_ids(&self) -> impl DoubleEndedIterator<Item = NodeId> + use<N, E> {
+        (0..self.nodes.len()).map(NodeId)
+    }
+
+    pub fn nodes(&self) -> impl DoubleEndedIterator<Item = (NodeId, &N)> {
+        self.nodes
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (NodeId(i), &node.weight))
+    }
+
+    /// Returns every edge once, as it was added.
+    pub fn edges(&self) -> impl DoubleEndedIterator<Item = EdgeRef<'_, E>> {
+        self.edges.iter().enumerate().map(|(i, edge)| EdgeRef {
+            id: EdgeId(i),
+            source: edge.source,
+            target: edge.target,
+            weight: &edge.weight,
+        })
+    }
+
+    /// Returns the edges leaving `node`, or every edge of `node` in an undirected graph,
+    /// with `source` set to `node`.
+    pub fn edges_of(&self, node: NodeId) -> impl DoubleEndedIterator<Item = EdgeRef<'_, E>> {
+        self.edges_directed(node, Direction::Outgoing)
+    }
+
+    /// Returns the edges of `node` in `direction`. Incoming edges have `target` set to
+    /// `node`, outgoing edges have `source` set to it.
+    pub fn edges_directed(
+        &self,
+        node: NodeId,
+        direction: Direction,
+    ) -> impl DoubleEndedIterator<Item = EdgeRef<'_, E>> {
+        let incoming = direction == Direction::Incoming && self.is_directed();
+        let list = if incoming {
+            &self.nodes[node.0].incoming
+        } else {
+            &self.nodes[node.0].outgoing
+        };
+
+        list.iter().map(move |&id| {
+            let edge = &self.edges[id.0];
+            let other = if edge.source == node {
+                edge.target
+            } else {
+                edge.source
+            };
+            let (source, target) = if incoming {
+                (other, node)
+            } else {
+                (node, other)
+            };
+
+            EdgeRef {
+                id,
+                source,
+                target,
+                weight: &edge.weight,
+            }
+        })
+    }
+
+    /// Returns the nodes reachable from `node` over one edge, once per edge.
+    pub fn neighbors(&self, node: NodeId) -> impl DoubleEndedIterator<Item = NodeId> {
+        self.edges_of(node).map(|edge| edge.target)
+    }
+
+    /// Returns the nodes at the other end of the edges of `node` in `direction`.
+    pub fn neighbors_directed(
+        &self,
+        node: NodeId,
+        direction: Direction,
+    ) -> impl DoubleEndedIterator<Item = NodeId> {
+        self.edges_directed(node, direction)
+            .map(move |edge| match direction {
+                Direction::Incoming if self.is_directed() => edge.source,
+                _ => edge.target,
+            })
+    }
+
+    /// Returns the number of edges of `node` in `direction`.
+    pub fn degree(&self, node: NodeId, direction: Direction) -> usize {
+        match direction {
+            Direction::Incoming if self.is_directed() => self.nodes[node.0].incoming.len(),
+            _ => self.nodes[node.0].outgoing.len(),
+        }
+    }
+
+    /// Returns the first edge from `source` to `target`, or between them in an undirected
+    /// graph.
+    pub fn find_edge(&self, source: NodeId, target: NodeId) -> Option<EdgeId> {
+        self.edges_of(source)
+            .find(|edge| edge.target == target)
+            .map(|edge| edge.id)
+    }
+
+    pub fn contains_edge(&self, source: NodeId, target: NodeId) -> bool {
+        self.find_edge(source, target).is_some()
+    }
+
+    /// Returns the `index`th neighbor of `node` without building an iterator, for the
+    /// algorithms that walk adjacency lists with an explicit stack.
+    pub(super) fn neighbor_at(&self, node: NodeId, index: usize) -> Option<NodeId> {
+        let id = self.nodes[node.0].outgoing.get(index)?;
+        let edge = &self.edges[id.0];
+        Some(if edge.source == node {
+            edge.target
+        } else {
+            edge.source
+        })
+    }
+}
+
+impl<N, E> Index<NodeId> for Graph<N, E> {
+    type Output = N;
+
+    fn index(&self, id: NodeId) -> &N {
+        &self.nodes[id.0].weight
+    }
+}
+
+impl<N, E> IndexMut<NodeId> for Graph<N, E> {
+    fn index_mut(&mut self, id: NodeId) -> &mut N {
+        &mut self.nodes[id.0].weight
+    }
+}
+
+impl<N, E> Index<EdgeId> for Graph<N, E> {
+    type Output = E;
+
+    fn index(&self, id: EdgeId) -> &E {
+        &self.edges[id.0].weight
+    }
+}
+
+impl<N, E> IndexMut<EdgeId> for Graph<N, E> {
+    fn index_mut(&mut self, id: EdgeId) -> &mut E {
+        &mut self.edges[id.0].weight
+    }
+}
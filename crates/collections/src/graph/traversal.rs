@@ -0,0 +1,157 @@
+use std::{collections::VecDeque, fmt};
+
+use super::{Graph, NodeId};
+use crate::{Error, bit_set::BitSet};
+
+/// Visits the nodes reachable from a start node in breadth-first order.
+pub struct Bfs<'a, N, E> {
+    graph: &'a Graph<N, E>,
+    queue: VecDeque<NodeId>,
+    visited: BitSet,
+}
+
+impl<N, E> Iterator for Bfs<'_, N, E> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<NodeId> {
+        let node = self.queue.pop_front()?;
+
+        for next in self.graph.neighbors(node) {
+            if !self.visited.set(next.index()) {
+                self.queue.push_back(next);
+            }
+        }
+
+        Some(node)
+    }
+}
+
+/// Visits the nodes reachable from a start node in depth-first preorder, following the
+/// edges of each node in the order they were added.
+pub struct Dfs<'a, N, E> {
+    graph: &'a Graph<N, E>,
+    stack: Vec<NodeId>,
+    visited: BitSet,
+}
+
+impl<N, E> Iterator for Dfs<'_, N, E> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<NodeId> {
+        loop {
+            let node = self.stack.pop()?;
+
+            if self.visited.set(node.index()) {
+                continue;
+            }
+
+            self.stack.extend(
+                self.graph
+                    .neighbors(node)
+                    .rev()
+                    .filter(|next| !self.visited.contains(next.index())),
+            );
+            return Some(node);
+        }
+    }
+}
+
+/// A cycle that prevents a topological order. Each node has an edge to the next, and
+/// the last node has an edge back to the first.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cycle {
+    pub nodes: Vec<NodeId>,
+}
+
+impl fmt::Display for Cycle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Cycle through nodes ")?;
+
+        for node in &self.nodes {
+            write!(f, "{} -> ", node)?;
+        }
+
+        match self.nodes.first() {
+            Some(first) => write!(f, "{}", first),
+            None => Ok(()),
+        }
+    }
+}
+
+impl std::error::Error for Cycle {}
+
+impl From<Cycle> for Error {
+    fn from(cycle: Cycle) -> Self {
+        Error::InvalidOperation(cycle.to_string())
+    }
+}
+
+impl<N, E> Graph<N, E> {
+    pub fn bfs(&self, start: NodeId) -> Bfs<'_, N, E> {
+        let mut visited = BitSet::with_len(self.node_count());
+        visited.set(start.index());
+        Bfs {
+            graph: self,
+            queue: VecDeque::from([start]),
+            visited,
+        }
+    }
+
+    pub fn dfs(&self, start: NodeId) -> Dfs<'_, N, E> {
+        Dfs {
+            graph: self,
+            stack: vec![start],
+            visited: BitSet::with_len(self.node_count()),
+        }
+    }
+
+    /// Orders the nodes so every edge goes from an earlier node to a later one, such as
+    /// jobs after the jobs they depend on. Returns a cycle if there is no such order. In
+    /// an undirected graph every edge is a cycle of its two nodes.
+    pub fn topological_sort(&self) -> Result<Vec<NodeId>, Cycle> {
+        let count = self.node_count();
+        let mut done = BitSet::with_len(count);
+        let mut on_path = BitSet::with_len(count);
+        let mut order = Vec::with_capacity(count);
+        // The path from the current root, with the index of the next edge to follow.
+        let mut path: Vec<(NodeId, usize)> = Vec::new();
+
+        for root in self.node_ids() {
+            if done.contains(root.index()) {
+                continue;
+            }
+
+            path.push((root, 0));
+            on_path.set(root.index());
+
+            while let Some((node, next)) = path.last_mut() {
+                let node = *node;
+
+                let Some(neighbor) = self.neighbor_at(node, *next) else {
+                    path.pop();
+                    on_path.clear(node.index());
+                    done.set(node.index());
+                    order.push(node);
+                    continue;
+                };
+
+                *next += 1;
+
+                if on_path.contains(neighbor.index()) {
+                    let start = path.iter().position(|(it, _)| *it == neighbor).unwrap_or(0);
+                    return Err(Cycle {
+                        nodes: path[start..].iter().map(|(it, _)| *it).collect(),
+                    });
+                }
+
+                if !done.contains(neighbor.index()) {
+                    on_path.set(neighbor.index());
+                    path.push((neighbor, 0));
+                }
+            }
+        }
+
+        order.reverse();
+        Ok(order)
+    }
+}
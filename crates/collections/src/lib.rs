@@ -1,4 +1,10 @@
 pub mod bit_helper;
+pub mod bit_set;
+pub mod bit_stream;
+pub mod cache;
+pub mod concurrent;
+pub mod graph;
+pub mod probabilistic;
 pub mod range;
 
 pub use emixcore::{Error, Result};
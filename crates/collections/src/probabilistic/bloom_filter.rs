@@ -0,0 +1,124 @@
+use std::hash::Hash;
+
+use super::{bloom_size, check_size, indexes};
+use crate::{Error, Result, bit_set::BitSet};
+
+/// A set that answers "possibly present" or "definitely absent" in fixed space.
+///
+/// Items are never stored, only the bits their hashes select, so `contains` may return
+/// false positives but never false negatives. Filters built with the same size can be
+/// merged, and their state can be persisted with serde.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "BloomFilterRepr")
+)]
+pub struct BloomFilter {
+    bits: BitSet,
+    hashes: u32,
+}
+
+impl BloomFilter {
+    /// Creates a filter sized to hold `items` items with a false positive rate of `rate`.
+    pub fn new(items: usize, rate: f64) -> Result<Self> {
+        let (bits, hashes) = bloom_size(items, rate)?;
+        Self::with_size(bits, hashes)
+    }
+
+    /// Creates a filter of `bits` bits that sets `hashes` bits per item.
+    pub fn with_size(bits: usize, hashes: u32) -> Result<Self> {
+        check_size(bits, hashes)?;
+        Ok(Self {
+            bits: BitSet::with_len(bits),
+            hashes,
+        })
+    }
+
+    /// Returns the number of bits.
+    pub fn bit_len(&self) -> usize {
+        self.bits.len()
+    }
+
+    /// Returns the number of bits set per item.
+    pub fn hashes(&self) -> u32 {
+        self.hashes
+    }
+
+    /// Returns true if no item was inserted.
+    pub fn is_empty(&self) -> bool {
+        !self.bits.any()
+    }
+
+    /// Adds an item. Returns true if it was definitely not in the filter before.
+    pub fn insert<T: Hash + ?Sized>(&mut self, item: &T) -> bool {
+        let mut added = false;
+
+        for index in indexes(item, self.hashes, self.bits.len()) {
+            added |= !self.bits.set(index);
+        }
+
+        added
+    }
+
+    /// Returns true if the item may have been inserted, and false if it definitely was
+    /// not.
+    pub fn contains<T: Hash + ?Sized>(&self, item: &T) -> bool {
+        indexes(item, self.hashes, self.bits.len()).all(|index| self.bits.contains(index))
+    }
+
+    /// Estimates the number of distinct items inserted.
+    pub fn estimated_len(&self) -> f64 {
+        let bits = self.bits.len() as f64;
+        let set = self.bits.count_ones() as f64;
+
+        if set >= bits {
+            return f64::INFINITY;
+        }
+
+        -bits / self.hashes as f64 * (1.0 - set / bits).ln()
+    }
+
+    /// Estimates the current false positive rate from the share of bits set.
+    pub fn false_positive_rate(&self) -> f64 {
+        let share = self.bits.count_ones() as f64 / self.bits.len() as f64;
+        share.powi(self.hashes as i32)
+    }
+
+    /// Removes every item.
+    pub fn clear(&mut self) {
+        self.bits.clear_all();
+    }
+
+    /// Adds every item of `other`, which must have the same size and number of hashes.
+    pub fn merge(&mut self, other: &BloomFilter) -> Result<()> {
+        if self.bits.len() != other.bits.len() || self.hashes != other.hashes {
+            return Err(Error::InvalidOperation(
+                "Cannot merge Bloom filters of different sizes".to_string(),
+            ));
+        }
+
+        self.bits.union_with(&other.bits);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct BloomFilterRepr {
+    bits: BitSet,
+    hashes: u32,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<BloomFilterRepr> for BloomFilter {
+    type Error = Error;
+
+    fn try_from(repr: BloomFilterRepr) -> Result<Self> {
+        check_size(repr.bits.len(), repr.hashes)?;
+        Ok(Self {
+            bits: repr.bits,
+            hashes: repr.hashes,
+        })
+    }
+}
@@ -0,0 +1,157 @@
+use std::hash::Hash;
+
+use super::{check_size, indexes};
+use crate::{Error, Result};
+
+/// Estimates how often each item occurs in a stream in fixed space.
+///
+/// Estimates never undercount; with a sketch built by `new(epsilon, delta)` they
+/// overcount by more than `epsilon` times the total count with probability at most
+/// `delta`. To track heavy hitters, keep the items whose estimate returned by `add`
+/// crosses a share of `total`. Sketches of the same size can be merged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "CountMinSketchRepr")
+)]
+pub struct CountMinSketch {
+    width: usize,
+    depth: u32,
+    /// `depth` rows of `width` counters.
+    counters: Vec<u64>,
+    total: u64,
+}
+
+impl CountMinSketch {
+    /// Creates a sketch whose estimates are within `epsilon` times the total count with
+    /// probability `1 - delta`.
+    pub fn new(epsilon: f64, delta: f64) -> Result<Self> {
+        if !(epsilon > 0.0 && epsilon < 1.0 && delta > 0.0 && delta < 1.0) {
+            return Err(Error::Argument(format!(
+                "Epsilon and delta must be between 0 and 1, got {} and {}",
+                epsilon, delta
+            )));
+        }
+
+        let width = (std::f64::consts::E / epsilon).ceil() as usize;
+        let depth = (1.0 / delta).ln().ceil().max(1.0) as u32;
+        Self::with_size(width, depth)
+    }
+
+    /// Creates a sketch of `depth` rows of `width` counters.
+    pub fn with_size(width: usize, depth: u32) -> Result<Self> {
+        check_size(width, depth)?;
+        Ok(Self {
+            width,
+            depth,
+            counters: vec![0; width * depth as usize],
+            total: 0,
+        })
+    }
+
+    /// Returns the number of counters per row.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the number of rows.
+    pub fn depth(&self) -> u32 {
+        self.depth
+    }
+
+    /// Returns the sum of all counts added.
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// Returns true if nothing was added.
+    pub fn is_empty(&self) -> bool {
+        self.total == 0
+    }
+
+    /// Counts one occurrence of an item. Returns its new estimate.
+    pub fn insert<T: Hash + ?Sized>(&mut self, item: &T) -> u64 {
+        self.add(item, 1)
+    }
+
+    /// Counts `count` occurrences of an item. Returns its new estimate.
+    pub fn add<T: Hash + ?Sized>(&mut self, item: &T, count: u64) -> u64 {
+        let mut estimate = u64::MAX;
+
+        for (row, index) in indexes(item, self.depth, self.width).enumerate() {
+            let counter = &mut self.counters[row * self.width + index];
+            *counter = counter.saturating_add(count);
+            estimate = estimate.min(*counter);
+        }
+
+        self.total = self.total.saturating_add(count);
+        estimate
+    }
+
+    /// Returns an upper bound on the count of an item.
+    pub fn estimate<T: Hash + ?Sized>(&self, item: &T) -> u64 {
+        indexes(item, self.depth, self.width)
+            .enumerate()
+            .map(|(row, index)| self.counters[row * self.width + index])
+            .min()
+            .unwrap_or(0)
+    }
+
+    /// Resets every count.
+    pub fn clear(&mut self) {
+        self.counters.fill(0);
+        self.total = 0;
+    }
+
+    /// Adds the counts of `other`, which must have the same size.
+    pub fn merge(&mut self, other: &CountMinSketch) -> Result<()> {
+        if self.width != other.width || self.depth != other.depth {
+            return Err(Error::InvalidOperation(
+                "Cannot merge Count-Min sketches of different sizes".to_string(),
+            ));
+        }
+
+        for (a, b) in self.counters.iter_mut().zip(&other.counters) {
+            *a = a.saturating_add(*b);
+        }
+
+        self.total = self.total.saturating_add(other.total);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct CountMinSketchRepr {
+    width: usize,
+    depth: u32,
+    counters: Vec<u64>,
+    total: u64,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<CountMinSketchRepr> for CountMinSketch {
+    type Error = Error;
+
+    fn try_from(repr: CountMinSketchRepr) -> Result<Self> {
+        check_size(repr.width, repr.depth)?;
+
+        if repr.counters.len() != repr.width * repr.depth as usize {
+            return Err(Error::InvalidInput(format!(
+                "Expected {} counters for {} rows of {}, found {}",
+                repr.width * repr.depth as usize,
+                repr.depth,
+                repr.width,
+                repr.counters.len()
+            )));
+        }
+
+        Ok(Self {
+            width: repr.width,
+            depth: repr.depth,
+            counters: repr.counters,
+            total: repr.total,
+        })
+    }
+}
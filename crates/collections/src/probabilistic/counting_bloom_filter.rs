@@ -0,0 +1,128 @@
+use std::hash::Hash;
+
+use super::{bloom_size, check_size, indexes};
+use crate::{Error, Result};
+
+/// A Bloom filter that keeps a counter per slot so items can be removed.
+///
+/// Counters saturate at 255 and then stay there, so an item whose slots all saturated can
+/// no longer be removed completely. Removing an item that was never inserted can cause
+/// false negatives for other items, so `remove` only acts on items the filter may contain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "CountingBloomFilterRepr")
+)]
+pub struct CountingBloomFilter {
+    counters: Vec<u8>,
+    hashes: u32,
+}
+
+impl CountingBloomFilter {
+    /// Creates a filter sized to hold `items` items with a false positive rate of `rate`.
+    pub fn new(items: usize, rate: f64) -> Result<Self> {
+        let (slots, hashes) = bloom_size(items, rate)?;
+        Self::with_size(slots, hashes)
+    }
+
+    /// Creates a filter of `slots` counters that updates `hashes` counters per item.
+    pub fn with_size(slots: usize, hashes: u32) -> Result<Self> {
+        check_size(slots, hashes)?;
+        Ok(Self {
+            counters: vec![0; slots],
+            hashes,
+        })
+    }
+
+    /// Returns the number of counters.
+    pub fn slot_len(&self) -> usize {
+        self.counters.len()
+    }
+
+    /// Returns the number of counters updated per item.
+    pub fn hashes(&self) -> u32 {
+        self.hashes
+    }
+
+    /// Returns true if no item is in the filter.
+    pub fn is_empty(&self) -> bool {
+        self.counters.iter().all(|&c| c == 0)
+    }
+
+    /// Adds an item.
+    pub fn insert<T: Hash + ?Sized>(&mut self, item: &T) {
+        for index in indexes(item, self.hashes, self.counters.len()) {
+            self.counters[index] = self.counters[index].saturating_add(1);
+        }
+    }
+
+    /// Removes an item. Returns false, changing nothing, if the item is definitely not in
+    /// the filter.
+    pub fn remove<T: Hash + ?Sized>(&mut self, item: &T) -> bool {
+        if !self.contains(item) {
+            return false;
+        }
+
+        for index in indexes(item, self.hashes, self.counters.len()) {
+            if self.counters[index] < u8::MAX {
+                self.counters[index] -= 1;
+            }
+        }
+
+        true
+    }
+
+    /// Returns true if the item may be in the filter, and false if it definitely is not.
+    pub fn contains<T: Hash + ?Sized>(&self, item: &T) -> bool {
+        self.count(item) > 0
+    }
+
+    /// Returns an upper bound on how many times the item was inserted and not removed.
+    pub fn count<T: Hash + ?Sized>(&self, item: &T) -> u8 {
+        indexes(item, self.hashes, self.counters.len())
+            .map(|index| self.counters[index])
+            .min()
+            .unwrap_or(0)
+    }
+
+    /// Removes every item.
+    pub fn clear(&mut self) {
+        self.counters.fill(0);
+    }
+
+    /// Adds every item of `other`, which must have the same size and number of hashes.
+    pub fn merge(&mut self, other: &CountingBloomFilter) -> Result<()> {
+        if self.counters.len() != other.counters.len() || self.hashes != other.hashes {
+            return Err(Error::InvalidOperation(
+                "Cannot merge counting Bloom filters of different sizes".to_string(),
+            ));
+        }
+
+        for (a, b) in self.counters.iter_mut().zip(&other.counters) {
+            *a = a.saturating_add(*b);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct CountingBloomFilterRepr {
+    counters: Vec<u8>,
+    hashes: u32,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<CountingBloomFilterRepr> for CountingBloomFilter {
+    type Error = Error;
+
+    fn try_from(repr: CountingBloomFilterRepr) -> Result<Self> {
+        check_size(repr.counters.len(), repr.hashes)?;
+        Ok(Self {
+            counters: repr.counters,
+            hashes: repr.hashes,
+        })
+    }
+}
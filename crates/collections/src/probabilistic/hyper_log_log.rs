@@ -0,0 +1,162 @@
+use std::hash::Hash;
+
+use super::hash;
+use crate::{Error, Result};
+
+/// Lowest supported precision.
+pub const HLL_PRECISION_MIN: u8 = 4;
+/// Highest supported precision.
+pub const HLL_PRECISION_MAX: u8 = 18;
+
+/// Estimates the number of distinct items in a stream using `2^precision` bytes.
+///
+/// The standard error is about `1.04 / sqrt(2^precision)`, so precision 14 (16 KiB)
+/// estimates within about 0.8%. Sketches with the same precision can be merged to count
+/// the distinct items of the combined streams.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "HyperLogLogRepr")
+)]
+pub struct HyperLogLog {
+    precision: u8,
+    registers: Vec<u8>,
+}
+
+impl HyperLogLog {
+    /// Creates a sketch with `2^precision` registers, for a precision between
+    /// `HLL_PRECISION_MIN` and `HLL_PRECISION_MAX`.
+    pub fn new(precision: u8) -> Result<Self> {
+        check_precision(precision)?;
+        Ok(Self {
+            precision,
+            registers: vec![0; 1 << precision],
+        })
+    }
+
+    /// Creates the smallest sketch whose standard error is at most `error`.
+    pub fn with_error(error: f64) -> Result<Self> {
+        if !(error > 0.0 && error < 1.0) {
+            return Err(Error::Argument(format!(
+                "The standard error must be between 0 and 1, got {}",
+                error
+            )));
+        }
+
+        let registers = (1.04 / error).powi(2);
+        let precision = registers.log2().ceil().max(HLL_PRECISION_MIN as f64);
+        Self::new(precision.min(u8::MAX as f64) as u8)
+    }
+
+    /// Returns the precision.
+    pub fn precision(&self) -> u8 {
+        self.precision
+    }
+
+    /// Returns the expected relative error of `count`.
+    pub fn standard_error(&self) -> f64 {
+        1.04 / (self.registers.len() as f64).sqrt()
+    }
+
+    /// Returns true if no item was inserted.
+    pub fn is_empty(&self) -> bool {
+        self.registers.iter().all(|&r| r == 0)
+    }
+
+    /// Adds an item.
+    pub fn insert<T: Hash + ?Sized>(&mut self, item: &T) {
+        let hash = hash(item);
+        let index = (hash >> (64 - self.precision)) as usize;
+        // A guard bit caps the rank when the remaining bits are all zero.
+        let rest = (hash << self.precision) | (1 << (self.precision - 1));
+        let rank = rest.leading_zeros() as u8 + 1;
+        self.registers[index] = self.registers[index].max(rank);
+    }
+
+    /// Estimates the number of distinct items inserted.
+    pub fn count(&self) -> u64 {
+        let m = self.registers.len() as f64;
+        let alpha = match self.registers.len() {
+            16 => 0.673,
+            32 => 0.697,
+            64 => 0.709,
+            _ => 0.7213 / (1.0 + 1.079 / m),
+        };
+        let sum: f64 = self
+            .registers
+            .iter()
+            .map(|&r| 1.0 / (1u64 << r) as f64)
+            .sum();
+        let estimate = alpha * m * m / sum;
+        let zeros = self.registers.iter().filter(|&&r| r == 0).count();
+
+        if estimate <= 2.5 * m && zeros > 0 {
+            // Linear counting is more accurate while many registers are still empty.
+            (m * (m / zeros as f64).ln()).round() as u64
+        } else {
+            estimate.round() as u64
+        }
+    }
+
+    /// Removes every item.
+    pub fn clear(&mut self) {
+        self.registers.fill(0);
+    }
+
+    /// Adds every item of `other`, which must have the same precision.
+    pub fn merge(&mut self, other: &HyperLogLog) -> Result<()> {
+        if self.precision != other.precision {
+            return Err(Error::InvalidOperation(
+                "Cannot merge HyperLogLog sketches of different precisions".to_string(),
+            ));
+        }
+
+        for (a, b) in self.registers.iter_mut().zip(&other.registers) {
+            *a = (*a).max(*b);
+        }
+
+        Ok(())
+    }
+}
+
+fn check_precision(precision: u8) -> Result<()> {
+    if (HLL_PRECISION_MIN..=HLL_PRECISION_MAX).contains(&precision) {
+        Ok(())
+    } else {
+        Err(Error::Argument(format!(
+            "The precision must be between {} and {}, got {}",
+            HLL_PRECISION_MIN, HLL_PRECISION_MAX, precision
+        )))
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct HyperLogLogRepr {
+    precision: u8,
+    registers: Vec<u8>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<HyperLogLogRepr> for HyperLogLog {
+    type Error = Error;
+
+    fn try_from(repr: HyperLogLogRepr) -> Result<Self> {
+        check_precision(repr.precision)?;
+
+        if repr.registers.len() != 1 << repr.precision {
+            return Err(Error::InvalidInput(format!(
+                "Expected {} registers for precision {}, found {}",
+                1 << repr.precision,
+                repr.precision,
+                repr.registers.len()
+            )));
+        }
+
+        Ok(Self {
+            precision: repr.precision,
+            registers: repr.registers,
+        })
+    }
+}
@@ -0,0 +1,126 @@
+mod bloom_filter;
+pub use bloom_filter::*;
+mod count_min_sketch;
+pub use count_min_sketch::*;
+mod counting_bloom_filter;
+pub use counting_bloom_filter::*;
+mod hyper_log_log;
+pub use hyper_log_log::*;
+
+use std::hash::{Hash, Hasher};
+
+use crate::{Error, Result};
+
+const FNV_OFFSET: u64 = 0xCBF2_9CE4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01B3;
+
+/// FNV-1a with a final avalanche step. Unlike `DefaultHasher`, its output is the same
+/// across Rust versions and platforms, so sketches stay valid when persisted.
+struct StableHasher(u64);
+
+impl Hasher for StableHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ byte as u64).wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    fn write_u16(&mut self, value: u16) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_u32(&mut self, value: u32) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_u128(&mut self, value: u128) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, value: usize) {
+        self.write_u64(value as u64);
+    }
+
+    fn write_i16(&mut self, value: i16) {
+        self.write_u16(value as u16);
+    }
+
+    fn write_i32(&mut self, value: i32) {
+        self.write_u32(value as u32);
+    }
+
+    fn write_i64(&mut self, value: i64) {
+        self.write_u64(value as u64);
+    }
+
+    fn write_i128(&mut self, value: i128) {
+        self.write_u128(value as u128);
+    }
+
+    fn write_isize(&mut self, value: isize) {
+        self.write_u64(value as u64);
+    }
+
+    fn finish(&self) -> u64 {
+        mix(self.0)
+    }
+}
+
+/// The finalizer of MurmurHash3, spreading every input bit over the whole output.
+fn mix(mut value: u64) -> u64 {
+    value ^= value >> 33;
+    value = value.wrapping_mul(0xFF51_AFD7_ED55_8CCD);
+    value ^= value >> 33;
+    value = value.wrapping_mul(0xC4CE_B9FE_1A85_EC53);
+    value ^ (value >> 33)
+}
+
+fn hash<T: Hash + ?Sized>(item: &T) -> u64 {
+    let mut hasher = StableHasher(FNV_OFFSET);
+    item.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Returns `count` indexes below `len` for an item, derived from one hash by double
+/// hashing.
+fn indexes<T: Hash + ?Sized>(item: &T, count: u32, len: usize) -> impl Iterator<Item = usize> {
+    let h1 = hash(item);
+    let h2 = mix(h1 ^ FNV_OFFSET) | 1;
+    (0..count as u64).map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % len as u64) as usize)
+}
+
+/// Returns the number of bits and hashes of a Bloom filter holding `items` items with a
+/// false positive rate of `rate`.
+fn bloom_size(items: usize, rate: f64) -> Result<(usize, u32)> {
+    if items == 0 {
+        return Err(Error::Argument(
+            "The expected number of items must be greater than 0".to_string(),
+        ));
+    }
+
+    if !(rate > 0.0 && rate < 1.0) {
+        return Err(Error::Argument(format!(
+            "The false positive rate must be between 0 and 1, got {}",
+            rate
+        )));
+    }
+
+    let ln2 = std::f64::consts::LN_2;
+    let bits = (-(items as f64) * rate.ln() / (ln2 * ln2)).ceil().max(1.0);
+    let hashes = (bits / items as f64 * ln2).round().max(1.0);
+    Ok((bits as usize, hashes as u32))
+}
+
+fn check_size(bits: usize, hashes: u32) -> Result<()> {
+    if bits == 0 || hashes == 0 {
+        Err(Error::Argument(
+            "The size and number of hashes must be greater than 0".to_string(),
+        ))
+    } else {
+        Ok(())
+    }
+}
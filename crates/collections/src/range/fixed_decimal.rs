@@ -0,0 +1,99 @@
+use std::fmt;
+
+use super::Step;
+
+/// A signed decimal with `SCALE` fractional digits, stored as a count of its smallest
+/// unit.
+///
+/// Steps move by one unit of the last fractional digit, so a `Range<FixedDecimal<2>>`
+/// walks in increments of 0.01.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FixedDecimal<const SCALE: u32> {
+    units: i64,
+}
+
+impl<const SCALE: u32> FixedDecimal<SCALE> {
+    /// Number of units in one whole.
+    pub const FACTOR: i64 = 10i64.pow(SCALE);
+    pub const MIN: Self = Self { units: i64::MIN };
+    pub const MAX: Self = Self { units: i64::MAX };
+    pub const ZERO: Self = Self { units: 0 };
+
+    /// Creates a decimal from a count of its smallest unit, so `from_units(125)` is 1.25
+    /// at scale 2.
+    pub const fn from_units(units: i64) -> Self {
+        Self { units }
+    }
+
+    /// Creates a decimal from a whole number. Returns `None` if it does not fit.
+    pub fn from_integer(value: i64) -> Option<Self> {
+        value.checked_mul(Self::FACTOR).map(Self::from_units)
+    }
+
+    /// Creates a decimal from the nearest representable value. Returns `None` for
+    /// values that are not finite or do not fit.
+    pub fn from_f64(value: f64) -> Option<Self> {
+        let units = (value * Self::FACTOR as f64).round();
+
+        if units.is_finite() && units >= i64::MIN as f64 && units < i64::MAX as f64 {
+            Some(Self::from_units(units as i64))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the count of the smallest unit.
+    pub const fn units(self) -> i64 {
+        self.units
+    }
+
+    /// Returns the value as a float.
+    pub fn to_f64(self) -> f64 {
+        self.units as f64 / Self::FACTOR as f64
+    }
+}
+
+impl<const SCALE: u32> fmt::Display for FixedDecimal<SCALE> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.units < 0 { "-" } else { "" };
+        let units = self.units.unsigned_abs();
+        let factor = Self::FACTOR as u64;
+
+        if SCALE == 0 {
+            write!(f, "{}{}", sign, units)
+        } else {
+            write!(
+                f,
+                "{}{}.{:0width$}",
+                sign,
+                units / factor,
+                units % factor,
+                width = SCALE as usize
+            )
+        }
+    }
+}
+
+impl<const SCALE: u32> Step for FixedDecimal<SCALE> {
+    fn forward(self) -> Self {
+        Self::from_units(self.units.forward())
+    }
+    fn backward(self) -> Self {
+        Self::from_units(self.units.backward())
+    }
+    fn forward_by(self, count: usize) -> Self {
+        Self::from_units(self.units.forward_by(count))
+    }
+    fn backward_by(self, count: usize) -> Self {
+        Self::from_units(self.units.backward_by(count))
+    }
+    fn checked_forward_by(self, count: usize) -> Option<Self> {
+        self.units.checked_forward_by(count).map(Self::from_units)
+    }
+    fn checked_backward_by(self, count: usize) -> Option<Self> {
+        self.units.checked_backward_by(count).map(Self::from_units)
+    }
+    fn steps_between(start: &Self, end: &Self) -> Option<usize> {
+        i64::steps_between(&start.units, &end.units)
+    }
+}
@@ -0,0 +1,429 @@
+use std::{cmp::Ordering, fmt, vec};
+
+use super::{Range, Step};
+
+type Link<T, V> = Option<Box<Node<T, V>>>;
+
+#[derive(Clone)]
+struct Node<T: Step, V> {
+    range: Range<T>,
+    value: V,
+    /// The highest `max` of the intervals in this subtree.
+    max: T,
+    height: usize,
+    left: Link<T, V>,
+    right: Link<T, V>,
+}
+
+impl<T: Step, V> Node<T, V> {
+    fn update(&mut self) {
+        self.height = 1 + height(&self.left).max(height(&self.right));
+        self.max = [&self.left, &self.right]
+            .into_iter()
+            .flatten()
+            .fold(self.range.max, |max, it| max.max(it.max));
+    }
+}
+
+/// A collection of possibly overlapping intervals with values.
+///
+/// Intervals are kept in an AVL tree ordered by their bounds, where each node also holds
+/// the highest `max` in its subtree. Inserting and removing an interval take O(log n),
+/// and point and overlap queries take O(log n + k) for k results. Intervals with equal
+/// bounds are kept in insertion order.
+#[derive(Clone)]
+pub struct IntervalTree<T: Step, V> {
+    root: Link<T, V>,
+    len: usize,
+}
+
+impl<T: Step, V> Default for IntervalTree<T, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Step + fmt::Debug, V: fmt::Debug> fmt::Debug for IntervalTree<T, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: Step, V: PartialEq> PartialEq for IntervalTree<T, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Step, V: Eq> Eq for IntervalTree<T, V> {}
+
+impl<T: Step, V> IntervalTree<T, V> {
+    /// Creates an empty tree.
+    pub fn new() -> Self {
+        Self { root: None, len: 0 }
+    }
+
+    /// Returns the number of intervals.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the tree has no intervals.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Removes every interval.
+    pub fn clear(&mut self) {
+        self.root = None;
+        self.len = 0;
+    }
+
+    /// Adds an interval. Intervals may overlap or repeat. An empty range is ignored.
+    pub fn insert(&mut self, range: Range<T>, value: V) {
+        if range.is_empty() {
+            return;
+        }
+
+        self.root = Some(insert(self.root.take(), range, value));
+        self.len += 1;
+    }
+
+    /// Removes the first interval equal to `range` and returns its value.
+    pub fn remove(&mut self, range: &Range<T>) -> Option<V> {
+        let (root, value) = remove(self.root.take(), range);
+        self.root = root;
+
+        if value.is_some() {
+            self.len -= 1;
+        }
+
+        value
+    }
+
+    /// Keeps only the intervals for which `f` returns true.
+    pub fn retain(&mut self, mut f: impl FnMut(&Range<T>, &V) -> bool) {
+        let mut entries = Vec::with_capacity(self.len);
+        into_entries(self.root.take(), &mut entries);
+        entries.retain(|(range, value)| f(range, value));
+        *self = Self::from_sorted(entries);
+    }
+
+    /// Returns the intervals that contain a point, in ascending order.
+    pub fn containing(&self, point: T) -> impl Iterator<Item = (Range<T>, &V)> + '_ {
+        self.overlapping(&Range::single(point))
+    }
+
+    /// Returns the intervals that overlap `range`, in ascending order. Nothing overlaps an
+    /// empty range.
+    pub fn overlapping(
+        &self,
+        range: &Range<T>,
+    ) -> impl Iterator<Item = (Range<T>, &V)> + use<'_, T, V> {
+        let mut found = Vec::new();
+
+        if !range.is_empty() {
+            collect(&self.root, range, &mut found);
+        }
+
+        found.into_iter()
+    }
+
+    /// Checks if any interval contains a point.
+    pub fn contains(&self, point: T) -> bool {
+        self.containing(point).next().is_some()
+    }
+
+    /// Returns an iterator over the intervals and their values in ascending order.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (Range<T>, &V)> + '_ {
+        let mut iter = Iter {
+            front: Vec::new(),
+            back: Vec::new(),
+            len: self.len,
+        };
+        iter.push_front(&self.root);
+        iter.push_back(&self.root);
+        iter
+    }
+
+    /// Builds a balanced tree from entries sorted by their range.
+    fn from_sorted(entries: Vec<(Range<T>, V)>) -> Self {
+        let len = entries.len();
+        Self {
+            root: build(&mut entries.into_iter(), len),
+            len,
+        }
+    }
+}
+
+fn height<T: Step, V>(link: &Link<T, V>) -> usize {
+    link.as_ref().map_or(0, |it| it.height)
+}
+
+fn rotate_left<T: Step, V>(mut node: Box<Node<T, V>>) -> Box<Node<T, V>> {
+    let mut right = node.right.take().expect("rotate_left needs a right child");
+    node.right = right.left.take();
+    node.update();
+    right.left = Some(node);
+    right.update();
+    right
+}
+
+fn rotate_right<T: Step, V>(mut node: Box<Node<T, V>>) -> Box<Node<T, V>> {
+    let mut left = node.left.take().expect("rotate_right needs a left child");
+    node.left = left.right.take();
+    node.update();
+    left.right = Some(node);
+    left.update();
+    left
+}
+
+/// Updates a node whose children changed and restores the AVL balance below it.
+fn balance<T: Step, V>(mut node: Box<Node<T, V>>) -> Box<Node<T, V>> {
+    node.update();
+    let left = height(&node.left);
+    let right = height(&node.right);
+
+    if left > right + 1 {
+        if let Some(child) = node.left.take() {
+            node.left = Some(if height(&child.left) < height(&child.right) {
+                rotate_left(child)
+            } else {
+                child
+            });
+        }
+
+        rotate_right(node)
+    } else if right > left + 1 {
+        if let Some(child) = node.right.take() {
+            node.right = Some(if height(&child.right) < height(&child.left) {
+                rotate_right(child)
+            } else {
+                child
+            });
+        }
+
+        rotate_left(node)
+    } else {
+        node
+    }
+}
+
+fn insert<T: Step, V>(link: Link<T, V>, range: Range<T>, value: V) -> Box<Node<T, V>> {
+    let Some(mut node) = link else {
+        return Box::new(Node {
+            range,
+            value,
+            max: range.max,
+            height: 1,
+            left: None,
+            right: None,
+        });
+    };
+
+    // Equal ranges go right, after the ones already there.
+    if range < node.range {
+        node.left = Some(insert(node.left.take(), range, value));
+    } else {
+        node.right = Some(insert(node.right.take(), range, value));
+    }
+
+    balance(node)
+}
+
+fn remove<T: Step, V>(link: Link<T, V>, range: &Range<T>) -> (Link<T, V>, Option<V>) {
+    let Some(mut node) = link else {
+        return (None, None);
+    };
+
+    let value = match range.cmp(&node.range) {
+        Ordering::Less => {
+            let (left, value) = remove(node.left.take(), range);
+            node.left = left;
+            value
+        }
+        Ordering::Greater => {
+            let (right, value) = remove(node.right.take(), range);
+            node.right = right;
+            value
+        }
+        Ordering::Equal => {
+            // An equal interval inserted earlier can only be on the left.
+            let (left, value) = remove(node.left.take(), range);
+            node.left = left;
+
+            if value.is_none() {
+                let Node {
+                    value, left, right, ..
+                } = *node;
+                return (join(left, right), Some(value));
+            }
+
+            value
+        }
+    };
+
+    (Some(balance(node)), value)
+}
+
+/// Joins the children of a removed node, taking the lowest interval on the right as
+/// their new parent.
+fn join<T: Step, V>(left: Link<T, V>, right: Link<T, V>) -> Link<T, V> {
+    let Some(right) = right else {
+        return left;
+    };
+
+    let (rest, mut min) = remove_min(right);
+    min.left = left;
+    min.right = rest;
+    Some(balance(min))
+}
+
+fn remove_min<T: Step, V>(mut node: Box<Node<T, V>>) -> (Link<T, V>, Box<Node<T, V>>) {
+    match node.left.take() {
+        None => (node.right.take(), node),
+        Some(left) => {
+            let (left, min) = remove_min(left);
+            node.left = left;
+            (Some(balance(node)), min)
+        }
+    }
+}
+
+/// Collects the intervals in the subtree that overlap `range`, in ascending order,
+/// skipping every subtree whose highest `max` ends before `range.min`.
+fn collect<'a, T: Step, V>(
+    link: &'a Link<T, V>,
+    range: &Range<T>,
+    found: &mut Vec<(Range<T>, &'a V)>,
+) {
+    let Some(node) = link else {
+        return;
+    };
+
+    if node.max < range.min {
+        return;
+    }
+
+    collect(&node.left, range, found);
+
+    // Everything to the right starts at or after this interval.
+    if node.range.min > range.max {
+        return;
+    }
+
+    if node.range.max >= range.min {
+        found.push((node.range, &node.value));
+    }
+
+    collect(&node.right, range, found);
+}
+
+fn into_entries<T: Step, V>(link: Link<T, V>, entries: &mut Vec<(Range<T>, V)>) {
+    if let Some(node) = link {
+        let Node {
+            range,
+            value,
+            left,
+            right,
+            ..
+        } = *node;
+        into_entries(left, entries);
+        entries.push((range, value));
+        into_entries(right, entries);
+    }
+}
+
+fn build<T: Step, V>(entries: &mut vec::IntoIter<(Range<T>, V)>, len: usize) -> Link<T, V> {
+    if len == 0 {
+        return None;
+    }
+
+    let left = build(entries, len / 2);
+    let (range, value) = entries.next()?;
+    let right = build(entries, len - len / 2 - 1);
+    let mut node = Box::new(Node {
+        range,
+        value,
+        max: range.max,
+        height: 1,
+        left,
+        right,
+    });
+    node.update();
+    Some(node)
+}
+
+/// An in-order iterator walking the tree from both ends.
+struct Iter<'a, T: Step, V> {
+    front: Vec<&'a Node<T, V>>,
+    back: Vec<&'a Node<T, V>>,
+    len: usize,
+}
+
+impl<'a, T: Step, V> Iter<'a, T, V> {
+    fn push_front(&mut self, mut link: &'a Link<T, V>) {
+        while let Some(node) = link {
+            self.front.push(node);
+            link = &node.left;
+        }
+    }
+
+    fn push_back(&mut self, mut link: &'a Link<T, V>) {
+        while let Some(node) = link {
+            self.back.push(node);
+            link = &node.right;
+        }
+    }
+}
+
+impl<'a, T: Step, V> Iterator for Iter<'a, T, V> {
+    type Item = (Range<T>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let node = self.front.pop()?;
+        self.push_front(&node.right);
+        self.len -= 1;
+        Some((node.range, &node.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T: Step, V> DoubleEndedIterator for Iter<'_, T, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let node = self.back.pop()?;
+        self.push_back(&node.left);
+        self.len -= 1;
+        Some((node.range, &node.value))
+    }
+}
+
+impl<T: Step, V> FromIterator<(Range<T>, V)> for IntervalTree<T, V> {
+    fn from_iter<I: IntoIterator<Item = (Range<T>, V)>>(iter: I) -> Self {
+        let mut entries = iter
+            .into_iter()
+            .filter(|(range, _)| !range.is_empty())
+            .collect::<Vec<_>>();
+        entries.sort_by_key(|(range, _)| *range);
+        Self::from_sorted(entries)
+    }
+}
+
+impl<T: Step, V> Extend<(Range<T>, V)> for IntervalTree<T, V> {
+    fn extend<I: IntoIterator<Item = (Range<T>, V)>>(&mut self, iter: I) {
+        for (range, value) in iter {
+            self.insert(range, value);
+        }
+    }
+}
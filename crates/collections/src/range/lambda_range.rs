@@ -121,24 +121,12 @@ impl<T: Step> LambdaRange<T> {
 
     /// Returns an iterator that steps forward by count on each iteration.
     pub fn up_by(&self, count: usize) -> LambdaRangeIter<T, impl Fn(T) -> T> {
-        self.from_start(move |v| {
-            let mut result = v;
-            for _ in 0..count {
-                result = result.forward();
-            }
-            result
-        })
+        self.from_start(move |v| v.forward_by(count))
     }
 
     /// Returns an iterator that steps backward by count on each iteration.
     pub fn down_by(&self, count: usize) -> LambdaRangeIter<T, impl Fn(T) -> T> {
-        self.from_end(move |v| {
-            let mut result = v;
-            for _ in 0..count {
-                result = result.backward();
-            }
-            result
-        })
+        self.from_end(move |v| v.backward_by(count))
     }
 
     /// Returns an iterator with a custom step function, automatically determining direction.
@@ -1,126 +1,20 @@
+mod fixed_decimal;
+pub use fixed_decimal::*;
+mod interval_tree;
+pub use interval_tree::*;
 mod lambda_range;
 pub use lambda_range::*;
+mod parse;
+pub use parse::*;
 mod range;
 pub use range::*;
-
-pub trait Step: Ord + Copy {
-    fn forward(self) -> Self;
-    fn backward(self) -> Self;
-}
-
-impl Step for i8 {
-    fn forward(self) -> Self {
-        self.saturating_add(1)
-    }
-    fn backward(self) -> Self {
-        self.saturating_sub(1)
-    }
-}
-
-impl Step for i16 {
-    fn forward(self) -> Self {
-        self.saturating_add(1)
-    }
-    fn backward(self) -> Self {
-        self.saturating_sub(1)
-    }
-}
-
-impl Step for i32 {
-    fn forward(self) -> Self {
-        self.saturating_add(1)
-    }
-    fn backward(self) -> Self {
-        self.saturating_sub(1)
-    }
-}
-
-impl Step for i64 {
-    fn forward(self) -> Self {
-        self.saturating_add(1)
-    }
-    fn backward(self) -> Self {
-        self.saturating_sub(1)
-    }
-}
-
-impl Step for i128 {
-    fn forward(self) -> Self {
-        self.saturating_add(1)
-    }
-    fn backward(self) -> Self {
-        self.saturating_sub(1)
-    }
-}
-
-impl Step for isize {
-    fn forward(self) -> Self {
-        self.saturating_add(1)
-    }
-    fn backward(self) -> Self {
-        self.saturating_sub(1)
-    }
-}
-
-impl Step for u8 {
-    fn forward(self) -> Self {
-        self.saturating_add(1)
-    }
-    fn backward(self) -> Self {
-        self.saturating_sub(1)
-    }
-}
-
-impl Step for u16 {
-    fn forward(self) -> Self {
-        self.saturating_add(1)
-    }
-    fn backward(self) -> Self {
-        self.saturating_sub(1)
-    }
-}
-
-impl Step for u32 {
-    fn forward(self) -> Self {
-        self.saturating_add(1)
-    }
-    fn backward(self) -> Self {
-        self.saturating_sub(1)
-    }
-}
-
-impl Step for u64 {
-    fn forward(self) -> Self {
-        self.saturating_add(1)
-    }
-    fn backward(self) -> Self {
-        self.saturating_sub(1)
-    }
-}
-
-impl Step for u128 {
-    fn forward(self) -> Self {
-        self.saturating_add(1)
-    }
-    fn backward(self) -> Self {
-        self.saturating_sub(1)
-    }
-}
-
-impl Step for usize {
-    fn forward(self) -> Self {
-        self.saturating_add(1)
-    }
-    fn backward(self) -> Self {
-        self.saturating_sub(1)
-    }
-}
-
-impl Step for char {
-    fn forward(self) -> Self {
-        char::from_u32(self as u32 + 1).unwrap_or(self)
-    }
-    fn backward(self) -> Self {
-        char::from_u32((self as u32).saturating_sub(1)).unwrap_or(self)
-    }
-}
+mod range_map;
+pub use range_map::*;
+mod range_set;
+pub use range_set::*;
+mod step;
+pub use step::*;
+#[cfg(feature = "chrono")]
+mod time_step;
+#[cfg(feature = "chrono")]
+pub use time_step::*;
@@ -0,0 +1,261 @@
+use std::{
+    fmt::{self, Write},
+    str::FromStr,
+};
+
+use super::{Range, RangeSet, Step};
+use crate::{Error, Result};
+
+/// A `Step` type with a lowest and a highest value, used to resolve open-ended bounds
+/// when parsing ranges.
+pub trait Bounded: Step {
+    const MIN: Self;
+    const MAX: Self;
+}
+
+macro_rules! impl_bounded {
+    ($($t:ty),*) => {$(
+        impl Bounded for $t {
+            const MIN: Self = <$t>::MIN;
+            const MAX: Self = <$t>::MAX;
+        }
+    )*};
+}
+
+impl_bounded!(
+    i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize
+);
+
+impl Bounded for char {
+    const MIN: Self = '\0';
+    const MAX: Self = char::MAX;
+}
+
+impl<T: Bounded + FromStr> Range<T> {
+    /// Parses a comma-separated list of ranges, keeping their order and duplicates.
+    ///
+    /// Each item is a single value or a range in one of these forms, with optional
+    /// whitespace around values and separators:
+    ///
+    /// - `a-b`, `a..=b`: from `a` to `b` inclusive.
+    /// - `a..b`: from `a` up to but excluding `b`.
+    /// - `a-`, `a..`, `..=b`, `..b`, `..`: open-ended, extending to the bounds of `T`.
+    ///
+    /// A leading `-` is a sign, so `-5--1` is the range from -5 to -1. A backslash makes
+    /// the next character part of the value, so `\,` and `\ ` are a comma and a space
+    /// rather than a separator and padding. Errors report the byte position of the
+    /// offending item or value in `text`.
+    pub fn parse_list(text: &str) -> Result<Vec<Range<T>>> {
+        let mut ranges = Vec::new();
+        let mut offset = 0;
+
+        for (index, _, _) in chars(text).filter(|&(_, c, escaped)| c == ',' && !escaped) {
+            ranges.push(parse_item(&text[offset..index], offset)?);
+            offset = index + 1;
+        }
+
+        ranges.push(parse_item(&text[offset..], offset)?);
+        Ok(ranges)
+    }
+}
+
+/// Parses one item of a range list; `offset` is the position of `item` in the input.
+fn parse_item<T: Bounded + FromStr>(item: &str, offset: usize) -> Result<Range<T>> {
+    let start = offset + item.len() - item.trim_start().len();
+    let text = trim(item);
+
+    if text.is_empty() {
+        return Err(Error::Parse(format!("Empty item at position {}", start)));
+    }
+
+    let find = |pattern: &str| {
+        chars(text)
+            .find(|&(i, _, escaped)| !escaped && text[i..].starts_with(pattern))
+            .map(|(i, _, _)| i)
+    };
+
+    let (min, max, exclusive) = if let Some(index) = find("..=") {
+        (&text[..index], Some((&text[index + 3..], index + 3)), false)
+    } else if let Some(index) = find("..") {
+        (&text[..index], Some((&text[index + 2..], index + 2)), true)
+    } else if let Some(index) = chars(text)
+        .find(|&(i, c, escaped)| i > 0 && c == '-' && !escaped)
+        .map(|(i, _, _)| i)
+    {
+        (&text[..index], Some((&text[index + 1..], index + 1)), false)
+    } else {
+        (text, None, false)
+    };
+
+    let min_value = parse_bound(min, start)?.unwrap_or(T::MIN);
+    let Some((max, max_start)) = max else {
+        return Ok(Range::single(min_value));
+    };
+    let max_value = match parse_bound::<T>(max, start + max_start)? {
+        Some(value) if exclusive => value.checked_backward_by(1).filter(|it| *it >= min_value),
+        Some(value) => Some(value).filter(|it| *it >= min_value),
+        None => Some(T::MAX),
+    };
+
+    match max_value {
+        Some(max_value) => Ok(Range {
+            min: min_value,
+            max: max_value,
+        }),
+        None => Err(Error::Parse(format!(
+            "Empty range '{}' at position {}",
+            text, start
+        ))),
+    }
+}
+
+/// Parses a bound; an empty bound is open and returns `None`.
+fn parse_bound<T: FromStr>(text: &str, offset: usize) -> Result<Option<T>> {
+    let value = trim(text);
+
+    if value.is_empty() {
+        return Ok(None);
+    }
+
+    let start = offset + text.len() - text.trim_start().len();
+    chars(value)
+        .map(|(_, c, _)| c)
+        .collect::<String>()
+        .parse()
+        .map(Some)
+        .map_err(|_| Error::Parse(format!("Invalid value '{}' at position {}", value, start)))
+}
+
+/// Yields the characters of `text` with their byte positions and whether a backslash
+/// escapes them. The escaping backslashes themselves are skipped; a trailing lone
+/// backslash is a plain character.
+fn chars(text: &str) -> impl Iterator<Item = (usize, char, bool)> + '_ {
+    let mut iter = text.char_indices();
+    std::iter::from_fn(move || match iter.next()? {
+        (i, '\\') => match iter.next() {
+            Some((j, c)) => Some((j, c, true)),
+            None => Some((i, '\\', false)),
+        },
+        (i, c) => Some((i, c, false)),
+    })
+}
+
+/// Trims the whitespace around `text`, keeping any that is escaped.
+fn trim(text: &str) -> &str {
+    let text = text.trim_start();
+    let end = chars(text)
+        .filter(|&(_, c, escaped)| escaped || !c.is_whitespace())
+        .last()
+        .map_or(0, |(i, c, _)| i + c.len_utf8());
+    &text[..end]
+}
+
+/// Escapes the characters that `Range::parse_list` would otherwise read as a separator
+/// or padding, so the written values parse back.
+struct Escaper<'a, 'b>(&'a mut fmt::Formatter<'b>);
+
+impl Write for Escaper<'_, '_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            if c == ',' || c == '\\' || c.is_whitespace() {
+                self.0.write_char('\\')?;
+            }
+
+            self.0.write_char(c)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<T: Bounded + FromStr> FromStr for Range<T> {
+    type Err = Error;
+
+    /// Parses a single range item in any of the forms accepted by `Range::parse_list`.
+    fn from_str(s: &str) -> Result<Self> {
+        parse_item(s, 0)
+    }
+}
+
+impl<T: Step + fmt::Display> fmt::Display for Range<T> {
+    /// Writes the canonical form: `min` for a single value and `min-max` otherwise.
+    /// Commas, whitespace, and backslashes in the values are escaped with a backslash.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(Escaper(f), "{}", self.min)?;
+
+        if !self.is_single() {
+            f.write_str("-")?;
+            write!(Escaper(f), "{}", self.max)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<T: Bounded + FromStr> FromStr for RangeSet<T> {
+    type Err = Error;
+
+    /// Parses a comma-separated list of ranges as accepted by `Range::parse_list`. An
+    /// empty or blank string is the empty set.
+    fn from_str(s: &str) -> Result<Self> {
+        if s.trim().is_empty() {
+            return Ok(RangeSet::new());
+        }
+
+        Ok(Range::parse_list(s)?.into_iter().collect())
+    }
+}
+
+impl<T: Step + fmt::Display> fmt::Display for RangeSet<T> {
+    /// Writes the ranges in ascending order, separated by commas.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, range) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_str(",")?;
+            }
+
+            write!(f, "{}", range)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use std::{fmt, str::FromStr};
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
+
+    use super::{Bounded, Range, RangeSet, Step};
+
+    impl<T: Step + fmt::Display> Serialize for Range<T> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+            serializer.collect_str(self)
+        }
+    }
+
+    impl<'de, T: Bounded + FromStr> Deserialize<'de> for Range<T> {
+        fn deserialize<D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> std::result::Result<Self, D::Error> {
+            let text = String::deserialize(deserializer)?;
+            text.parse().map_err(de::Error::custom)
+        }
+    }
+
+    impl<T: Step + fmt::Display> Serialize for RangeSet<T> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+            serializer.collect_str(self)
+        }
+    }
+
+    impl<'de, T: Bounded + FromStr> Deserialize<'de> for RangeSet<T> {
+        fn deserialize<D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> std::result::Result<Self, D::Error> {
+            let text = String::deserialize(deserializer)?;
+            text.parse().map_err(de::Error::custom)
+        }
+    }
+}
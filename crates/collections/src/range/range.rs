@@ -29,6 +29,12 @@ impl<T: Step> Range<T> {
         self.min == self.max
     }
 
+    /// Returns true if min passed max, as `deflate` can leave it, so the range holds no
+    /// values.
+    pub fn is_empty(&self) -> bool {
+        self.min > self.max
+    }
+
     /// Checks if a value is within the range (inclusive).
     pub fn contains(&self, value: T) -> bool {
         self.min <= value && value <= self.max
@@ -137,49 +143,41 @@ impl<T: Step> Range<T> {
 
     /// Expands the range by moving min backward and max forward by count steps.
     pub fn inflate(&self, count: usize) -> Range<T> {
-        let mut min = self.min;
-        let mut max = self.max;
-        for _ in 0..count {
-            min = min.backward();
-            max = max.forward();
+        Range {
+            min: self.min.backward_by(count),
+            max: self.max.forward_by(count),
         }
-        Range { min, max }
     }
 
     /// Shrinks the range by moving min forward and max backward by count steps.
+    ///
+    /// Stops at the first step where min passes max.
     pub fn deflate(&self, count: usize) -> Range<T> {
-        let mut min = self.min;
-        let mut max = self.max;
-        for _ in 0..count {
-            min = min.forward();
-            max = max.backward();
-            if min > max {
-                break;
-            }
+        let count = if self.is_empty() {
+            count.min(1)
+        } else {
+            T::steps_between(&self.min, &self.max).map_or(count, |n| count.min(n / 2 + 1))
+        };
+        Range {
+            min: self.min.forward_by(count),
+            max: self.max.backward_by(count),
         }
-        Range { min, max }
     }
 
     /// Shifts the range forward by count steps.
     pub fn shift_forward(&self, count: usize) -> Range<T> {
-        let mut min = self.min;
-        let mut max = self.max;
-        for _ in 0..count {
-            min = min.forward();
-            max = max.forward();
+        Range {
+            min: self.min.forward_by(count),
+            max: self.max.forward_by(count),
         }
-        Range { min, max }
     }
 
     /// Shifts the range backward by count steps.
     pub fn shift_backward(&self, count: usize) -> Range<T> {
-        let mut min = self.min;
-        let mut max = self.max;
-        for _ in 0..count {
-            min = min.backward();
-            max = max.backward();
+        Range {
+            min: self.min.backward_by(count),
+            max: self.max.backward_by(count),
         }
-        Range { min, max }
     }
 
     /// Returns an iterator over the values in the range.
@@ -254,4 +252,28 @@ impl<T: Step> Iterator for RangeIter<T> {
         self.current = Some(value);
         Some(value)
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (start, first) = match self.current {
+            None => (self.min, 1),
+            Some(cur) => (cur, 0),
+        };
+
+        if start > self.max {
+            return (0, Some(0));
+        }
+
+        match T::steps_between(&start, &self.max).and_then(|n| n.checked_add(first)) {
+            Some(n) => (n, Some(n)),
+            // Only a range that really has that many values can claim them all; a step
+            // that stops moving leaves the count unknown.
+            None if start
+                .checked_forward_by(usize::MAX)
+                .is_some_and(|it| it <= self.max) =>
+            {
+                (usize::MAX, None)
+            }
+            None => (0, None),
+        }
+    }
 }
@@ -0,0 +1,179 @@
+use std::{collections::BTreeMap, ops::Bound};
+
+use super::{Range, Step};
+
+/// A map from non-overlapping ranges to values.
+///
+/// Inserting a range overwrites whatever it overlaps: ranges cut by the new one keep
+/// their value on the parts left outside it. Lookups by point are O(log n) in the number
+/// of ranges.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RangeMap<T: Step, V> {
+    entries: BTreeMap<T, (T, V)>,
+}
+
+impl<T: Step, V> Default for RangeMap<T, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Step, V> RangeMap<T, V> {
+    /// Creates an empty map.
+    pub fn new() -> Self {
+        Self {
+            entries: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the number of ranges in the map.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if the map has no ranges.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Removes every range.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Returns the value of the range that contains a point.
+    pub fn get(&self, point: T) -> Option<&V> {
+        self.get_key_value(point).map(|(_, value)| value)
+    }
+
+    /// Returns a mutable reference to the value of the range that contains a point.
+    pub fn get_mut(&mut self, point: T) -> Option<&mut V> {
+        let (_, (max, value)) = self.entries.range_mut(..=point).next_back()?;
+
+        if *max >= point { Some(value) } else { None }
+    }
+
+    /// Returns the range that contains a point and its value.
+    pub fn get_key_value(&self, point: T) -> Option<(Range<T>, &V)> {
+        let (&min, (max, value)) = self.entries.range(..=point).next_back()?;
+
+        if *max >= point {
+            Some((Range { min, max: *max }, value))
+        } else {
+            None
+        }
+    }
+
+    /// Checks if a point is covered by any range.
+    pub fn contains(&self, point: T) -> bool {
+        self.get_key_value(point).is_some()
+    }
+
+    /// Returns the ranges that overlap `range` and their values, in ascending order.
+    /// Nothing overlaps an empty range.
+    pub fn overlapping(
+        &self,
+        range: &Range<T>,
+    ) -> impl Iterator<Item = (Range<T>, &V)> + use<'_, T, V> {
+        let bounds = if range.is_empty() {
+            (Bound::Included(range.min), Bound::Excluded(range.min))
+        } else {
+            let start = self
+                .get_key_value(range.min)
+                .map_or(range.min, |(it, _)| it.min);
+            (Bound::Included(start), Bound::Included(range.max))
+        };
+        self.entries
+            .range(bounds)
+            .map(|(&min, (max, value))| (Range { min, max: *max }, value))
+    }
+
+    /// Returns an iterator over the ranges and their values in ascending order.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (Range<T>, &V)> + '_ {
+        self.entries
+            .iter()
+            .map(|(&min, (max, value))| (Range { min, max: *max }, value))
+    }
+
+    /// Returns an iterator over the ranges in ascending order.
+    pub fn ranges(&self) -> impl DoubleEndedIterator<Item = Range<T>> + '_ {
+        self.entries
+            .iter()
+            .map(|(&min, (max, _))| Range { min, max: *max })
+    }
+
+    /// Returns an iterator over the values in the order of their ranges.
+    pub fn values(&self) -> impl DoubleEndedIterator<Item = &V> + '_ {
+        self.entries.values().map(|(_, value)| value)
+    }
+}
+
+impl<T: Step, V: Clone> RangeMap<T, V> {
+    /// Associates a range with a value, splitting or replacing the ranges it overlaps.
+    /// An empty range is ignored.
+    pub fn insert(&mut self, range: Range<T>, value: V) {
+        if range.is_empty() {
+            return;
+        }
+
+        self.remove(&range);
+        self.entries.insert(range.min, (range.max, value));
+    }
+
+    /// Removes a range, splitting the ranges it cuts through. Returns true if the map
+    /// changed, so an empty range is ignored.
+    pub fn remove(&mut self, range: &Range<T>) -> bool {
+        if range.is_empty() {
+            return false;
+        }
+
+        let mut changed = false;
+
+        if let Some((&start, (end, _))) = self.entries.range(..range.min).next_back()
+            && *end >= range.min
+            && let Some((end, value)) = self.entries.remove(&start)
+        {
+            if end > range.max {
+                self.entries
+                    .insert(range.max.forward(), (end, value.clone()));
+            }
+
+            self.entries.insert(start, (range.min.backward(), value));
+            changed = true;
+        }
+
+        let inside = self
+            .entries
+            .range(range.min..=range.max)
+            .map(|(&start, _)| start)
+            .collect::<Vec<_>>();
+
+        for start in inside {
+            if let Some((end, value)) = self.entries.remove(&start)
+                && end > range.max
+            {
+                self.entries.insert(range.max.forward(), (end, value));
+            }
+
+            changed = true;
+        }
+
+        changed
+    }
+}
+
+impl<T: Step, V: Clone> FromIterator<(Range<T>, V)> for RangeMap<T, V> {
+    fn from_iter<I: IntoIterator<Item = (Range<T>, V)>>(iter: I) -> Self {
+        let mut map = RangeMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<T: Step, V: Clone> Extend<(Range<T>, V)> for RangeMap<T, V> {
+    fn extend<I: IntoIterator<Item = (Range<T>, V)>>(&mut self, iter: I) {
+        for (range, value) in iter {
+            self.insert(range, value);
+        }
+    }
+}
@@ -0,0 +1,362 @@
+use std::collections::{BTreeMap, btree_map};
+
+use super::{Range, Step};
+
+/// A set of values stored as disjoint, coalesced ranges.
+///
+/// Overlapping or adjacent ranges are merged on insert, and removing a range splits the
+/// ranges it cuts through. Point and range lookups are O(log n) in the number of ranges.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RangeSet<T: Step> {
+    ranges: BTreeMap<T, T>,
+}
+
+impl<T: Step> Default for RangeSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Step> RangeSet<T> {
+    /// Creates an empty set.
+    pub fn new() -> Self {
+        Self {
+            ranges: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the number of disjoint ranges in the set.
+    pub fn len(&self) -> usize {
+        self.ranges.len()
+    }
+
+    /// Returns true if the set holds no values.
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Removes every range.
+    pub fn clear(&mut self) {
+        self.ranges.clear();
+    }
+
+    /// Returns the lowest range.
+    pub fn first(&self) -> Option<Range<T>> {
+        self.ranges
+            .first_key_value()
+            .map(|(&min, &max)| Range { min, max })
+    }
+
+    /// Returns the highest range.
+    pub fn last(&self) -> Option<Range<T>> {
+        self.ranges
+            .last_key_value()
+            .map(|(&min, &max)| Range { min, max })
+    }
+
+    /// Returns the range from the lowest to the highest value in the set.
+    pub fn span(&self) -> Option<Range<T>> {
+        Some(Range {
+            min: *self.ranges.first_key_value()?.0,
+            max: *self.ranges.last_key_value()?.1,
+        })
+    }
+
+    /// Returns the range that contains a value.
+    pub fn get(&self, value: T) -> Option<Range<T>> {
+        let (&min, &max) = self.ranges.range(..=value).next_back()?;
+
+        if max >= value {
+            Some(Range { min, max })
+        } else {
+            None
+        }
+    }
+
+    /// Checks if a value is in the set.
+    pub fn contains(&self, value: T) -> bool {
+        self.get(value).is_some()
+    }
+
+    /// Checks if every value of a range is in the set.
+    pub fn contains_range(&self, range: &Range<T>) -> bool {
+        self.get(range.min).is_some_and(|it| it.max >= range.max)
+    }
+
+    /// Checks if any value of a range is in the set.
+    pub fn overlaps(&self, range: &Range<T>) -> bool {
+        !range.is_empty()
+            && self
+                .ranges
+                .range(..=range.max)
+                .next_back()
+                .is_some_and(|(_, &max)| max >= range.min)
+    }
+
+    /// Adds a range, merging it with the ranges it overlaps or touches. Returns true if
+    /// the set changed, so an empty range is ignored.
+    pub fn insert(&mut self, range: Range<T>) -> bool {
+        if range.is_empty() {
+            return false;
+        }
+
+        let mut min = range.min;
+        let mut max = range.max;
+
+        if let Some((&start, &end)) = self.ranges.range(..=min).next_back() {
+            if end >= max {
+                return false;
+            }
+
+            if end >= min || end.forward() == min {
+                min = start;
+            }
+        }
+
+        let merged = self
+            .ranges
+            .range(min..=max.forward())
+            .map(|(&start, _)| start)
+            .collect::<Vec<_>>();
+
+        for start in merged {
+            if let Some(end) = self.ranges.remove(&start)
+                && end > max
+            {
+                max = end;
+            }
+        }
+
+        self.ranges.insert(min, max);
+        true
+    }
+
+    /// Adds a single value. Returns true if it was not already in the set.
+    pub fn insert_value(&mut self, value: T) -> bool {
+        self.insert(Range::single(value))
+    }
+
+    /// Removes a range, splitting the ranges it cuts through. Returns true if the set
+    /// changed, so an empty range is ignored.
+    pub fn remove(&mut self, range: &Range<T>) -> bool {
+        if range.is_empty() {
+            return false;
+        }
+
+        let mut changed = false;
+
+        if let Some((&start, &end)) = self.ranges.range(..range.min).next_back()
+            && end >= range.min
+        {
+            self.ranges.insert(start, range.min.backward());
+
+            if end > range.max {
+                self.ranges.insert(range.max.forward(), end);
+            }
+
+            changed = true;
+        }
+
+        let inside = self
+            .ranges
+            .range(range.min..=range.max)
+            .map(|(&start, &end)| (start, end))
+            .collect::<Vec<_>>();
+
+        for (start, end) in inside {
+            self.ranges.remove(&start);
+
+            if end > range.max {
+                self.ranges.insert(range.max.forward(), end);
+            }
+
+            changed = true;
+        }
+
+        changed
+    }
+
+    /// Removes a single value. Returns true if it was in the set.
+    pub fn remove_value(&mut self, value: T) -> bool {
+        self.remove(&Range::single(value))
+    }
+
+    /// Returns the values in either set.
+    pub fn union(&self, other: &RangeSet<T>) -> RangeSet<T> {
+        let mut result = self.clone();
+        result.extend(other.iter());
+        result
+    }
+
+    /// Returns the values in both sets.
+    pub fn intersection(&self, other: &RangeSet<T>) -> RangeSet<T> {
+        let mut result = RangeSet::new();
+        let mut left = self.iter().peekable();
+        let mut right = other.iter().peekable();
+
+        while let (Some(a), Some(b)) = (left.peek(), right.peek()) {
+            if a.overlaps(b) {
+                result.ranges.insert(a.min.max(b.min), a.max.min(b.max));
+            }
+
+            if a.max < b.max {
+                left.next();
+            } else {
+                right.next();
+            }
+        }
+
+        result
+    }
+
+    /// Returns the values in this set that are not in `other`.
+    pub fn difference(&self, other: &RangeSet<T>) -> RangeSet<T> {
+        let mut result = self.clone();
+
+        for range in other.iter() {
+            result.remove(&range);
+        }
+
+        result
+    }
+
+    /// Returns the values within `bounds` that are not in the set.
+    pub fn complement(&self, bounds: &Range<T>) -> RangeSet<T> {
+        RangeSet {
+            ranges: self
+                .gaps(bounds)
+                .map(|range| (range.min, range.max))
+                .collect(),
+        }
+    }
+
+    /// Returns an iterator over the ranges in ascending order.
+    pub fn iter(&self) -> RangeSetIter<'_, T> {
+        RangeSetIter {
+            inner: self.ranges.iter(),
+        }
+    }
+
+    /// Returns an iterator over the ranges within `bounds` that are not in the set, in
+    /// ascending order. Empty bounds have no gaps.
+    pub fn gaps(&self, bounds: &Range<T>) -> Gaps<'_, T> {
+        if bounds.is_empty() {
+            return Gaps {
+                inner: self.ranges.range(..),
+                next: None,
+                max: bounds.max,
+            };
+        }
+
+        let start = self.get(bounds.min).map_or(bounds.min, |it| it.min);
+        Gaps {
+            inner: self.ranges.range(start..=bounds.max),
+            next: Some(bounds.min),
+            max: bounds.max,
+        }
+    }
+}
+
+impl<T: Step> FromIterator<Range<T>> for RangeSet<T> {
+    fn from_iter<I: IntoIterator<Item = Range<T>>>(iter: I) -> Self {
+        let mut set = RangeSet::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<T: Step> Extend<Range<T>> for RangeSet<T> {
+    fn extend<I: IntoIterator<Item = Range<T>>>(&mut self, iter: I) {
+        for range in iter {
+            self.insert(range);
+        }
+    }
+}
+
+impl<'a, T: Step> IntoIterator for &'a RangeSet<T> {
+    type Item = Range<T>;
+    type IntoIter = RangeSetIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the ranges of a `RangeSet`.
+#[derive(Debug, Clone)]
+pub struct RangeSetIter<'a, T: Step> {
+    inner: btree_map::Iter<'a, T, T>,
+}
+
+impl<T: Step> Iterator for RangeSetIter<'_, T> {
+    type Item = Range<T>;
+
+    fn next(&mut self) -> Option<Range<T>> {
+        self.inner.next().map(|(&min, &max)| Range { min, max })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<T: Step> DoubleEndedIterator for RangeSetIter<'_, T> {
+    fn next_back(&mut self) -> Option<Range<T>> {
+        self.inner
+            .next_back()
+            .map(|(&min, &max)| Range { min, max })
+    }
+}
+
+impl<T: Step> ExactSizeIterator for RangeSetIter<'_, T> {}
+
+/// Iterator over the gaps of a `RangeSet` within bounds.
+#[derive(Debug, Clone)]
+pub struct Gaps<'a, T: Step> {
+    inner: btree_map::Range<'a, T, T>,
+    next: Option<T>,
+    max: T,
+}
+
+impl<T: Step> Gaps<'_, T> {
+    /// Returns the value after `end`, or `None` if `end` reaches the bounds or cannot
+    /// step further.
+    fn after(&self, end: T) -> Option<T> {
+        let next = end.forward();
+
+        if end >= self.max || next == end {
+            None
+        } else {
+            Some(next)
+        }
+    }
+}
+
+impl<T: Step> Iterator for Gaps<'_, T> {
+    type Item = Range<T>;
+
+    fn next(&mut self) -> Option<Range<T>> {
+        let mut cursor = self.next?;
+
+        loop {
+            let Some((&start, &end)) = self.inner.next() else {
+                self.next = None;
+                return Some(Range {
+                    min: cursor,
+                    max: self.max,
+                });
+            };
+
+            if start > cursor {
+                self.next = self.after(end);
+                return Some(Range {
+                    min: cursor,
+                    max: start.backward(),
+                });
+            }
+
+            self.next = self.after(end);
+            cursor = self.next?;
+        }
+    }
+}
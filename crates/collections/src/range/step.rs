@@ -0,0 +1,194 @@
+/// A type whose values can be walked one step at a time.
+///
+/// `forward` and `backward` saturate at the ends of the domain. The `_by` methods move
+/// `count` steps at once: the plain ones saturate and the `checked_` ones return `None`
+/// instead. Their default implementations walk one step at a time, so types that can do
+/// the arithmetic directly should override them.
+pub trait Step: Ord + Copy {
+    fn forward(self) -> Self;
+    fn backward(self) -> Self;
+
+    /// Moves `count` steps forward, stopping at the end of the domain.
+    fn forward_by(self, count: usize) -> Self {
+        let mut value = self;
+
+        for _ in 0..count {
+            let next = value.forward();
+
+            if next == value {
+                break;
+            }
+
+            value = next;
+        }
+
+        value
+    }
+
+    /// Moves `count` steps backward, stopping at the start of the domain.
+    fn backward_by(self, count: usize) -> Self {
+        let mut value = self;
+
+        for _ in 0..count {
+            let next = value.backward();
+
+            if next == value {
+                break;
+            }
+
+            value = next;
+        }
+
+        value
+    }
+
+    /// Moves `count` steps forward, or returns `None` if that passes the end of the
+    /// domain.
+    fn checked_forward_by(self, count: usize) -> Option<Self> {
+        let mut value = self;
+
+        for _ in 0..count {
+            let next = value.forward();
+
+            if next == value {
+                return None;
+            }
+
+            value = next;
+        }
+
+        Some(value)
+    }
+
+    /// Moves `count` steps backward, or returns `None` if that passes the start of the
+    /// domain.
+    fn checked_backward_by(self, count: usize) -> Option<Self> {
+        let mut value = self;
+
+        for _ in 0..count {
+            let next = value.backward();
+
+            if next == value {
+                return None;
+            }
+
+            value = next;
+        }
+
+        Some(value)
+    }
+
+    /// Returns the number of whole steps from `start` forward to `end`, or `None` if
+    /// `end` is before `start` or the count does not fit in a `usize`.
+    fn steps_between(start: &Self, end: &Self) -> Option<usize> {
+        if start > end {
+            return None;
+        }
+
+        let mut value = *start;
+        let mut count = 0usize;
+
+        while value < *end {
+            let next = value.forward();
+
+            if next == value {
+                return None;
+            }
+
+            if next > *end {
+                break;
+            }
+
+            value = next;
+            count = count.checked_add(1)?;
+        }
+
+        Some(count)
+    }
+}
+
+macro_rules! impl_step {
+    ($add:ident, $sub:ident; $($t:ty),*) => {$(
+        impl Step for $t {
+            fn forward(self) -> Self {
+                self.saturating_add(1)
+            }
+            fn backward(self) -> Self {
+                self.saturating_sub(1)
+            }
+            fn forward_by(self, count: usize) -> Self {
+                self.checked_forward_by(count).unwrap_or(<$t>::MAX)
+            }
+            fn backward_by(self, count: usize) -> Self {
+                self.checked_backward_by(count).unwrap_or(<$t>::MIN)
+            }
+            fn checked_forward_by(self, count: usize) -> Option<Self> {
+                // A count too large for the unsigned counterpart always overflows.
+                self.$add(count.try_into().ok()?)
+            }
+            fn checked_backward_by(self, count: usize) -> Option<Self> {
+                self.$sub(count.try_into().ok()?)
+            }
+            fn steps_between(start: &Self, end: &Self) -> Option<usize> {
+                if start > end {
+                    None
+                } else {
+                    end.abs_diff(*start).try_into().ok()
+                }
+            }
+        }
+    )*};
+}
+
+impl_step!(checked_add_unsigned, checked_sub_unsigned; i8, i16, i32, i64, i128, isize);
+impl_step!(checked_add, checked_sub; u8, u16, u32, u64, u128, usize);
+
+const SURROGATE_START: u32 = 0xD800;
+const SURROGATE_COUNT: u32 = 0xE000 - SURROGATE_START;
+
+/// Returns the position of a char among all chars, skipping the surrogate gap.
+fn char_index(value: char) -> u32 {
+    let value = value as u32;
+
+    if value >= SURROGATE_START {
+        value - SURROGATE_COUNT
+    } else {
+        value
+    }
+}
+
+fn char_from_index(index: u32) -> Option<char> {
+    if index >= SURROGATE_START {
+        char::from_u32(index.checked_add(SURROGATE_COUNT)?)
+    } else {
+        char::from_u32(index)
+    }
+}
+
+impl Step for char {
+    fn forward(self) -> Self {
+        self.checked_forward_by(1).unwrap_or(self)
+    }
+    fn backward(self) -> Self {
+        self.checked_backward_by(1).unwrap_or(self)
+    }
+    fn forward_by(self, count: usize) -> Self {
+        self.checked_forward_by(count).unwrap_or(char::MAX)
+    }
+    fn backward_by(self, count: usize) -> Self {
+        self.checked_backward_by(count).unwrap_or('\0')
+    }
+    fn checked_forward_by(self, count: usize) -> Option<Self> {
+        char_from_index(char_index(self).checked_add(count.try_into().ok()?)?)
+    }
+    fn checked_backward_by(self, count: usize) -> Option<Self> {
+        char_from_index(char_index(self).checked_sub(count.try_into().ok()?)?)
+    }
+    fn steps_between(start: &Self, end: &Self) -> Option<usize> {
+        if start > end {
+            None
+        } else {
+            (char_index(*end) - char_index(*start)).try_into().ok()
+        }
+    }
+}
@@ -0,0 +1,198 @@
+use std::{fmt, hash::Hash, marker::PhantomData};
+
+use chrono::{DateTime, Days, NaiveDate, NaiveDateTime, TimeDelta, TimeZone, Utc};
+
+use super::Step;
+
+/// A fixed unit of time to step chrono values by.
+pub trait TimeUnit: Copy + Ord + Hash + Default + fmt::Debug {
+    /// Length of one step in milliseconds.
+    const MILLIS: i64;
+}
+
+macro_rules! time_unit {
+    ($($(#[$doc:meta])* $name:ident = $millis:expr;)*) => {$(
+        $(#[$doc])*
+        #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name;
+
+        impl TimeUnit for $name {
+            const MILLIS: i64 = $millis;
+        }
+    )*};
+}
+
+time_unit! {
+    /// Steps by one millisecond.
+    Millisecond = 1;
+    /// Steps by one second.
+    Second = 1_000;
+    /// Steps by one minute.
+    Minute = 60_000;
+    /// Steps by one hour.
+    Hour = 3_600_000;
+    /// Steps by one day of 24 hours.
+    Day = 86_400_000;
+    /// Steps by one week of 7 days.
+    Week = 604_800_000;
+}
+
+/// A chrono date or time that can be stepped by a `TimeUnit`.
+pub trait TimeValue: Ord + Copy {
+    /// Smallest step, in milliseconds, that changes the value.
+    const PRECISION_MILLIS: i64;
+
+    fn checked_add(self, delta: TimeDelta) -> Option<Self>;
+    fn since(self, earlier: Self) -> TimeDelta;
+    /// Returns the earliest and latest representable values, in the time zone of `self`.
+    fn bounds(self) -> (Self, Self);
+}
+
+impl TimeValue for NaiveDate {
+    const PRECISION_MILLIS: i64 = Day::MILLIS;
+
+    fn checked_add(self, delta: TimeDelta) -> Option<Self> {
+        self.checked_add_signed(delta)
+    }
+    fn since(self, earlier: Self) -> TimeDelta {
+        self.signed_duration_since(earlier)
+    }
+    fn bounds(self) -> (Self, Self) {
+        (NaiveDate::MIN, NaiveDate::MAX)
+    }
+}
+
+impl TimeValue for NaiveDateTime {
+    const PRECISION_MILLIS: i64 = 1;
+
+    fn checked_add(self, delta: TimeDelta) -> Option<Self> {
+        self.checked_add_signed(delta)
+    }
+    fn since(self, earlier: Self) -> TimeDelta {
+        self.signed_duration_since(earlier)
+    }
+    fn bounds(self) -> (Self, Self) {
+        (NaiveDateTime::MIN, NaiveDateTime::MAX)
+    }
+}
+
+impl<Tz: TimeZone> TimeValue for DateTime<Tz>
+where
+    Tz::Offset: Copy,
+{
+    const PRECISION_MILLIS: i64 = 1;
+
+    fn checked_add(self, delta: TimeDelta) -> Option<Self> {
+        self.checked_add_signed(delta)
+    }
+    fn since(self, earlier: Self) -> TimeDelta {
+        self.signed_duration_since(earlier)
+    }
+    fn bounds(self) -> (Self, Self) {
+        let tz = self.timezone();
+        (
+            DateTime::<Utc>::MIN_UTC.with_timezone(&tz),
+            DateTime::<Utc>::MAX_UTC.with_timezone(&tz),
+        )
+    }
+}
+
+/// A chrono value stepped by a fixed unit, such as `Stepped<DateTime<Utc>, Hour>`.
+///
+/// The unit must be a whole multiple of what the value can represent, so
+/// `Stepped<NaiveDate, Hour>` fails to compile while `Stepped<NaiveDate, Week>` works.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Stepped<T, U> {
+    pub value: T,
+    unit: PhantomData<U>,
+}
+
+impl<T: TimeValue, U: TimeUnit> Stepped<T, U> {
+    pub fn new(value: T) -> Self {
+        const {
+            assert!(
+                U::MILLIS % T::PRECISION_MILLIS == 0,
+                "the unit is finer than the value can represent"
+            )
+        };
+        Self {
+            value,
+            unit: PhantomData,
+        }
+    }
+
+    fn delta(count: usize) -> Option<TimeDelta> {
+        TimeDelta::try_milliseconds(i64::try_from(count).ok()?.checked_mul(U::MILLIS)?)
+    }
+}
+
+impl<T: TimeValue, U: TimeUnit> From<T> for Stepped<T, U> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T: TimeValue, U: TimeUnit> Step for Stepped<T, U> {
+    fn forward(self) -> Self {
+        self.checked_forward_by(1).unwrap_or(self)
+    }
+    fn backward(self) -> Self {
+        self.checked_backward_by(1).unwrap_or(self)
+    }
+    fn forward_by(self, count: usize) -> Self {
+        self.checked_forward_by(count).unwrap_or_else(|| {
+            let (_, max) = self.value.bounds();
+            let count = Self::steps_between(&self, &Self::new(max)).unwrap_or(0);
+            self.checked_forward_by(count).unwrap_or(self)
+        })
+    }
+    fn backward_by(self, count: usize) -> Self {
+        self.checked_backward_by(count).unwrap_or_else(|| {
+            let (min, _) = self.value.bounds();
+            let count = Self::steps_between(&Self::new(min), &self).unwrap_or(0);
+            self.checked_backward_by(count).unwrap_or(self)
+        })
+    }
+    fn checked_forward_by(self, count: usize) -> Option<Self> {
+        self.value.checked_add(Self::delta(count)?).map(Self::new)
+    }
+    fn checked_backward_by(self, count: usize) -> Option<Self> {
+        self.value.checked_add(-Self::delta(count)?).map(Self::new)
+    }
+    fn steps_between(start: &Self, end: &Self) -> Option<usize> {
+        if start > end {
+            return None;
+        }
+
+        let millis = end.value.since(start.value).num_milliseconds();
+        usize::try_from(millis / U::MILLIS).ok()
+    }
+}
+
+impl Step for NaiveDate {
+    fn forward(self) -> Self {
+        self.succ_opt().unwrap_or(self)
+    }
+    fn backward(self) -> Self {
+        self.pred_opt().unwrap_or(self)
+    }
+    fn forward_by(self, count: usize) -> Self {
+        self.checked_forward_by(count).unwrap_or(NaiveDate::MAX)
+    }
+    fn backward_by(self, count: usize) -> Self {
+        self.checked_backward_by(count).unwrap_or(NaiveDate::MIN)
+    }
+    fn checked_forward_by(self, count: usize) -> Option<Self> {
+        self.checked_add_days(Days::new(count.try_into().ok()?))
+    }
+    fn checked_backward_by(self, count: usize) -> Option<Self> {
+        self.checked_sub_days(Days::new(count.try_into().ok()?))
+    }
+    fn steps_between(start: &Self, end: &Self) -> Option<usize> {
+        if start > end {
+            None
+        } else {
+            end.signed_duration_since(*start).num_days().try_into().ok()
+        }
+    }
+}
@@ -0,0 +1,164 @@
+#[cfg(test)]
+mod tests {
+    use emixcollections::{bit_helper::BitHelper, bit_set::BitSet, range::Range};
+
+    fn bits(set: &BitSet) -> Vec<usize> {
+        set.iter().collect()
+    }
+
+    #[test]
+    fn test_set_clear_toggle() {
+        let mut s = BitSet::with_len(10);
+        assert_eq!(s.len(), 10);
+        assert!(!s.set(3));
+        assert!(s.set(3));
+        assert!(s.contains(3));
+        assert!(s.clear(3));
+        assert!(!s.clear(3));
+        assert!(!s.toggle(4));
+        assert!(s.toggle(4));
+        assert!(!s.contains(4));
+
+        assert!(!s.clear(500));
+        assert_eq!(s.len(), 10);
+        assert!(!s.contains(500));
+
+        s.set(100);
+        assert_eq!(s.len(), 101);
+        assert!(s.contains(100));
+        s.toggle(200);
+        assert_eq!(s.len(), 201);
+        assert_eq!(bits(&s), vec![100, 200]);
+    }
+
+    #[test]
+    fn test_fill_ranges() {
+        let mut s = BitSet::new();
+        s.fill(&Range::new(5, 70), true);
+        assert_eq!(s.len(), 71);
+        assert_eq!(s.count_ones(), 66);
+        assert_eq!(s.first_set(), Some(5));
+        assert_eq!(s.last_set(), Some(70));
+
+        s.fill(&Range::new(30, 1000), false);
+        assert_eq!(s.len(), 71);
+        assert_eq!(bits(&s), (5..30).collect::<Vec<_>>());
+
+        s.toggle_range(&Range::new(0, 9));
+        assert_eq!(
+            bits(&s),
+            [0, 1, 2, 3, 4]
+                .into_iter()
+                .chain(10..30)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(s.count_zeros(), 71 - 25);
+
+        s.set_all();
+        assert_eq!(s.count_ones(), 71);
+        s.toggle_all();
+        assert!(!s.any());
+        s.set(3);
+        s.clear_all();
+        assert!(!s.any());
+        assert_eq!(s.len(), 71);
+    }
+
+    #[test]
+    fn test_next_and_iteration() {
+        let s: BitSet = [1, 31, 32, 64, 95].into_iter().collect();
+        assert_eq!(s.len(), 96);
+        assert_eq!(bits(&s), vec![1, 31, 32, 64, 95]);
+        assert_eq!(s.next_set(2), Some(31));
+        assert_eq!(s.next_set(33), Some(64));
+        assert_eq!(s.next_set(96), None);
+        assert_eq!(s.next_clear(31), Some(33));
+        assert_eq!(s.next_clear(95), None);
+        assert_eq!(BitSet::ones(40).next_clear(0), None);
+        assert_eq!(BitSet::new().first_set(), None);
+        assert_eq!(BitSet::new().iter().count(), 0);
+    }
+
+    #[test]
+    fn test_rank_select() {
+        let s: BitSet = [0, 5, 31, 32, 40, 100].into_iter().collect();
+        assert_eq!(s.rank(0), 0);
+        assert_eq!(s.rank(1), 1);
+        assert_eq!(s.rank(32), 3);
+        assert_eq!(s.rank(33), 4);
+        assert_eq!(s.rank(1000), 6);
+
+        for (n, index) in s.iter().enumerate() {
+            assert_eq!(s.select(n), Some(index));
+            assert_eq!(s.rank(index), n);
+        }
+
+        assert_eq!(s.select(6), None);
+    }
+
+    #[test]
+    fn test_set_algebra() {
+        let a: BitSet = [1, 2, 3, 40].into_iter().collect();
+        let b: BitSet = [2, 3, 4, 70].into_iter().collect();
+
+        assert_eq!(bits(&(&a | &b)), vec![1, 2, 3, 4, 40, 70]);
+        assert_eq!(bits(&(&a & &b)), vec![2, 3]);
+        assert_eq!(bits(&(&a ^ &b)), vec![1, 4, 40, 70]);
+        assert_eq!(bits(&(&a - &b)), vec![1, 40]);
+        assert_eq!((&a & &b).len(), 71);
+
+        let not = !&BitSet::from_bytes(&[0b1010_0101]);
+        assert_eq!(not.to_bytes(), vec![0b0101_1010]);
+
+        let mut c = a.clone();
+        c |= &b;
+        c -= &a;
+        assert_eq!(bits(&c), vec![4, 70]);
+        c &= &b;
+        c ^= &b;
+        assert_eq!(bits(&c), vec![2, 3]);
+
+        let sub: BitSet = [2, 3].into_iter().collect();
+        assert!(sub.is_subset(&a));
+        assert!(a.is_superset(&sub));
+        assert!(!a.is_subset(&sub));
+        assert!(!a.is_disjoint(&b));
+        assert!(sub.is_disjoint(&[1, 40].into_iter().collect()));
+    }
+
+    #[test]
+    fn test_bytes_and_words() {
+        let s = BitSet::from_bytes(&[0x01, 0x80, 0xFF]);
+        assert_eq!(s.len(), 24);
+        assert_eq!(
+            bits(&s),
+            [0, 15].into_iter().chain(16..24).collect::<Vec<_>>()
+        );
+        assert_eq!(s.to_bytes(), vec![0x01, 0x80, 0xFF]);
+
+        let mut t = BitSet::ones(12);
+        assert_eq!(t.to_bytes(), vec![0xFF, 0x0F]);
+        t.resize(4);
+        assert_eq!(t.to_bytes(), vec![0x0F]);
+        t.resize(40);
+        assert_eq!(t.count_ones(), 4);
+
+        let mut array = vec![0u32; BitHelper::to_int_array_length(40)];
+        let mut helper = BitHelper::new(&mut array);
+        helper.mark_bit(3);
+        helper.mark_bit(35);
+        let from_helper = BitSet::from_words(array, 36);
+        assert_eq!(bits(&from_helper), vec![3, 35]);
+        assert_eq!(from_helper.words(), &[8, 8]);
+        assert_eq!(BitSet::from_words(vec![u32::MAX], 4).count_ones(), 4);
+    }
+
+    #[test]
+    fn test_serde() {
+        let s: BitSet = [0, 9, 10].into_iter().collect();
+        let json = serde_json::to_string(&s).unwrap();
+        assert_eq!(json, r#"{"len":11,"bytes":[1,6]}"#);
+        assert_eq!(serde_json::from_str::<BitSet>(&json).unwrap(), s);
+        assert!(serde_json::from_str::<BitSet>(r#"{"len":30,"bytes":[1]}"#).is_err());
+    }
+}
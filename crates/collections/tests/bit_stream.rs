@@ -0,0 +1,196 @@
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use emixcollections::{
+        Error,
+        bit_stream::{BitOrder, BitReader, BitWriter},
+    };
+
+    #[test]
+    fn test_msb_first() {
+        let bytes = [0b1011_0011, 0b0101_1100];
+        let mut r = BitReader::new(&bytes[..]);
+        assert!(r.read_bit().unwrap());
+        assert_eq!(r.read_bits(3).unwrap(), 0b011);
+        assert_eq!(r.read_bits(6).unwrap(), 0b00_1101);
+        assert_eq!(r.position(), 10);
+        assert!(!r.is_aligned());
+        assert_eq!(r.read_bits(6).unwrap(), 0b01_1100);
+        assert!(r.is_aligned());
+
+        let mut w = BitWriter::new(Vec::new());
+        w.write_bit(true).unwrap();
+        w.write_bits(3, 0b011).unwrap();
+        w.write_bits(6, 0b00_1101).unwrap();
+        w.write_bits(6, 0b01_1100).unwrap();
+        assert_eq!(w.finish().unwrap(), bytes);
+    }
+
+    #[test]
+    fn test_lsb_first() {
+        let mut w = BitWriter::with_order(Vec::new(), BitOrder::LsbFirst);
+        w.write_bits(3, 0b101).unwrap();
+        w.write_bits(7, 0b110_0111).unwrap();
+        w.write_bits(2, 0b10).unwrap();
+        let bytes = w.finish().unwrap();
+        assert_eq!(bytes, vec![0b0011_1101, 0b0000_1011]);
+
+        let mut r = BitReader::with_order(&bytes[..], BitOrder::LsbFirst);
+        assert_eq!(r.order(), BitOrder::LsbFirst);
+        assert_eq!(r.read_bits(3).unwrap(), 0b101);
+        assert_eq!(r.read_bits(7).unwrap(), 0b110_0111);
+        assert_eq!(r.read_bits(2).unwrap(), 0b10);
+    }
+
+    #[test]
+    fn test_wide_and_signed_values() {
+        for order in [BitOrder::MsbFirst, BitOrder::LsbFirst] {
+            let mut w = BitWriter::with_order(Vec::new(), order);
+            w.write_bits(5, 0b10110).unwrap();
+            w.write_bits(64, 0xDEAD_BEEF_0123_4567).unwrap();
+            w.write_signed(7, -64).unwrap();
+            w.write_signed(7, 63).unwrap();
+            w.write_signed(64, i64::MIN).unwrap();
+            w.write_bits(0, 0).unwrap();
+            let bytes = w.finish().unwrap();
+
+            let mut r = BitReader::with_order(&bytes[..], order);
+            assert_eq!(r.read_bits(5).unwrap(), 0b10110);
+            assert_eq!(r.read_bits(64).unwrap(), 0xDEAD_BEEF_0123_4567);
+            assert_eq!(r.read_signed(7).unwrap(), -64);
+            assert_eq!(r.read_signed(7).unwrap(), 63);
+            assert_eq!(r.read_signed(64).unwrap(), i64::MIN);
+            assert_eq!(r.read_signed(0).unwrap(), 0);
+        }
+    }
+
+    #[test]
+    fn test_peek_skip_align() {
+        let bytes = [0xAB, 0xCD, 0xEF];
+        let mut r = BitReader::new(Cursor::new(bytes));
+        assert_eq!(r.peek_bits(12).unwrap(), 0xABC);
+        assert_eq!(r.position(), 0);
+        assert_eq!(r.read_bits(4).unwrap(), 0xA);
+        r.align();
+        assert_eq!(r.position(), 8);
+        r.skip_bits(4).unwrap();
+        assert_eq!(r.read_bits(4).unwrap(), 0xD);
+        let mut buf = [0u8; 1];
+        r.read_bytes(&mut buf).unwrap();
+        assert_eq!(buf, [0xEF]);
+
+        let mut w = BitWriter::new(Vec::new());
+        w.write_bits(3, 0b111).unwrap();
+        w.align().unwrap();
+        assert_eq!(w.position(), 8);
+        assert!(w.is_aligned());
+        w.write_bytes(&[0x12]).unwrap();
+        assert_eq!(w.finish().unwrap(), vec![0b1110_0000, 0x12]);
+    }
+
+    #[test]
+    fn test_errors_instead_of_panics() {
+        let bytes = [0xFF];
+        let mut r = BitReader::new(&bytes[..]);
+        assert!(matches!(r.read_bits(9), Err(Error::NotEnoughData)));
+        assert_eq!(r.position(), 0);
+        assert_eq!(r.read_bits(8).unwrap(), 0xFF);
+        assert!(matches!(r.read_bit(), Err(Error::NotEnoughData)));
+        assert!(matches!(r.peek_bits(1), Err(Error::NotEnoughData)));
+        assert!(matches!(r.skip_bits(1), Err(Error::NotEnoughData)));
+        assert!(matches!(r.read_bits(65), Err(Error::Argument(_))));
+
+        let mut w = BitWriter::new(Vec::new());
+        assert!(matches!(w.write_bits(3, 8), Err(Error::Argument(_))));
+        assert!(matches!(w.write_signed(4, 8), Err(Error::Argument(_))));
+        assert!(matches!(w.write_signed(4, -9), Err(Error::Argument(_))));
+        assert!(matches!(w.write_bits(65, 0), Err(Error::Argument(_))));
+        assert!(matches!(
+            w.write_signed_exp_golomb(i64::MIN),
+            Err(Error::Argument(_))
+        ));
+        assert_eq!(w.position(), 0);
+    }
+
+    #[test]
+    fn test_exp_golomb() {
+        let mut w = BitWriter::new(Vec::new());
+        for v in 0..5 {
+            w.write_exp_golomb(v).unwrap();
+        }
+        // 1 010 011 00100 00101
+        assert_eq!(
+            w.finish().unwrap(),
+            vec![0b1010_0110, 0b0100_0010, 0b1000_0000]
+        );
+
+        let values = [0, 1, 2, 7, 8, 1000, u32::MAX as u64, u64::MAX - 1, u64::MAX];
+        let signed = [0, 1, -1, 2, -2, 1000, -1000, i64::MAX, i64::MIN + 1];
+        let mut w = BitWriter::new(Vec::new());
+        for &v in &values {
+            w.write_exp_golomb(v).unwrap();
+        }
+        for &v in &signed {
+            w.write_signed_exp_golomb(v).unwrap();
+        }
+        let bytes = w.finish().unwrap();
+
+        let mut r = BitReader::new(&bytes[..]);
+        for &v in &values {
+            assert_eq!(r.read_exp_golomb().unwrap(), v);
+        }
+        for &v in &signed {
+            assert_eq!(r.read_signed_exp_golomb().unwrap(), v);
+        }
+
+        let zeros = [0u8; 10];
+        assert!(matches!(
+            BitReader::new(&zeros[..]).read_exp_golomb(),
+            Err(Error::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn test_leb128() {
+        let mut w = BitWriter::new(Vec::new());
+        w.write_leb128(624_485).unwrap();
+        w.write_signed_leb128(-123_456).unwrap();
+        assert_eq!(
+            w.finish().unwrap(),
+            vec![0xE5, 0x8E, 0x26, 0xC0, 0xBB, 0x78]
+        );
+
+        let values = [0, 1, 127, 128, 300, u64::MAX];
+        let signed = [0, -1, 63, -64, 64, -65, i64::MAX, i64::MIN];
+        let mut w = BitWriter::with_order(Vec::new(), BitOrder::LsbFirst);
+        w.write_bits(3, 0b101).unwrap();
+        for &v in &values {
+            w.write_leb128(v).unwrap();
+        }
+        for &v in &signed {
+            w.write_signed_leb128(v).unwrap();
+        }
+        let bytes = w.finish().unwrap();
+
+        let mut r = BitReader::with_order(&bytes[..], BitOrder::LsbFirst);
+        assert_eq!(r.read_bits(3).unwrap(), 0b101);
+        for &v in &values {
+            assert_eq!(r.read_leb128().unwrap(), v);
+        }
+        for &v in &signed {
+            assert_eq!(r.read_signed_leb128().unwrap(), v);
+        }
+
+        let overflow = [0xFF; 10];
+        assert!(matches!(
+            BitReader::new(&overflow[..]).read_leb128(),
+            Err(Error::Parse(_))
+        ));
+        let truncated = [0x80];
+        assert!(matches!(
+            BitReader::new(&truncated[..]).read_leb128(),
+            Err(Error::NotEnoughData)
+        ));
+    }
+}
@@ -0,0 +1,235 @@
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            Arc, Barrier, Mutex,
+            atomic::{AtomicUsize, Ordering},
+        },
+        thread,
+        time::Duration,
+    };
+
+    use emixcollections::{
+        Error,
+        cache::{CacheOptions, CacheStats, Cleanup, EvictionReason, LfuCache, LruCache, TtlCache},
+    };
+
+    type Log = Arc<Mutex<Vec<(String, u32, EvictionReason)>>>;
+
+    fn logging_options(capacity: usize) -> (CacheOptions<String, u32>, Log) {
+        let log: Log = Arc::default();
+        let sink = log.clone();
+        let options = CacheOptions::new(capacity)
+            .with_eviction_listener(move |k, v, reason| sink.lock().unwrap().push((k, v, reason)));
+        (options, log)
+    }
+
+    #[test]
+    fn test_lru_eviction() {
+        let (options, log) = logging_options(2);
+        let cache = LruCache::with_options(options);
+        cache.insert("a".to_string(), 1).unwrap();
+        cache.insert("b".to_string(), 2).unwrap();
+        assert_eq!(cache.get("a").unwrap(), Some(1));
+        cache.insert("c".to_string(), 3).unwrap();
+
+        assert_eq!(cache.len().unwrap(), 2);
+        assert!(!cache.contains_key("b").unwrap());
+        assert!(cache.contains_key("a").unwrap());
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec![("b".to_string(), 2, EvictionReason::Capacity)]
+        );
+
+        // Peeking does not protect an entry from eviction.
+        assert_eq!(cache.peek("a").unwrap(), Some(1));
+        cache.insert("d".to_string(), 4).unwrap();
+        assert!(!cache.contains_key("a").unwrap());
+
+        cache.insert("c".to_string(), 30).unwrap();
+        assert_eq!(
+            log.lock().unwrap().last(),
+            Some(&("c".to_string(), 3, EvictionReason::Replaced))
+        );
+        assert_eq!(cache.remove("c").unwrap(), Some(30));
+        assert_eq!(cache.remove("c").unwrap(), None);
+
+        cache.clear().unwrap();
+        assert!(cache.is_empty().unwrap());
+    }
+
+    #[test]
+    fn test_lfu_eviction() {
+        let cache = LfuCache::new(3);
+        cache.insert(1, "one").unwrap();
+        cache.insert(2, "two").unwrap();
+        cache.insert(3, "three").unwrap();
+
+        for _ in 0..3 {
+            cache.get(&1).unwrap();
+        }
+
+        cache.get(&2).unwrap();
+        cache.get(&3).unwrap();
+        cache.get(&3).unwrap();
+        cache.insert(4, "four").unwrap();
+        assert!(!cache.contains_key(&2).unwrap());
+
+        // The new entry is the least frequently used, so it goes first.
+        cache.insert(5, "five").unwrap();
+        assert!(!cache.contains_key(&4).unwrap());
+        assert!(cache.contains_key(&1).unwrap());
+        assert!(cache.contains_key(&3).unwrap());
+    }
+
+    #[test]
+    fn test_weighted_capacity() {
+        let (options, log) = logging_options(10);
+        let cache = LruCache::with_options(options.with_weigher(|_, v| *v as usize));
+        cache.insert("a".to_string(), 4).unwrap();
+        cache.insert("b".to_string(), 4).unwrap();
+        assert_eq!(cache.weight().unwrap(), 8);
+
+        cache.insert("c".to_string(), 5).unwrap();
+        assert_eq!(cache.weight().unwrap(), 9);
+        assert_eq!(cache.len().unwrap(), 2);
+        assert!(!cache.contains_key("a").unwrap());
+
+        cache.insert("huge".to_string(), 11).unwrap();
+        assert!(!cache.contains_key("huge").unwrap());
+        assert_eq!(cache.len().unwrap(), 2);
+        assert_eq!(
+            log.lock().unwrap().last(),
+            Some(&("huge".to_string(), 11, EvictionReason::Capacity))
+        );
+        assert_eq!(cache.stats().unwrap().evictions, 2);
+    }
+
+    #[test]
+    fn test_ttl_expiry() {
+        let (options, log) = logging_options(10);
+        let cache = TtlCache::with_options(options, Duration::from_millis(50), Cleanup::Lazy);
+        cache.insert("a".to_string(), 1).unwrap();
+        cache
+            .insert_with_ttl("b".to_string(), 2, Duration::from_secs(60))
+            .unwrap();
+        assert_eq!(cache.get("a").unwrap(), Some(1));
+        assert!(cache.time_to_live("a").unwrap().unwrap() <= Duration::from_millis(50));
+        assert_eq!(cache.ttl(), Duration::from_millis(50));
+
+        thread::sleep(Duration::from_millis(80));
+        assert_eq!(cache.peek("a").unwrap(), None);
+        assert_eq!(cache.time_to_live("a").unwrap(), None);
+        assert_eq!(cache.len().unwrap(), 2);
+        assert_eq!(cache.get("a").unwrap(), None);
+        assert_eq!(cache.len().unwrap(), 1);
+        assert_eq!(cache.get("b").unwrap(), Some(2));
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec![("a".to_string(), 1, EvictionReason::Expired)]
+        );
+
+        cache.insert("c".to_string(), 3).unwrap();
+        thread::sleep(Duration::from_millis(80));
+        assert_eq!(cache.purge_expired().unwrap(), 1);
+        assert_eq!(cache.stats().unwrap().expirations, 2);
+    }
+
+    #[test]
+    fn test_ttl_background_cleanup() {
+        // A zero interval is raised to `CLEANUP_INTERVAL_MIN` rather than spinning.
+        for interval in [Duration::from_millis(10), Duration::ZERO] {
+            let (options, log) = logging_options(10);
+            let cache = TtlCache::with_options(
+                options,
+                Duration::from_millis(20),
+                Cleanup::Background(interval),
+            );
+            cache.insert("a".to_string(), 1).unwrap();
+            cache.insert("b".to_string(), 2).unwrap();
+
+            for _ in 0..100 {
+                if cache.is_empty().unwrap() {
+                    break;
+                }
+
+                thread::sleep(Duration::from_millis(10));
+            }
+
+            assert!(cache.is_empty().unwrap());
+            assert_eq!(log.lock().unwrap().len(), 2);
+        }
+    }
+
+    #[test]
+    fn test_get_or_insert_with_single_flight() {
+        let cache = LruCache::new(10);
+        let calls = Arc::new(AtomicUsize::new(0));
+        let barrier = Arc::new(Barrier::new(8));
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let cache = cache.clone();
+                let calls = calls.clone();
+                let barrier = barrier.clone();
+                thread::spawn(move || {
+                    barrier.wait();
+                    cache
+                        .get_or_insert_with("key", || {
+                            calls.fetch_add(1, Ordering::SeqCst);
+                            thread::sleep(Duration::from_millis(50));
+                            42
+                        })
+                        .unwrap()
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), 42);
+        }
+
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        let stats = cache.stats().unwrap();
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.hits, 7);
+    }
+
+    #[test]
+    fn test_try_get_or_insert_with_errors() {
+        let cache = LruCache::new(10);
+        let result = cache.try_get_or_insert_with(1, || Err(Error::Timeout));
+        assert!(matches!(result, Err(Error::Timeout)));
+        assert!(!cache.contains_key(&1).unwrap());
+
+        // A failed load is not cached; the next caller loads again.
+        assert_eq!(cache.try_get_or_insert_with(1, || Ok("up")).unwrap(), "up");
+        assert_eq!(
+            cache.try_get_or_insert_with(1, || Ok("ignored")).unwrap(),
+            "up"
+        );
+    }
+
+    #[test]
+    fn test_stats() {
+        let cache = LruCache::new(10);
+        assert_eq!(cache.stats().unwrap(), CacheStats::default());
+        assert_eq!(cache.stats().unwrap().hit_rate(), 0.0);
+
+        cache.insert(1, 1).unwrap();
+        cache.get(&1).unwrap();
+        cache.get(&1).unwrap();
+        cache.get(&1).unwrap();
+        cache.get(&2).unwrap();
+        cache.peek(&2).unwrap();
+
+        let stats = cache.stats().unwrap();
+        assert_eq!(stats.hits, 3);
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.insertions, 1);
+        assert_eq!(stats.lookups(), 4);
+        assert_eq!(stats.hit_rate(), 0.75);
+
+        cache.reset_stats().unwrap();
+        assert_eq!(cache.stats().unwrap(), CacheStats::default());
+    }
+}
@@ -0,0 +1,252 @@
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashSet,
+        sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+        },
+        thread,
+    };
+
+    use emixcollections::concurrent::{ObjectPool, RingBuffer, ShardedMap};
+
+    #[test]
+    fn test_ring_buffer_push_pop() {
+        let ring = RingBuffer::new(3);
+        assert_eq!(ring.capacity(), 3);
+        assert!(ring.is_empty());
+        assert_eq!(ring.pop(), None);
+
+        assert_eq!(ring.push(1), Ok(()));
+        assert_eq!(ring.push(2), Ok(()));
+        assert_eq!(ring.push(3), Ok(()));
+        assert!(ring.is_full());
+        assert_eq!(ring.push(4), Err(4));
+        assert_eq!(ring.pop(), Some(1));
+        assert_eq!(ring.push(4), Ok(()));
+        assert_eq!(ring.drain().collect::<Vec<_>>(), vec![2, 3, 4]);
+        assert_eq!(ring.len(), 0);
+
+        let single = RingBuffer::new(0);
+        assert_eq!(single.capacity(), 1);
+        assert_eq!(single.push('a'), Ok(()));
+        assert_eq!(single.push('b'), Err('b'));
+        assert_eq!(single.pop(), Some('a'));
+        assert_eq!(single.pop(), None);
+    }
+
+    #[test]
+    fn test_ring_buffer_overwrite() {
+        let ring = RingBuffer::new(3);
+
+        for i in 0..3 {
+            assert_eq!(ring.push_overwrite(i), None);
+        }
+
+        assert_eq!(ring.push_overwrite(3), Some(0));
+        assert_eq!(ring.push_overwrite(4), Some(1));
+        assert_eq!(ring.drain().collect::<Vec<_>>(), vec![2, 3, 4]);
+
+        let single = RingBuffer::new(1);
+        single.push_overwrite("old");
+        assert_eq!(single.push_overwrite("new"), Some("old"));
+        assert_eq!(single.pop(), Some("new"));
+    }
+
+    #[test]
+    fn test_ring_buffer_drops_items() {
+        let item = Arc::new(());
+        let ring = RingBuffer::new(4);
+        ring.push(item.clone()).unwrap();
+        ring.push(item.clone()).unwrap();
+        assert_eq!(Arc::strong_count(&item), 3);
+        drop(ring);
+        assert_eq!(Arc::strong_count(&item), 1);
+    }
+
+    #[test]
+    fn test_ring_buffer_mpmc() {
+        const PRODUCERS: usize = 4;
+        const ITEMS: usize = 10_000;
+        let ring = Arc::new(RingBuffer::new(64));
+        let consumed = Arc::new(AtomicUsize::new(0));
+
+        let producers: Vec<_> = (0..PRODUCERS)
+            .map(|p| {
+                let ring = ring.clone();
+                thread::spawn(move || {
+                    for i in 0..ITEMS {
+                        let mut item = p * ITEMS + i;
+
+                        while let Err(rejected) = ring.push(item) {
+                            item = rejected;
+                            thread::yield_now();
+                        }
+                    }
+                })
+            })
+            .collect();
+        let consumers: Vec<_> = (0..PRODUCERS)
+            .map(|_| {
+                let ring = ring.clone();
+                let consumed = consumed.clone();
+                thread::spawn(move || {
+                    let mut seen = Vec::new();
+
+                    while consumed.load(Ordering::SeqCst) < PRODUCERS * ITEMS {
+                        match ring.pop() {
+                            Some(item) => {
+                                seen.push(item);
+                                consumed.fetch_add(1, Ordering::SeqCst);
+                            }
+                            None => thread::yield_now(),
+                        }
+                    }
+
+                    seen
+                })
+            })
+            .collect();
+
+        for producer in producers {
+            producer.join().unwrap();
+        }
+
+        let mut all = HashSet::new();
+
+        for consumer in consumers {
+            for item in consumer.join().unwrap() {
+                assert!(all.insert(item), "{} was delivered twice", item);
+            }
+        }
+
+        assert_eq!(all.len(), PRODUCERS * ITEMS);
+        assert!(ring.is_empty());
+    }
+
+    #[test]
+    fn test_sharded_map() {
+        let map = ShardedMap::with_shards(5);
+        assert_eq!(map.shard_count(), 8);
+        assert!(map.is_empty());
+
+        assert_eq!(map.insert("a".to_string(), 1), None);
+        assert_eq!(map.insert("a".to_string(), 2), Some(1));
+        assert_eq!(map.get("a"), Some(2));
+        assert!(map.contains_key("a"));
+        assert_eq!(map.get_with("a", |v| v * 10), Some(20));
+        assert_eq!(map.update("a", |v| *v += 1), Some(()));
+        assert_eq!(map.get("a"), Some(3));
+        assert_eq!(map.update("b", |v| *v += 1), None);
+        assert_eq!(map.get_or_insert_with("b".to_string(), || 7), 7);
+        assert_eq!(map.get_or_insert_with("b".to_string(), || 8), 7);
+        assert_eq!(map.len(), 2);
+
+        map.retain(|_, v| *v > 5);
+        assert_eq!(map.remove("a"), None);
+        assert_eq!(map.remove("b"), Some(7));
+
+        let map: ShardedMap<u32, u32> = (0..100).map(|i| (i, i * i)).collect();
+        assert_eq!(map.len(), 100);
+        assert_eq!(map.to_map().get(&9), Some(&81));
+        assert_eq!(map.into_map().len(), 100);
+    }
+
+    #[test]
+    fn test_sharded_map_concurrent() {
+        let map = Arc::new(ShardedMap::new());
+        let calls = Arc::new(AtomicUsize::new(0));
+        let handles: Vec<_> = (0..8u64)
+            .map(|t| {
+                let map = map.clone();
+                let calls = calls.clone();
+                thread::spawn(move || {
+                    for i in 0..1_000u64 {
+                        map.insert(t * 1_000 + i, i);
+                        map.update(&(t * 1_000 + i), |v| *v += 1);
+                    }
+
+                    map.get_or_insert_with(u64::MAX, || {
+                        calls.fetch_add(1, Ordering::SeqCst);
+                        0
+                    });
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(map.len(), 8_001);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(map.get(&7_999), Some(1_000));
+    }
+
+    #[test]
+    fn test_object_pool() {
+        let created = Arc::new(AtomicUsize::new(0));
+        let counter = created.clone();
+        let pool = ObjectPool::with_reset(
+            2,
+            move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+                Vec::<u8>::with_capacity(16)
+            },
+            Vec::clear,
+        );
+        assert!(pool.try_get().is_none());
+
+        {
+            let mut buffer = pool.get();
+            buffer.extend_from_slice(b"abc");
+            assert_eq!(&buffer[..], b"abc");
+        }
+
+        assert_eq!(pool.idle(), 1);
+        let buffer = pool.get();
+        assert!(buffer.is_empty());
+        assert!(buffer.capacity() >= 16);
+        assert_eq!(created.load(Ordering::SeqCst), 1);
+
+        let others = [pool.get(), pool.get()];
+        drop(buffer);
+        drop(others);
+        assert_eq!(pool.idle(), 2);
+        assert_eq!(created.load(Ordering::SeqCst), 3);
+
+        let detached = pool.get().detach();
+        assert!(detached.is_empty());
+        assert_eq!(pool.idle(), 1);
+
+        pool.prefill(5);
+        assert_eq!(pool.idle(), 2);
+        pool.clear();
+        assert_eq!(pool.idle(), 0);
+    }
+
+    #[test]
+    fn test_object_pool_threads() {
+        let pool = ObjectPool::new(4, String::new);
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                let pool = pool.clone();
+                thread::spawn(move || {
+                    for _ in 0..100 {
+                        let mut s = pool.get();
+                        s.clear();
+                        s.push_str(&i.to_string());
+                        assert_eq!(*s, i.to_string());
+                    }
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert!(pool.idle() <= pool.max_idle());
+    }
+}
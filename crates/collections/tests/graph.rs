@@ -0,0 +1,255 @@
+#[cfg(test)]
+mod tests {
+    use emixcollections::{
+        Error,
+        graph::{Cycle, Direction, Graph, NodeId},
+    };
+
+    /// A - 1 -> B - 2 -> C, A - 5 -> C, C - 1 -> D
+    fn weighted() -> (Graph<&'static str, u32>, [NodeId; 4]) {
+        let mut graph = Graph::directed();
+        let a = graph.add_node("A");
+        let b = graph.add_node("B");
+        let c = graph.add_node("C");
+        let d = graph.add_node("D");
+        graph.add_edge(a, b, 1);
+        graph.add_edge(b, c, 2);
+        graph.add_edge(a, c, 5);
+        graph.add_edge(c, d, 1);
+        (graph, [a, b, c, d])
+    }
+
+    #[test]
+    fn test_structure() {
+        let (mut graph, [a, b, c, d]) = weighted();
+        assert!(graph.is_directed());
+        assert_eq!(graph.node_count(), 4);
+        assert_eq!(graph.edge_count(), 4);
+        assert_eq!(graph[b], "B");
+        assert_eq!(graph.node(NodeId::new(9)), None);
+        assert_eq!(graph.neighbors(a).collect::<Vec<_>>(), vec![b, c]);
+        assert_eq!(
+            graph
+                .neighbors_directed(c, Direction::Incoming)
+                .collect::<Vec<_>>(),
+            vec![b, a]
+        );
+        assert_eq!(graph.degree(c, Direction::Incoming), 2);
+        assert_eq!(graph.degree(d, Direction::Outgoing), 0);
+        assert!(graph.contains_edge(a, b));
+        assert!(!graph.contains_edge(b, a));
+
+        let edge = graph.find_edge(b, c).unwrap();
+        assert_eq!(graph.endpoints(edge), Some((b, c)));
+        graph[edge] = 7;
+        assert_eq!(graph.edge(edge), Some(&7));
+
+        let mut undirected = Graph::undirected();
+        let x = undirected.add_node(());
+        let y = undirected.add_node(());
+        undirected.add_edge(x, y, ());
+        undirected.add_edge(y, y, ());
+        assert!(undirected.contains_edge(y, x));
+        assert_eq!(undirected.neighbors(y).collect::<Vec<_>>(), vec![x, y]);
+        assert_eq!(undirected.degree(x, Direction::Incoming), 1);
+    }
+
+    #[test]
+    fn test_bfs_dfs() {
+        //   0
+        //  / \
+        // 1   2
+        // |   |
+        // 3   4
+        let mut graph = Graph::<u32, ()>::undirected();
+        let nodes: Vec<_> = (0..6).map(|i| graph.add_node(i)).collect();
+        graph.add_edge(nodes[0], nodes[1], ());
+        graph.add_edge(nodes[0], nodes[2], ());
+        graph.add_edge(nodes[1], nodes[3], ());
+        graph.add_edge(nodes[2], nodes[4], ());
+
+        let order =
+            |it: &mut dyn Iterator<Item = NodeId>| it.map(|n| n.index()).collect::<Vec<_>>();
+        assert_eq!(order(&mut graph.bfs(nodes[0])), vec![0, 1, 2, 3, 4]);
+        assert_eq!(order(&mut graph.dfs(nodes[0])), vec![0, 1, 3, 2, 4]);
+        assert_eq!(order(&mut graph.bfs(nodes[5])), vec![5]);
+        assert_eq!(order(&mut graph.dfs(nodes[4])), vec![4, 2, 0, 1, 3]);
+    }
+
+    #[test]
+    fn test_topological_sort() {
+        let mut graph = Graph::<&str, ()>::directed();
+        let compile = graph.add_node("compile");
+        let test = graph.add_node("test");
+        let fetch = graph.add_node("fetch");
+        let deploy = graph.add_node("deploy");
+        graph.add_edge(compile, test, ());
+        graph.add_edge(test, deploy, ());
+        graph.add_edge(fetch, compile, ());
+        graph.add_edge(compile, deploy, ());
+
+        let order = graph.topological_sort().unwrap();
+        assert_eq!(order.len(), 4);
+        let position = |node| order.iter().position(|it| *it == node).unwrap();
+
+        for edge in graph.edges() {
+            assert!(position(edge.source) < position(edge.target));
+        }
+
+        graph.add_edge(deploy, fetch, ());
+        let cycle = graph.topological_sort().unwrap_err();
+        assert_eq!(cycle.nodes, vec![compile, test, deploy, fetch]);
+        assert_eq!(
+            cycle.to_string(),
+            "Cycle through nodes 0 -> 1 -> 3 -> 2 -> 0"
+        );
+
+        let error: Error = cycle.into();
+        assert!(matches!(error, Error::InvalidOperation(_)));
+
+        let mut looped = Graph::<(), ()>::directed();
+        let only = looped.add_node(());
+        looped.add_edge(only, only, ());
+        assert_eq!(looped.topological_sort(), Err(Cycle { nodes: vec![only] }));
+    }
+
+    #[test]
+    fn test_dijkstra() {
+        let (graph, [a, b, c, d]) = weighted();
+        let paths = graph.dijkstra(a, |cost| *cost);
+        assert_eq!(paths.start(), a);
+        assert_eq!(paths.distance(c), Some(3));
+        assert_eq!(paths.distance(d), Some(4));
+        assert_eq!(paths.path_to(d), Some(vec![a, b, c, d]));
+        assert_eq!(paths.path_to(a), Some(vec![a]));
+
+        let from_c = graph.dijkstra(c, |cost| *cost);
+        assert_eq!(from_c.distance(a), None);
+        assert_eq!(from_c.path_to(b), None);
+        assert_eq!(from_c.reachable().collect::<Vec<_>>(), vec![(c, 0), (d, 1)]);
+
+        let hops = graph.dijkstra(a, |_| 1.0f64);
+        assert_eq!(hops.path_to(c), Some(vec![a, c]));
+    }
+
+    #[test]
+    fn test_astar() {
+        // A 5x5 grid with a wall in column 2 except at the bottom row.
+        const SIZE: usize = 5;
+        let mut graph = Graph::<(usize, usize), u32>::undirected();
+        let mut ids = Vec::new();
+
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                ids.push(graph.add_node((x, y)));
+            }
+        }
+
+        let open = |x: usize, y: usize| x != 2 || y == SIZE - 1;
+
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                if !open(x, y) {
+                    continue;
+                }
+
+                if x + 1 < SIZE && open(x + 1, y) {
+                    graph.add_edge(ids[y * SIZE + x], ids[y * SIZE + x + 1], 1);
+                }
+
+                if y + 1 < SIZE && open(x, y + 1) {
+                    graph.add_edge(ids[y * SIZE + x], ids[(y + 1) * SIZE + x], 1);
+                }
+            }
+        }
+
+        let start = ids[0];
+        let goal = ids[SIZE - 1];
+        let (gx, gy) = graph[goal];
+        let manhattan = |node: NodeId| {
+            let (x, y) = graph[node];
+            (x.abs_diff(gx) + y.abs_diff(gy)) as u32
+        };
+        let (cost, path) = graph.astar(start, goal, |cost| *cost, manhattan).unwrap();
+        assert_eq!(cost, 12);
+        assert_eq!(path.len(), 13);
+        assert_eq!(path.first(), Some(&start));
+        assert_eq!(path.last(), Some(&goal));
+        assert!(path.contains(&ids[(SIZE - 1) * SIZE + 2]));
+        assert_eq!(
+            graph.dijkstra(start, |cost| *cost).distance(goal),
+            Some(cost)
+        );
+
+        let walled = ids[2];
+        assert_eq!(graph.astar(start, walled, |cost| *cost, |_| 0), None);
+    }
+
+    #[test]
+    fn test_strongly_connected_components() {
+        let mut graph = Graph::<(), ()>::directed();
+        let n: Vec<_> = (0..6).map(|_| graph.add_node(())).collect();
+        // {0, 1, 2} -> {3, 4} -> {5}
+        graph.add_edge(n[0], n[1], ());
+        graph.add_edge(n[1], n[2], ());
+        graph.add_edge(n[2], n[0], ());
+        graph.add_edge(n[2], n[3], ());
+        graph.add_edge(n[3], n[4], ());
+        graph.add_edge(n[4], n[3], ());
+        graph.add_edge(n[4], n[5], ());
+
+        let components = graph.strongly_connected_components();
+        assert_eq!(
+            components,
+            vec![vec![n[5]], vec![n[3], n[4]], vec![n[0], n[1], n[2]]]
+        );
+
+        let mut undirected = Graph::<(), ()>::undirected();
+        let m: Vec<_> = (0..4).map(|_| undirected.add_node(())).collect();
+        undirected.add_edge(m[0], m[1], ());
+        undirected.add_edge(m[2], m[3], ());
+        assert_eq!(undirected.strongly_connected_components().len(), 2);
+    }
+
+    #[test]
+    fn test_minimum_spanning_tree() {
+        let mut graph = Graph::<char, f64>::undirected();
+        let a = graph.add_node('a');
+        let b = graph.add_node('b');
+        let c = graph.add_node('c');
+        let d = graph.add_node('d');
+        let e = graph.add_node('e');
+        let ab = graph.add_edge(a, b, 1.0);
+        let bc = graph.add_edge(b, c, 2.5);
+        graph.add_edge(a, c, 3.0);
+        let cd = graph.add_edge(c, d, 0.5);
+        graph.add_edge(b, d, 4.0);
+        graph.add_edge(d, d, 0.0);
+
+        let tree = graph.minimum_spanning_tree(|cost| *cost);
+        assert_eq!(tree, vec![cd, ab, bc]);
+        let total: f64 = tree.iter().map(|id| graph[*id]).sum();
+        assert_eq!(total, 4.0);
+        assert!(tree.iter().all(|id| graph.endpoints(*id).unwrap().0 != e));
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let (graph, _) = weighted();
+        assert_eq!(
+            graph.to_dot(),
+            "digraph {\n    0 [label=\"A\"];\n    1 [label=\"B\"];\n    2 [label=\"C\"];\n    \
+             3 [label=\"D\"];\n    0 -> 1 [label=\"1\"];\n    1 -> 2 [label=\"2\"];\n    \
+             0 -> 2 [label=\"5\"];\n    2 -> 3 [label=\"1\"];\n}\n"
+        );
+
+        let mut undirected = Graph::<&str, ()>::undirected();
+        let a = undirected.add_node("say \"hi\"");
+        let b = undirected.add_node("b\\c");
+        undirected.add_edge(a, b, ());
+        assert_eq!(
+            undirected.to_dot_with(|it| it.to_string(), |_| String::new()),
+            "graph {\n    0 [label=\"say \\\"hi\\\"\"];\n    1 [label=\"b\\\\c\"];\n    0 -- 1;\n}\n"
+        );
+    }
+}
@@ -0,0 +1,196 @@
+#[cfg(test)]
+mod tests {
+    use emixcollections::range::{IntervalTree, Range};
+
+    fn found<'a>(it: impl Iterator<Item = (Range<i32>, &'a &'static str)>) -> Vec<&'static str> {
+        it.map(|(_, v)| *v).collect()
+    }
+
+    fn tree() -> IntervalTree<i32, &'static str> {
+        [
+            (Range::new(0, 100), "all"),
+            (Range::new(10, 20), "a"),
+            (Range::new(15, 25), "b"),
+            (Range::new(30, 40), "c"),
+            (Range::new(35, 35), "d"),
+            (Range::new(50, 60), "e"),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    #[test]
+    fn test_containing() {
+        let t = tree();
+        assert_eq!(t.len(), 6);
+        assert_eq!(found(t.containing(17)), vec!["all", "a", "b"]);
+        assert_eq!(found(t.containing(35)), vec!["all", "c", "d"]);
+        assert_eq!(found(t.containing(45)), vec!["all"]);
+        assert_eq!(found(t.containing(101)), Vec::<&str>::new());
+        assert!(t.contains(0));
+        assert!(!t.contains(-1));
+    }
+
+    #[test]
+    fn test_overlapping() {
+        let t = tree();
+        assert_eq!(
+            found(t.overlapping(&Range::new(21, 31))),
+            vec!["all", "b", "c"]
+        );
+        assert_eq!(found(t.overlapping(&Range::new(-5, 12))), vec!["all", "a"]);
+        assert_eq!(
+            found(t.overlapping(&Range::new(101, 200))),
+            Vec::<&str>::new()
+        );
+        assert_eq!(
+            found(t.overlapping(&Range { min: 40, max: 30 })),
+            Vec::<&str>::new()
+        );
+    }
+
+    #[test]
+    fn test_insert_and_remove() {
+        let mut t = IntervalTree::new();
+        assert!(t.is_empty());
+        assert_eq!(t.containing(5).count(), 0);
+
+        t.insert(Range::new(0, 10), 1);
+        t.insert(Range::new(5, 15), 2);
+        t.insert(Range::new(5, 15), 3);
+        assert_eq!(
+            t.containing(7).map(|(_, v)| *v).collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+
+        assert_eq!(t.remove(&Range::new(5, 15)), Some(2));
+        assert_eq!(t.remove(&Range::new(5, 14)), None);
+        assert_eq!(
+            t.containing(12).map(|(_, v)| *v).collect::<Vec<_>>(),
+            vec![3]
+        );
+
+        t.retain(|r, _| r.min > 0);
+        assert_eq!(t.iter().map(|(_, v)| *v).collect::<Vec<_>>(), vec![3]);
+        t.clear();
+        assert!(t.is_empty());
+    }
+
+    #[test]
+    fn test_matches_linear_scan() {
+        let intervals = (0..200)
+            .map(|i| {
+                let min = (i * 37) % 500;
+                (Range::new(min, min + (i * 13) % 60), i)
+            })
+            .collect::<Vec<_>>();
+        let mut t = IntervalTree::new();
+        t.extend(intervals.iter().take(100).cloned());
+        t.extend(intervals.iter().skip(100).cloned());
+
+        for point in -5..570 {
+            let mut expected = intervals
+                .iter()
+                .filter(|(r, _)| r.contains(point))
+                .map(|(_, v)| *v)
+                .collect::<Vec<_>>();
+            let mut actual = t.containing(point).map(|(_, v)| *v).collect::<Vec<_>>();
+            expected.sort();
+            actual.sort();
+            assert_eq!(actual, expected, "point {point}");
+        }
+    }
+
+    #[test]
+    fn test_unicode_scripts() {
+        let t: IntervalTree<char, &str> = [
+            (Range::new('\u{0000}', '\u{024F}'), "Latin"),
+            (Range::new('\u{0000}', '\u{007F}'), "ASCII"),
+            (Range::new('\u{0370}', '\u{03FF}'), "Greek"),
+            (Range::new('\u{1F00}', '\u{1FFF}'), "Greek"),
+        ]
+        .into_iter()
+        .collect();
+
+        let scripts = |c: char| t.containing(c).map(|(_, v)| *v).collect::<Vec<_>>();
+        assert_eq!(scripts('a'), vec!["ASCII", "Latin"]);
+        assert_eq!(scripts('ÿ'), vec!["Latin"]);
+        assert_eq!(scripts('ἀ'), vec!["Greek"]);
+        assert_eq!(
+            t.overlapping(&Range::new('\u{0200}', '\u{0400}')).count(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_ignores_empty_ranges() {
+        let mut t = IntervalTree::new();
+        t.insert(Range { min: 5, max: 0 }, "empty");
+        t.insert(Range::new(2, 3), "a");
+        assert_eq!(t.len(), 1);
+        assert_eq!(found(t.overlapping(&Range::new(0, 10))), vec!["a"]);
+        assert_eq!(found(t.containing(4)), Vec::<&str>::new());
+
+        let t: IntervalTree<i32, &str> =
+            [(Range { min: 5, max: 0 }, "empty")].into_iter().collect();
+        assert!(t.is_empty());
+    }
+
+    #[test]
+    fn test_incremental_inserts_and_removes_match_linear_scan() {
+        let mut intervals = Vec::new();
+        let mut t = IntervalTree::new();
+
+        for i in 0..2000 {
+            let min = (i * 7919) % 1000;
+            let range = Range::new(min, min + (i * 31) % 50);
+            t.insert(range, i);
+            intervals.push((range, i));
+        }
+
+        let removed = intervals
+            .iter()
+            .step_by(3)
+            .map(|(r, _)| *r)
+            .collect::<Vec<_>>();
+
+        for range in removed {
+            let first = intervals.iter().position(|(it, _)| *it == range).unwrap();
+            assert_eq!(t.remove(&range), Some(intervals.remove(first).1));
+        }
+
+        assert_eq!(t.len(), intervals.len());
+        intervals.sort_by_key(|(range, _)| *range);
+        let all = t.iter().map(|(r, v)| (r, *v)).collect::<Vec<_>>();
+        assert_eq!(all, intervals);
+        let mut reversed = t.iter().rev().map(|(r, v)| (r, *v)).collect::<Vec<_>>();
+        reversed.reverse();
+        assert_eq!(reversed, intervals);
+
+        for min in (-5..1060).step_by(7) {
+            let query = Range::new(min, min + 3);
+            let expected = intervals
+                .iter()
+                .filter(|(r, _)| r.overlaps(&query))
+                .map(|(_, v)| *v)
+                .collect::<Vec<_>>();
+            let actual = t.overlapping(&query).map(|(_, v)| *v).collect::<Vec<_>>();
+            assert_eq!(actual, expected, "query {query:?}");
+        }
+    }
+
+    #[test]
+    fn test_equality_ignores_shape() {
+        let mut a = IntervalTree::new();
+        let mut b = IntervalTree::new();
+
+        for i in 0..20 {
+            a.insert(Range::new(i, i + 1), i);
+            b.insert(Range::new(19 - i, 20 - i), 19 - i);
+        }
+
+        assert_eq!(a, b);
+        b.remove(&Range::new(0, 1));
+        assert_ne!(a, b);
+    }
+}
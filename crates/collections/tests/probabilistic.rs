@@ -0,0 +1,209 @@
+#[cfg(test)]
+mod tests {
+    use emixcollections::probabilistic::{
+        BloomFilter, CountMinSketch, CountingBloomFilter, HyperLogLog,
+    };
+
+    fn url(i: usize) -> String {
+        format!("https://example.com/page/{}", i)
+    }
+
+    #[test]
+    fn test_bloom_filter() {
+        let mut filter = BloomFilter::new(10_000, 0.01).unwrap();
+        assert_eq!(filter.bit_len(), 95_851);
+        assert_eq!(filter.hashes(), 7);
+        assert!(filter.is_empty());
+
+        for i in 0..10_000 {
+            filter.insert(url(i).as_str());
+        }
+
+        assert!((0..10_000).all(|i| filter.contains(url(i).as_str())));
+        assert!(!filter.insert(url(42).as_str()));
+
+        let false_positives = (10_000..20_000)
+            .filter(|&i| filter.contains(url(i).as_str()))
+            .count();
+        assert!(false_positives < 200, "{} false positives", false_positives);
+        assert!((filter.estimated_len() - 10_000.0).abs() < 300.0);
+        assert!(filter.false_positive_rate() < 0.02);
+
+        filter.clear();
+        assert!(filter.is_empty());
+        assert!(!filter.contains(url(1).as_str()));
+
+        assert!(BloomFilter::new(0, 0.01).is_err());
+        assert!(BloomFilter::new(10, 1.0).is_err());
+        assert!(BloomFilter::with_size(0, 3).is_err());
+    }
+
+    #[test]
+    fn test_bloom_filter_merge() {
+        let mut a = BloomFilter::new(1_000, 0.01).unwrap();
+        let mut b = BloomFilter::new(1_000, 0.01).unwrap();
+        (0..500).for_each(|i| _ = a.insert(&i));
+        (500..1_000).for_each(|i| _ = b.insert(&i));
+        a.merge(&b).unwrap();
+        assert!((0..1_000).all(|i| a.contains(&i)));
+
+        let c = BloomFilter::new(2_000, 0.01).unwrap();
+        assert!(a.merge(&c).is_err());
+    }
+
+    #[test]
+    fn test_counting_bloom_filter() {
+        let mut filter = CountingBloomFilter::new(1_000, 0.01).unwrap();
+        filter.insert("a");
+        filter.insert("a");
+        filter.insert("b");
+        assert_eq!(filter.count("a"), 2);
+        assert!(filter.contains("b"));
+
+        assert!(filter.remove("b"));
+        assert!(!filter.contains("b"));
+        assert!(!filter.remove("b"));
+        assert!(filter.remove("a"));
+        assert!(filter.contains("a"));
+        assert!(filter.remove("a"));
+        assert!(filter.is_empty());
+
+        let mut other = CountingBloomFilter::new(1_000, 0.01).unwrap();
+        other.insert("c");
+        filter.insert("c");
+        filter.merge(&other).unwrap();
+        assert_eq!(filter.count("c"), 2);
+        assert!(
+            filter
+                .merge(&CountingBloomFilter::with_size(10, 2).unwrap())
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_hyper_log_log() {
+        let mut hll = HyperLogLog::new(14).unwrap();
+        assert!(hll.is_empty());
+        assert_eq!(hll.count(), 0);
+
+        for _ in 0..3 {
+            for i in 0..100_000 {
+                hll.insert(url(i).as_str());
+            }
+        }
+
+        let error = (hll.count() as f64 - 100_000.0).abs() / 100_000.0;
+        assert!(error < 3.0 * hll.standard_error(), "error {}", error);
+
+        let mut small = HyperLogLog::new(10).unwrap();
+        (0..100).for_each(|i| small.insert(&i));
+        assert!((95..=105).contains(&small.count()));
+
+        assert_eq!(HyperLogLog::with_error(0.01).unwrap().precision(), 14);
+        assert!(HyperLogLog::new(3).is_err());
+        assert!(HyperLogLog::new(19).is_err());
+    }
+
+    #[test]
+    fn test_hyper_log_log_merge() {
+        let mut a = HyperLogLog::new(12).unwrap();
+        let mut b = HyperLogLog::new(12).unwrap();
+        (0..20_000).for_each(|i| a.insert(&i));
+        (10_000..30_000).for_each(|i| b.insert(&i));
+        a.merge(&b).unwrap();
+
+        let error = (a.count() as f64 - 30_000.0).abs() / 30_000.0;
+        assert!(error < 3.0 * a.standard_error(), "error {}", error);
+        assert!(a.merge(&HyperLogLog::new(10).unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_count_min_sketch() {
+        let mut sketch = CountMinSketch::new(0.001, 0.01).unwrap();
+        assert_eq!(sketch.width(), 2_719);
+        assert_eq!(sketch.depth(), 5);
+
+        let mut hitters = Vec::new();
+
+        for i in 0..20_000 {
+            let host = if i % 10 == 0 { i % 30 } else { i };
+            let estimate = sketch.insert(&host);
+
+            if estimate * 100 >= sketch.total() && !hitters.contains(&host) {
+                hitters.push(host);
+            }
+        }
+
+        assert_eq!(sketch.total(), 20_000);
+        assert!(sketch.estimate(&0) >= 667);
+        assert!(sketch.estimate(&0) <= 667 + 20);
+        assert!(sketch.estimate(&-1) <= 20);
+        assert!(hitters.contains(&0) && hitters.contains(&10) && hitters.contains(&20));
+
+        let mut other = CountMinSketch::new(0.001, 0.01).unwrap();
+        other.add(&0, 1_000);
+        sketch.merge(&other).unwrap();
+        assert!(sketch.estimate(&0) >= 1_667);
+        assert_eq!(sketch.total(), 21_000);
+        assert!(
+            sketch
+                .merge(&CountMinSketch::with_size(10, 5).unwrap())
+                .is_err()
+        );
+
+        sketch.clear();
+        assert!(sketch.is_empty());
+        assert_eq!(sketch.estimate(&0), 0);
+    }
+
+    #[test]
+    fn test_serde() {
+        let mut bloom = BloomFilter::new(100, 0.01).unwrap();
+        bloom.insert("a");
+        let json = serde_json::to_string(&bloom).unwrap();
+        let restored: BloomFilter = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, bloom);
+        assert!(restored.contains("a"));
+
+        let mut counting = CountingBloomFilter::new(100, 0.01).unwrap();
+        counting.insert("a");
+        let json = serde_json::to_string(&counting).unwrap();
+        assert_eq!(
+            serde_json::from_str::<CountingBloomFilter>(&json).unwrap(),
+            counting
+        );
+
+        let mut hll = HyperLogLog::new(4).unwrap();
+        hll.insert("a");
+        let json = serde_json::to_string(&hll).unwrap();
+        assert_eq!(serde_json::from_str::<HyperLogLog>(&json).unwrap(), hll);
+        assert!(
+            serde_json::from_str::<HyperLogLog>(r#"{"precision":4,"registers":[0,0]}"#).is_err()
+        );
+
+        let mut sketch = CountMinSketch::with_size(4, 2).unwrap();
+        sketch.add("a", 3);
+        let json = serde_json::to_string(&sketch).unwrap();
+        let restored: CountMinSketch = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.estimate("a"), 3);
+        assert!(
+            serde_json::from_str::<CountMinSketch>(
+                r#"{"width":4,"depth":2,"counters":[0],"total":0}"#
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_stable_hashing() {
+        // Persisted state is only useful if the same items select the same bits in later
+        // runs, so the hash must not depend on the Rust version or platform.
+        let mut filter = BloomFilter::with_size(16, 2).unwrap();
+        filter.insert("https://example.com");
+        filter.insert(&42u64);
+        assert_eq!(
+            serde_json::to_string(&filter).unwrap(),
+            r#"{"bits":{"len":16,"bytes":[8,67]},"hashes":2}"#
+        );
+    }
+}
@@ -0,0 +1,117 @@
+#[cfg(test)]
+mod tests {
+    use emixcollections::range::{Range, RangeMap};
+
+    fn entries(map: &RangeMap<i32, &'static str>) -> Vec<(i32, i32, &'static str)> {
+        map.iter().map(|(r, v)| (r.min, r.max, *v)).collect()
+    }
+
+    #[test]
+    fn test_insert_and_get() {
+        let mut m = RangeMap::new();
+        m.insert(Range::new(10, 19), "a");
+        m.insert(Range::new(30, 39), "b");
+        assert_eq!(m.len(), 2);
+        assert_eq!(m.get(10), Some(&"a"));
+        assert_eq!(m.get(19), Some(&"a"));
+        assert_eq!(m.get(25), None);
+        assert_eq!(m.get_key_value(35), Some((Range::new(30, 39), &"b")));
+        assert!(!m.contains(40));
+
+        *m.get_mut(15).unwrap() = "c";
+        assert_eq!(m.get(12), Some(&"c"));
+        assert!(m.get_mut(20).is_none());
+    }
+
+    #[test]
+    fn test_insert_splits_overlapping() {
+        let mut m = RangeMap::new();
+        m.insert(Range::new(0, 99), "outer");
+        m.insert(Range::new(40, 59), "inner");
+        assert_eq!(
+            entries(&m),
+            vec![(0, 39, "outer"), (40, 59, "inner"), (60, 99, "outer")]
+        );
+
+        m.insert(Range::new(30, 69), "wide");
+        assert_eq!(
+            entries(&m),
+            vec![(0, 29, "outer"), (30, 69, "wide"), (70, 99, "outer")]
+        );
+
+        m.insert(Range::new(-10, 200), "all");
+        assert_eq!(entries(&m), vec![(-10, 200, "all")]);
+    }
+
+    #[test]
+    fn test_remove_splits() {
+        let mut m: RangeMap<i32, &str> = [(Range::new(0, 9), "a"), (Range::new(20, 29), "b")]
+            .into_iter()
+            .collect();
+        assert!(m.remove(&Range::new(5, 24)));
+        assert_eq!(entries(&m), vec![(0, 4, "a"), (25, 29, "b")]);
+        assert!(!m.remove(&Range::new(10, 19)));
+        assert!(m.remove(&Range::new(2, 2)));
+        assert_eq!(entries(&m), vec![(0, 1, "a"), (3, 4, "a"), (25, 29, "b")]);
+        m.clear();
+        assert!(m.is_empty());
+    }
+
+    #[test]
+    fn test_overlapping() {
+        let m: RangeMap<i32, &str> = [
+            (Range::new(0, 9), "a"),
+            (Range::new(10, 19), "b"),
+            (Range::new(30, 39), "c"),
+        ]
+        .into_iter()
+        .collect();
+        let found = m
+            .overlapping(&Range::new(5, 30))
+            .map(|(r, v)| (r.min, r.max, *v))
+            .collect::<Vec<_>>();
+        assert_eq!(found, vec![(0, 9, "a"), (10, 19, "b"), (30, 39, "c")]);
+        assert_eq!(m.overlapping(&Range::new(20, 29)).count(), 0);
+        assert_eq!(m.ranges().count(), 3);
+        assert_eq!(m.values().copied().collect::<Vec<_>>(), vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_inverted_ranges_are_empty() {
+        let mut m = RangeMap::new();
+        m.insert(Range::new(0, 9), "a");
+        let inverted = Range { min: 10, max: 5 };
+
+        assert_eq!(m.overlapping(&inverted).count(), 0);
+        assert!(!m.remove(&inverted));
+        m.insert(inverted, "b");
+        assert_eq!(entries(&m), vec![(0, 9, "a")]);
+    }
+
+    #[test]
+    fn test_bounds_saturate() {
+        let mut m = RangeMap::new();
+        m.insert(Range::new(u8::MIN, u8::MAX), 1);
+        m.insert(Range::new(u8::MIN, 9), 2);
+        m.insert(Range::new(250, u8::MAX), 3);
+        assert_eq!(m.get(0), Some(&2));
+        assert_eq!(m.get(100), Some(&1));
+        assert_eq!(m.get(255), Some(&3));
+        assert_eq!(m.len(), 3);
+    }
+
+    #[test]
+    fn test_unicode_blocks() {
+        let mut blocks = RangeMap::new();
+        blocks.insert(Range::new('\u{0000}', '\u{007F}'), "Basic Latin");
+        blocks.insert(Range::new('\u{0080}', '\u{00FF}'), "Latin-1 Supplement");
+        blocks.insert(Range::new('\u{0370}', '\u{03FF}'), "Greek and Coptic");
+        blocks.insert(Range::new('\u{0400}', '\u{04FF}'), "Cyrillic");
+
+        assert_eq!(blocks.get('A'), Some(&"Basic Latin"));
+        assert_eq!(blocks.get('é'), Some(&"Latin-1 Supplement"));
+        assert_eq!(blocks.get('λ'), Some(&"Greek and Coptic"));
+        assert_eq!(blocks.get('Ж'), Some(&"Cyrillic"));
+        assert_eq!(blocks.get('\u{0300}'), None);
+    }
+}
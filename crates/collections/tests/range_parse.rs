@@ -0,0 +1,154 @@
+#[cfg(test)]
+mod tests {
+    use emixcollections::{
+        Error,
+        range::{Range, RangeSet},
+    };
+
+    fn parse(text: &str) -> Vec<(i32, i32)> {
+        Range::<i32>::parse_list(text)
+            .unwrap()
+            .into_iter()
+            .map(|r| (r.min, r.max))
+            .collect()
+    }
+
+    fn error(text: &str) -> String {
+        match text.parse::<RangeSet<i32>>() {
+            Err(Error::Parse(message)) => message,
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_syntaxes() {
+        assert_eq!(
+            parse("1-5,7,10..12,20..=22"),
+            vec![(1, 5), (7, 7), (10, 11), (20, 22)]
+        );
+        assert_eq!(
+            parse(" 1 - 5 ,\t7 , 10 ..= 12 "),
+            vec![(1, 5), (7, 7), (10, 12)]
+        );
+        assert_eq!(parse("5,1-2,5"), vec![(5, 5), (1, 2), (5, 5)]);
+    }
+
+    #[test]
+    fn test_parse_negative_numbers() {
+        assert_eq!(
+            parse("-5--1,-3,-2-2,-10..-8"),
+            vec![(-5, -1), (-3, -3), (-2, 2), (-10, -9)]
+        );
+        assert_eq!(parse("-5 - -1"), vec![(-5, -1)]);
+    }
+
+    #[test]
+    fn test_parse_open_ended() {
+        assert_eq!(parse("5-"), vec![(5, i32::MAX)]);
+        assert_eq!(parse("5.."), vec![(5, i32::MAX)]);
+        assert_eq!(parse("..=5"), vec![(i32::MIN, 5)]);
+        assert_eq!(parse("..5"), vec![(i32::MIN, 4)]);
+        assert_eq!(parse(".."), vec![(i32::MIN, i32::MAX)]);
+        assert_eq!("3-".parse::<Range<u8>>().unwrap(), Range::new(3, u8::MAX));
+    }
+
+    #[test]
+    fn test_parse_chars() {
+        let set: RangeSet<char> = "a-z, A-Z, 0..=9, _".parse().unwrap();
+        assert!(set.contains('q'));
+        assert!(set.contains('Q'));
+        assert!(set.contains('_'));
+        assert!(!set.contains('-'));
+        assert_eq!(set.to_string(), "0-9,A-Z,_,a-z");
+        assert_eq!("-".parse::<Range<char>>().unwrap(), Range::single('-'));
+    }
+
+    #[test]
+    fn test_escaped_chars_round_trip() {
+        assert_eq!(Range::single(',').to_string(), "\\,");
+        assert_eq!(Range::new('\t', ' ').to_string(), "\\\t-\\ ");
+        assert_eq!(Range::new(' ', '-').to_string(), "\\ --");
+        assert_eq!(Range::single('\\').to_string(), "\\\\");
+
+        let set: RangeSet<char> = " \\ , \\,, a-c, \\\\, \\-..=\\. ".parse().unwrap();
+        assert_eq!(set.len(), 4);
+        assert!(set.contains(' '));
+        assert!(set.contains(','));
+        assert!(set.contains('-'));
+        assert!(set.contains('.'));
+        assert!(set.contains('\\'));
+        assert_eq!(set.to_string(), "\\ ,\\,-.,\\\\,a-c");
+
+        for range in [
+            Range::single(','),
+            Range::single(' '),
+            Range::new('\t', '\r'),
+            Range::new(',', '.'),
+            Range::new('\\', 'a'),
+            Range::new('-', '-'),
+        ] {
+            assert_eq!(range.to_string().parse::<Range<char>>().unwrap(), range);
+            let set: RangeSet<char> = [range, Range::single('z')].into_iter().collect();
+            assert_eq!(set.to_string().parse::<RangeSet<char>>().unwrap(), set);
+            let json = serde_json::to_string(&set).unwrap();
+            assert_eq!(serde_json::from_str::<RangeSet<char>>(&json).unwrap(), set);
+        }
+
+        assert_eq!(
+            "a\\".parse::<Range<char>>().unwrap_err().to_string(),
+            "Parse error. Invalid value 'a\\' at position 0"
+        );
+    }
+
+    #[test]
+    fn test_error_positions() {
+        assert_eq!(error("1-5,x,7"), "Invalid value 'x' at position 4");
+        assert_eq!(error("1-5, 7-  y"), "Invalid value 'y' at position 9");
+        assert_eq!(error("1,,2"), "Empty item at position 2");
+        assert_eq!(error("1,2,"), "Empty item at position 4");
+        assert_eq!(error("1, 5-3"), "Empty range '5-3' at position 3");
+        assert_eq!(error("4..4"), "Empty range '4..4' at position 0");
+        assert_eq!(error("1-2-3"), "Invalid value '2-3' at position 2");
+
+        let err = "1-a".parse::<Range<i32>>().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Parse error. Invalid value 'a' at position 2"
+        );
+    }
+
+    #[test]
+    fn test_display_canonical_round_trip() {
+        assert_eq!(Range::new(1, 5).to_string(), "1-5");
+        assert_eq!(Range::single(7).to_string(), "7");
+        assert_eq!(Range::new(-5, -1).to_string(), "-5--1");
+
+        let set: RangeSet<i32> = "10..12, 1-3, 4, 7, -2--1, 8..=8".parse().unwrap();
+        let text = set.to_string();
+        assert_eq!(text, "-2--1,1-4,7-8,10-11");
+        assert_eq!(text.parse::<RangeSet<i32>>().unwrap(), set);
+
+        for range in set.iter() {
+            assert_eq!(range.to_string().parse::<Range<i32>>().unwrap(), range);
+        }
+
+        assert_eq!("".parse::<RangeSet<i32>>().unwrap(), RangeSet::new());
+        assert_eq!(RangeSet::<i32>::new().to_string(), "");
+    }
+
+    #[test]
+    fn test_serde_uses_string_form() {
+        let range = Range::new(3u32, 9);
+        assert_eq!(serde_json::to_string(&range).unwrap(), "\"3-9\"");
+        assert_eq!(
+            serde_json::from_str::<Range<u32>>("\"3..=9\"").unwrap(),
+            range
+        );
+
+        let set: RangeSet<u32> = serde_json::from_str("\"1-5,7,10..12\"").unwrap();
+        assert_eq!(serde_json::to_string(&set).unwrap(), "\"1-5,7,10-11\"");
+
+        let err = serde_json::from_str::<RangeSet<u32>>("\"1-x\"").unwrap_err();
+        assert!(err.to_string().contains("Invalid value 'x' at position 2"));
+    }
+}
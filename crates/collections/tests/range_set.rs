@@ -0,0 +1,170 @@
+#[cfg(test)]
+mod tests {
+    use emixcollections::range::{Range, RangeSet};
+
+    fn set(ranges: &[(i32, i32)]) -> RangeSet<i32> {
+        ranges
+            .iter()
+            .map(|&(min, max)| Range::new(min, max))
+            .collect()
+    }
+
+    fn ranges(set: &RangeSet<i32>) -> Vec<(i32, i32)> {
+        set.iter().map(|r| (r.min, r.max)).collect()
+    }
+
+    #[test]
+    fn test_insert_coalesces_overlapping_and_adjacent() {
+        let mut s = RangeSet::new();
+        assert!(s.insert(Range::new(10, 20)));
+        assert!(s.insert(Range::new(30, 40)));
+        assert!(s.insert(Range::new(21, 25)));
+        assert_eq!(ranges(&s), vec![(10, 25), (30, 40)]);
+
+        assert!(!s.insert(Range::new(12, 18)));
+        assert!(s.insert(Range::new(5, 35)));
+        assert_eq!(ranges(&s), vec![(5, 40)]);
+
+        assert!(s.insert_value(42));
+        assert!(s.insert_value(41));
+        assert_eq!(ranges(&s), vec![(5, 42)]);
+    }
+
+    #[test]
+    fn test_remove_splits_ranges() {
+        let mut s = set(&[(0, 100)]);
+        assert!(s.remove(&Range::new(40, 60)));
+        assert_eq!(ranges(&s), vec![(0, 39), (61, 100)]);
+
+        assert!(s.remove(&Range::new(30, 70)));
+        assert_eq!(ranges(&s), vec![(0, 29), (71, 100)]);
+
+        assert!(!s.remove(&Range::new(40, 60)));
+        assert!(s.remove_value(0));
+        assert!(s.remove_value(100));
+        assert_eq!(ranges(&s), vec![(1, 29), (71, 99)]);
+
+        assert!(s.remove(&Range::new(-10, 200)));
+        assert!(s.is_empty());
+    }
+
+    #[test]
+    fn test_membership() {
+        let s = set(&[(10, 20), (30, 40)]);
+        assert!(s.contains(10));
+        assert!(s.contains(20));
+        assert!(!s.contains(25));
+        assert!(!s.contains(41));
+        assert_eq!(s.get(35), Some(Range::new(30, 40)));
+        assert_eq!(s.get(5), None);
+
+        assert!(s.contains_range(&Range::new(12, 18)));
+        assert!(!s.contains_range(&Range::new(15, 35)));
+        assert!(s.overlaps(&Range::new(15, 35)));
+        assert!(s.overlaps(&Range::new(40, 50)));
+        assert!(!s.overlaps(&Range::new(21, 29)));
+        assert!(!s.overlaps(&Range::new(0, 9)));
+    }
+
+    #[test]
+    fn test_first_last_and_span() {
+        let s = set(&[(30, 40), (10, 20)]);
+        assert_eq!(s.first(), Some(Range::new(10, 20)));
+        assert_eq!(s.last(), Some(Range::new(30, 40)));
+        assert_eq!(s.span(), Some(Range::new(10, 40)));
+        assert_eq!(s.len(), 2);
+        assert_eq!(RangeSet::<i32>::new().span(), None);
+    }
+
+    #[test]
+    fn test_union_intersection_difference() {
+        let a = set(&[(0, 10), (20, 30), (40, 50)]);
+        let b = set(&[(5, 25), (45, 60)]);
+
+        assert_eq!(ranges(&a.union(&b)), vec![(0, 30), (40, 60)]);
+        assert_eq!(
+            ranges(&a.intersection(&b)),
+            vec![(5, 10), (20, 25), (45, 50)]
+        );
+        assert_eq!(ranges(&a.difference(&b)), vec![(0, 4), (26, 30), (40, 44)]);
+        assert_eq!(ranges(&b.difference(&a)), vec![(11, 19), (51, 60)]);
+    }
+
+    #[test]
+    fn test_complement_within_bounds() {
+        let s = set(&[(10, 20), (30, 40)]);
+        assert_eq!(
+            ranges(&s.complement(&Range::new(0, 50))),
+            vec![(0, 9), (21, 29), (41, 50)]
+        );
+        assert_eq!(ranges(&s.complement(&Range::new(15, 35))), vec![(21, 29)]);
+        assert!(s.complement(&Range::new(12, 18)).is_empty());
+    }
+
+    #[test]
+    fn test_gaps() {
+        let s = set(&[(10, 20), (30, 40)]);
+        let gaps: Vec<_> = s.gaps(&Range::new(0, 45)).collect();
+        assert_eq!(
+            gaps,
+            vec![Range::new(0, 9), Range::new(21, 29), Range::new(41, 45)]
+        );
+
+        let gaps: Vec<_> = s.gaps(&Range::new(10, 40)).collect();
+        assert_eq!(gaps, vec![Range::new(21, 29)]);
+
+        let gaps: Vec<_> = RangeSet::new().gaps(&Range::new(1, 3)).collect();
+        assert_eq!(gaps, vec![Range::new(1, 3)]);
+    }
+
+    #[test]
+    fn test_inverted_ranges_are_empty() {
+        let mut s = set(&[(0, 1)]);
+        let inverted = Range { min: 10, max: 5 };
+
+        assert!(inverted.is_empty());
+        assert!(!s.insert(inverted));
+        assert!(!s.remove(&inverted));
+        assert!(!s.overlaps(&inverted));
+        assert_eq!(s.gaps(&inverted).count(), 0);
+        assert!(s.complement(&inverted).is_empty());
+        assert_eq!(ranges(&s), vec![(0, 1)]);
+    }
+
+    #[test]
+    fn test_gaps_at_type_limits() {
+        let s: RangeSet<u8> = [Range::new(0, 10), Range::new(250, 255)]
+            .into_iter()
+            .collect();
+        let gaps: Vec<_> = s.gaps(&Range::new(0, 255)).collect();
+        assert_eq!(gaps, vec![Range::new(11, 249)]);
+
+        let full: RangeSet<u8> = std::iter::once(Range::new(0, 255)).collect();
+        assert_eq!(full.gaps(&Range::new(0, 255)).count(), 0);
+    }
+
+    #[test]
+    fn test_id_allocation() {
+        let bounds = Range::new(1u32, 1000);
+        let mut used = RangeSet::new();
+
+        for _ in 0..5 {
+            let id = used.gaps(&bounds).next().unwrap().min;
+            used.insert_value(id);
+        }
+
+        assert_eq!(used.iter().collect::<Vec<_>>(), vec![Range::new(1, 5)]);
+        used.remove_value(3);
+        assert_eq!(used.gaps(&bounds).next().unwrap().min, 3);
+    }
+
+    #[test]
+    fn test_char_ranges() {
+        let mut s = RangeSet::new();
+        s.insert(Range::new('a', 'f'));
+        s.insert(Range::new('g', 'k'));
+        s.remove(&Range::single('c'));
+        let all: Vec<_> = s.iter().collect();
+        assert_eq!(all, vec![Range::new('a', 'b'), Range::new('d', 'k')]);
+    }
+}
@@ -0,0 +1,205 @@
+#[cfg(test)]
+mod tests {
+    use emixcollections::range::{FixedDecimal, LambdaRange, Range, Step};
+
+    #[test]
+    fn test_integer_by() {
+        assert_eq!(10i32.forward_by(5), 15);
+        assert_eq!(10i32.backward_by(15), -5);
+        assert_eq!(250u8.forward_by(10), u8::MAX);
+        assert_eq!(5u8.backward_by(10), u8::MIN);
+        assert_eq!(250u8.checked_forward_by(10), None);
+        assert_eq!(250u8.checked_forward_by(5), Some(255));
+        assert_eq!((-100i8).checked_forward_by(200), Some(100));
+        assert_eq!((-100i8).checked_forward_by(300), None);
+        assert_eq!(100i8.checked_backward_by(228), Some(-128));
+        assert_eq!(
+            i128::MIN.forward_by(usize::MAX),
+            i128::MIN + usize::MAX as i128
+        );
+        assert_eq!(u128::MAX.backward_by(1), u128::MAX - 1);
+    }
+
+    #[test]
+    fn test_integer_steps_between() {
+        assert_eq!(i32::steps_between(&-5, &5), Some(10));
+        assert_eq!(i32::steps_between(&5, &5), Some(0));
+        assert_eq!(i32::steps_between(&5, &-5), None);
+        assert_eq!(
+            i8::steps_between(&i8::MIN, &i8::MAX),
+            Some(u8::MAX as usize)
+        );
+        assert_eq!(u128::steps_between(&0, &u128::MAX), None);
+    }
+
+    #[test]
+    fn test_char_skips_surrogates() {
+        assert_eq!('\u{D7FF}'.forward(), '\u{E000}');
+        assert_eq!('\u{E000}'.backward(), '\u{D7FF}');
+        assert_eq!('a'.forward_by(25), 'z');
+        assert_eq!('\u{D7FE}'.forward_by(3), '\u{E001}');
+        assert_eq!(char::steps_between(&'\u{D7FF}', &'\u{E000}'), Some(1));
+        assert_eq!(char::MAX.forward(), char::MAX);
+        assert_eq!(char::MAX.checked_forward_by(1), None);
+        assert_eq!('\0'.backward_by(5), '\0');
+
+        let r = Range::new('\u{D7FE}', '\u{E001}');
+        assert_eq!(r.iter().count(), 4);
+        assert_eq!(r.iter().size_hint(), (4, Some(4)));
+    }
+
+    #[test]
+    fn test_range_ops_are_constant_time() {
+        let r = Range::new(0u64, 10);
+        let shifted = r.shift_forward(1_000_000_000_000);
+        assert_eq!(shifted, Range::new(1_000_000_000_000, 1_000_000_000_010));
+        assert_eq!(shifted.shift_backward(1_000_000_000_000), r);
+        assert_eq!(r.inflate(usize::MAX), Range::new(0, u64::MAX));
+        assert_eq!(r.shift_forward(usize::MAX).max, u64::MAX);
+    }
+
+    #[test]
+    fn test_deflate_stops_when_crossing() {
+        let r = Range::new(1, 4);
+        assert_eq!(r.deflate(1), Range::new(2, 3));
+        let crossed = r.deflate(1_000_000);
+        assert_eq!((crossed.min, crossed.max), (3, 2));
+        let single = Range::single(7);
+        let crossed = single.deflate(10);
+        assert_eq!((crossed.min, crossed.max), (8, 6));
+        let crossed = crossed.deflate(10);
+        assert_eq!((crossed.min, crossed.max), (9, 5));
+        assert_eq!(crossed.deflate(0), crossed);
+    }
+
+    #[test]
+    fn test_range_iter_size_hint() {
+        let mut it = Range::new(1, 5).iter();
+        assert_eq!(it.size_hint(), (5, Some(5)));
+        it.next();
+        it.next();
+        assert_eq!(it.size_hint(), (3, Some(3)));
+        assert_eq!(it.by_ref().count(), 3);
+        assert_eq!(it.size_hint(), (0, Some(0)));
+
+        let full = Range::new(u128::MIN, u128::MAX).iter();
+        assert_eq!(full.size_hint(), (usize::MAX, None));
+    }
+
+    /// Steps up to 3 and then stops moving.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+    struct Stuck(u8);
+
+    impl Step for Stuck {
+        fn forward(self) -> Self {
+            Stuck(self.0.saturating_add(1).min(3))
+        }
+
+        fn backward(self) -> Self {
+            Stuck(self.0.saturating_sub(1))
+        }
+    }
+
+    #[test]
+    fn test_range_iter_size_hint_with_stuck_step() {
+        let r = Range::new(Stuck(0), Stuck(5));
+        assert_eq!(Stuck::steps_between(&r.min, &r.max), None);
+        assert_eq!(r.iter().size_hint(), (0, None));
+    }
+
+    #[test]
+    fn test_lambda_range_up_by_large_step() {
+        let r = LambdaRange::new(0u64, 3_000_000_000);
+        let values: Vec<u64> = r.up_by(1_000_000_000).collect();
+        assert_eq!(values, vec![0, 1_000_000_000, 2_000_000_000, 3_000_000_000]);
+    }
+
+    #[test]
+    fn test_fixed_decimal() {
+        type Cents = FixedDecimal<2>;
+
+        let r = Range::new(Cents::from_units(100), Cents::from_units(105));
+        let values: Vec<String> = r.iter().map(|v| v.to_string()).collect();
+        assert_eq!(values, vec!["1.00", "1.01", "1.02", "1.03", "1.04", "1.05"]);
+
+        assert_eq!(Cents::from_f64(-0.5).unwrap().to_string(), "-0.50");
+        assert_eq!(Cents::from_integer(3).unwrap().units(), 300);
+        assert_eq!(Cents::from_f64(f64::NAN), None);
+        assert_eq!(Cents::from_units(150).to_f64(), 1.5);
+        assert_eq!(Cents::ZERO.forward_by(250), Cents::from_units(250));
+        assert_eq!(Cents::MAX.forward(), Cents::MAX);
+        assert_eq!(
+            Cents::steps_between(&Cents::ZERO, &Cents::from_units(42)),
+            Some(42)
+        );
+        assert_eq!(FixedDecimal::<0>::from_units(-7).to_string(), "-7");
+    }
+
+    #[cfg(feature = "chrono")]
+    mod chrono {
+        use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+        use emixcollections::range::{Hour, Range, Step, Stepped, Week};
+
+        fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+            NaiveDate::from_ymd_opt(y, m, d).unwrap()
+        }
+
+        #[test]
+        fn test_naive_date_by_day() {
+            let r = Range::new(date(2024, 2, 27), date(2024, 3, 1));
+            let days: Vec<NaiveDate> = r.iter().collect();
+            assert_eq!(
+                days,
+                vec![
+                    date(2024, 2, 27),
+                    date(2024, 2, 28),
+                    date(2024, 2, 29),
+                    date(2024, 3, 1)
+                ]
+            );
+            assert_eq!(r.iter().size_hint(), (4, Some(4)));
+            assert_eq!(date(2024, 1, 1).forward_by(366), date(2025, 1, 1));
+            assert_eq!(NaiveDate::MAX.forward_by(10), NaiveDate::MAX);
+            assert_eq!(NaiveDate::MAX.checked_forward_by(1), None);
+            assert_eq!(
+                NaiveDate::steps_between(&date(2024, 1, 1), &date(2025, 1, 1)),
+                Some(366)
+            );
+        }
+
+        #[test]
+        fn test_naive_date_by_week() {
+            let start: Stepped<NaiveDate, Week> = date(2024, 1, 1).into();
+            let end = Stepped::new(date(2024, 1, 31));
+            let weeks: Vec<NaiveDate> = Range::new(start, end).iter().map(|w| w.value).collect();
+            assert_eq!(
+                weeks,
+                vec![
+                    date(2024, 1, 1),
+                    date(2024, 1, 8),
+                    date(2024, 1, 15),
+                    date(2024, 1, 22),
+                    date(2024, 1, 29)
+                ]
+            );
+            assert_eq!(Stepped::steps_between(&start, &end), Some(4));
+        }
+
+        #[test]
+        fn test_date_time_by_hour() {
+            let at = |h: u32| Utc.with_ymd_and_hms(2024, 6, 1, h, 0, 0).unwrap();
+            let start: Stepped<DateTime<Utc>, Hour> = Stepped::new(at(0));
+            let end = Stepped::new(at(5) + Duration::minutes(30));
+            let r = Range::new(start, end);
+
+            assert_eq!(r.iter().size_hint(), (6, Some(6)));
+            assert_eq!(r.iter().last().unwrap().value, at(5));
+            assert_eq!(start.forward_by(3).value, at(3));
+            assert_eq!(Range::single(start).shift_forward(1).min.value, at(1));
+
+            let far = start.forward_by(usize::MAX);
+            assert!(far.checked_forward_by(1).is_none());
+            assert_eq!(far.backward_by(usize::MAX).checked_backward_by(1), None);
+        }
+    }
+}
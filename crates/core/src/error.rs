@@ -53,6 +53,9 @@ pub enum Error {
     #[error("Queue already completed")]
     QueueCompleted,
 
+    #[error("Circuit is open")]
+    CircuitOpen,
+
     #[error("Guard was poisoned. {0}")]
     Poisoned(String),
 
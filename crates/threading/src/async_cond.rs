@@ -0,0 +1,266 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::{sync::Notify, time};
+
+use crate::{Error, Result};
+
+/// Waits on `notify` until `ready` returns true. The notification is registered before
+/// `ready` is checked, so a signal that arrives in between is not lost, and dropping the
+/// future at any point leaves the primitive's state untouched.
+async fn notified_until(notify: &Notify, mut ready: impl FnMut() -> Result<bool>) -> Result<()> {
+    loop {
+        let notified = notify.notified();
+        tokio::pin!(notified);
+        notified.as_mut().enable();
+
+        if ready()? {
+            return Ok(());
+        }
+
+        notified.await;
+    }
+}
+
+async fn with_timeout(timeout: Duration, wait: impl Future<Output = Result<()>>) -> Result<bool> {
+    match time::timeout(timeout, wait).await {
+        Ok(result) => result.map(|_| true),
+        Err(_) => Ok(false),
+    }
+}
+
+/// The async counterpart of `AutoResetCond`. Waiting suspends the task instead of
+/// blocking the thread.
+#[derive(Clone, Debug)]
+pub struct AsyncAutoResetCond {
+    inner: Arc<(Mutex<bool>, Notify)>,
+}
+
+impl AsyncAutoResetCond {
+    /// Creates a new AsyncAutoResetCond with the specified initial state.
+    /// If `initial_state` is true, the event is initially signaled.
+    pub fn new(initial_state: bool) -> Self {
+        Self {
+            inner: Arc::new((Mutex::new(initial_state), Notify::new())),
+        }
+    }
+
+    /// Creates a new AsyncAutoResetCond in the unset state.
+    pub fn new_unset() -> Self {
+        Self::new(false)
+    }
+
+    /// Creates a new AsyncAutoResetCond in the set state.
+    pub fn new_set() -> Self {
+        Self::new(true)
+    }
+
+    /// Sets the event, releasing one waiting task. The event automatically
+    /// resets after one task has been released.
+    pub fn set(&self) -> Result<()> {
+        let (lock, notify) = &*self.inner;
+        let mut guard = Error::handle_poison_error(lock.lock())?;
+
+        if !*guard {
+            *guard = true;
+            notify.notify_one();
+        }
+
+        Ok(())
+    }
+
+    /// Resets the event to the non-signaled state.
+    pub fn reset(&self) -> Result<()> {
+        let (lock, _) = &*self.inner;
+        let mut guard = Error::handle_poison_error(lock.lock())?;
+        *guard = false;
+        Ok(())
+    }
+
+    /// Consumes the signal if the event is set. Returns true if it was.
+    pub fn try_wait(&self) -> Result<bool> {
+        let (lock, _) = &*self.inner;
+        let mut guard = Error::handle_poison_error(lock.lock())?;
+        Ok(std::mem::replace(&mut *guard, false))
+    }
+
+    /// Waits for the event to be set. When the event is set, this task is
+    /// released and the event is automatically reset.
+    pub async fn wait(&self) -> Result<()> {
+        notified_until(&self.inner.1, || self.try_wait()).await
+    }
+
+    /// Waits for the event to be set, with a timeout.
+    /// Returns Ok(true) if the event was set, Ok(false) if the timeout expired.
+    pub async fn wait_timeout(&self, timeout: Duration) -> Result<bool> {
+        with_timeout(timeout, self.wait()).await
+    }
+
+    /// Gets whether the event is currently in the signaled state.
+    pub fn is_set(&self) -> Result<bool> {
+        let (lock, _) = &*self.inner;
+        let guard = Error::handle_poison_error(lock.lock())?;
+        Ok(*guard)
+    }
+}
+
+/// The async counterpart of `ManualResetCond`.
+#[derive(Clone, Debug)]
+pub struct AsyncManualResetCond {
+    inner: Arc<(Mutex<bool>, Notify)>,
+}
+
+impl AsyncManualResetCond {
+    /// Creates a new AsyncManualResetCond with the specified initial state.
+    /// If `initial_state` is true, the event is initially signaled.
+    pub fn new(initial_state: bool) -> Self {
+        Self {
+            inner: Arc::new((Mutex::new(initial_state), Notify::new())),
+        }
+    }
+
+    /// Creates a new AsyncManualResetCond in the unset state.
+    pub fn new_unset() -> Self {
+        Self::new(false)
+    }
+
+    /// Creates a new AsyncManualResetCond in the set state.
+    pub fn new_set() -> Self {
+        Self::new(true)
+    }
+
+    /// Sets the event, releasing all waiting tasks. The event remains set
+    /// until manually reset.
+    pub fn set(&self) -> Result<()> {
+        let (lock, notify) = &*self.inner;
+        let mut guard = Error::handle_poison_error(lock.lock())?;
+
+        if *guard {
+            return Ok(()); // Already set
+        }
+
+        *guard = true;
+        notify.notify_waiters();
+        Ok(())
+    }
+
+    /// Resets the event to the non-signaled state.
+    pub fn reset(&self) -> Result<()> {
+        let (lock, _) = &*self.inner;
+        let mut guard = Error::handle_poison_error(lock.lock())?;
+        *guard = false;
+        Ok(())
+    }
+
+    /// Waits for the event to be set.
+    pub async fn wait(&self) -> Result<()> {
+        notified_until(&self.inner.1, || self.is_set()).await
+    }
+
+    /// Waits for the event to be set, with a timeout.
+    /// Returns Ok(true) if the event was set, Ok(false) if the timeout expired.
+    pub async fn wait_timeout(&self, timeout: Duration) -> Result<bool> {
+        with_timeout(timeout, self.wait()).await
+    }
+
+    /// Gets whether the event is currently in the signaled state.
+    pub fn is_set(&self) -> Result<bool> {
+        let (lock, _) = &*self.inner;
+        let guard = Error::handle_poison_error(lock.lock())?;
+        Ok(*guard)
+    }
+}
+
+/// The async counterpart of `CountdownCond`.
+#[derive(Clone, Debug)]
+pub struct AsyncCountdownCond {
+    inner: Arc<(Mutex<usize>, Notify)>,
+    initial_count: usize,
+}
+
+impl AsyncCountdownCond {
+    /// Creates a new AsyncCountdownCond with the specified initial count.
+    /// The event becomes signaled when the count reaches zero.
+    pub fn new(initial_count: usize) -> Self {
+        Self {
+            inner: Arc::new((Mutex::new(initial_count), Notify::new())),
+            initial_count,
+        }
+    }
+
+    /// Gets the current remaining count.
+    pub fn current_count(&self) -> Result<usize> {
+        let (lock, _) = &*self.inner;
+        let guard = Error::handle_poison_error(lock.lock())?;
+        Ok(*guard)
+    }
+
+    /// Gets the initial count that was specified when the AsyncCountdownCond was created.
+    pub fn initial_count(&self) -> usize {
+        self.initial_count
+    }
+
+    /// Signals the event, decrementing the count by one.
+    /// Returns the new count value.
+    pub fn signal(&self) -> Result<usize> {
+        self.signal_n(1)
+    }
+
+    /// Signals the event, decrementing the count by the specified amount.
+    /// Returns the new count value.
+    pub fn signal_n(&self, signal_count: usize) -> Result<usize> {
+        let (lock, notify) = &*self.inner;
+        let mut guard = Error::handle_poison_error(lock.lock())?;
+
+        if *guard == 0 {
+            return Ok(0);
+        }
+
+        *guard = guard.saturating_sub(signal_count);
+
+        if *guard == 0 {
+            notify.notify_waiters();
+        }
+
+        Ok(*guard)
+    }
+
+    /// Increments the current count by the specified amount.
+    pub fn add_count(&self, increment: usize) -> Result<usize> {
+        let (lock, _) = &*self.inner;
+        let mut guard = Error::handle_poison_error(lock.lock())?;
+        *guard += increment;
+        Ok(*guard)
+    }
+
+    /// Attempts to increment the current count by the specified amount.
+    /// Returns Ok(Some(new_count)) if successful, Ok(None) if the count is already zero.
+    pub fn try_add_count(&self, increment: usize) -> Result<Option<usize>> {
+        let (lock, _) = &*self.inner;
+        let mut guard = Error::handle_poison_error(lock.lock())?;
+
+        if *guard == 0 {
+            return Ok(None);
+        }
+
+        *guard += increment;
+        Ok(Some(*guard))
+    }
+
+    /// Waits until the count reaches zero.
+    pub async fn wait(&self) -> Result<()> {
+        notified_until(&self.inner.1, || self.is_set()).await
+    }
+
+    /// Waits until the count reaches zero, with a timeout.
+    /// Returns Ok(true) if the count reached zero, Ok(false) if the timeout expired.
+    pub async fn wait_timeout(&self, timeout: Duration) -> Result<bool> {
+        with_timeout(timeout, self.wait()).await
+    }
+
+    /// Gets whether the count has reached zero (event is signaled).
+    pub fn is_set(&self) -> Result<bool> {
+        Ok(self.current_count()? == 0)
+    }
+}
@@ -0,0 +1,270 @@
+use std::{
+    fmt,
+    sync::{Arc, Condvar, Mutex},
+    time::{Duration, Instant},
+};
+use tokio::{sync::Notify, time};
+
+use crate::{Error, Result};
+
+type PhaseCallback = Box<dyn Fn(u64) + Send + Sync>;
+
+#[derive(Debug)]
+struct BarrierState {
+    participants: usize,
+    remaining: usize,
+    phase: u64,
+}
+
+struct BarrierInner {
+    state: Mutex<BarrierState>,
+    cvar: Condvar,
+    notify: Notify,
+    callback: Option<PhaseCallback>,
+}
+
+/// Lets a group of participants work through an algorithm in phases.
+///
+/// Modeled after .NET's `Barrier`: each participant calls `signal_and_wait` when it
+/// finishes a phase, and all of them are released once the last one arrives. The phase
+/// callback runs on the last participant's thread before the others are released and
+/// must not call back into the barrier. Participants can be added or removed between
+/// phases.
+#[derive(Clone)]
+pub struct Barrier {
+    inner: Arc<BarrierInner>,
+}
+
+impl fmt::Debug for Barrier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Barrier")
+            .field("state", &self.inner.state)
+            .field("callback", &self.inner.callback.is_some())
+            .finish()
+    }
+}
+
+impl Barrier {
+    pub fn new(participants: usize) -> Self {
+        Self::build(participants, None)
+    }
+
+    /// Creates a barrier that calls `callback` with the completed phase number each time
+    /// every participant has signaled.
+    pub fn with_callback(
+        participants: usize,
+        callback: impl Fn(u64) + Send + Sync + 'static,
+    ) -> Self {
+        Self::build(participants, Some(Box::new(callback)))
+    }
+
+    fn build(participants: usize, callback: Option<PhaseCallback>) -> Self {
+        Self {
+            inner: Arc::new(BarrierInner {
+                state: Mutex::new(BarrierState {
+                    participants,
+                    remaining: participants,
+                    phase: 0,
+                }),
+                cvar: Condvar::new(),
+                notify: Notify::new(),
+                callback,
+            }),
+        }
+    }
+
+    /// Gets the number of participants in the barrier.
+    pub fn participant_count(&self) -> Result<usize> {
+        let state = Error::handle_poison_error(self.inner.state.lock())?;
+        Ok(state.participants)
+    }
+
+    /// Gets the number of participants that have not signaled in the current phase.
+    pub fn participants_remaining(&self) -> Result<usize> {
+        let state = Error::handle_poison_error(self.inner.state.lock())?;
+        Ok(state.remaining)
+    }
+
+    /// Gets the number of the current phase, starting at zero.
+    pub fn current_phase(&self) -> Result<u64> {
+        let state = Error::handle_poison_error(self.inner.state.lock())?;
+        Ok(state.phase)
+    }
+
+    /// Adds participants to the current phase. Returns the current phase number.
+    pub fn add_participants(&self, count: usize) -> Result<u64> {
+        let mut state = Error::handle_poison_error(self.inner.state.lock())?;
+        state.participants += count;
+        state.remaining += count;
+        Ok(state.phase)
+    }
+
+    /// Removes participants that have not signaled in the current phase. Completes the
+    /// phase if they were the last ones being waited for.
+    pub fn remove_participants(&self, count: usize) -> Result<()> {
+        let mut state = Error::handle_poison_error(self.inner.state.lock())?;
+
+        if count > state.remaining {
+            return Err(Error::InvalidOperation(format!(
+                "Cannot remove {} participants, only {} have not signaled",
+                count, state.remaining
+            )));
+        }
+
+        state.participants -= count;
+        state.remaining -= count;
+
+        if state.remaining == 0 && state.participants > 0 {
+            self.finish_phase(&mut state);
+        }
+
+        Ok(())
+    }
+
+    fn finish_phase(&self, state: &mut BarrierState) {
+        if let Some(callback) = &self.inner.callback {
+            callback(state.phase);
+        }
+
+        state.phase += 1;
+        state.remaining = state.participants;
+        self.inner.cvar.notify_all();
+        self.inner.notify.notify_waiters();
+    }
+
+    /// Signals arrival and returns the phase the participant arrived in.
+    fn signal(&self) -> Result<u64> {
+        let mut state = Error::handle_poison_error(self.inner.state.lock())?;
+
+        if state.remaining == 0 {
+            return Err(Error::InvalidOperation(
+                "Barrier has no participants left to signal".to_string(),
+            ));
+        }
+
+        let phase = state.phase;
+        state.remaining -= 1;
+
+        if state.remaining == 0 {
+            self.finish_phase(&mut state);
+        }
+
+        Ok(phase)
+    }
+
+    /// Takes back a signal that timed out. Returns false if the phase completed anyway.
+    fn withdraw(&self, phase: u64) -> Result<bool> {
+        let mut state = Error::handle_poison_error(self.inner.state.lock())?;
+
+        if state.phase != phase {
+            return Ok(false);
+        }
+
+        state.remaining += 1;
+        Ok(true)
+    }
+
+    /// Signals that this participant reached the barrier and blocks until every other
+    /// participant has too. Returns the number of the completed phase.
+    pub fn signal_and_wait(&self) -> Result<u64> {
+        let phase = self.signal()?;
+        let mut state = Error::handle_poison_error(self.inner.state.lock())?;
+
+        while state.phase == phase {
+            state = Error::handle_poison_error(self.inner.cvar.wait(state))?;
+        }
+
+        Ok(phase)
+    }
+
+    /// Like `signal_and_wait`, but gives up after `timeout`. A participant that times
+    /// out is no longer counted as arrived. Returns Ok(false) if the timeout expired.
+    pub fn signal_and_wait_timeout(&self, timeout: Duration) -> Result<bool> {
+        let phase = self.signal()?;
+        let start = Instant::now();
+        let mut state = Error::handle_poison_error(self.inner.state.lock())?;
+
+        while state.phase == phase {
+            let Some(remaining) = timeout.checked_sub(start.elapsed()) else {
+                drop(state);
+                return self.withdraw(phase).map(|withdrawn| !withdrawn);
+            };
+            let (new_state, _) =
+                Error::handle_poison_error(self.inner.cvar.wait_timeout(state, remaining))?;
+            state = new_state;
+        }
+
+        Ok(true)
+    }
+
+    async fn wait_phase_async(&self, phase: u64) -> Result<()> {
+        loop {
+            let notified = self.inner.notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            if self.current_phase()? != phase {
+                return Ok(());
+            }
+
+            notified.await;
+        }
+    }
+
+    /// The async counterpart of `signal_and_wait`. Dropping the future before the phase
+    /// completes withdraws the signal.
+    pub async fn signal_and_wait_async(&self) -> Result<u64> {
+        let signal = PendingSignal::new(self)?;
+        self.wait_phase_async(signal.phase).await?;
+        Ok(signal.keep())
+    }
+
+    /// The async counterpart of `signal_and_wait_timeout`. Dropping the future before the
+    /// phase completes withdraws the signal.
+    pub async fn signal_and_wait_timeout_async(&self, timeout: Duration) -> Result<bool> {
+        let signal = PendingSignal::new(self)?;
+
+        match time::timeout(timeout, self.wait_phase_async(signal.phase)).await {
+            Ok(result) => result.map(|_| {
+                signal.keep();
+                true
+            }),
+            Err(_) => {
+                let phase = signal.keep();
+                self.withdraw(phase).map(|withdrawn| !withdrawn)
+            }
+        }
+    }
+}
+
+/// A signal given by an async participant that is withdrawn if its future is dropped
+/// while waiting for the phase to complete.
+struct PendingSignal<'a> {
+    barrier: &'a Barrier,
+    phase: u64,
+    armed: bool,
+}
+
+impl<'a> PendingSignal<'a> {
+    fn new(barrier: &'a Barrier) -> Result<Self> {
+        Ok(Self {
+            barrier,
+            phase: barrier.signal()?,
+            armed: true,
+        })
+    }
+
+    /// Keeps the signal and returns the phase it was given in.
+    fn keep(mut self) -> u64 {
+        self.armed = false;
+        self.phase
+    }
+}
+
+impl Drop for PendingSignal<'_> {
+    fn drop(&mut self) {
+        if self.armed {
+            let _ = self.barrier.withdraw(self.phase);
+        }
+    }
+}
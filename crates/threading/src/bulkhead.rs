@@ -0,0 +1,123 @@
+use std::{future::Future, time::Duration};
+
+use crate::{Error, Result, SemaphoreSlim};
+
+/// Limits how many calls run at once so one slow dependency cannot tie up every thread.
+///
+/// Callers wait for a free slot, up to `max_wait` if one is set, and are rejected with
+/// `Error::Exceeded` when it expires.
+#[derive(Clone, Debug)]
+#[must_use]
+pub struct Bulkhead {
+    semaphore: SemaphoreSlim,
+    max_concurrent: usize,
+    max_wait: Option<Duration>,
+}
+
+impl Bulkhead {
+    /// Creates a bulkhead that lets up to `max_concurrent` calls run at once and makes the
+    /// others wait as long as it takes.
+    pub fn new(max_concurrent: usize) -> Self {
+        let max_concurrent = max_concurrent.max(1);
+        Bulkhead {
+            semaphore: SemaphoreSlim::with_max(max_concurrent, max_concurrent),
+            max_concurrent,
+            max_wait: None,
+        }
+    }
+
+    /// Creates a bulkhead whose callers give up after waiting `max_wait` for a slot.
+    pub fn with_max_wait(max_concurrent: usize, max_wait: Duration) -> Self {
+        Bulkhead {
+            max_wait: Some(max_wait),
+            ..Self::new(max_concurrent)
+        }
+    }
+
+    pub fn max_concurrent(&self) -> usize {
+        self.max_concurrent
+    }
+
+    pub fn max_wait(&self) -> Option<Duration> {
+        self.max_wait
+    }
+
+    /// Gets the number of free slots.
+    pub fn available(&self) -> Result<usize> {
+        self.semaphore.current_count()
+    }
+
+    fn rejected(&self) -> Error {
+        Error::Exceeded(format!(
+            "Bulkhead allows {} concurrent calls",
+            self.max_concurrent
+        ))
+    }
+
+    /// Takes a slot if one is free without waiting.
+    pub fn try_enter(&self) -> Result<Option<BulkheadPermit>> {
+        if !self.semaphore.try_wait()? {
+            return Ok(None);
+        }
+
+        Ok(Some(BulkheadPermit {
+            semaphore: self.semaphore.clone(),
+        }))
+    }
+
+    /// Blocks until a slot is free. The slot is returned when the permit is dropped.
+    pub fn enter(&self) -> Result<BulkheadPermit> {
+        match self.max_wait {
+            Some(max_wait) => {
+                if !self.semaphore.wait_timeout(max_wait)? {
+                    return Err(self.rejected());
+                }
+            }
+            None => self.semaphore.wait()?,
+        }
+
+        Ok(BulkheadPermit {
+            semaphore: self.semaphore.clone(),
+        })
+    }
+
+    pub async fn enter_async(&self) -> Result<BulkheadPermit> {
+        match self.max_wait {
+            Some(max_wait) => {
+                if !self.semaphore.wait_timeout_async(max_wait).await? {
+                    return Err(self.rejected());
+                }
+            }
+            None => self.semaphore.wait_async().await?,
+        }
+
+        Ok(BulkheadPermit {
+            semaphore: self.semaphore.clone(),
+        })
+    }
+
+    /// Runs `f` once a slot is free.
+    pub fn call<R>(&self, f: impl FnOnce() -> Result<R>) -> Result<R> {
+        let _permit = self.enter()?;
+        f()
+    }
+
+    /// Awaits `future` once a slot is free.
+    pub async fn call_async<R>(&self, future: impl Future<Output = Result<R>>) -> Result<R> {
+        let _permit = self.enter_async().await?;
+        future.await
+    }
+}
+
+/// A slot in a `Bulkhead`, given back when dropped.
+#[derive(Debug)]
+#[must_use]
+pub struct BulkheadPermit {
+    semaphore: SemaphoreSlim,
+}
+
+impl Drop for BulkheadPermit {
+    fn drop(&mut self) {
+        let _ = self.semaphore.release();
+    }
+}
@@ -0,0 +1,238 @@
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+use tokio::sync::Notify;
+
+use crate::{Error, ManualResetCond, Result};
+
+/// Why a token was cancelled. Child tokens inherit their parent's reason.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum CancelReason {
+    Requested,
+    TimedOut,
+    Shutdown,
+    Failed(String),
+    Other(String),
+}
+
+impl fmt::Display for CancelReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CancelReason::Requested => write!(f, "Requested"),
+            CancelReason::TimedOut => write!(f, "Timedout"),
+            CancelReason::Shutdown => write!(f, "Shutdown"),
+            CancelReason::Failed(e) => write!(f, "Failed: {}", e),
+            CancelReason::Other(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+type CancelCallback = Box<dyn FnOnce(&CancelReason) + Send>;
+
+/// Identifies a callback registered with `CancellationToken::on_cancel`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CancelRegistration(u64);
+
+#[derive(Default)]
+struct TokenState {
+    reason: Option<CancelReason>,
+    callbacks: Vec<(u64, CancelCallback)>,
+    children: Vec<Arc<TokenInner>>,
+    next_id: u64,
+}
+
+struct TokenInner {
+    state: Mutex<TokenState>,
+    cond: ManualResetCond,
+    notify: Notify,
+}
+
+impl TokenInner {
+    /// A child nobody holds a handle to still matters if cancelling it runs callbacks or
+    /// reaches further descendants.
+    fn is_observed(self: &Arc<Self>) -> bool {
+        if Arc::strong_count(self) > 1 {
+            return true;
+        }
+
+        let state = self.state.lock().unwrap();
+        state.reason.is_none() && (!state.callbacks.is_empty() || !state.children.is_empty())
+    }
+}
+
+/// A cloneable cancellation flag that can be composed into a tree.
+///
+/// Cancelling a token cancels all of its children (but not its parent), runs the
+/// registered `on_cancel` callbacks once, and wakes both blocking (`wait`) and async
+/// (`cancelled`) waiters.
+#[derive(Clone)]
+pub struct CancellationToken {
+    inner: Arc<TokenInner>,
+}
+
+impl fmt::Debug for CancellationToken {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CancellationToken")
+            .field("reason", &self.reason())
+            .finish()
+    }
+}
+
+impl Default for CancellationToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(TokenInner {
+                state: Mutex::new(TokenState::default()),
+                cond: ManualResetCond::new_unset(),
+                notify: Notify::new(),
+            }),
+        }
+    }
+
+    /// Creates a token that is cancelled when this token is cancelled. Cancelling the
+    /// child does not affect this token.
+    pub fn child_token(&self) -> CancellationToken {
+        let child = CancellationToken::new();
+        let reason = {
+            let mut state = self.inner.state.lock().unwrap();
+
+            if state.reason.is_none() {
+                state.children.retain(TokenInner::is_observed);
+                state.children.push(child.inner.clone());
+            }
+
+            state.reason.clone()
+        };
+
+        if let Some(reason) = reason {
+            child.cancel_with(reason);
+        }
+
+        child
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.state.lock().unwrap().reason.is_some()
+    }
+
+    pub fn reason(&self) -> Option<CancelReason> {
+        self.inner.state.lock().unwrap().reason.clone()
+    }
+
+    /// Returns `Err(Error::Canceled)` if the token has been cancelled.
+    pub fn check(&self) -> Result<()> {
+        if self.is_cancelled() {
+            return Err(Error::Canceled);
+        }
+
+        Ok(())
+    }
+
+    pub fn cancel(&self) {
+        self.cancel_with(CancelReason::Requested);
+    }
+
+    /// Cancels the token with the given reason. Only the first call has an effect.
+    pub fn cancel_with(&self, reason: CancelReason) {
+        let (callbacks, children) = {
+            let mut state = self.inner.state.lock().unwrap();
+
+            if state.reason.is_some() {
+                return;
+            }
+
+            state.reason = Some(reason.clone());
+            (
+                std::mem::take(&mut state.callbacks),
+                std::mem::take(&mut state.children),
+            )
+        };
+
+        let _ = self.inner.cond.set();
+        self.inner.notify.notify_waiters();
+
+        for (_, callback) in callbacks {
+            callback(&reason);
+        }
+
+        for child in children {
+            CancellationToken { inner: child }.cancel_with(reason.clone());
+        }
+    }
+
+    /// Cancels the token with `CancelReason::TimedOut` after `timeout` unless it was
+    /// cancelled earlier.
+    pub fn cancel_after(&self, timeout: Duration) {
+        let this = self.clone();
+        thread::spawn(move || {
+            if !this.wait_timeout(timeout).unwrap_or(true) {
+                this.cancel_with(CancelReason::TimedOut);
+            }
+        });
+    }
+
+    /// Registers a callback that runs once when the token is cancelled. If the token is
+    /// already cancelled the callback runs immediately on the calling thread.
+    pub fn on_cancel(
+        &self,
+        callback: impl FnOnce(&CancelReason) + Send + 'static,
+    ) -> CancelRegistration {
+        let mut state = self.inner.state.lock().unwrap();
+        let id = state.next_id;
+        state.next_id += 1;
+
+        if let Some(reason) = state.reason.clone() {
+            drop(state);
+            callback(&reason);
+            return CancelRegistration(id);
+        }
+
+        state.callbacks.push((id, Box::new(callback)));
+        CancelRegistration(id)
+    }
+
+    /// Removes a callback registered with `on_cancel`. Returns false if it already ran.
+    pub fn unregister(&self, registration: CancelRegistration) -> bool {
+        let mut state = self.inner.state.lock().unwrap();
+        let len = state.callbacks.len();
+        state.callbacks.retain(|(id, _)| *id != registration.0);
+        state.callbacks.len() != len
+    }
+
+    /// Blocks until the token is cancelled.
+    pub fn wait(&self) -> Result<()> {
+        self.inner.cond.wait()
+    }
+
+    /// Blocks until the token is cancelled or the timeout expires.
+    /// Returns Ok(true) if the token was cancelled.
+    pub fn wait_timeout(&self, timeout: Duration) -> Result<bool> {
+        self.inner
+            .cond
+            .wait_timeout_while(|| !self.is_cancelled(), timeout)
+    }
+
+    /// Completes when the token is cancelled. Safe to drop before completion.
+    pub async fn cancelled(&self) {
+        loop {
+            let notified = self.inner.notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            if self.is_cancelled() {
+                return;
+            }
+
+            notified.await;
+        }
+    }
+}
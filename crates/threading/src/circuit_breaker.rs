@@ -0,0 +1,362 @@
+use std::{
+    collections::VecDeque,
+    fmt,
+    future::Future,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::{Error, Result, constants::*};
+
+/// The state of a `CircuitBreaker`.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CircuitState {
+    /// Calls go through and their outcomes are tracked.
+    #[default]
+    Closed,
+    /// Calls are rejected until the cooldown expires.
+    Open,
+    /// A limited number of trial calls decide whether to close or reopen the circuit.
+    HalfOpen,
+}
+
+impl fmt::Display for CircuitState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CircuitState::Closed => write!(f, "Closed"),
+            CircuitState::Open => write!(f, "Open"),
+            CircuitState::HalfOpen => write!(f, "HalfOpen"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[must_use]
+pub struct CircuitBreakerOptions {
+    /// Opens the circuit after this many failures in a row. Zero disables the check.
+    pub failure_threshold: usize,
+    /// Opens the circuit when the share of failures in the last `window_size` calls
+    /// reaches this rate. Zero disables the check.
+    pub failure_rate: f64,
+    pub window_size: usize,
+    /// How long the circuit stays open before trial calls are let through.
+    pub cooldown: Duration,
+    /// How many trial calls must succeed in the half-open state to close the circuit.
+    pub half_open_calls: usize,
+}
+
+impl Default for CircuitBreakerOptions {
+    fn default() -> Self {
+        CircuitBreakerOptions {
+            failure_threshold: FAILURE_THRESHOLD_DEF,
+            failure_rate: FAILURE_RATE_DEF,
+            window_size: FAILURE_WINDOW_DEF,
+            cooldown: COOLDOWN_DEF,
+            half_open_calls: HALF_OPEN_CALLS_DEF,
+        }
+    }
+}
+
+impl CircuitBreakerOptions {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn with_failure_threshold(&self, failure_threshold: usize) -> Self {
+        CircuitBreakerOptions {
+            failure_threshold,
+            ..self.clone()
+        }
+    }
+
+    /// Sets the failure rate, clamped to `0.0..=1.0`, and the number of most recent calls
+    /// it is measured over.
+    pub fn with_failure_rate(&self, failure_rate: f64, window_size: usize) -> Self {
+        CircuitBreakerOptions {
+            failure_rate: failure_rate.clamp(0.0, 1.0),
+            window_size: window_size.max(1),
+            ..self.clone()
+        }
+    }
+
+    pub fn with_cooldown(&self, cooldown: Duration) -> Self {
+        CircuitBreakerOptions {
+            cooldown,
+            ..self.clone()
+        }
+    }
+
+    pub fn with_half_open_calls(&self, half_open_calls: usize) -> Self {
+        CircuitBreakerOptions {
+            half_open_calls: half_open_calls.max(1),
+            ..self.clone()
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct BreakerState {
+    state: CircuitState,
+    consecutive_failures: usize,
+    /// The outcomes of the most recent calls, true for failures.
+    window: VecDeque<bool>,
+    opened_at: Option<Instant>,
+    /// Trial calls let through in the half-open state.
+    trials: usize,
+    trial_successes: usize,
+}
+
+/// Stops calling a failing dependency for a while so it can recover.
+///
+/// The circuit opens once the consecutive failure threshold or the failure rate is
+/// reached. While open, calls fail with `Error::CircuitOpen`. After the cooldown, a few
+/// trial calls are let through: if they all succeed the circuit closes, and the first
+/// failure opens it again.
+#[derive(Clone, Debug)]
+#[must_use]
+pub struct CircuitBreaker {
+    pub options: CircuitBreakerOptions,
+    state: Arc<Mutex<BreakerState>>,
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CircuitBreaker {
+    pub fn new() -> Self {
+        Self::with_options(CircuitBreakerOptions::default())
+    }
+
+    pub fn with_options(options: CircuitBreakerOptions) -> Self {
+        CircuitBreaker {
+            options,
+            state: Arc::new(Mutex::new(BreakerState::default())),
+        }
+    }
+
+    /// Gets the current state. An open circuit whose cooldown expired reports
+    /// `HalfOpen`.
+    pub fn state(&self) -> Result<CircuitState> {
+        let mut state = Error::handle_poison_error(self.state.lock())?;
+        self.refresh(&mut state);
+        Ok(state.state)
+    }
+
+    /// Gets how long until an open circuit lets trial calls through.
+    pub fn remaining_cooldown(&self) -> Result<Duration> {
+        let state = Error::handle_poison_error(self.state.lock())?;
+
+        match (state.state, state.opened_at) {
+            (CircuitState::Open, Some(opened_at)) => {
+                Ok(self.options.cooldown.saturating_sub(opened_at.elapsed()))
+            }
+            _ => Ok(Duration::ZERO),
+        }
+    }
+
+    fn refresh(&self, state: &mut BreakerState) {
+        if state.state != CircuitState::Open {
+            return;
+        }
+
+        if state
+            .opened_at
+            .is_some_and(|it| it.elapsed() >= self.options.cooldown)
+        {
+            state.state = CircuitState::HalfOpen;
+            state.trials = 0;
+            state.trial_successes = 0;
+        }
+    }
+
+    fn open(state: &mut BreakerState) {
+        state.state = CircuitState::Open;
+        state.opened_at = Some(Instant::now());
+        state.consecutive_failures = 0;
+        state.window.clear();
+    }
+
+    /// Asks to make a call. Returns `Error::CircuitOpen` if the circuit rejects it.
+    /// Record the outcome of an accepted call through the returned permit.
+    pub fn try_acquire(&self) -> Result<CircuitPermit> {
+        let mut state = Error::handle_poison_error(self.state.lock())?;
+        self.refresh(&mut state);
+
+        let trial = match state.state {
+            CircuitState::Closed => None,
+            CircuitState::Open => return Err(Error::CircuitOpen),
+            CircuitState::HalfOpen => {
+                if state.trials >= self.options.half_open_calls {
+                    return Err(Error::CircuitOpen);
+                }
+
+                state.trials += 1;
+                state.opened_at
+            }
+        };
+
+        Ok(CircuitPermit {
+            breaker: self.clone(),
+            trial,
+            recorded: false,
+        })
+    }
+
+    /// Gives back the trial slot of a half-open call that ended without an outcome, as
+    /// long as the circuit is still in the half-open state that let it through.
+    fn abandon(&self, opened_at: Instant) {
+        let Ok(mut state) = Error::handle_poison_error(self.state.lock()) else {
+            return;
+        };
+
+        if state.state == CircuitState::HalfOpen && state.opened_at == Some(opened_at) {
+            state.trials = state.trials.saturating_sub(1);
+        }
+    }
+
+    /// Whether an outcome decides the half-open state: only the trial calls let through
+    /// since the circuit last opened count, not calls that started before it opened.
+    fn is_current_trial(state: &BreakerState, trial: Option<Instant>) -> bool {
+        trial.is_some() && trial == state.opened_at
+    }
+
+    fn record_success(&self, trial: Option<Instant>) -> Result<()> {
+        let mut state = Error::handle_poison_error(self.state.lock())?;
+
+        match state.state {
+            CircuitState::Closed => {
+                state.consecutive_failures = 0;
+                self.push_outcome(&mut state, false);
+            }
+            CircuitState::HalfOpen if Self::is_current_trial(&state, trial) => {
+                state.trial_successes += 1;
+
+                if state.trial_successes >= self.options.half_open_calls {
+                    *state = BreakerState::default();
+                }
+            }
+            CircuitState::HalfOpen | CircuitState::Open => {}
+        }
+
+        Ok(())
+    }
+
+    fn record_failure(&self, trial: Option<Instant>) -> Result<()> {
+        let mut state = Error::handle_poison_error(self.state.lock())?;
+
+        match state.state {
+            CircuitState::Closed => {
+                state.consecutive_failures += 1;
+                self.push_outcome(&mut state, true);
+
+                if self.should_open(&state) {
+                    Self::open(&mut state);
+                }
+            }
+            CircuitState::HalfOpen if Self::is_current_trial(&state, trial) => {
+                Self::open(&mut state)
+            }
+            CircuitState::HalfOpen | CircuitState::Open => {}
+        }
+
+        Ok(())
+    }
+
+    fn push_outcome(&self, state: &mut BreakerState, failed: bool) {
+        if state.window.len() >= self.options.window_size {
+            state.window.pop_front();
+        }
+
+        state.window.push_back(failed);
+    }
+
+    fn should_open(&self, state: &BreakerState) -> bool {
+        if self.options.failure_threshold > 0
+            && state.consecutive_failures >= self.options.failure_threshold
+        {
+            return true;
+        }
+
+        if self.options.failure_rate <= 0.0 || state.window.len() < self.options.window_size {
+            return false;
+        }
+
+        let failures = state.window.iter().filter(|it| **it).count();
+        failures as f64 / state.window.len() as f64 >= self.options.failure_rate
+    }
+
+    /// Runs `f` through the circuit and records its outcome.
+    pub fn call<R>(&self, f: impl FnOnce() -> Result<R>) -> Result<R> {
+        let permit = self.try_acquire()?;
+        let result = f();
+        permit.record(&result)?;
+        result
+    }
+
+    /// Awaits `future` through the circuit and records its outcome.
+    pub async fn call_async<R>(&self, future: impl Future<Output = Result<R>>) -> Result<R> {
+        let permit = self.try_acquire()?;
+        let result = future.await;
+        permit.record(&result)?;
+        result
+    }
+
+    /// Closes the circuit and forgets past outcomes.
+    pub fn reset(&self) -> Result<()> {
+        let mut state = Error::handle_poison_error(self.state.lock())?;
+        *state = BreakerState::default();
+        Ok(())
+    }
+
+    /// Opens the circuit now, as if the failure threshold had been reached.
+    pub fn trip(&self) -> Result<()> {
+        let mut state = Error::handle_poison_error(self.state.lock())?;
+        Self::open(&mut state);
+        Ok(())
+    }
+}
+
+/// A call let through by a `CircuitBreaker`. A permit dropped without an outcome, because
+/// the call panicked or its future was dropped, gives its half-open trial slot back.
+#[derive(Debug)]
+#[must_use]
+pub struct CircuitPermit {
+    breaker: CircuitBreaker,
+    /// When the circuit last opened, if this is a half-open trial call.
+    trial: Option<Instant>,
+    recorded: bool,
+}
+
+impl CircuitPermit {
+    pub fn success(mut self) -> Result<()> {
+        self.recorded = true;
+        self.breaker.record_success(self.trial)
+    }
+
+    pub fn failure(mut self) -> Result<()> {
+        self.recorded = true;
+        self.breaker.record_failure(self.trial)
+    }
+
+    fn record<R>(self, result: &Result<R>) -> Result<()> {
+        match result {
+            Ok(_) => self.success(),
+            Err(_) => self.failure(),
+        }
+    }
+}
+
+impl Drop for CircuitPermit {
+    fn drop(&mut self) {
+        if self.recorded {
+            return;
+        }
+
+        if let Some(opened_at) = self.trial {
+            self.breaker.abandon(opened_at);
+        }
+    }
+}
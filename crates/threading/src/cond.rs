@@ -1,7 +1,8 @@
-use std::{
-    sync::{Arc, Condvar, Mutex},
-    time::Duration,
-};
+#[cfg(emixthreading_loom)]
+use loom::sync::{Arc, Condvar, Mutex};
+#[cfg(not(emixthreading_loom))]
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
 
 use crate::{Error, Result};
 
@@ -15,4 +15,23 @@ pub const PEEK_TIMEOUT_MAX: Duration = Duration::from_secs(5);
 pub const PAUSE_TIMEOUT_DEF: Duration = Duration::from_millis(50);
 pub const PAUSE_TIMEOUT_MIN: Duration = Duration::from_millis(10);
 pub const PAUSE_TIMEOUT_MAX: Duration = Duration::from_secs(5);
+pub const BATCH_SIZE_DEF: usize = 100;
+pub const BATCH_SIZE_MIN: usize = 1;
+pub const MAX_LATENCY_DEF: Duration = Duration::from_millis(100);
 pub const INTERVAL: u64 = 100;
+pub const PROGRESS_LOG_INTERVAL_DEF: Duration = Duration::from_secs(5);
+pub const BACKLOG_THRESHOLD_DEF: usize = 4;
+pub const LATENCY_THRESHOLD_DEF: Duration = Duration::ZERO;
+pub const IDLE_TIMEOUT_DEF: Duration = Duration::from_secs(5);
+pub const SCALE_INTERVAL_DEF: Duration = Duration::from_millis(100);
+pub const SCALE_INTERVAL_MIN: Duration = Duration::from_millis(10);
+pub const SCALE_INTERVAL_MAX: Duration = Duration::from_secs(5);
+pub const FAILURE_THRESHOLD_DEF: usize = 5;
+pub const FAILURE_RATE_DEF: f64 = 0.5;
+pub const FAILURE_WINDOW_DEF: usize = 20;
+pub const COOLDOWN_DEF: Duration = Duration::from_secs(30);
+pub const HALF_OPEN_CALLS_DEF: usize = 1;
+pub const SHUTDOWN_DEADLINE_DEF: Duration = Duration::from_secs(30);
+pub const SHUTDOWN_CANCEL_TIMEOUT_DEF: Duration = Duration::from_secs(5);
+pub const SCHEDULER_POLL_MAX: Duration = Duration::from_secs(1);
+pub const MISSED_RUNS_MAX: usize = 1024;
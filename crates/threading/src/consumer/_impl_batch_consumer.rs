@@ -0,0 +1,462 @@
+use crossbeam::channel;
+use std::{
+    mem,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
+    thread,
+};
+use tokio::{sync::Notify, time::Duration};
+
+use super::Sleeper;
+use crate::{constants::*, *};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[must_use]
+pub struct BatchConsumerOptions {
+    pub capacity: usize,
+    pub threads: usize,
+    pub batch_size: usize,
+    pub max_latency: Duration,
+    pub peek_timeout: Duration,
+    pub pause_timeout: Duration,
+}
+
+impl Default for BatchConsumerOptions {
+    fn default() -> Self {
+        BatchConsumerOptions {
+            capacity: CAPACITY_DEF,
+            threads: THREADS_DEF.clamp(THREADS_MIN, THREADS_MAX),
+            batch_size: BATCH_SIZE_DEF,
+            max_latency: MAX_LATENCY_DEF,
+            peek_timeout: PEEK_TIMEOUT_DEF.clamp(PEEK_TIMEOUT_MIN, PEEK_TIMEOUT_MAX),
+            pause_timeout: PAUSE_TIMEOUT_DEF.clamp(PAUSE_TIMEOUT_MIN, PAUSE_TIMEOUT_MAX),
+        }
+    }
+}
+
+impl BatchConsumerOptions {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn with_capacity(&self, capacity: usize) -> Self {
+        BatchConsumerOptions {
+            capacity,
+            ..self.clone()
+        }
+    }
+
+    pub fn with_threads(&self, threads: usize) -> Self {
+        BatchConsumerOptions {
+            threads: threads.clamp(THREADS_MIN, THREADS_MAX),
+            ..self.clone()
+        }
+    }
+
+    /// Sets the number of items that flushes a batch as soon as it is reached.
+    pub fn with_batch_size(&self, batch_size: usize) -> Self {
+        BatchConsumerOptions {
+            batch_size: batch_size.max(BATCH_SIZE_MIN),
+            ..self.clone()
+        }
+    }
+
+    /// Sets how long the first item of a batch may wait before the batch is flushed
+    /// regardless of its size.
+    pub fn with_max_latency(&self, max_latency: Duration) -> Self {
+        BatchConsumerOptions {
+            max_latency,
+            ..self.clone()
+        }
+    }
+}
+
+/// A producer/consumer queue that hands items to its handler in batches.
+///
+/// Each worker collects items until it has `batch_size` of them or the oldest one has
+/// waited `max_latency`, whichever comes first. Partial batches are flushed once the
+/// queue is completed and drained; a cancelled queue drops them.
+#[derive(Clone, Debug)]
+#[must_use]
+pub struct BatchConsumer<T: StaticTaskItem> {
+    pub options: BatchConsumerOptions,
+    started: Arc<Mutex<bool>>,
+    finished: Arc<AtomicBool>,
+    finished_cond: Arc<ManualResetCond>,
+    finished_noti: Arc<Notify>,
+    completed: Arc<AtomicBool>,
+    paused: Arc<AtomicBool>,
+    cancelled: Arc<AtomicBool>,
+    consumers: Arc<AtomicUsize>,
+    running: Arc<AtomicUsize>,
+    sender: channel::Sender<T>,
+    receiver: channel::Receiver<T>,
+    sleeper: Sleeper,
+}
+
+impl<T: StaticTaskItem> Default for BatchConsumer<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: StaticTaskItem> BatchConsumer<T> {
+    pub fn new() -> Self {
+        Self::with_options(Default::default())
+    }
+
+    pub fn with_options(options: BatchConsumerOptions) -> Self {
+        let (sender, receiver) = channel::bounded(options.capacity);
+        BatchConsumer {
+            options,
+            sender,
+            receiver,
+            started: Arc::new(Mutex::new(false)),
+            finished: Arc::new(AtomicBool::new(false)),
+            finished_cond: Arc::new(ManualResetCond::new_unset()),
+            finished_noti: Arc::new(Notify::new()),
+            completed: Arc::new(AtomicBool::new(false)),
+            paused: Arc::new(AtomicBool::new(false)),
+            cancelled: Arc::new(AtomicBool::new(false)),
+            consumers: Arc::new(AtomicUsize::new(0)),
+            running: Arc::new(AtomicUsize::new(0)),
+            sleeper: Sleeper::default(),
+        }
+    }
+
+    /// Runs the peek and pause timeouts and the batch latency on `clock` instead of in
+    /// real time. Call it before `start`.
+    #[cfg(feature = "test-support")]
+    pub fn with_clock(mut self, clock: &test_support::VirtualClock) -> Self {
+        self.sleeper = Sleeper::Virtual(clock.clone());
+        self
+    }
+
+    pub fn is_started(&self) -> bool {
+        *self.started.lock().unwrap()
+    }
+
+    fn set_started(&self, value: bool) -> bool {
+        let mut started = self.started.lock().unwrap();
+
+        if *started && value {
+            return false;
+        }
+
+        *started = true;
+        true
+    }
+
+    pub fn is_completed(&self) -> bool {
+        self.completed.load(Ordering::SeqCst)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished.load(Ordering::SeqCst)
+    }
+
+    pub fn is_busy(&self) -> bool {
+        self.len() + self.running.load(Ordering::SeqCst) > 0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Gets the number of items waiting in the queue, not counting those already
+    /// collected into a batch.
+    pub fn len(&self) -> usize {
+        self.receiver.len()
+    }
+
+    pub fn capacity(&self) -> Option<usize> {
+        Some(self.options.capacity)
+    }
+
+    pub fn consumers(&self) -> usize {
+        self.consumers.load(Ordering::SeqCst)
+    }
+
+    fn set_consumers(&self, value: usize) {
+        self.consumers.store(value, Ordering::SeqCst);
+    }
+
+    fn dec_consumers(&self) -> bool {
+        self.consumers.fetch_sub(1, Ordering::SeqCst);
+        self.consumers() == 0 && (self.is_completed() || self.is_cancelled())
+    }
+
+    /// Drops every queued item.
+    fn clear_queue(&self) {
+        while self.receiver.try_recv().is_ok() {}
+    }
+
+    /// Marks the consumer finished and releases its waiters.
+    fn set_finished(&self) {
+        self.completed.store(true, Ordering::SeqCst);
+        self.finished.store(true, Ordering::SeqCst);
+        self.set_started(false);
+        let _ = self.finished_cond.set();
+        self.finished_noti.notify_waiters();
+        thread::sleep(Duration::ZERO);
+    }
+
+    /// Gets the number of items that were taken from the queue and are either waiting in
+    /// a batch or being processed.
+    pub fn running(&self) -> usize {
+        self.running.load(Ordering::SeqCst)
+    }
+
+    pub fn start<H: BatchTaskDelegation<BatchConsumer<T>, T>>(&self, handler: &H) -> Result<()> {
+        if self.is_cancelled() {
+            return Err(Error::Canceled);
+        }
+
+        if self.is_completed() && self.is_empty() {
+            return Err(Error::QueueCompleted);
+        }
+
+        if !self.set_started(true) {
+            return Err(Error::QueueStarted);
+        }
+
+        self.set_consumers(self.options.threads);
+        handler.on_started(self);
+
+        for _ in 0..self.options.threads {
+            let this = self.clone();
+            let handler = handler.clone();
+            thread::spawn(move || this.run_worker(&handler));
+        }
+
+        Ok(())
+    }
+
+    fn run_worker<H: BatchTaskDelegation<BatchConsumer<T>, T>>(&self, handler: &H) {
+        let mut batch = Vec::with_capacity(self.options.batch_size);
+        let mut deadline = self.sleeper.now();
+
+        loop {
+            if self.is_cancelled() {
+                break;
+            }
+
+            if self.is_paused() {
+                self.sleeper.sleep(self.options.pause_timeout);
+                continue;
+            }
+
+            let drained = self.is_completed() && self.receiver.is_empty();
+
+            if batch.is_empty() && drained {
+                break;
+            }
+
+            if !batch.is_empty() && (drained || self.sleeper.now() >= deadline) {
+                if !self.flush(handler, &mut batch) {
+                    break;
+                }
+
+                continue;
+            }
+
+            let timeout = if batch.is_empty() {
+                self.options.peek_timeout
+            } else {
+                deadline
+                    .saturating_duration_since(self.sleeper.now())
+                    .min(self.options.peek_timeout)
+            };
+            let item = self
+                .sleeper
+                .wait_timeout(timeout, |timeout| self.receiver.recv_timeout(timeout).ok());
+            let Some(item) = item else {
+                continue;
+            };
+
+            if batch.is_empty() {
+                deadline = self.sleeper.now() + self.options.max_latency;
+            }
+
+            self.running.fetch_add(1, Ordering::SeqCst);
+            batch.push(item);
+
+            if batch.len() >= self.options.batch_size && !self.flush(handler, &mut batch) {
+                break;
+            }
+        }
+
+        // Items still held by a worker that stopped early are dropped with the batch.
+        self.running.fetch_sub(batch.len(), Ordering::SeqCst);
+
+        if !self.dec_consumers() {
+            return;
+        }
+
+        if self.is_cancelled() {
+            handler.on_cancelled(self);
+        } else {
+            handler.on_finished(self);
+        }
+
+        self.finish();
+    }
+
+    /// Processes the batch and reports every item to `on_completed`. Returns false if the
+    /// handler asked the worker to stop.
+    fn flush<H: BatchTaskDelegation<BatchConsumer<T>, T>>(
+        &self,
+        handler: &H,
+        batch: &mut Vec<T>,
+    ) -> bool {
+        let items = mem::replace(batch, Vec::with_capacity(self.options.batch_size));
+        let mut results = match handler.process(self, &items) {
+            Ok(it) => it.into_iter(),
+            Err(e) => vec![TaskResult::Error(e.to_string()); items.len()].into_iter(),
+        };
+        let mut proceed = true;
+
+        for item in &items {
+            let result = results.next().unwrap_or_default();
+            proceed &= handler.on_completed(self, item, &result);
+        }
+
+        self.running.fetch_sub(items.len(), Ordering::SeqCst);
+        proceed
+    }
+
+    pub fn enqueue(&self, item: T) -> Result<()> {
+        self.enq(item, true)
+    }
+
+    /// Enqueues an item without waiting for room, failing with `Error::Exceeded` if the
+    /// queue is full.
+    pub fn try_enqueue(&self, item: T) -> Result<()> {
+        self.enq(item, false)
+    }
+
+    fn enq(&self, item: T, wait_for_room: bool) -> Result<()> {
+        if self.is_cancelled() {
+            return Err(Error::Canceled);
+        }
+
+        if self.is_completed() {
+            return Err(Error::QueueCompleted);
+        }
+
+        let mut item = item;
+
+        if !wait_for_room {
+            return match self.sender.try_send(item) {
+                Ok(()) => Ok(()),
+                Err(channel::TrySendError::Full(_)) => {
+                    Err(Error::Exceeded("Queue is full".to_string()))
+                }
+                Err(e) => Err(Error::from_std_error(e)),
+            };
+        }
+
+        // Retry in slices so a producer blocked on a full queue notices cancellation.
+        loop {
+            match self.sender.send_timeout(item, self.options.peek_timeout) {
+                Ok(()) => return Ok(()),
+                Err(channel::SendTimeoutError::Timeout(it)) => {
+                    if self.is_cancelled() {
+                        return Err(Error::Canceled);
+                    }
+
+                    item = it;
+                }
+                Err(e) => return Err(Error::from_std_error(e)),
+            }
+        }
+    }
+
+    pub fn stop(&self, enforce: bool) {
+        if enforce {
+            self.cancel();
+        } else {
+            self.complete();
+        }
+    }
+
+    /// Stops accepting items. Workers flush what they collected once the queue is drained.
+    pub fn complete(&self) {
+        self.completed.store(true, Ordering::SeqCst);
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// Cancels the queue when `token` is cancelled.
+    pub fn cancel_on(&self, token: &CancellationToken) {
+        let this = self.clone();
+        token.on_cancel(move |_| this.cancel());
+    }
+
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::SeqCst);
+    }
+
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::SeqCst);
+    }
+
+    pub fn wait(&self) -> Result<()> {
+        wait(self, &self.finished_cond)
+    }
+
+    pub async fn wait_async(&self) -> Result<()> {
+        wait_async(self, &self.finished_noti).await
+    }
+
+    pub fn wait_until(&self, cond: impl Fn(&BatchConsumer<T>) -> bool) -> Result<()> {
+        wait_until(self, &self.finished_cond, cond)
+    }
+
+    pub async fn wait_until_async(
+        &self,
+        cond: impl Fn(&BatchConsumer<T>) -> Pin<Box<dyn Future<Output = bool> + Send>>,
+    ) -> Result<()> {
+        wait_until_async(self, &self.finished_noti, cond).await
+    }
+
+    pub fn wait_for(&self, timeout: Duration) -> Result<()> {
+        wait_for(self, timeout, &self.finished_cond)
+    }
+
+    pub async fn wait_for_async(&self, timeout: Duration) -> Result<()> {
+        wait_for_async(self, timeout, &self.finished_noti).await
+    }
+
+    pub fn wait_for_until(
+        &self,
+        timeout: Duration,
+        cond: impl Fn(&BatchConsumer<T>) -> bool,
+    ) -> Result<()> {
+        wait_for_until(self, timeout, &self.finished_cond, cond)
+    }
+
+    pub async fn wait_for_until_async<
+        F: Fn(&BatchConsumer<T>) -> Pin<Box<dyn Future<Output = bool> + Send>>,
+    >(
+        &self,
+        timeout: Duration,
+        cond: F,
+    ) -> Result<()> {
+        wait_for_until_async(self, timeout, &self.finished_noti, cond).await
+    }
+}
+
+impl_consumer!(BatchConsumer, StaticTaskItem);
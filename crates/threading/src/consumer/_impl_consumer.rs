@@ -13,6 +13,7 @@ use tokio::{
     time::{Duration, Instant},
 };
 
+use super::{ScalingOptions, Sleeper, Turn, WorkerScaler};
 use crate::{constants::*, *};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -23,6 +24,7 @@ pub struct ConsumerOptions {
     pub sleep_after_send: Duration,
     pub peek_timeout: Duration,
     pub pause_timeout: Duration,
+    pub scaling: Option<ScalingOptions>,
 }
 
 impl Default for ConsumerOptions {
@@ -33,6 +35,7 @@ impl Default for ConsumerOptions {
             sleep_after_send: SLEEP_AFTER_SEND_DEF,
             peek_timeout: PEEK_TIMEOUT_DEF.clamp(PEEK_TIMEOUT_MIN, PEEK_TIMEOUT_MAX),
             pause_timeout: PAUSE_TIMEOUT_DEF.clamp(PAUSE_TIMEOUT_MIN, PAUSE_TIMEOUT_MAX),
+            scaling: None,
         }
     }
 }
@@ -62,6 +65,13 @@ impl ConsumerOptions {
             ..self.clone()
         }
     }
+
+    pub fn with_scaling(&self, scaling: ScalingOptions) -> Self {
+        ConsumerOptions {
+            scaling: Some(scaling),
+            ..self.clone()
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -79,6 +89,8 @@ pub struct Consumer<T: StaticTaskItem> {
     cancelled: Arc<AtomicBool>,
     consumers: Arc<AtomicUsize>,
     running: Arc<AtomicUsize>,
+    scaler: Arc<WorkerScaler>,
+    sleeper: Sleeper,
 }
 
 impl<T: StaticTaskItem> Consumer<T> {
@@ -96,6 +108,8 @@ impl<T: StaticTaskItem> Consumer<T> {
             cancelled: Arc::new(AtomicBool::new(false)),
             consumers: Arc::new(AtomicUsize::new(0)),
             running: Arc::new(AtomicUsize::new(0)),
+            scaler: Arc::new(WorkerScaler::new()),
+            sleeper: Sleeper::default(),
         }
     }
 
@@ -113,9 +127,19 @@ impl<T: StaticTaskItem> Consumer<T> {
             cancelled: Arc::new(AtomicBool::new(false)),
             consumers: Arc::new(AtomicUsize::new(0)),
             running: Arc::new(AtomicUsize::new(0)),
+            scaler: Arc::new(WorkerScaler::new()),
+            sleeper: Sleeper::default(),
         }
     }
 
+    /// Runs the threshold, peek, pause, and sleep-after-send timeouts on `clock` instead
+    /// of in real time. Call it before `start`.
+    #[cfg(feature = "test-support")]
+    pub fn with_clock(mut self, clock: &test_support::VirtualClock) -> Self {
+        self.sleeper = Sleeper::Virtual(clock.clone());
+        self
+    }
+
     pub fn is_started(&self) -> bool {
         *self.started.lock().unwrap()
     }
@@ -159,6 +183,11 @@ impl<T: StaticTaskItem> Consumer<T> {
         self.items.len()
     }
 
+    /// Returns `None`, as the queue is unbounded.
+    pub fn capacity(&self) -> Option<usize> {
+        None
+    }
+
     pub fn consumers(&self) -> usize {
         self.consumers.load(Ordering::SeqCst)
     }
@@ -172,14 +201,17 @@ impl<T: StaticTaskItem> Consumer<T> {
         self.consumers() == 0 && (self.is_completed() || self.is_cancelled())
     }
 
-    fn finish(&self) {
-        if !self.is_completed() && !self.is_cancelled() {
-            return;
-        }
+    /// Drops every queued item.
+    fn clear_queue(&self) {
+        while self.items.pop().is_some() {}
+    }
 
+    /// Marks the consumer finished and releases its waiters.
+    fn set_finished(&self) {
         self.completed.store(true, Ordering::SeqCst);
         self.finished.store(true, Ordering::SeqCst);
         self.set_started(false);
+        self.scaler.clear();
         if let Err(_) = self.finished_cond.set() {
             // Mutex was poisoned - this is a serious error but we'll continue cleanup
             // The error information is preserved in the Result type for caller handling
@@ -201,6 +233,28 @@ impl<T: StaticTaskItem> Consumer<T> {
     }
 
     pub fn start<H: TaskDelegation<Consumer<T>, T>>(&self, handler: &H) -> Result<()> {
+        self.begin(self.options.threads)?;
+        let this = self.clone();
+        let spawner = handler.clone();
+        self.scaler.install(
+            self.options.threads,
+            Arc::new(move || this.spawn_worker(&spawner)),
+        );
+        handler.on_started(self);
+
+        for _ in 0..self.options.threads {
+            self.spawn_worker(handler);
+        }
+
+        if let Some(scaling) = &self.options.scaling {
+            self.spawn_scaler(scaling.clone());
+        }
+
+        Ok(())
+    }
+
+    /// Marks the consumer started with `workers` workers.
+    fn begin(&self, workers: usize) -> Result<()> {
         if self.is_cancelled() {
             return Err(Error::Canceled);
         }
@@ -213,122 +267,143 @@ impl<T: StaticTaskItem> Consumer<T> {
             return Err(Error::QueueStarted);
         }
 
-        self.set_consumers(self.options.threads);
-        handler.on_started(self);
+        self.set_consumers(workers);
+        Ok(())
+    }
 
-        for _ in 0..self.options.threads {
-            let this = self.clone();
-            let handler = handler.clone();
-            thread::spawn(move || {
-                if this.options.threshold.is_zero() {
-                    loop {
-                        if this.is_cancelled() || (!this.is_busy() && this.is_completed()) {
-                            break;
-                        }
-
-                        if this.is_paused() {
-                            thread::sleep(this.options.pause_timeout);
-                            continue;
-                        }
-
-                        let Some(item) = this.dequeue_wait() else {
-                            continue;
-                        };
-                        this.inc_running();
-                        match handler.process(&this, &item) {
-                            Ok(it) => {
-                                if !handler.on_completed(&this, &item, &it) {
-                                    this.dec_running();
-                                    break;
-                                }
-                            }
-                            Err(e) => {
-                                if !handler.on_completed(
-                                    &this,
-                                    &item,
-                                    &TaskResult::Error(e.to_string()),
-                                ) {
-                                    this.dec_running();
-                                    break;
-                                }
-                            }
-                        }
-                        this.dec_running();
-                    }
+    /// Changes the number of worker threads while the consumer is running. Extra workers
+    /// are spawned immediately; surplus workers retire after their current item.
+    pub fn set_workers(&self, workers: usize) -> Result<()> {
+        if self.is_cancelled() {
+            return Err(Error::Canceled);
+        }
 
-                    if !this.dec_consumers() {
-                        return;
-                    }
+        if !self.is_started() {
+            return Err(Error::InvalidOperation("Queue is not started".to_string()));
+        }
 
-                    if this.is_cancelled() {
-                        handler.on_cancelled(&this);
-                    } else {
-                        handler.on_finished(&this);
-                    }
+        if !self
+            .scaler
+            .resize(&self.consumers, workers.clamp(THREADS_MIN, THREADS_MAX))
+        {
+            return Err(Error::QueueCompleted);
+        }
 
-                    this.finish();
-                    return;
-                }
+        Ok(())
+    }
 
-                loop {
-                    if this.is_cancelled() || (!this.is_busy() && this.is_completed()) {
-                        break;
-                    }
+    pub fn target_workers(&self) -> usize {
+        self.scaler.target()
+    }
 
-                    if this.is_paused() {
-                        thread::sleep(this.options.pause_timeout);
-                        continue;
-                    }
+    pub fn latency(&self) -> Duration {
+        self.scaler.latency()
+    }
 
-                    let Some(item) = this.dequeue_wait() else {
-                        continue;
-                    };
-                    this.inc_running();
-                    match handler.process(&this, &item) {
-                        Ok(it) => {
-                            if !handler.on_completed(&this, &item, &it) {
-                                this.dec_running();
-                                break;
-                            }
-
-                            if !this.options.threshold.is_zero() {
-                                let time = Instant::now();
-
-                                if time.elapsed() < this.options.threshold {
-                                    let remaining = this.options.threshold - time.elapsed();
-                                    thread::sleep(remaining);
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            if !handler.on_completed(
-                                &this,
-                                &item,
-                                &TaskResult::Error(e.to_string()),
-                            ) {
-                                this.dec_running();
-                                break;
-                            }
-                        }
-                    }
-                    this.dec_running();
-                }
+    fn spawn_worker<H: TaskDelegation<Consumer<T>, T>>(&self, handler: &H) {
+        let this = self.clone();
+        let handler = handler.clone();
+        thread::spawn(move || this.run_worker(&handler));
+    }
+
+    fn spawn_scaler(&self, scaling: ScalingOptions) {
+        let this = self.clone();
+        thread::spawn(move || {
+            while !this.is_finished() && !this.is_cancelled() {
+                thread::sleep(scaling.interval);
 
-                if !this.dec_consumers() {
-                    return;
+                if this.is_paused() {
+                    continue;
                 }
 
-                if this.is_cancelled() {
-                    handler.on_cancelled(&this);
-                } else {
-                    handler.on_finished(&this);
+                let workers = this.consumers();
+
+                if scaling.should_scale_up(workers, this.len(), this.latency()) {
+                    let _ = this.set_workers(workers + 1);
                 }
+            }
+        });
+    }
+
+    fn run_worker<H: TaskDelegation<Consumer<T>, T>>(&self, handler: &H) {
+        let mut idle_since = Instant::now();
+
+        loop {
+            if self.should_stop() {
+                break;
+            }
 
-                this.finish();
-            });
+            if self.scaler.try_retire(
+                &self.consumers,
+                self.options.scaling.as_ref(),
+                idle_since.elapsed(),
+            ) {
+                return;
+            }
+
+            match self.turn(handler, true) {
+                Turn::Processed => idle_since = Instant::now(),
+                Turn::Stopped => break,
+                Turn::Paused | Turn::Idle => {}
+            }
         }
 
-        Ok(())
+        self.leave(handler);
+    }
+
+    fn should_stop(&self) -> bool {
+        self.is_cancelled() || (!self.is_busy() && self.is_completed())
+    }
+
+    /// Runs one turn of a worker: takes an item and processes it. With `wait`, it also
+    /// waits out the pause, peek, and threshold timeouts; without it, it returns instead.
+    fn turn<H: TaskDelegation<Consumer<T>, T>>(&self, handler: &H, wait: bool) -> Turn {
+        if self.is_paused() {
+            if wait {
+                self.sleeper.sleep(self.options.pause_timeout);
+            }
+
+            return Turn::Paused;
+        }
+
+        let Some(item) = self.deq(wait) else {
+            return Turn::Idle;
+        };
+        self.inc_running();
+        let time = Instant::now();
+        let (result, processed) = match handler.process(self, &item) {
+            Ok(it) => (it, true),
+            Err(e) => (TaskResult::Error(e.to_string()), false),
+        };
+        self.scaler.record_latency(time.elapsed());
+
+        if !handler.on_completed(self, &item, &result) {
+            self.dec_running();
+            return Turn::Stopped;
+        }
+
+        if wait && processed && !self.options.threshold.is_zero() {
+            self.sleeper.sleep(self.options.threshold);
+        }
+
+        self.dec_running();
+        Turn::Processed
+    }
+
+    /// Takes a worker out of the pool. The last one calls `on_cancelled` or
+    /// `on_finished` and finishes the consumer.
+    fn leave<H: TaskDelegation<Consumer<T>, T>>(&self, handler: &H) {
+        if !self.dec_consumers() {
+            return;
+        }
+
+        if self.is_cancelled() {
+            handler.on_cancelled(self);
+        } else {
+            handler.on_finished(self);
+        }
+
+        self.finish();
     }
 
     pub fn enqueue(&self, item: T) -> Result<()> {
@@ -343,7 +418,7 @@ impl<T: StaticTaskItem> Consumer<T> {
         self.items.push(item);
 
         if !self.options.sleep_after_send.is_zero() {
-            thread::sleep(self.options.sleep_after_send);
+            self.sleeper.sleep(self.options.sleep_after_send);
         }
 
         if let Err(_) = self.items_cond.set() {
@@ -352,6 +427,11 @@ impl<T: StaticTaskItem> Consumer<T> {
         Ok(())
     }
 
+    /// Same as `enqueue`, as the queue is unbounded and never waits for room.
+    pub fn try_enqueue(&self, item: T) -> Result<()> {
+        self.enqueue(item)
+    }
+
     pub fn dequeue(&self) -> Option<T> {
         self.deq(false)
     }
@@ -363,19 +443,16 @@ impl<T: StaticTaskItem> Consumer<T> {
     fn deq(&self, wait_for_item: bool) -> Option<T> {
         if wait_for_item {
             while self.items.is_empty() && !self.is_cancelled() && !self.is_completed() {
-                match self.items_cond.wait_timeout(self.options.peek_timeout) {
-                    Ok(true) => {
-                        if self.is_cancelled() || self.is_completed() {
-                            return None;
-                        }
-                        return self.items.pop();
-                    }
-                    Ok(false) => continue,
-                    Err(_) => {
-                        // Log or handle the error, but continue the loop
-                        // In case of poison error, we want to continue rather than panic
-                        continue;
+                // A poisoned condition counts as a timeout, so the loop continues rather
+                // than panicking.
+                let wait = |timeout| self.items_cond.wait_timeout(timeout).ok().filter(|it| *it);
+                let signaled = self.sleeper.wait_timeout(self.options.peek_timeout, wait);
+
+                if signaled.is_some() {
+                    if self.is_cancelled() || self.is_completed() {
+                        return None;
                     }
+                    return self.items.pop();
                 }
             }
         }
@@ -413,6 +490,12 @@ impl<T: StaticTaskItem> Consumer<T> {
         }
     }
 
+    /// Cancels the queue when `token` is cancelled.
+    pub fn cancel_on(&self, token: &CancellationToken) {
+        let this = self.clone();
+        token.on_cancel(move |_| this.cancel());
+    }
+
     pub fn pause(&self) {
         self.paused.store(true, Ordering::SeqCst);
     }
@@ -470,12 +553,37 @@ impl<T: StaticTaskItem> Consumer<T> {
     }
 }
 
-impl<T: StaticTaskItem> AwaitableConsumer<T> for Consumer<T> {
-    fn is_cancelled(&self) -> bool {
-        Consumer::is_cancelled(self)
+impl_consumer!(Consumer, StaticTaskItem);
+
+#[cfg(feature = "test-support")]
+impl<T: StaticTaskItem> test_support::InlineConsumer<T> for Consumer<T> {
+    type Worker = ();
+
+    fn start_inline<H: TaskDelegation<Self, T>>(&self, handler: &H) -> Result<()> {
+        self.begin(1)?;
+        handler.on_started(self);
+        Ok(())
     }
 
-    fn is_finished(&self) -> bool {
-        Consumer::is_finished(self)
+    fn step_inline<H: TaskDelegation<Self, T>>(
+        &self,
+        handler: &H,
+        _worker: &mut (),
+    ) -> test_support::Step {
+        if self.is_finished() || self.consumers() == 0 {
+            return test_support::Step::stopped(self.is_finished());
+        }
+
+        if !self.should_stop() {
+            match self.turn(handler, false) {
+                Turn::Processed => return test_support::Step::Processed,
+                Turn::Paused => return test_support::Step::Paused,
+                Turn::Idle if !self.should_stop() => return test_support::Step::Idle,
+                Turn::Idle | Turn::Stopped => {}
+            }
+        }
+
+        self.leave(handler);
+        test_support::Step::stopped(self.is_finished())
     }
 }
@@ -1,9 +1,12 @@
-use crossbeam::deque::{Injector, Steal, Stealer, Worker};
+use crossbeam::{
+    deque::{Injector, Steal, Stealer, Worker},
+    utils::Backoff,
+};
 use std::{
-    mem,
+    iter, mem,
     sync::{
-        Arc, Mutex,
-        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Condvar, Mutex,
+        atomic::{self, AtomicBool, AtomicUsize, Ordering},
     },
     thread,
 };
@@ -12,6 +15,7 @@ use tokio::{
     time::{Duration, Instant},
 };
 
+use super::{ScalingOptions, Sleeper, Turn, WorkerScaler};
 use crate::{constants::*, *};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -22,6 +26,11 @@ pub struct InjectorWorkerOptions {
     pub threshold: Duration,
     pub sleep_after_send: Duration,
     pub pause_timeout: Duration,
+    pub scaling: Option<ScalingOptions>,
+    /// Gives each worker its own deque that siblings steal from, and parks idle workers
+    /// until items arrive. Waking a parked worker takes a few microseconds longer, but
+    /// idle workers do not burn CPU. When off, idle workers keep polling the queues.
+    pub work_stealing: bool,
 }
 
 impl Default for InjectorWorkerOptions {
@@ -32,6 +41,8 @@ impl Default for InjectorWorkerOptions {
             threshold: THRESHOLD_DEF,
             sleep_after_send: SLEEP_AFTER_SEND_DEF,
             pause_timeout: PAUSE_TIMEOUT_DEF.clamp(PAUSE_TIMEOUT_MIN, PAUSE_TIMEOUT_MAX),
+            scaling: None,
+            work_stealing: true,
         }
     }
 }
@@ -68,6 +79,61 @@ impl InjectorWorkerOptions {
             ..self.clone()
         }
     }
+
+    pub fn with_scaling(&self, scaling: ScalingOptions) -> Self {
+        InjectorWorkerOptions {
+            scaling: Some(scaling),
+            ..self.clone()
+        }
+    }
+
+    pub fn with_work_stealing(&self, work_stealing: bool) -> Self {
+        InjectorWorkerOptions {
+            work_stealing,
+            ..self.clone()
+        }
+    }
+}
+
+/// Where idle workers sleep until there is work for them.
+#[derive(Debug, Default)]
+struct Parking {
+    sleeping: AtomicUsize,
+    lock: Mutex<()>,
+    cvar: Condvar,
+}
+
+impl Parking {
+    /// Sleeps until woken or the timeout expires, unless `has_work` already holds.
+    fn park(&self, timeout: Duration, has_work: impl Fn() -> bool) {
+        let guard = self.lock.lock().unwrap();
+        self.sleeping.fetch_add(1, Ordering::SeqCst);
+        // Pairs with the fence in `unpark_one`: either the producer sees this worker
+        // sleeping or the worker sees the producer's item.
+        atomic::fence(Ordering::SeqCst);
+
+        if !has_work() {
+            let _ = self.cvar.wait_timeout(guard, timeout);
+        }
+
+        self.sleeping.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    fn unpark_one(&self) {
+        atomic::fence(Ordering::SeqCst);
+
+        if self.sleeping.load(Ordering::SeqCst) == 0 {
+            return;
+        }
+
+        let _guard = self.lock.lock().unwrap();
+        self.cvar.notify_one();
+    }
+
+    fn unpark_all(&self) {
+        let _guard = self.lock.lock().unwrap();
+        self.cvar.notify_all();
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -76,6 +142,9 @@ pub struct InjectorWorker<T: StaticTaskItem> {
     pub options: InjectorWorkerOptions,
     injector: Arc<Injector<T>>,
     stealers: Arc<Mutex<Vec<Stealer<T>>>>,
+    /// The id of the worker owning each of `stealers`, in the same order.
+    stealer_ids: Arc<Mutex<Vec<usize>>>,
+    next_worker_id: Arc<AtomicUsize>,
     len: Arc<AtomicUsize>,
     started: Arc<Mutex<bool>>,
     finished: Arc<AtomicBool>,
@@ -86,6 +155,9 @@ pub struct InjectorWorker<T: StaticTaskItem> {
     cancelled: Arc<AtomicBool>,
     workers: Arc<AtomicUsize>,
     running: Arc<AtomicUsize>,
+    scaler: Arc<WorkerScaler>,
+    sleeper: Sleeper,
+    parking: Arc<Parking>,
 }
 
 impl<T: StaticTaskItem> InjectorWorker<T> {
@@ -94,6 +166,8 @@ impl<T: StaticTaskItem> InjectorWorker<T> {
             options: Default::default(),
             injector: Arc::new(Injector::new()),
             stealers: Arc::new(Mutex::new(Vec::new())),
+            stealer_ids: Arc::new(Mutex::new(Vec::new())),
+            next_worker_id: Arc::new(AtomicUsize::new(0)),
             len: Arc::new(AtomicUsize::new(0)),
             started: Arc::new(Mutex::new(false)),
             finished: Arc::new(AtomicBool::new(false)),
@@ -104,6 +178,9 @@ impl<T: StaticTaskItem> InjectorWorker<T> {
             cancelled: Arc::new(AtomicBool::new(false)),
             workers: Arc::new(AtomicUsize::new(0)),
             running: Arc::new(AtomicUsize::new(0)),
+            scaler: Arc::new(WorkerScaler::new()),
+            sleeper: Sleeper::default(),
+            parking: Arc::new(Parking::default()),
         }
     }
 
@@ -112,6 +189,8 @@ impl<T: StaticTaskItem> InjectorWorker<T> {
             options,
             injector: Arc::new(Injector::new()),
             stealers: Arc::new(Mutex::new(Vec::new())),
+            stealer_ids: Arc::new(Mutex::new(Vec::new())),
+            next_worker_id: Arc::new(AtomicUsize::new(0)),
             len: Arc::new(AtomicUsize::new(0)),
             started: Arc::new(Mutex::new(false)),
             finished: Arc::new(AtomicBool::new(false)),
@@ -122,9 +201,20 @@ impl<T: StaticTaskItem> InjectorWorker<T> {
             cancelled: Arc::new(AtomicBool::new(false)),
             workers: Arc::new(AtomicUsize::new(0)),
             running: Arc::new(AtomicUsize::new(0)),
+            scaler: Arc::new(WorkerScaler::new()),
+            sleeper: Sleeper::default(),
+            parking: Arc::new(Parking::default()),
         }
     }
 
+    /// Runs the threshold, peek, pause, and sleep-after-send timeouts on `clock` instead
+    /// of in real time. Call it before `start`.
+    #[cfg(feature = "test-support")]
+    pub fn with_clock(mut self, clock: &test_support::VirtualClock) -> Self {
+        self.sleeper = Sleeper::Virtual(clock.clone());
+        self
+    }
+
     pub fn is_started(&self) -> bool {
         *self.started.lock().unwrap()
     }
@@ -168,11 +258,16 @@ impl<T: StaticTaskItem> InjectorWorker<T> {
         self.len.load(Ordering::SeqCst)
     }
 
+    /// Returns `None`, as the queue is unbounded.
+    pub fn capacity(&self) -> Option<usize> {
+        None
+    }
+
     pub fn workers(&self) -> usize {
         self.workers.load(Ordering::SeqCst)
     }
 
-    fn set_workers(&self, value: usize) {
+    fn init_workers(&self, value: usize) {
         self.workers.store(value, Ordering::SeqCst);
     }
 
@@ -181,14 +276,17 @@ impl<T: StaticTaskItem> InjectorWorker<T> {
         self.workers() == 0 && (self.is_completed() || self.is_cancelled())
     }
 
-    fn finish(&self) {
-        if !self.is_completed() && !self.is_cancelled() {
-            return;
-        }
+    /// Drops every queued item.
+    fn clear_queue(&self) {
+        while !matches!(self.injector.steal(), Steal::Empty) {}
+    }
 
+    /// Marks the consumer finished and releases its waiters.
+    fn set_finished(&self) {
         self.completed.store(true, Ordering::SeqCst);
         self.finished.store(true, Ordering::SeqCst);
         self.set_started(false);
+        self.scaler.clear();
         if let Err(_) = self.finished_cond.set() {
             // Mutex was poisoned - this is a serious error but we'll continue cleanup
         }
@@ -209,6 +307,29 @@ impl<T: StaticTaskItem> InjectorWorker<T> {
     }
 
     pub fn start<H: TaskDelegation<InjectorWorker<T>, T>>(&self, handler: &H) -> Result<()> {
+        self.begin(self.options.threads)?;
+        let this = self.clone();
+        let spawner = handler.clone();
+        self.scaler.install(
+            self.options.threads,
+            Arc::new(move || this.spawn_worker(&spawner)),
+        );
+        handler.on_started(self);
+        self.clear_stealers();
+
+        for _ in 0..self.options.threads {
+            self.spawn_worker(handler);
+        }
+
+        if let Some(scaling) = &self.options.scaling {
+            self.spawn_scaler(scaling.clone());
+        }
+
+        Ok(())
+    }
+
+    /// Marks the pool started with `workers` workers.
+    fn begin(&self, workers: usize) -> Result<()> {
         if self.is_cancelled() {
             return Err(Error::Canceled);
         }
@@ -221,134 +342,314 @@ impl<T: StaticTaskItem> InjectorWorker<T> {
             return Err(Error::QueueStarted);
         }
 
-        self.set_workers(self.options.threads);
-        handler.on_started(self);
-        let mut mutstealers = self.stealers.lock().unwrap();
-        mutstealers.clear();
+        self.init_workers(workers);
+        Ok(())
+    }
 
-        for _ in 0..self.options.threads {
-            let worker = if self.options.behavior == QueueBehavior::LIFO {
-                Worker::<T>::new_lifo()
-            } else {
-                Worker::<T>::new_fifo()
-            };
-            let stealer = worker.stealer();
-            mutstealers.push(stealer);
-            let this = self.clone();
-            let handler = handler.clone();
-            let global = self.injector.clone();
-            let local = Arc::new(Mutex::new(worker));
-            let stealers = self.stealers.clone();
-            thread::spawn(move || {
-                if this.options.threshold.is_zero() {
-                    loop {
-                        if this.is_cancelled() || (this.is_empty() && this.is_completed()) {
-                            break;
-                        }
-
-                        if this.is_paused() {
-                            thread::sleep(this.options.pause_timeout);
-                            continue;
-                        }
-
-                        let Some(item) = this.dequeue_wait(&global, &local, &stealers) else {
-                            continue;
-                        };
-                        this.inc_running();
-                        match handler.process(&this, &item) {
-                            Ok(it) => {
-                                if !handler.on_completed(&this, &item, &it) {
-                                    this.dec_running();
-                                    break;
-                                }
-                            }
-                            Err(e) => {
-                                if !handler.on_completed(
-                                    &this,
-                                    &item,
-                                    &TaskResult::Error(e.to_string()),
-                                ) {
-                                    this.dec_running();
-                                    break;
-                                }
-                            }
-                        }
-                        this.dec_running();
-                    }
-
-                    if !this.dec_workers() {
-                        return;
-                    }
-
-                    if this.is_cancelled() {
-                        handler.on_cancelled(&this);
-                    } else {
-                        handler.on_finished(&this);
-                    }
-
-                    this.finish();
-                    return;
-                }
+    /// Changes the number of worker threads while the worker pool is running. Extra
+    /// workers are spawned immediately; surplus workers hand their local items back to
+    /// the global queue and retire after their current item.
+    pub fn set_workers(&self, workers: usize) -> Result<()> {
+        if self.is_cancelled() {
+            return Err(Error::Canceled);
+        }
+
+        if !self.is_started() {
+            return Err(Error::InvalidOperation("Queue is not started".to_string()));
+        }
+
+        if !self
+            .scaler
+            .resize(&self.workers, workers.clamp(THREADS_MIN, THREADS_MAX))
+        {
+            return Err(Error::QueueCompleted);
+        }
+
+        Ok(())
+    }
+
+    pub fn target_workers(&self) -> usize {
+        self.scaler.target()
+    }
+
+    pub fn latency(&self) -> Duration {
+        self.scaler.latency()
+    }
+
+    fn spawn_worker<H: TaskDelegation<InjectorWorker<T>, T>>(&self, handler: &H) {
+        let (id, worker) = self.new_worker();
+        let this = self.clone();
+        let handler = handler.clone();
+
+        if self.options.work_stealing {
+            thread::spawn(move || this.run_stealing_worker(&handler, id, worker));
+            return;
+        }
+
+        let local = Arc::new(Mutex::new(worker));
+        thread::spawn(move || this.run_worker(&handler, id, &local));
+    }
+
+    /// Creates a worker's deque and registers it with its siblings. Returns the worker's
+    /// id and deque.
+    fn new_worker(&self) -> (usize, Worker<T>) {
+        let worker = if self.options.behavior == QueueBehavior::LIFO {
+            Worker::<T>::new_lifo()
+        } else {
+            Worker::<T>::new_fifo()
+        };
+        (self.add_stealer(worker.stealer()), worker)
+    }
+
+    /// Registers a worker's stealer so its siblings can take from its deque, and returns
+    /// the worker's id.
+    fn add_stealer(&self, stealer: Stealer<T>) -> usize {
+        let mut stealers = self.stealers.lock().unwrap();
+        let mut ids = self.stealer_ids.lock().unwrap();
+        let id = self.next_worker_id.fetch_add(1, Ordering::SeqCst);
+        stealers.push(stealer);
+        ids.push(id);
+        id
+    }
+
+    /// Unregisters the stealer of a worker that is leaving the pool.
+    fn remove_stealer(&self, id: usize) {
+        let mut stealers = self.stealers.lock().unwrap();
+        let mut ids = self.stealer_ids.lock().unwrap();
+
+        if let Some(index) = ids.iter().position(|it| *it == id) {
+            ids.remove(index);
+            stealers.remove(index);
+        }
+    }
+
+    fn clear_stealers(&self) {
+        let mut stealers = self.stealers.lock().unwrap();
+        let mut ids = self.stealer_ids.lock().unwrap();
+        stealers.clear();
+        ids.clear();
+    }
 
-                loop {
-                    if this.is_cancelled() || (this.is_empty() && this.is_completed()) {
-                        break;
-                    }
-
-                    if this.is_paused() {
-                        thread::sleep(this.options.pause_timeout);
-                        continue;
-                    }
-
-                    let Some(item) = this.dequeue_wait(&global, &local, &stealers) else {
-                        continue;
-                    };
-                    this.inc_running();
-                    match handler.process(&this, &item) {
-                        Ok(it) => {
-                            if !handler.on_completed(&this, &item, &it) {
-                                this.dec_running();
-                                break;
-                            }
-
-                            if !this.options.threshold.is_zero() {
-                                let time = Instant::now();
-
-                                if time.elapsed() < this.options.threshold {
-                                    let remaining = this.options.threshold - time.elapsed();
-                                    thread::sleep(remaining);
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            if !handler.on_completed(
-                                &this,
-                                &item,
-                                &TaskResult::Error(e.to_string()),
-                            ) {
-                                this.dec_running();
-                                break;
-                            }
-                        }
-                    }
-                    this.dec_running();
+    fn spawn_scaler(&self, scaling: ScalingOptions) {
+        let this = self.clone();
+        thread::spawn(move || {
+            while !this.is_finished() && !this.is_cancelled() {
+                thread::sleep(scaling.interval);
+
+                if this.is_paused() {
+                    continue;
                 }
 
-                if !this.dec_workers() {
-                    return;
+                let workers = this.workers();
+
+                if scaling.should_scale_up(workers, this.len(), this.latency()) {
+                    let _ = this.set_workers(workers + 1);
                 }
+            }
+        });
+    }
+
+    fn run_worker<H: TaskDelegation<InjectorWorker<T>, T>>(
+        &self,
+        handler: &H,
+        id: usize,
+        local: &Arc<Mutex<Worker<T>>>,
+    ) {
+        let global = self.injector.clone();
+        let stealers = self.stealers.clone();
+        let mut idle_since = Instant::now();
+
+        loop {
+            if self.should_stop() {
+                break;
+            }
+
+            if self.scaler.try_retire(
+                &self.workers,
+                self.options.scaling.as_ref(),
+                idle_since.elapsed(),
+            ) {
+                self.release(id, &local.lock().unwrap());
+                return;
+            }
+
+            match self.turn(handler, &global, local, &stealers, true) {
+                Turn::Processed => idle_since = Instant::now(),
+                Turn::Stopped => break,
+                Turn::Paused | Turn::Idle => {}
+            }
+        }
+
+        self.release(id, &local.lock().unwrap());
+        self.leave(handler);
+    }
+
+    fn should_stop(&self) -> bool {
+        self.is_cancelled() || (self.is_empty() && self.is_completed())
+    }
+
+    /// Runs one turn of a worker: takes an item and processes it. With `wait`, it also
+    /// waits out the pause and threshold timeouts; without it, it returns instead.
+    fn turn<H: TaskDelegation<InjectorWorker<T>, T>>(
+        &self,
+        handler: &H,
+        global: &Arc<Injector<T>>,
+        local: &Arc<Mutex<Worker<T>>>,
+        stealers: &Arc<Mutex<Vec<Stealer<T>>>>,
+        wait: bool,
+    ) -> Turn {
+        if self.is_paused() {
+            if wait {
+                self.sleeper.sleep(self.options.pause_timeout);
+            }
+
+            return Turn::Paused;
+        }
+
+        let Some(item) = self.deq(wait, global, local, stealers) else {
+            return Turn::Idle;
+        };
+        self.inc_running();
+        let time = Instant::now();
+        let (result, processed) = match handler.process(self, &item) {
+            Ok(it) => (it, true),
+            Err(e) => (TaskResult::Error(e.to_string()), false),
+        };
+        self.scaler.record_latency(time.elapsed());
+
+        if !handler.on_completed(self, &item, &result) {
+            self.dec_running();
+            return Turn::Stopped;
+        }
+
+        if wait && processed && !self.options.threshold.is_zero() {
+            self.sleeper.sleep(self.options.threshold);
+        }
+
+        self.dec_running();
+        Turn::Processed
+    }
+
+    /// Unregisters a leaving worker and hands the items in its deque back to the global
+    /// queue, since its siblings can no longer steal them.
+    fn release(&self, id: usize, local: &Worker<T>) {
+        self.remove_stealer(id);
 
-                if this.is_cancelled() {
-                    handler.on_cancelled(&this);
-                } else {
-                    handler.on_finished(&this);
+        while let Some(item) = local.pop() {
+            self.injector.push(item);
+        }
+    }
+
+    /// Takes a worker out of the pool. The last one calls `on_cancelled` or
+    /// `on_finished` and finishes the consumer.
+    fn leave<H: TaskDelegation<InjectorWorker<T>, T>>(&self, handler: &H) {
+        if !self.dec_workers() {
+            return;
+        }
+
+        if self.is_cancelled() {
+            handler.on_cancelled(self);
+        } else {
+            handler.on_finished(self);
+        }
+
+        self.finish();
+    }
+
+    /// Runs a worker that owns its deque: it takes items from its own deque first, then
+    /// steals batches from the global queue or a sibling, and parks when there is
+    /// nothing to steal.
+    fn run_stealing_worker<H: TaskDelegation<InjectorWorker<T>, T>>(
+        &self,
+        handler: &H,
+        id: usize,
+        local: Worker<T>,
+    ) {
+        let mut idle_since = Instant::now();
+        let backoff = Backoff::new();
+
+        loop {
+            if self.should_stop() {
+                break;
+            }
+
+            if self.scaler.try_retire(
+                &self.workers,
+                self.options.scaling.as_ref(),
+                idle_since.elapsed(),
+            ) {
+                self.release(id, &local);
+                self.parking.unpark_all();
+                return;
+            }
+
+            if self.is_paused() {
+                self.sleeper.sleep(self.options.pause_timeout);
+                continue;
+            }
+
+            let Some(item) = self.find_item(&local) else {
+                // Spin briefly before parking, since waking a parked worker is costly.
+                if !backoff.is_completed() {
+                    backoff.snooze();
+                    continue;
                 }
 
-                this.finish();
-            });
+                let has_work = || !self.is_empty() || self.is_completed() || self.is_cancelled();
+                self.sleeper.wait_timeout(PEEK_TIMEOUT_DEF, |timeout| {
+                    self.parking.park(timeout, has_work);
+                    has_work().then_some(())
+                });
+                continue;
+            };
+            backoff.reset();
+            self.len.fetch_sub(1, Ordering::SeqCst);
+
+            // Let a sleeping sibling steal the rest of a batch this worker just took.
+            if !local.is_empty() {
+                self.parking.unpark_one();
+            }
+
+            self.inc_running();
+            let time = Instant::now();
+            let (result, processed) = match handler.process(self, &item) {
+                Ok(it) => (it, true),
+                Err(e) => (TaskResult::Error(e.to_string()), false),
+            };
+            self.scaler.record_latency(time.elapsed());
+
+            if !handler.on_completed(self, &item, &result) {
+                self.dec_running();
+                break;
+            }
+
+            if processed && !self.options.threshold.is_zero() {
+                self.sleeper.sleep(self.options.threshold);
+            }
+
+            self.dec_running();
+            idle_since = Instant::now();
         }
 
-        Ok(())
+        self.release(id, &local);
+        self.leave(handler);
+    }
+
+    fn find_item(&self, local: &Worker<T>) -> Option<T> {
+        local.pop().or_else(|| {
+            iter::repeat_with(|| {
+                self.injector.steal_batch_and_pop(local).or_else(|| {
+                    self.stealers
+                        .lock()
+                        .unwrap()
+                        .iter()
+                        .map(|s| s.steal_batch_and_pop(local))
+                        .collect()
+                })
+            })
+            .find(|s| !s.is_retry())
+            .and_then(|s| s.success())
+        })
     }
 
     pub fn enqueue(&self, item: T) -> Result<()> {
@@ -362,14 +663,20 @@ impl<T: StaticTaskItem> InjectorWorker<T> {
 
         self.injector.push(item);
         self.len.fetch_add(1, Ordering::SeqCst);
+        self.parking.unpark_one();
 
         if !self.options.sleep_after_send.is_zero() {
-            thread::sleep(self.options.sleep_after_send);
+            self.sleeper.sleep(self.options.sleep_after_send);
         }
 
         Ok(())
     }
 
+    /// Same as `enqueue`, as the queue is unbounded and never waits for room.
+    pub fn try_enqueue(&self, item: T) -> Result<()> {
+        self.enqueue(item)
+    }
+
     pub fn dequeue(
         &self,
         global: &Arc<Injector<T>>,
@@ -404,7 +711,7 @@ impl<T: StaticTaskItem> InjectorWorker<T> {
             }
 
             if wait_for_item && self.is_paused() {
-                thread::sleep(self.options.pause_timeout);
+                self.sleeper.sleep(self.options.pause_timeout);
                 return None;
             }
 
@@ -434,8 +741,7 @@ impl<T: StaticTaskItem> InjectorWorker<T> {
 
     pub fn clear(&mut self) {
         self.injector = mem::replace(&mut self.injector, Arc::new(Injector::new()));
-        let mut stealers = self.stealers.lock().unwrap();
-        stealers.clear();
+        self.clear_stealers();
         self.len.store(0, Ordering::SeqCst);
     }
 
@@ -449,10 +755,18 @@ impl<T: StaticTaskItem> InjectorWorker<T> {
 
     pub fn complete(&self) {
         self.completed.store(true, Ordering::SeqCst);
+        self.parking.unpark_all();
     }
 
     pub fn cancel(&self) {
         self.cancelled.store(true, Ordering::SeqCst);
+        self.parking.unpark_all();
+    }
+
+    /// Cancels the queue when `token` is cancelled.
+    pub fn cancel_on(&self, token: &CancellationToken) {
+        let this = self.clone();
+        token.on_cancel(move |_| this.cancel());
     }
 
     pub fn pause(&self) {
@@ -461,6 +775,7 @@ impl<T: StaticTaskItem> InjectorWorker<T> {
 
     pub fn resume(&self) {
         self.paused.store(false, Ordering::SeqCst);
+        self.parking.unpark_all();
     }
 
     pub fn wait(&self) -> Result<()> {
@@ -511,12 +826,44 @@ impl<T: StaticTaskItem> InjectorWorker<T> {
     }
 }
 
-impl<T: StaticTaskItem> AwaitableConsumer<T> for InjectorWorker<T> {
-    fn is_cancelled(&self) -> bool {
-        self.is_cancelled()
+impl_consumer!(InjectorWorker, StaticTaskItem);
+
+/// Runs the worker loop that takes from the global queue, with no siblings to steal
+/// from, whether or not work stealing is enabled.
+#[cfg(feature = "test-support")]
+impl<T: StaticTaskItem> test_support::InlineConsumer<T> for InjectorWorker<T> {
+    type Worker = (usize, Arc<Mutex<Worker<T>>>);
+
+    fn start_inline<H: TaskDelegation<Self, T>>(&self, handler: &H) -> Result<Self::Worker> {
+        self.begin(1)?;
+        handler.on_started(self);
+        self.clear_stealers();
+        let (id, worker) = self.new_worker();
+        Ok((id, Arc::new(Mutex::new(worker))))
     }
 
-    fn is_finished(&self) -> bool {
-        self.is_finished()
+    fn step_inline<H: TaskDelegation<Self, T>>(
+        &self,
+        handler: &H,
+        (id, local): &mut Self::Worker,
+    ) -> test_support::Step {
+        if self.is_finished() || self.workers() == 0 {
+            return test_support::Step::stopped(self.is_finished());
+        }
+
+        if !self.should_stop() {
+            let stealers = self.stealers.clone();
+
+            match self.turn(handler, &self.injector, local, &stealers, false) {
+                Turn::Processed => return test_support::Step::Processed,
+                Turn::Paused => return test_support::Step::Paused,
+                Turn::Idle if !self.should_stop() => return test_support::Step::Idle,
+                Turn::Idle | Turn::Stopped => {}
+            }
+        }
+
+        self.release(*id, &local.lock().unwrap());
+        self.leave(handler);
+        test_support::Step::stopped(self.is_finished())
     }
 }
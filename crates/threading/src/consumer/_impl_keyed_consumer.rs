@@ -0,0 +1,519 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::{
+        Arc, Condvar, Mutex, MutexGuard,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
+    thread,
+    time::Instant,
+};
+use tokio::{sync::Notify, time::Duration};
+
+use super::Sleeper;
+use crate::{constants::*, *};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[must_use]
+pub struct KeyedConsumerOptions {
+    /// The most items that may be pending across all keys. Zero means unbounded.
+    pub capacity: usize,
+    pub threads: usize,
+    /// How long a pending item absorbs equal items enqueued after it. `None` disables
+    /// coalescing.
+    pub dedup_window: Option<Duration>,
+    pub peek_timeout: Duration,
+    pub pause_timeout: Duration,
+}
+
+impl Default for KeyedConsumerOptions {
+    fn default() -> Self {
+        KeyedConsumerOptions {
+            capacity: CAPACITY_DEF,
+            threads: THREADS_DEF.clamp(THREADS_MIN, THREADS_MAX),
+            dedup_window: None,
+            peek_timeout: PEEK_TIMEOUT_DEF.clamp(PEEK_TIMEOUT_MIN, PEEK_TIMEOUT_MAX),
+            pause_timeout: PAUSE_TIMEOUT_DEF.clamp(PAUSE_TIMEOUT_MIN, PAUSE_TIMEOUT_MAX),
+        }
+    }
+}
+
+impl KeyedConsumerOptions {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn with_capacity(&self, capacity: usize) -> Self {
+        KeyedConsumerOptions {
+            capacity,
+            ..self.clone()
+        }
+    }
+
+    pub fn with_threads(&self, threads: usize) -> Self {
+        KeyedConsumerOptions {
+            threads: threads.clamp(THREADS_MIN, THREADS_MAX),
+            ..self.clone()
+        }
+    }
+
+    /// Drops an enqueued item if an equal item with the same key has been pending for
+    /// no longer than `dedup_window`.
+    pub fn with_dedup_window(&self, dedup_window: Duration) -> Self {
+        KeyedConsumerOptions {
+            dedup_window: Some(dedup_window),
+            ..self.clone()
+        }
+    }
+}
+
+#[derive(Debug)]
+struct KeyedState<T: KeyedTaskItem> {
+    queues: HashMap<T::Key, VecDeque<(T, Instant)>>,
+    /// Keys with pending items that no worker is processing, in the order they became
+    /// ready.
+    ready: VecDeque<T::Key>,
+    active: HashSet<T::Key>,
+    len: usize,
+}
+
+impl<T: KeyedTaskItem> Default for KeyedState<T> {
+    fn default() -> Self {
+        KeyedState {
+            queues: HashMap::new(),
+            ready: VecDeque::new(),
+            active: HashSet::new(),
+            len: 0,
+        }
+    }
+}
+
+/// A consumer that serializes items by key.
+///
+/// Items with the same key are processed one at a time in the order they were enqueued,
+/// while items with different keys run in parallel on the worker threads. With a dedup
+/// window, items equal to one that is still pending are coalesced into it.
+#[derive(Clone, Debug)]
+#[must_use]
+pub struct KeyedConsumer<T: KeyedTaskItem> {
+    pub options: KeyedConsumerOptions,
+    started: Arc<Mutex<bool>>,
+    finished: Arc<AtomicBool>,
+    finished_cond: Arc<ManualResetCond>,
+    finished_noti: Arc<Notify>,
+    completed: Arc<AtomicBool>,
+    paused: Arc<AtomicBool>,
+    cancelled: Arc<AtomicBool>,
+    consumers: Arc<AtomicUsize>,
+    running: Arc<AtomicUsize>,
+    coalesced: Arc<AtomicUsize>,
+    state: Arc<Mutex<KeyedState<T>>>,
+    items_cond: Arc<Condvar>,
+    space_cond: Arc<Condvar>,
+    sleeper: Sleeper,
+}
+
+impl<T: KeyedTaskItem> Default for KeyedConsumer<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: KeyedTaskItem> KeyedConsumer<T> {
+    pub fn new() -> Self {
+        Self::with_options(Default::default())
+    }
+
+    pub fn with_options(options: KeyedConsumerOptions) -> Self {
+        KeyedConsumer {
+            options,
+            started: Arc::new(Mutex::new(false)),
+            finished: Arc::new(AtomicBool::new(false)),
+            finished_cond: Arc::new(ManualResetCond::new_unset()),
+            finished_noti: Arc::new(Notify::new()),
+            completed: Arc::new(AtomicBool::new(false)),
+            paused: Arc::new(AtomicBool::new(false)),
+            cancelled: Arc::new(AtomicBool::new(false)),
+            consumers: Arc::new(AtomicUsize::new(0)),
+            running: Arc::new(AtomicUsize::new(0)),
+            coalesced: Arc::new(AtomicUsize::new(0)),
+            state: Arc::new(Mutex::new(KeyedState::default())),
+            items_cond: Arc::new(Condvar::new()),
+            space_cond: Arc::new(Condvar::new()),
+            sleeper: Sleeper::default(),
+        }
+    }
+
+    /// Runs the peek and pause timeouts and the dedup window on `clock` instead of in
+    /// real time. Call it before `start`.
+    #[cfg(feature = "test-support")]
+    pub fn with_clock(mut self, clock: &test_support::VirtualClock) -> Self {
+        self.sleeper = Sleeper::Virtual(clock.clone());
+        self
+    }
+
+    fn state(&self) -> MutexGuard<'_, KeyedState<T>> {
+        self.state.lock().unwrap()
+    }
+
+    pub fn is_started(&self) -> bool {
+        *self.started.lock().unwrap()
+    }
+
+    fn set_started(&self, value: bool) -> bool {
+        let mut started = self.started.lock().unwrap();
+
+        if *started && value {
+            return false;
+        }
+
+        *started = true;
+        true
+    }
+
+    pub fn is_completed(&self) -> bool {
+        self.completed.load(Ordering::SeqCst)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished.load(Ordering::SeqCst)
+    }
+
+    pub fn is_busy(&self) -> bool {
+        self.len() + self.running.load(Ordering::SeqCst) > 0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Gets the number of pending items across all keys.
+    pub fn len(&self) -> usize {
+        self.state().len
+    }
+
+    pub fn capacity(&self) -> Option<usize> {
+        (self.options.capacity > 0).then_some(self.options.capacity)
+    }
+
+    /// Gets the number of keys with pending or running items.
+    pub fn keys(&self) -> usize {
+        let state = self.state();
+        state.queues.len()
+    }
+
+    /// Gets the number of items dropped because an equal item was already pending.
+    pub fn coalesced(&self) -> usize {
+        self.coalesced.load(Ordering::SeqCst)
+    }
+
+    pub fn consumers(&self) -> usize {
+        self.consumers.load(Ordering::SeqCst)
+    }
+
+    fn set_consumers(&self, value: usize) {
+        self.consumers.store(value, Ordering::SeqCst);
+    }
+
+    fn dec_consumers(&self) -> bool {
+        self.consumers.fetch_sub(1, Ordering::SeqCst);
+        self.consumers() == 0 && (self.is_completed() || self.is_cancelled())
+    }
+
+    /// Drops every queued item.
+    fn clear_queue(&self) {
+        *self.state() = KeyedState::default();
+        self.space_cond.notify_all();
+    }
+
+    /// Marks the consumer finished and releases its waiters.
+    fn set_finished(&self) {
+        self.completed.store(true, Ordering::SeqCst);
+        self.finished.store(true, Ordering::SeqCst);
+        self.set_started(false);
+        let _ = self.finished_cond.set();
+        self.finished_noti.notify_waiters();
+        thread::sleep(Duration::ZERO);
+    }
+
+    pub fn running(&self) -> usize {
+        self.running.load(Ordering::SeqCst)
+    }
+
+    pub fn start<H: TaskDelegation<KeyedConsumer<T>, T>>(&self, handler: &H) -> Result<()> {
+        if self.is_cancelled() {
+            return Err(Error::Canceled);
+        }
+
+        if self.is_completed() && self.is_empty() {
+            return Err(Error::QueueCompleted);
+        }
+
+        if !self.set_started(true) {
+            return Err(Error::QueueStarted);
+        }
+
+        self.set_consumers(self.options.threads);
+        handler.on_started(self);
+
+        for _ in 0..self.options.threads {
+            let this = self.clone();
+            let handler = handler.clone();
+            thread::spawn(move || this.run_worker(&handler));
+        }
+
+        Ok(())
+    }
+
+    fn run_worker<H: TaskDelegation<KeyedConsumer<T>, T>>(&self, handler: &H) {
+        loop {
+            if self.is_cancelled() {
+                break;
+            }
+
+            if self.is_paused() {
+                self.sleeper.sleep(self.options.pause_timeout);
+                continue;
+            }
+
+            let Some((key, item)) = self.take() else {
+                if self.is_completed() && self.is_empty() {
+                    break;
+                }
+
+                continue;
+            };
+
+            self.running.fetch_add(1, Ordering::SeqCst);
+            let result = handler
+                .process(self, &item)
+                .unwrap_or_else(|e| TaskResult::Error(e.to_string()));
+            let proceed = handler.on_completed(self, &item, &result);
+            self.release(key);
+            self.running.fetch_sub(1, Ordering::SeqCst);
+
+            if !proceed {
+                break;
+            }
+        }
+
+        if !self.dec_consumers() {
+            return;
+        }
+
+        if self.is_cancelled() {
+            handler.on_cancelled(self);
+        } else {
+            handler.on_finished(self);
+        }
+
+        self.finish();
+    }
+
+    /// Takes the next item of a key no other worker holds, waiting up to the peek timeout
+    /// for one. Items still queued behind a busy key are waited for after completion too,
+    /// so idle workers do not spin until that key is released.
+    fn take(&self) -> Option<(T::Key, T)> {
+        {
+            let state = self.state();
+
+            if state.ready.is_empty() && self.is_completed() && state.len == 0 {
+                return None;
+            }
+        }
+
+        self.sleeper
+            .wait_timeout(self.options.peek_timeout, |timeout| {
+                self.take_within(timeout)
+            })
+    }
+
+    /// Takes the next item of a key no other worker holds, waiting up to `timeout` for
+    /// one.
+    fn take_within(&self, timeout: Duration) -> Option<(T::Key, T)> {
+        let mut state = self.state();
+
+        if state.ready.is_empty() {
+            state = self.items_cond.wait_timeout(state, timeout).unwrap().0;
+        }
+
+        let key = state.ready.pop_front()?;
+        let (item, _) = state.queues.get_mut(&key)?.pop_front()?;
+        state.active.insert(key.clone());
+        state.len -= 1;
+        self.space_cond.notify_one();
+        Some((key, item))
+    }
+
+    /// Hands the key back so its next item, if any, can be taken.
+    fn release(&self, key: T::Key) {
+        let mut state = self.state();
+        state.active.remove(&key);
+
+        if state.queues.get(&key).is_some_and(|it| !it.is_empty()) {
+            state.ready.push_back(key);
+            self.items_cond.notify_one();
+        } else {
+            state.queues.remove(&key);
+
+            // Wake the idle workers so they can exit once the last item is done.
+            if state.len == 0 && self.is_completed() {
+                self.items_cond.notify_all();
+            }
+        }
+    }
+
+    pub fn enqueue(&self, item: T) -> Result<()> {
+        self.enq(item, true)
+    }
+
+    /// Enqueues an item without waiting for room, failing with `Error::Exceeded` if the
+    /// queue is full.
+    pub fn try_enqueue(&self, item: T) -> Result<()> {
+        self.enq(item, false)
+    }
+
+    fn enq(&self, item: T, wait_for_room: bool) -> Result<()> {
+        if self.is_cancelled() {
+            return Err(Error::Canceled);
+        }
+
+        if self.is_completed() {
+            return Err(Error::QueueCompleted);
+        }
+
+        let key = item.key();
+        let mut state = self.state();
+
+        if let Some(window) = self.options.dedup_window {
+            let now = self.sleeper.now();
+            let duplicate = state.queues.get(&key).is_some_and(|queue| {
+                queue.iter().any(|(pending, at)| {
+                    *pending == item && now.saturating_duration_since(*at) <= window
+                })
+            });
+
+            if duplicate {
+                self.coalesced.fetch_add(1, Ordering::SeqCst);
+                return Ok(());
+            }
+        }
+
+        // Wait in slices so a producer blocked on a full queue notices cancellation.
+        while self.options.capacity > 0 && state.len >= self.options.capacity {
+            if !wait_for_room {
+                return Err(Error::Exceeded("Queue is full".to_string()));
+            }
+
+            if self.is_cancelled() {
+                return Err(Error::Canceled);
+            }
+
+            state = self
+                .space_cond
+                .wait_timeout(state, self.options.peek_timeout)
+                .unwrap()
+                .0;
+        }
+
+        let is_idle = !state.active.contains(&key);
+        let queue = state.queues.entry(key.clone()).or_default();
+        let was_empty = queue.is_empty();
+        queue.push_back((item, self.sleeper.now()));
+        state.len += 1;
+
+        if was_empty && is_idle {
+            state.ready.push_back(key);
+            self.items_cond.notify_one();
+        }
+
+        Ok(())
+    }
+
+    pub fn stop(&self, enforce: bool) {
+        if enforce {
+            self.cancel();
+        } else {
+            self.complete();
+        }
+    }
+
+    pub fn complete(&self) {
+        self.completed.store(true, Ordering::SeqCst);
+        self.items_cond.notify_all();
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+        self.items_cond.notify_all();
+        self.space_cond.notify_all();
+    }
+
+    /// Cancels the queue when `token` is cancelled.
+    pub fn cancel_on(&self, token: &CancellationToken) {
+        let this = self.clone();
+        token.on_cancel(move |_| this.cancel());
+    }
+
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::SeqCst);
+    }
+
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::SeqCst);
+    }
+
+    pub fn wait(&self) -> Result<()> {
+        wait(self, &self.finished_cond)
+    }
+
+    pub async fn wait_async(&self) -> Result<()> {
+        wait_async(self, &self.finished_noti).await
+    }
+
+    pub fn wait_until(&self, cond: impl Fn(&KeyedConsumer<T>) -> bool) -> Result<()> {
+        wait_until(self, &self.finished_cond, cond)
+    }
+
+    pub async fn wait_until_async(
+        &self,
+        cond: impl Fn(&KeyedConsumer<T>) -> Pin<Box<dyn Future<Output = bool> + Send>>,
+    ) -> Result<()> {
+        wait_until_async(self, &self.finished_noti, cond).await
+    }
+
+    pub fn wait_for(&self, timeout: Duration) -> Result<()> {
+        wait_for(self, timeout, &self.finished_cond)
+    }
+
+    pub async fn wait_for_async(&self, timeout: Duration) -> Result<()> {
+        wait_for_async(self, timeout, &self.finished_noti).await
+    }
+
+    pub fn wait_for_until(
+        &self,
+        timeout: Duration,
+        cond: impl Fn(&KeyedConsumer<T>) -> bool,
+    ) -> Result<()> {
+        wait_for_until(self, timeout, &self.finished_cond, cond)
+    }
+
+    pub async fn wait_for_until_async<
+        F: Fn(&KeyedConsumer<T>) -> Pin<Box<dyn Future<Output = bool> + Send>>,
+    >(
+        &self,
+        timeout: Duration,
+        cond: F,
+    ) -> Result<()> {
+        wait_for_until_async(self, timeout, &self.finished_noti, cond).await
+    }
+}
+
+impl_consumer!(KeyedConsumer, KeyedTaskItem);
@@ -1,5 +1,6 @@
 use crossbeam::channel;
 use std::{
+    mem,
     sync::{
         Arc, Mutex,
         atomic::{AtomicBool, AtomicUsize, Ordering},
@@ -11,6 +12,7 @@ use tokio::{
     time::{Duration, Instant},
 };
 
+use super::{QueueStore, ScalingOptions, Sleeper, Turn, WorkerScaler};
 use crate::{constants::*, *};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -22,6 +24,7 @@ pub struct ProducerConsumerOptions {
     pub sleep_after_send: Duration,
     pub peek_timeout: Duration,
     pub pause_timeout: Duration,
+    pub scaling: Option<ScalingOptions>,
 }
 
 impl Default for ProducerConsumerOptions {
@@ -33,6 +36,7 @@ impl Default for ProducerConsumerOptions {
             sleep_after_send: SLEEP_AFTER_SEND_DEF,
             peek_timeout: PEEK_TIMEOUT_DEF.clamp(PEEK_TIMEOUT_MIN, PEEK_TIMEOUT_MAX),
             pause_timeout: PAUSE_TIMEOUT_DEF.clamp(PAUSE_TIMEOUT_MIN, PAUSE_TIMEOUT_MAX),
+            scaling: None,
         }
     }
 }
@@ -69,6 +73,13 @@ impl ProducerConsumerOptions {
             ..self.clone()
         }
     }
+
+    pub fn with_scaling(&self, scaling: ScalingOptions) -> Self {
+        ProducerConsumerOptions {
+            scaling: Some(scaling),
+            ..self.clone()
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -84,18 +95,27 @@ pub struct ProducerConsumer<T: StaticTaskItem> {
     cancelled: Arc<AtomicBool>,
     consumers: Arc<AtomicUsize>,
     running: Arc<AtomicUsize>,
-    sender: channel::Sender<T>,
-    receiver: channel::Receiver<T>,
+    scaler: Arc<WorkerScaler>,
+    sleeper: Sleeper,
+    sender: channel::Sender<(Option<u64>, T)>,
+    receiver: channel::Receiver<(Option<u64>, T)>,
+    store: Option<Arc<dyn QueueStore<T>>>,
+    replay: Arc<Mutex<Vec<(u64, T)>>>,
+    /// Replayed items not yet handed to the channel, counted by `len`.
+    replaying: Arc<AtomicUsize>,
 }
 
 impl<T: StaticTaskItem> ProducerConsumer<T> {
     pub fn new() -> Self {
         let options: ProducerConsumerOptions = Default::default();
-        let (sender, receiver) = channel::bounded::<T>(options.capacity);
+        let (sender, receiver) = channel::bounded(options.capacity);
         ProducerConsumer {
             options,
             sender,
             receiver,
+            store: None,
+            replay: Arc::new(Mutex::new(Vec::new())),
+            replaying: Arc::new(AtomicUsize::new(0)),
             started: Arc::new(Mutex::new(false)),
             finished: Arc::new(AtomicBool::new(false)),
             finished_cond: Arc::new(ManualResetCond::new_unset()),
@@ -105,15 +125,20 @@ impl<T: StaticTaskItem> ProducerConsumer<T> {
             cancelled: Arc::new(AtomicBool::new(false)),
             consumers: Arc::new(AtomicUsize::new(0)),
             running: Arc::new(AtomicUsize::new(0)),
+            scaler: Arc::new(WorkerScaler::new()),
+            sleeper: Sleeper::default(),
         }
     }
 
     pub fn with_options(options: ProducerConsumerOptions) -> Self {
-        let (sender, receiver) = channel::bounded::<T>(options.capacity);
+        let (sender, receiver) = channel::bounded(options.capacity);
         ProducerConsumer {
             options,
             sender,
             receiver,
+            store: None,
+            replay: Arc::new(Mutex::new(Vec::new())),
+            replaying: Arc::new(AtomicUsize::new(0)),
             started: Arc::new(Mutex::new(false)),
             finished: Arc::new(AtomicBool::new(false)),
             finished_cond: Arc::new(ManualResetCond::new_unset()),
@@ -123,9 +148,33 @@ impl<T: StaticTaskItem> ProducerConsumer<T> {
             cancelled: Arc::new(AtomicBool::new(false)),
             consumers: Arc::new(AtomicUsize::new(0)),
             running: Arc::new(AtomicUsize::new(0)),
+            scaler: Arc::new(WorkerScaler::new()),
+            sleeper: Sleeper::default(),
         }
     }
 
+    /// Creates a consumer whose items are persisted in `store`. Items the store still
+    /// holds from a previous run are replayed when the consumer starts.
+    pub fn with_store(
+        options: ProducerConsumerOptions,
+        store: impl QueueStore<T> + 'static,
+    ) -> Result<Self> {
+        let replay = store.pending()?;
+        let mut this = Self::with_options(options);
+        this.store = Some(Arc::new(store));
+        this.replaying = Arc::new(AtomicUsize::new(replay.len()));
+        this.replay = Arc::new(Mutex::new(replay));
+        Ok(this)
+    }
+
+    /// Runs the threshold, peek, pause, and sleep-after-send timeouts on `clock` instead
+    /// of in real time. Call it before `start`.
+    #[cfg(feature = "test-support")]
+    pub fn with_clock(mut self, clock: &test_support::VirtualClock) -> Self {
+        self.sleeper = Sleeper::Virtual(clock.clone());
+        self
+    }
+
     pub fn is_started(&self) -> bool {
         *self.started.lock().unwrap()
     }
@@ -166,7 +215,11 @@ impl<T: StaticTaskItem> ProducerConsumer<T> {
     }
 
     pub fn len(&self) -> usize {
-        self.sender.len() + self.receiver.len()
+        self.sender.len() + self.receiver.len() + self.replaying.load(Ordering::SeqCst)
+    }
+
+    pub fn capacity(&self) -> Option<usize> {
+        Some(self.options.capacity)
     }
 
     pub fn consumers(&self) -> usize {
@@ -182,14 +235,17 @@ impl<T: StaticTaskItem> ProducerConsumer<T> {
         self.consumers() == 0 && (self.is_completed() || self.is_cancelled())
     }
 
-    fn finish(&self) {
-        if !self.is_completed() && !self.is_cancelled() {
-            return;
-        }
+    /// Drops every queued item.
+    fn clear_queue(&self) {
+        while self.receiver.try_recv().is_ok() {}
+    }
 
+    /// Marks the consumer finished and releases its waiters.
+    fn set_finished(&self) {
         self.completed.store(true, Ordering::SeqCst);
         self.finished.store(true, Ordering::SeqCst);
         self.set_started(false);
+        self.scaler.clear();
         if let Err(_) = self.finished_cond.set() {
             // Mutex was poisoned - this is a serious error but we'll continue cleanup
             // The error information is preserved in the Result type for caller handling
@@ -211,6 +267,28 @@ impl<T: StaticTaskItem> ProducerConsumer<T> {
     }
 
     pub fn start<H: TaskDelegation<ProducerConsumer<T>, T>>(&self, handler: &H) -> Result<()> {
+        self.begin(self.options.threads)?;
+        let this = self.clone();
+        let spawner = handler.clone();
+        self.scaler.install(
+            self.options.threads,
+            Arc::new(move || this.spawn_worker(&spawner)),
+        );
+        handler.on_started(self);
+
+        for _ in 0..self.options.threads {
+            self.spawn_worker(handler);
+        }
+
+        if let Some(scaling) = &self.options.scaling {
+            self.spawn_scaler(scaling.clone());
+        }
+
+        self.replay()
+    }
+
+    /// Marks the consumer started with `workers` workers.
+    fn begin(&self, workers: usize) -> Result<()> {
         if self.is_cancelled() {
             return Err(Error::Canceled);
         }
@@ -223,125 +301,183 @@ impl<T: StaticTaskItem> ProducerConsumer<T> {
             return Err(Error::QueueStarted);
         }
 
-        self.set_consumers(self.options.threads);
-        handler.on_started(self);
+        self.set_consumers(workers);
+        Ok(())
+    }
 
-        for _ in 0..self.options.threads {
-            let this = self.clone();
-            let handler = handler.clone();
-            thread::spawn(move || {
-                if this.options.threshold.is_zero() {
-                    loop {
-                        if this.is_cancelled() || (!this.is_busy() && this.is_completed()) {
-                            break;
-                        }
-
-                        if this.is_paused() {
-                            thread::sleep(this.options.pause_timeout);
-                            continue;
-                        }
-
-                        let Ok(item) = this.receiver.recv_timeout(this.options.peek_timeout) else {
-                            continue;
-                        };
-                        this.inc_running();
-                        match handler.process(&this, &item) {
-                            Ok(it) => {
-                                if !handler.on_completed(&this, &item, &it) {
-                                    this.dec_running();
-                                    break;
-                                }
-                            }
-                            Err(e) => {
-                                if !handler.on_completed(
-                                    &this,
-                                    &item,
-                                    &TaskResult::Error(e.to_string()),
-                                ) {
-                                    this.dec_running();
-                                    break;
-                                }
-                            }
-                        }
-                        this.dec_running();
-                    }
+    /// Sends the items the store still had pending when the consumer was created.
+    fn replay(&self) -> Result<()> {
+        let replay = mem::take(&mut *self.replay.lock().unwrap());
 
-                    if !this.dec_consumers() {
-                        return;
-                    }
+        for (id, item) in replay {
+            if let Err(e) = self.send((Some(id), item)) {
+                // The rest stays in the store and is replayed on the next run.
+                self.replaying.store(0, Ordering::SeqCst);
+                return Err(e);
+            }
 
-                    if this.is_cancelled() {
-                        handler.on_cancelled(&this);
-                    } else {
-                        handler.on_finished(&this);
-                    }
+            self.replaying.fetch_sub(1, Ordering::SeqCst);
+        }
 
-                    this.finish();
-                    return;
-                }
+        Ok(())
+    }
 
-                loop {
-                    if this.is_cancelled() || (!this.is_busy() && this.is_completed()) {
-                        break;
-                    }
+    /// Changes the number of worker threads while the consumer is running. Extra workers
+    /// are spawned immediately; surplus workers retire after their current item.
+    pub fn set_workers(&self, workers: usize) -> Result<()> {
+        if self.is_cancelled() {
+            return Err(Error::Canceled);
+        }
 
-                    if this.is_paused() {
-                        thread::sleep(this.options.pause_timeout);
-                        continue;
-                    }
+        if !self.is_started() {
+            return Err(Error::InvalidOperation("Queue is not started".to_string()));
+        }
 
-                    let Ok(item) = this.receiver.recv_timeout(this.options.peek_timeout) else {
-                        continue;
-                    };
-                    this.inc_running();
-                    match handler.process(&this, &item) {
-                        Ok(it) => {
-                            if !handler.on_completed(&this, &item, &it) {
-                                this.dec_running();
-                                break;
-                            }
-
-                            if !this.options.threshold.is_zero() {
-                                let time = Instant::now();
-
-                                if time.elapsed() < this.options.threshold {
-                                    let remaining = this.options.threshold - time.elapsed();
-                                    thread::sleep(remaining);
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            if !handler.on_completed(
-                                &this,
-                                &item,
-                                &TaskResult::Error(e.to_string()),
-                            ) {
-                                this.dec_running();
-                                break;
-                            }
-                        }
-                    }
-                    this.dec_running();
-                }
+        if !self
+            .scaler
+            .resize(&self.consumers, workers.clamp(THREADS_MIN, THREADS_MAX))
+        {
+            return Err(Error::QueueCompleted);
+        }
+
+        Ok(())
+    }
+
+    pub fn target_workers(&self) -> usize {
+        self.scaler.target()
+    }
 
-                if !this.dec_consumers() {
-                    return;
+    pub fn latency(&self) -> Duration {
+        self.scaler.latency()
+    }
+
+    fn spawn_worker<H: TaskDelegation<ProducerConsumer<T>, T>>(&self, handler: &H) {
+        let this = self.clone();
+        let handler = handler.clone();
+        thread::spawn(move || this.run_worker(&handler));
+    }
+
+    fn spawn_scaler(&self, scaling: ScalingOptions) {
+        let this = self.clone();
+        thread::spawn(move || {
+            while !this.is_finished() && !this.is_cancelled() {
+                thread::sleep(scaling.interval);
+
+                if this.is_paused() {
+                    continue;
                 }
 
-                if this.is_cancelled() {
-                    handler.on_cancelled(&this);
-                } else {
-                    handler.on_finished(&this);
+                let workers = this.consumers();
+
+                if scaling.should_scale_up(workers, this.len(), this.latency()) {
+                    let _ = this.set_workers(workers + 1);
                 }
+            }
+        });
+    }
+
+    fn run_worker<H: TaskDelegation<ProducerConsumer<T>, T>>(&self, handler: &H) {
+        let mut idle_since = Instant::now();
+
+        loop {
+            if self.should_stop() {
+                break;
+            }
+
+            if self.scaler.try_retire(
+                &self.consumers,
+                self.options.scaling.as_ref(),
+                idle_since.elapsed(),
+            ) {
+                return;
+            }
+
+            match self.turn(handler, true) {
+                Turn::Processed => idle_since = Instant::now(),
+                Turn::Stopped => break,
+                Turn::Paused | Turn::Idle => {}
+            }
+        }
+
+        self.leave(handler);
+    }
 
-                this.finish();
-            });
+    fn should_stop(&self) -> bool {
+        self.is_cancelled() || (!self.is_busy() && self.is_completed())
+    }
+
+    /// Runs one turn of a worker: takes an item and processes it. With `wait`, it also
+    /// waits out the pause, peek, and threshold timeouts; without it, it returns instead.
+    fn turn<H: TaskDelegation<ProducerConsumer<T>, T>>(&self, handler: &H, wait: bool) -> Turn {
+        if self.is_paused() {
+            if wait {
+                self.sleeper.sleep(self.options.pause_timeout);
+            }
+
+            return Turn::Paused;
         }
 
-        Ok(())
+        let message = if wait {
+            self.sleeper
+                .wait_timeout(self.options.peek_timeout, |timeout| {
+                    self.receiver.recv_timeout(timeout).ok()
+                })
+        } else {
+            self.receiver.try_recv().ok()
+        };
+        let Some((id, item)) = message else {
+            return Turn::Idle;
+        };
+        self.inc_running();
+        let time = Instant::now();
+        let (result, processed) = match handler.process(self, &item) {
+            Ok(it) => (it, true),
+            Err(e) => (TaskResult::Error(e.to_string()), false),
+        };
+        self.scaler.record_latency(time.elapsed());
+
+        if !handler.on_completed(self, &item, &result) {
+            self.dec_running();
+            return Turn::Stopped;
+        }
+
+        self.ack(id);
+
+        if wait && processed && !self.options.threshold.is_zero() {
+            self.sleeper.sleep(self.options.threshold);
+        }
+
+        self.dec_running();
+        Turn::Processed
+    }
+
+    /// Takes a worker out of the pool. The last one calls `on_cancelled` or
+    /// `on_finished` and finishes the consumer.
+    fn leave<H: TaskDelegation<ProducerConsumer<T>, T>>(&self, handler: &H) {
+        if !self.dec_consumers() {
+            return;
+        }
+
+        if self.is_cancelled() {
+            handler.on_cancelled(self);
+        } else {
+            handler.on_finished(self);
+        }
+
+        self.finish();
     }
 
     pub fn enqueue(&self, item: T) -> Result<()> {
+        self.enq(item, true)
+    }
+
+    /// Enqueues an item without waiting for room, failing with `Error::Exceeded` if the
+    /// queue is full.
+    pub fn try_enqueue(&self, item: T) -> Result<()> {
+        self.enq(item, false)
+    }
+
+    fn enq(&self, item: T, wait_for_room: bool) -> Result<()> {
         if self.is_cancelled() {
             return Err(Error::Canceled);
         }
@@ -350,15 +486,69 @@ impl<T: StaticTaskItem> ProducerConsumer<T> {
             return Err(Error::QueueCompleted);
         }
 
-        self.sender.send(item).map_err(Error::from_std_error)?;
+        let id = match &self.store {
+            Some(store) => Some(store.append(&item)?),
+            None => None,
+        };
+
+        if wait_for_room {
+            self.send((id, item))?;
+        } else {
+            self.try_send((id, item))?;
+        }
 
         if !self.options.sleep_after_send.is_zero() {
-            thread::sleep(self.options.sleep_after_send);
+            self.sleeper.sleep(self.options.sleep_after_send);
         }
 
         Ok(())
     }
 
+    /// Sends a message, retrying in slices so a producer blocked on a full queue notices
+    /// cancellation or that no worker is left to make room.
+    fn send(&self, mut message: (Option<u64>, T)) -> Result<()> {
+        loop {
+            match self.sender.send_timeout(message, self.options.peek_timeout) {
+                Ok(()) => return Ok(()),
+                Err(channel::SendTimeoutError::Timeout(it)) => {
+                    if self.is_cancelled() {
+                        return Err(Error::Canceled);
+                    }
+
+                    if self.is_started() && self.consumers() == 0 {
+                        return Err(Error::InvalidOperation(
+                            "Queue has no workers left".to_string(),
+                        ));
+                    }
+
+                    message = it;
+                }
+                Err(e) => return Err(Error::from_std_error(e)),
+            }
+        }
+    }
+
+    /// Sends a message only if the queue has room, dropping it from the store otherwise.
+    fn try_send(&self, message: (Option<u64>, T)) -> Result<()> {
+        let id = message.0;
+
+        match self.sender.try_send(message) {
+            Ok(()) => Ok(()),
+            Err(channel::TrySendError::Full(_)) => {
+                self.ack(id);
+                Err(Error::Exceeded("Queue is full".to_string()))
+            }
+            Err(e) => Err(Error::from_std_error(e)),
+        }
+    }
+
+    fn ack(&self, id: Option<u64>) {
+        if let (Some(store), Some(id)) = (&self.store, id) {
+            // A failed ack only means the item is delivered again on the next run.
+            let _ = store.ack(id);
+        }
+    }
+
     pub fn stop(&self, enforce: bool) {
         if enforce {
             self.cancel();
@@ -375,6 +565,12 @@ impl<T: StaticTaskItem> ProducerConsumer<T> {
         self.cancelled.store(true, Ordering::SeqCst);
     }
 
+    /// Cancels the queue when `token` is cancelled.
+    pub fn cancel_on(&self, token: &CancellationToken) {
+        let this = self.clone();
+        token.on_cancel(move |_| this.cancel());
+    }
+
     pub fn pause(&self) {
         self.paused.store(true, Ordering::SeqCst);
     }
@@ -429,12 +625,37 @@ impl<T: StaticTaskItem> ProducerConsumer<T> {
     }
 }
 
-impl<T: StaticTaskItem> AwaitableConsumer<T> for ProducerConsumer<T> {
-    fn is_cancelled(&self) -> bool {
-        ProducerConsumer::is_cancelled(self)
+impl_consumer!(ProducerConsumer, StaticTaskItem);
+
+#[cfg(feature = "test-support")]
+impl<T: StaticTaskItem> test_support::InlineConsumer<T> for ProducerConsumer<T> {
+    type Worker = ();
+
+    fn start_inline<H: TaskDelegation<Self, T>>(&self, handler: &H) -> Result<()> {
+        self.begin(1)?;
+        handler.on_started(self);
+        self.replay()
     }
 
-    fn is_finished(&self) -> bool {
-        ProducerConsumer::is_finished(self)
+    fn step_inline<H: TaskDelegation<Self, T>>(
+        &self,
+        handler: &H,
+        _worker: &mut (),
+    ) -> test_support::Step {
+        if self.is_finished() || self.consumers() == 0 {
+            return test_support::Step::stopped(self.is_finished());
+        }
+
+        if !self.should_stop() {
+            match self.turn(handler, false) {
+                Turn::Processed => return test_support::Step::Processed,
+                Turn::Paused => return test_support::Step::Paused,
+                Turn::Idle if !self.should_stop() => return test_support::Step::Idle,
+                Turn::Idle | Turn::Stopped => {}
+            }
+        }
+
+        self.leave(handler);
+        test_support::Step::stopped(self.is_finished())
     }
 }
@@ -0,0 +1,252 @@
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::{
+    collections::BTreeMap,
+    fmt,
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use crate::{Error, Result};
+
+/// Durable storage for queued items.
+///
+/// Items are appended before they are handed to a worker and acknowledged once the
+/// handler's `on_completed` returns `true`. Anything appended but not acknowledged is
+/// returned by `pending` and replayed when the queue starts again, so delivery is
+/// at-least-once.
+pub trait QueueStore<T>: Send + Sync + fmt::Debug {
+    /// Persists an item and returns the id used to acknowledge it.
+    fn append(&self, item: &T) -> Result<u64>;
+    /// Marks an item as processed so it is not replayed.
+    fn ack(&self, id: u64) -> Result<()>;
+    /// Returns the unacknowledged items in the order they were appended.
+    fn pending(&self) -> Result<Vec<(u64, T)>>;
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+enum LogEntry {
+    Enqueue { id: u64, item: serde_json::Value },
+    Ack { id: u64 },
+}
+
+#[derive(Debug)]
+struct LogState {
+    file: File,
+    next_id: u64,
+    pending: BTreeMap<u64, serde_json::Value>,
+}
+
+/// An append-only, line-delimited JSON log.
+///
+/// Every `append` and `ack` writes one line; with `sync` enabled (the default) the line
+/// is flushed to disk before returning. A line torn by a crash is ignored on open, but an
+/// unreadable line anywhere else fails it. The log is compacted on open and truncated
+/// whenever nothing is pending.
+pub struct FileQueueStore<T> {
+    path: PathBuf,
+    sync: bool,
+    state: Mutex<LogState>,
+    _item: PhantomData<fn() -> T>,
+}
+
+impl<T> fmt::Debug for FileQueueStore<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FileQueueStore")
+            .field("path", &self.path)
+            .field("sync", &self.sync)
+            .finish()
+    }
+}
+
+impl<T: Serialize + DeserializeOwned> FileQueueStore<T> {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+
+        let (pending, next_id) = Self::load(&path)?;
+        let file = Self::rewrite(&path, &pending)?;
+        Ok(FileQueueStore {
+            path,
+            sync: true,
+            state: Mutex::new(LogState {
+                file,
+                next_id,
+                pending,
+            }),
+            _item: PhantomData,
+        })
+    }
+
+    /// Controls whether each write is flushed to disk before returning. Disabling it
+    /// trades durability on power loss for throughput; process crashes are still safe.
+    pub fn with_sync(self, sync: bool) -> Self {
+        FileQueueStore { sync, ..self }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn len(&self) -> usize {
+        Error::handle_poison_error(self.state.lock()).map_or(0, |state| state.pending.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Rewrites the log so it only holds the pending items.
+    pub fn compact(&self) -> Result<()> {
+        let mut state = Error::handle_poison_error(self.state.lock())?;
+        state.file = Self::rewrite(&self.path, &state.pending)?;
+        Ok(())
+    }
+
+    fn load(path: &Path) -> Result<(BTreeMap<u64, serde_json::Value>, u64)> {
+        let mut pending = BTreeMap::new();
+        let mut next_id = 1;
+
+        if !path.exists() {
+            return Ok((pending, next_id));
+        }
+
+        let mut lines = BufReader::new(File::open(path)?).lines().enumerate();
+
+        while let Some((number, line)) = lines.next() {
+            let line = line?;
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let entry = match serde_json::from_str::<LogEntry>(&line) {
+                Ok(it) => it,
+                Err(e) => {
+                    // A partially written line can only be the last one; skip it. A bad
+                    // line with more after it means the log is corrupt.
+                    for (_, rest) in lines.by_ref() {
+                        if !rest?.trim().is_empty() {
+                            return Err(Error::Parse(format!(
+                                "{} line {}: {}",
+                                path.display(),
+                                number + 1,
+                                e
+                            )));
+                        }
+                    }
+
+                    break;
+                }
+            };
+
+            match entry {
+                LogEntry::Enqueue { id, item } => {
+                    next_id = next_id.max(id + 1);
+                    pending.insert(id, item);
+                }
+                LogEntry::Ack { id } => {
+                    pending.remove(&id);
+                }
+            }
+        }
+
+        Ok((pending, next_id))
+    }
+
+    fn rewrite(path: &Path, pending: &BTreeMap<u64, serde_json::Value>) -> Result<File> {
+        // Appended to the whole name so logs that differ only by extension do not share it.
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".compact");
+        let tmp = PathBuf::from(tmp);
+        {
+            let mut file = File::create(&tmp)?;
+
+            for (id, item) in pending {
+                let entry = LogEntry::Enqueue {
+                    id: *id,
+                    item: item.clone(),
+                };
+                Self::write_line(&mut file, &entry)?;
+            }
+
+            file.sync_all()?;
+        }
+        fs::rename(&tmp, path)?;
+        Ok(OpenOptions::new().append(true).open(path)?)
+    }
+
+    fn write_line(file: &mut File, entry: &LogEntry) -> Result<()> {
+        let mut line = serde_json::to_vec(entry).map_err(Error::from_std_error)?;
+        line.push(b'\n');
+        file.write_all(&line)?;
+        Ok(())
+    }
+
+    fn write(&self, state: &mut LogState, entry: &LogEntry) -> Result<()> {
+        Self::write_line(&mut state.file, entry)?;
+
+        if self.sync {
+            state.file.sync_data()?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<T: Serialize + DeserializeOwned> QueueStore<T> for FileQueueStore<T> {
+    fn append(&self, item: &T) -> Result<u64> {
+        let item = serde_json::to_value(item).map_err(Error::from_std_error)?;
+        let mut state = Error::handle_poison_error(self.state.lock())?;
+        let id = state.next_id;
+        self.write(
+            &mut state,
+            &LogEntry::Enqueue {
+                id,
+                item: item.clone(),
+            },
+        )?;
+        state.next_id += 1;
+        state.pending.insert(id, item);
+        Ok(id)
+    }
+
+    fn ack(&self, id: u64) -> Result<()> {
+        let mut state = Error::handle_poison_error(self.state.lock())?;
+
+        if state.pending.remove(&id).is_none() {
+            return Ok(());
+        }
+
+        if state.pending.is_empty() {
+            state.file.set_len(0)?;
+
+            if self.sync {
+                state.file.sync_data()?;
+            }
+
+            return Ok(());
+        }
+
+        self.write(&mut state, &LogEntry::Ack { id })
+    }
+
+    fn pending(&self) -> Result<Vec<(u64, T)>> {
+        let state = Error::handle_poison_error(self.state.lock())?;
+        state
+            .pending
+            .iter()
+            .map(|(id, item)| {
+                serde_json::from_value(item.clone())
+                    .map(|item| (*id, item))
+                    .map_err(Error::from_std_error)
+            })
+            .collect()
+    }
+}
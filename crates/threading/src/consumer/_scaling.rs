@@ -0,0 +1,237 @@
+use std::{
+    fmt,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+    },
+};
+use tokio::time::Duration;
+
+use crate::constants::*;
+
+/// Controls how a consumer grows and shrinks its worker threads at runtime.
+///
+/// A monitor thread samples the queue every `interval`. A worker is added while the
+/// backlog exceeds `backlog_threshold` items per live worker, or while the average
+/// processing latency exceeds `latency_threshold` and items are waiting. Workers that
+/// stay idle for `idle_timeout` retire until `min_workers` are left.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[must_use]
+pub struct ScalingOptions {
+    pub min_workers: usize,
+    pub max_workers: usize,
+    pub backlog_threshold: usize,
+    pub latency_threshold: Duration,
+    pub idle_timeout: Duration,
+    pub interval: Duration,
+}
+
+impl Default for ScalingOptions {
+    fn default() -> Self {
+        ScalingOptions {
+            min_workers: THREADS_MIN,
+            max_workers: emixcore::system::num_cpus().clamp(THREADS_MIN, THREADS_MAX),
+            backlog_threshold: BACKLOG_THRESHOLD_DEF,
+            latency_threshold: LATENCY_THRESHOLD_DEF,
+            idle_timeout: IDLE_TIMEOUT_DEF,
+            interval: SCALE_INTERVAL_DEF.clamp(SCALE_INTERVAL_MIN, SCALE_INTERVAL_MAX),
+        }
+    }
+}
+
+impl ScalingOptions {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn with_min_workers(&self, min_workers: usize) -> Self {
+        let min_workers = min_workers.clamp(THREADS_MIN, THREADS_MAX);
+        ScalingOptions {
+            min_workers,
+            max_workers: self.max_workers.max(min_workers),
+            ..self.clone()
+        }
+    }
+
+    pub fn with_max_workers(&self, max_workers: usize) -> Self {
+        let max_workers = max_workers.clamp(THREADS_MIN, THREADS_MAX);
+        ScalingOptions {
+            min_workers: self.min_workers.min(max_workers),
+            max_workers,
+            ..self.clone()
+        }
+    }
+
+    pub fn with_backlog_threshold(&self, backlog_threshold: usize) -> Self {
+        ScalingOptions {
+            backlog_threshold,
+            ..self.clone()
+        }
+    }
+
+    pub fn with_latency_threshold(&self, latency_threshold: Duration) -> Self {
+        ScalingOptions {
+            latency_threshold,
+            ..self.clone()
+        }
+    }
+
+    pub fn with_idle_timeout(&self, idle_timeout: Duration) -> Self {
+        ScalingOptions {
+            idle_timeout,
+            ..self.clone()
+        }
+    }
+
+    pub fn with_interval(&self, interval: Duration) -> Self {
+        ScalingOptions {
+            interval: interval.clamp(SCALE_INTERVAL_MIN, SCALE_INTERVAL_MAX),
+            ..self.clone()
+        }
+    }
+
+    /// Returns true if another worker should be added given the current load.
+    pub fn should_scale_up(&self, workers: usize, backlog: usize, latency: Duration) -> bool {
+        if workers >= self.max_workers || backlog == 0 {
+            return false;
+        }
+
+        if backlog > workers.saturating_mul(self.backlog_threshold) {
+            return true;
+        }
+
+        !self.latency_threshold.is_zero() && latency > self.latency_threshold
+    }
+}
+
+type Spawner = Arc<dyn Fn() + Send + Sync>;
+
+/// Shared bookkeeping used by the consumers to resize their worker pool.
+///
+/// The spawner closure captures a clone of the consumer, so it must be cleared once the
+/// consumer finishes to break the reference cycle.
+pub(crate) struct WorkerScaler {
+    target: AtomicUsize,
+    latency: AtomicU64,
+    spawner: Mutex<Option<Spawner>>,
+}
+
+impl fmt::Debug for WorkerScaler {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("WorkerScaler")
+            .field("target", &self.target())
+            .field("latency", &self.latency())
+            .finish()
+    }
+}
+
+impl WorkerScaler {
+    pub fn new() -> Self {
+        WorkerScaler {
+            target: AtomicUsize::new(0),
+            latency: AtomicU64::new(0),
+            spawner: Mutex::new(None),
+        }
+    }
+
+    pub fn target(&self) -> usize {
+        self.target.load(Ordering::SeqCst)
+    }
+
+    pub fn latency(&self) -> Duration {
+        Duration::from_nanos(self.latency.load(Ordering::SeqCst))
+    }
+
+    /// Folds a processing time into an exponential moving average (alpha = 1/8).
+    pub fn record_latency(&self, elapsed: Duration) {
+        let sample = elapsed.as_nanos().min(u64::MAX as u128) as u64;
+        let _ = self
+            .latency
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |avg| {
+                if avg == 0 {
+                    Some(sample)
+                } else {
+                    Some(avg - avg / 8 + sample / 8)
+                }
+            });
+    }
+
+    pub fn install(&self, workers: usize, spawner: Spawner) {
+        self.target.store(workers, Ordering::SeqCst);
+        self.latency.store(0, Ordering::SeqCst);
+        *self.spawner.lock().unwrap() = Some(spawner);
+    }
+
+    pub fn clear(&self) {
+        self.spawner.lock().unwrap().take();
+    }
+
+    /// Sets the desired worker count and spawns the missing workers. Surplus workers
+    /// retire on their own the next time they check `try_retire`.
+    pub fn resize(&self, live: &AtomicUsize, workers: usize) -> bool {
+        let spawner = self.spawner.lock().unwrap();
+        let Some(spawner) = spawner.as_ref() else {
+            return false;
+        };
+
+        self.target.store(workers, Ordering::SeqCst);
+        let current = live.load(Ordering::SeqCst);
+
+        if workers <= current {
+            return true;
+        }
+
+        let missing = workers - current;
+        // A live count of zero means the last worker already finished the queue.
+        if live
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| {
+                if n == 0 { None } else { Some(n + missing) }
+            })
+            .is_err()
+        {
+            return false;
+        }
+
+        for _ in 0..missing {
+            spawner();
+        }
+
+        true
+    }
+
+    /// Decrements the live worker count if the pool is above its target, or if scaling is
+    /// enabled and the worker has been idle for too long. Returns true if the calling
+    /// worker should exit.
+    pub fn try_retire(
+        &self,
+        live: &AtomicUsize,
+        scaling: Option<&ScalingOptions>,
+        idle: Duration,
+    ) -> bool {
+        let target = self.target();
+        let idle_floor = scaling
+            .filter(|s| idle >= s.idle_timeout)
+            .map(|s| s.min_workers.max(THREADS_MIN));
+        let floor = match idle_floor {
+            Some(floor) => floor.min(target),
+            None => target,
+        }
+        .max(THREADS_MIN);
+        let retired = live
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| {
+                if n > floor { Some(n - 1) } else { None }
+            })
+            .is_ok();
+
+        if retired {
+            let remaining = live.load(Ordering::SeqCst);
+            let _ = self
+                .target
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |t| {
+                    if t > remaining { Some(remaining) } else { None }
+                });
+        }
+
+        retired
+    }
+}
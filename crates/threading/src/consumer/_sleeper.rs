@@ -0,0 +1,48 @@
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+#[cfg(feature = "test-support")]
+use crate::test_support::VirtualClock;
+
+/// Waits out the timeouts of a consumer: in real time, or on a `VirtualClock` in tests.
+#[derive(Clone, Debug, Default)]
+pub(crate) enum Sleeper {
+    #[default]
+    System,
+    #[cfg(feature = "test-support")]
+    Virtual(VirtualClock),
+}
+
+impl Sleeper {
+    pub(crate) fn now(&self) -> Instant {
+        match self {
+            Sleeper::System => Instant::now(),
+            #[cfg(feature = "test-support")]
+            Sleeper::Virtual(clock) => clock.now(),
+        }
+    }
+
+    pub(crate) fn sleep(&self, duration: Duration) {
+        match self {
+            Sleeper::System => thread::sleep(duration),
+            #[cfg(feature = "test-support")]
+            Sleeper::Virtual(clock) => clock.sleep(duration),
+        }
+    }
+
+    /// Calls `wait` with `timeout` and returns what it got. `wait` may be called again
+    /// with shorter timeouts until `timeout` passes on the clock.
+    pub(crate) fn wait_timeout<R>(
+        &self,
+        timeout: Duration,
+        mut wait: impl FnMut(Duration) -> Option<R>,
+    ) -> Option<R> {
+        match self {
+            Sleeper::System => wait(timeout),
+            #[cfg(feature = "test-support")]
+            Sleeper::Virtual(clock) => clock.wait_timeout(timeout, wait),
+        }
+    }
+}
@@ -1,6 +1,104 @@
+/// Implements `finish` and the adapter traits for a consumer whose item type is bounded by
+/// `$bound`. The consumer provides `clear_queue` and `set_finished`.
+macro_rules! impl_consumer {
+    ($name:ident, $bound:ident) => {
+        impl<T: $bound> $name<T> {
+            /// Ends the consumer once it is completed or cancelled. A cancelled consumer
+            /// first drops the items it will never process, resolving any handles they
+            /// carry.
+            fn finish(&self) {
+                if !self.is_completed() && !self.is_cancelled() {
+                    return;
+                }
+
+                if self.is_cancelled() {
+                    self.clear_queue();
+                }
+
+                self.set_finished();
+            }
+        }
+
+        impl<T: $bound> $crate::AwaitableConsumer<T> for $name<T> {
+            fn is_cancelled(&self) -> bool {
+                $name::is_cancelled(self)
+            }
+
+            fn is_finished(&self) -> bool {
+                $name::is_finished(self)
+            }
+        }
+
+        impl<T: $bound> $crate::TaskQueue<T> for $name<T> {
+            fn enqueue(&self, item: T) -> $crate::Result<()> {
+                $name::enqueue(self, item)
+            }
+
+            fn try_enqueue(&self, item: T) -> $crate::Result<()> {
+                $name::try_enqueue(self, item)
+            }
+
+            fn capacity(&self) -> Option<usize> {
+                $name::capacity(self)
+            }
+        }
+
+        impl<T: $bound> $crate::dataflow::DataflowTarget<T> for $name<T> {
+            fn post(&self, item: T) -> $crate::Result<()> {
+                $name::enqueue(self, item)
+            }
+
+            fn complete(&self) {
+                $name::complete(self);
+            }
+
+            fn cancel(&self) {
+                $name::cancel(self);
+            }
+        }
+
+        impl<T: $bound> $crate::GracefulShutdown for $name<T> {
+            fn drain(&self) {
+                $name::complete(self);
+            }
+
+            fn cancel(&self) {
+                $name::cancel(self);
+            }
+
+            fn is_finished(&self) -> bool {
+                $name::is_finished(self)
+            }
+        }
+    };
+}
+
+/// What one turn of a worker's loop did.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Turn {
+    /// An item was processed.
+    Processed,
+    /// The consumer is paused.
+    Paused,
+    /// No item was available.
+    Idle,
+    /// The handler asked the worker to stop.
+    Stopped,
+}
+
+mod _sleeper;
+use _sleeper::Sleeper;
+mod _impl_batch_consumer;
+pub use _impl_batch_consumer::*;
 mod _impl_consumer;
 pub use _impl_consumer::*;
 mod _impl_injector_consumer;
 pub use _impl_injector_consumer::*;
+mod _impl_keyed_consumer;
+pub use _impl_keyed_consumer::*;
 mod _impl_producer_consumer;
 pub use _impl_producer_consumer::*;
+mod _scaling;
+pub use _scaling::*;
+mod _queue_store;
+pub use _queue_store::*;
@@ -0,0 +1,54 @@
+use super::{Completion, DataflowTarget, start_stage};
+use crate::{
+    consumer::{ProducerConsumer, ProducerConsumerOptions},
+    *,
+};
+
+/// Runs a function on every input. Usually the last block of a pipeline.
+#[derive(Clone, Debug)]
+pub struct ActionBlock<T: StaticTaskItem> {
+    queue: ProducerConsumer<T>,
+    completion: Completion,
+}
+
+impl<T: StaticTaskItem> ActionBlock<T> {
+    pub fn new(action: impl Fn(&T) -> Result<()> + Send + Sync + 'static) -> Self {
+        Self::with_options(ProducerConsumerOptions::new(), action)
+    }
+
+    /// Creates a block whose input buffer and parallelism are set by `options`.
+    pub fn with_options(
+        options: ProducerConsumerOptions,
+        action: impl Fn(&T) -> Result<()> + Send + Sync + 'static,
+    ) -> Self {
+        let completion = Completion::new();
+        let done = completion.clone();
+        let queue = start_stage(options, &completion, action, move |cancelled| {
+            done.finish(cancelled)
+        });
+        ActionBlock { queue, completion }
+    }
+
+    pub fn completion(&self) -> &Completion {
+        &self.completion
+    }
+
+    /// Cancels the block when `token` is cancelled.
+    pub fn cancel_on(&self, token: &CancellationToken) {
+        self.queue.cancel_on(token);
+    }
+}
+
+impl<T: StaticTaskItem> DataflowTarget<T> for ActionBlock<T> {
+    fn post(&self, item: T) -> Result<()> {
+        self.queue.enqueue(item)
+    }
+
+    fn complete(&self) {
+        self.queue.complete();
+    }
+
+    fn cancel(&self) {
+        self.queue.cancel();
+    }
+}
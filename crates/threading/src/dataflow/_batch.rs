@@ -0,0 +1,103 @@
+use std::{
+    mem,
+    sync::{Arc, Mutex},
+};
+
+use super::{Completion, DataflowTarget, Links, start_stage};
+use crate::{
+    consumer::{ProducerConsumer, ProducerConsumerOptions},
+    *,
+};
+
+/// Groups inputs into batches of `batch_size` and sends each batch to the linked
+/// targets. A partial batch is sent when the block completes.
+#[derive(Clone, Debug)]
+pub struct BatchBlock<T: StaticTaskItem> {
+    queue: ProducerConsumer<T>,
+    links: Links<Vec<T>>,
+    completion: Completion,
+    batch_size: usize,
+}
+
+impl<T: StaticTaskItem> BatchBlock<T> {
+    pub fn new(batch_size: usize) -> Self {
+        Self::with_options(ProducerConsumerOptions::new(), batch_size)
+    }
+
+    /// Creates a block whose input buffer is set by `options`. Items are batched by a
+    /// single thread so batches keep the order items were posted in.
+    pub fn with_options(options: ProducerConsumerOptions, batch_size: usize) -> Self {
+        let batch_size = batch_size.max(1);
+        let completion = Completion::new();
+        let links = Links::new(&completion);
+        let batch = Arc::new(Mutex::new(Vec::with_capacity(batch_size)));
+        let sender = links.clone();
+        let pending = batch.clone();
+        let finisher = links.clone();
+        let done = completion.clone();
+        let queue = start_stage(
+            options.with_threads(1),
+            &completion,
+            move |item: &T| {
+                let full = {
+                    let mut batch = pending.lock().unwrap();
+                    batch.push(item.clone());
+
+                    if batch.len() < batch_size {
+                        return Ok(());
+                    }
+
+                    mem::replace(&mut *batch, Vec::with_capacity(batch_size))
+                };
+                sender.send(full)
+            },
+            move |cancelled| {
+                let rest = mem::take(&mut *batch.lock().unwrap());
+
+                if !cancelled && !rest.is_empty() && finisher.send(rest).is_err() {
+                    done.add_error();
+                }
+
+                finisher.propagate(cancelled);
+                done.finish(cancelled);
+            },
+        );
+        BatchBlock {
+            queue,
+            links,
+            completion,
+            batch_size,
+        }
+    }
+
+    pub fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    pub fn link_to(&self, target: &(impl DataflowTarget<Vec<T>> + Clone + 'static)) {
+        self.links.add(Arc::new(target.clone()));
+    }
+
+    pub fn completion(&self) -> &Completion {
+        &self.completion
+    }
+
+    /// Cancels the block when `token` is cancelled.
+    pub fn cancel_on(&self, token: &CancellationToken) {
+        self.queue.cancel_on(token);
+    }
+}
+
+impl<T: StaticTaskItem> DataflowTarget<T> for BatchBlock<T> {
+    fn post(&self, item: T) -> Result<()> {
+        self.queue.enqueue(item)
+    }
+
+    fn complete(&self) {
+        self.queue.complete();
+    }
+
+    fn cancel(&self) {
+        self.queue.cancel();
+    }
+}
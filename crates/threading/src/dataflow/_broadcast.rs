@@ -0,0 +1,78 @@
+use std::sync::Arc;
+
+use super::{Completion, DataflowTarget, Links, start_stage};
+use crate::{
+    consumer::{ProducerConsumer, ProducerConsumerOptions},
+    *,
+};
+
+/// Sends a copy of every input to each linked target.
+#[derive(Clone, Debug)]
+pub struct BroadcastBlock<T: StaticTaskItem> {
+    queue: ProducerConsumer<T>,
+    links: Links<T>,
+    completion: Completion,
+}
+
+impl<T: StaticTaskItem> Default for BroadcastBlock<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: StaticTaskItem> BroadcastBlock<T> {
+    pub fn new() -> Self {
+        Self::with_options(ProducerConsumerOptions::new())
+    }
+
+    /// Creates a block whose input buffer is set by `options`. Items are forwarded by a
+    /// single thread so every target sees them in order.
+    pub fn with_options(options: ProducerConsumerOptions) -> Self {
+        let completion = Completion::new();
+        let links = Links::new(&completion);
+        let sender = links.clone();
+        let finisher = links.clone();
+        let done = completion.clone();
+        let queue = start_stage(
+            options.with_threads(1),
+            &completion,
+            move |item| sender.broadcast(item),
+            move |cancelled| {
+                finisher.propagate(cancelled);
+                done.finish(cancelled);
+            },
+        );
+        BroadcastBlock {
+            queue,
+            links,
+            completion,
+        }
+    }
+
+    pub fn link_to(&self, target: &(impl DataflowTarget<T> + Clone + 'static)) {
+        self.links.add(Arc::new(target.clone()));
+    }
+
+    pub fn completion(&self) -> &Completion {
+        &self.completion
+    }
+
+    /// Cancels the block when `token` is cancelled.
+    pub fn cancel_on(&self, token: &CancellationToken) {
+        self.queue.cancel_on(token);
+    }
+}
+
+impl<T: StaticTaskItem> DataflowTarget<T> for BroadcastBlock<T> {
+    fn post(&self, item: T) -> Result<()> {
+        self.queue.enqueue(item)
+    }
+
+    fn complete(&self) {
+        self.queue.complete();
+    }
+
+    fn cancel(&self) {
+        self.queue.cancel();
+    }
+}
@@ -0,0 +1,170 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        Arc, Mutex, OnceLock,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
+};
+
+use super::{Completion, DataflowTarget, Links, start_stage};
+use crate::{
+    consumer::{ProducerConsumer, ProducerConsumerOptions},
+    *,
+};
+
+#[derive(Debug)]
+struct JoinState<A, B> {
+    first: VecDeque<A>,
+    second: VecDeque<B>,
+}
+
+/// Pairs the items posted to its two inputs in arrival order and sends each pair to the
+/// linked targets.
+///
+/// The block completes once both inputs have completed; items left without a partner
+/// are dropped. Cancelling either input cancels the whole block.
+#[derive(Clone, Debug)]
+pub struct JoinBlock<A: StaticTaskItem, B: StaticTaskItem> {
+    first: ProducerConsumer<A>,
+    second: ProducerConsumer<B>,
+    links: Links<(A, B)>,
+    completion: Completion,
+}
+
+impl<A: StaticTaskItem, B: StaticTaskItem> Default for JoinBlock<A, B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A: StaticTaskItem, B: StaticTaskItem> JoinBlock<A, B> {
+    pub fn new() -> Self {
+        Self::with_options(ProducerConsumerOptions::new())
+    }
+
+    /// Creates a block whose input buffers are set by `options`. Each input is read by a
+    /// single thread so pairs keep the order items were posted in.
+    pub fn with_options(options: ProducerConsumerOptions) -> Self {
+        let options = options.with_threads(1);
+        let completion = Completion::new();
+        let links = Links::new(&completion);
+        let state = Arc::new(Mutex::new(JoinState {
+            first: VecDeque::new(),
+            second: VecDeque::new(),
+        }));
+        let inputs_done = Arc::new(AtomicUsize::new(0));
+        let any_cancelled = Arc::new(AtomicBool::new(false));
+        let inputs: Arc<OnceLock<(ProducerConsumer<A>, ProducerConsumer<B>)>> =
+            Arc::new(OnceLock::new());
+        let finish = {
+            let links = links.clone();
+            let completion = completion.clone();
+            let inputs = inputs.clone();
+            move |cancelled: bool| {
+                any_cancelled.fetch_or(cancelled, Ordering::SeqCst);
+
+                // Cancelling one input cancels the other so the block does not wait
+                // forever for it to complete.
+                if cancelled && let Some((first, second)) = inputs.get() {
+                    first.cancel();
+                    second.cancel();
+                }
+
+                if inputs_done.fetch_add(1, Ordering::SeqCst) + 1 < 2 {
+                    return;
+                }
+
+                let cancelled = any_cancelled.load(Ordering::SeqCst);
+                links.propagate(cancelled);
+                completion.finish(cancelled);
+            }
+        };
+        let finish = Arc::new(finish);
+
+        let first_state = state.clone();
+        let first_links = links.clone();
+        let first_finish = finish.clone();
+        let first = start_stage(
+            options.clone(),
+            &completion,
+            move |item: &A| {
+                let pair = {
+                    let mut state = first_state.lock().unwrap();
+                    match state.second.pop_front() {
+                        Some(other) => (item.clone(), other),
+                        None => {
+                            state.first.push_back(item.clone());
+                            return Ok(());
+                        }
+                    }
+                };
+                first_links.send(pair)
+            },
+            move |cancelled| first_finish(cancelled),
+        );
+
+        let second_links = links.clone();
+        let second = start_stage(
+            options,
+            &completion,
+            move |item: &B| {
+                let pair = {
+                    let mut state = state.lock().unwrap();
+                    match state.first.pop_front() {
+                        Some(other) => (other, item.clone()),
+                        None => {
+                            state.second.push_back(item.clone());
+                            return Ok(());
+                        }
+                    }
+                };
+                second_links.send(pair)
+            },
+            move |cancelled| finish(cancelled),
+        );
+
+        let _ = inputs.set((first.clone(), second.clone()));
+        JoinBlock {
+            first,
+            second,
+            links,
+            completion,
+        }
+    }
+
+    /// The input that supplies the first item of each pair.
+    pub fn first(&self) -> &ProducerConsumer<A> {
+        &self.first
+    }
+
+    /// The input that supplies the second item of each pair.
+    pub fn second(&self) -> &ProducerConsumer<B> {
+        &self.second
+    }
+
+    pub fn link_to(&self, target: &(impl DataflowTarget<(A, B)> + Clone + 'static)) {
+        self.links.add(Arc::new(target.clone()));
+    }
+
+    pub fn completion(&self) -> &Completion {
+        &self.completion
+    }
+
+    /// Completes both inputs.
+    pub fn complete(&self) {
+        self.first.complete();
+        self.second.complete();
+    }
+
+    /// Cancels both inputs.
+    pub fn cancel(&self) {
+        self.first.cancel();
+        self.second.cancel();
+    }
+
+    /// Cancels the block when `token` is cancelled.
+    pub fn cancel_on(&self, token: &CancellationToken) {
+        let this = self.clone();
+        token.on_cancel(move |_| this.cancel());
+    }
+}
@@ -0,0 +1,155 @@
+use std::sync::Arc;
+
+use super::{Completion, DataflowTarget, Links, start_stage};
+use crate::{
+    consumer::{ProducerConsumer, ProducerConsumerOptions},
+    *,
+};
+
+/// Runs a function on every input and sends the result to the linked targets.
+///
+/// With more than one thread, outputs may be sent in a different order than the inputs
+/// were posted.
+#[derive(Clone, Debug)]
+pub struct TransformBlock<I: StaticTaskItem, O: StaticTaskItem> {
+    queue: ProducerConsumer<I>,
+    links: Links<O>,
+    completion: Completion,
+}
+
+impl<I: StaticTaskItem, O: StaticTaskItem> TransformBlock<I, O> {
+    pub fn new(transform: impl Fn(&I) -> Result<O> + Send + Sync + 'static) -> Self {
+        Self::with_options(ProducerConsumerOptions::new(), transform)
+    }
+
+    /// Creates a block whose input buffer and parallelism are set by `options`.
+    pub fn with_options(
+        options: ProducerConsumerOptions,
+        transform: impl Fn(&I) -> Result<O> + Send + Sync + 'static,
+    ) -> Self {
+        let completion = Completion::new();
+        let links = Links::new(&completion);
+        let sender = links.clone();
+        let finisher = links.clone();
+        let done = completion.clone();
+        let queue = start_stage(
+            options,
+            &completion,
+            move |item| sender.send(transform(item)?),
+            move |cancelled| {
+                finisher.propagate(cancelled);
+                done.finish(cancelled);
+            },
+        );
+        TransformBlock {
+            queue,
+            links,
+            completion,
+        }
+    }
+
+    /// Sends this block's output to `target`. Each output goes to the first linked
+    /// target that accepts it. Outputs produced before the first link wait for it.
+    pub fn link_to(&self, target: &(impl DataflowTarget<O> + Clone + 'static)) {
+        self.links.add(Arc::new(target.clone()));
+    }
+
+    pub fn completion(&self) -> &Completion {
+        &self.completion
+    }
+
+    /// Cancels the block when `token` is cancelled.
+    pub fn cancel_on(&self, token: &CancellationToken) {
+        self.queue.cancel_on(token);
+    }
+}
+
+impl<I: StaticTaskItem, O: StaticTaskItem> DataflowTarget<I> for TransformBlock<I, O> {
+    fn post(&self, item: I) -> Result<()> {
+        self.queue.enqueue(item)
+    }
+
+    fn complete(&self) {
+        self.queue.complete();
+    }
+
+    fn cancel(&self) {
+        self.queue.cancel();
+    }
+}
+
+/// Runs a function that turns every input into any number of outputs and sends each of
+/// them to the linked targets.
+#[derive(Clone, Debug)]
+pub struct TransformManyBlock<I: StaticTaskItem, O: StaticTaskItem> {
+    queue: ProducerConsumer<I>,
+    links: Links<O>,
+    completion: Completion,
+}
+
+impl<I: StaticTaskItem, O: StaticTaskItem> TransformManyBlock<I, O> {
+    pub fn new(transform: impl Fn(&I) -> Result<Vec<O>> + Send + Sync + 'static) -> Self {
+        Self::with_options(ProducerConsumerOptions::new(), transform)
+    }
+
+    /// Creates a block whose input buffer and parallelism are set by `options`.
+    pub fn with_options(
+        options: ProducerConsumerOptions,
+        transform: impl Fn(&I) -> Result<Vec<O>> + Send + Sync + 'static,
+    ) -> Self {
+        let completion = Completion::new();
+        let links = Links::new(&completion);
+        let sender = links.clone();
+        let finisher = links.clone();
+        let done = completion.clone();
+        let queue = start_stage(
+            options,
+            &completion,
+            move |item| {
+                for output in transform(item)? {
+                    sender.send(output)?;
+                }
+
+                Ok(())
+            },
+            move |cancelled| {
+                finisher.propagate(cancelled);
+                done.finish(cancelled);
+            },
+        );
+        TransformManyBlock {
+            queue,
+            links,
+            completion,
+        }
+    }
+
+    /// Sends this block's output to `target`. Each output goes to the first linked
+    /// target that accepts it. Outputs produced before the first link wait for it.
+    pub fn link_to(&self, target: &(impl DataflowTarget<O> + Clone + 'static)) {
+        self.links.add(Arc::new(target.clone()));
+    }
+
+    pub fn completion(&self) -> &Completion {
+        &self.completion
+    }
+
+    /// Cancels the block when `token` is cancelled.
+    pub fn cancel_on(&self, token: &CancellationToken) {
+        self.queue.cancel_on(token);
+    }
+}
+
+impl<I: StaticTaskItem, O: StaticTaskItem> DataflowTarget<I> for TransformManyBlock<I, O> {
+    fn post(&self, item: I) -> Result<()> {
+        self.queue.enqueue(item)
+    }
+
+    fn complete(&self) {
+        self.queue.complete();
+    }
+
+    fn cancel(&self) {
+        self.queue.cancel();
+    }
+}
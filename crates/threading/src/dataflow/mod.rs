@@ -0,0 +1,311 @@
+mod _action;
+pub use _action::*;
+mod _batch;
+pub use _batch::*;
+mod _broadcast;
+pub use _broadcast::*;
+mod _join;
+pub use _join::*;
+mod _transform;
+pub use _transform::*;
+
+use std::{
+    collections::VecDeque,
+    fmt, mem,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
+};
+use tokio::{sync::Notify, time::Duration};
+
+use crate::{
+    consumer::{ProducerConsumer, ProducerConsumerOptions},
+    *,
+};
+
+/// Something a dataflow block can send its output to.
+///
+/// Every block implements it for its input type, and so do the consumers, so a pipeline
+/// can end in an existing queue.
+pub trait DataflowTarget<T>: Send + Sync + fmt::Debug {
+    /// Hands an item to the target, blocking while its buffer is full.
+    fn post(&self, item: T) -> Result<()>;
+    /// Tells the target that no more items will be posted.
+    fn complete(&self);
+    fn cancel(&self);
+}
+
+/// Tracks when a block has processed its input and passed its completion or
+/// cancellation on to its links. It covers only this block: wait on the linked blocks'
+/// own completions to know when they are done.
+#[derive(Clone, Debug)]
+pub struct Completion {
+    finished: Arc<AtomicBool>,
+    cancelled: Arc<AtomicBool>,
+    errors: Arc<AtomicUsize>,
+    finished_cond: Arc<ManualResetCond>,
+    finished_noti: Arc<Notify>,
+}
+
+impl Completion {
+    fn new() -> Self {
+        Completion {
+            finished: Arc::new(AtomicBool::new(false)),
+            cancelled: Arc::new(AtomicBool::new(false)),
+            errors: Arc::new(AtomicUsize::new(0)),
+            finished_cond: Arc::new(ManualResetCond::new_unset()),
+            finished_noti: Arc::new(Notify::new()),
+        }
+    }
+
+    fn finish(&self, cancelled: bool) {
+        self.cancelled.fetch_or(cancelled, Ordering::SeqCst);
+        self.finished.store(true, Ordering::SeqCst);
+        let _ = self.finished_cond.set();
+        self.finished_noti.notify_waiters();
+    }
+
+    fn add_error(&self) {
+        self.errors.fetch_add(1, Ordering::SeqCst);
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished.load(Ordering::SeqCst)
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Gets the number of items whose processing returned an error. Those items are
+    /// dropped.
+    pub fn errors(&self) -> usize {
+        self.errors.load(Ordering::SeqCst)
+    }
+
+    pub fn wait(&self) -> Result<()> {
+        wait(self, &self.finished_cond)
+    }
+
+    pub async fn wait_async(&self) -> Result<()> {
+        wait_async(self, &self.finished_noti).await
+    }
+
+    pub fn wait_for(&self, timeout: Duration) -> Result<()> {
+        wait_for(self, timeout, &self.finished_cond)
+    }
+
+    pub async fn wait_for_async(&self, timeout: Duration) -> Result<()> {
+        wait_for_async(self, timeout, &self.finished_noti).await
+    }
+}
+
+impl AwaitableConsumer<()> for Completion {
+    fn is_cancelled(&self) -> bool {
+        Completion::is_cancelled(self)
+    }
+
+    fn is_finished(&self) -> bool {
+        Completion::is_finished(self)
+    }
+}
+
+/// The targets a block sends its output to, plus the outputs it sent before the first
+/// target was linked.
+struct Links<T> {
+    state: Arc<Mutex<LinkState<T>>>,
+    completion: Completion,
+}
+
+struct LinkState<T> {
+    targets: Vec<Arc<dyn DataflowTarget<T>>>,
+    pending: VecDeque<T>,
+    /// Whether the block already propagated its end, and if it was cancelled, so targets
+    /// linked later are told too.
+    propagated: Option<bool>,
+}
+
+impl<T> Clone for Links<T> {
+    fn clone(&self) -> Self {
+        Links {
+            state: self.state.clone(),
+            completion: self.completion.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for Links<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list()
+            .entries(self.state.lock().unwrap().targets.iter())
+            .finish()
+    }
+}
+
+impl<T: StaticTaskItem> Links<T> {
+    fn new(completion: &Completion) -> Self {
+        Links {
+            state: Arc::new(Mutex::new(LinkState {
+                targets: Vec::new(),
+                pending: VecDeque::new(),
+                propagated: None,
+            })),
+            completion: completion.clone(),
+        }
+    }
+
+    /// Links a target. The first one receives the outputs buffered until now, in the
+    /// order they were sent, and a target linked after the end is told about it.
+    fn add(&self, target: Arc<dyn DataflowTarget<T>>) {
+        let mut state = self.state.lock().unwrap();
+
+        // The lock is held while the buffer drains so later outputs cannot overtake it.
+        for item in mem::take(&mut state.pending) {
+            if target.post(item).is_err() {
+                self.completion.add_error();
+            }
+        }
+
+        match state.propagated {
+            Some(true) => target.cancel(),
+            Some(false) => target.complete(),
+            None => {}
+        }
+
+        state.targets.push(target);
+    }
+
+    /// Gets the linked targets, or buffers the item with `buffer` if there are none.
+    fn targets_or_buffer(&self, buffer: impl FnOnce() -> T) -> Vec<Arc<dyn DataflowTarget<T>>> {
+        let mut state = self.state.lock().unwrap();
+
+        if state.targets.is_empty() {
+            state.pending.push_back(buffer());
+        }
+
+        state.targets.clone()
+    }
+
+    /// Offers the item to each target in link order until one accepts it.
+    fn send(&self, item: T) -> Result<()> {
+        let targets = self.targets_or_buffer(|| item.clone());
+        let mut last = Ok(());
+
+        for target in targets {
+            last = target.post(item.clone());
+
+            if last.is_ok() {
+                break;
+            }
+        }
+
+        last
+    }
+
+    /// Posts a copy of the item to every target.
+    fn broadcast(&self, item: &T) -> Result<()> {
+        let mut result = Ok(());
+
+        for target in self.targets_or_buffer(|| item.clone()) {
+            if let Err(e) = target.post(item.clone()) {
+                result = Err(e);
+            }
+        }
+
+        result
+    }
+
+    fn propagate(&self, cancelled: bool) {
+        let targets = {
+            let mut state = self.state.lock().unwrap();
+            state.propagated = Some(cancelled);
+
+            if cancelled {
+                state.pending.clear();
+            }
+
+            state.targets.clone()
+        };
+
+        for target in targets {
+            if cancelled {
+                target.cancel();
+            } else {
+                target.complete();
+            }
+        }
+    }
+}
+
+type StageProcess<T> = Arc<dyn Fn(&T) -> Result<()> + Send + Sync>;
+type StageFinish = Arc<dyn Fn(bool) + Send + Sync>;
+
+/// The handler that runs a block's work on its `ProducerConsumer`.
+struct Stage<T> {
+    process: StageProcess<T>,
+    finish: StageFinish,
+    completion: Completion,
+}
+
+impl<T> Clone for Stage<T> {
+    fn clone(&self) -> Self {
+        Stage {
+            process: self.process.clone(),
+            finish: self.finish.clone(),
+            completion: self.completion.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for Stage<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Stage")
+            .field("completion", &self.completion)
+            .finish()
+    }
+}
+
+impl<T: StaticTaskItem> TaskDelegation<ProducerConsumer<T>, T> for Stage<T> {
+    fn on_started(&self, _pc: &ProducerConsumer<T>) {}
+
+    fn process(&self, _pc: &ProducerConsumer<T>, item: &T) -> Result<TaskResult> {
+        (self.process)(item)?;
+        Ok(TaskResult::Success)
+    }
+
+    fn on_completed(&self, _pc: &ProducerConsumer<T>, _item: &T, result: &TaskResult) -> bool {
+        if let TaskResult::Error(_) = result {
+            self.completion.add_error();
+        }
+
+        true
+    }
+
+    fn on_cancelled(&self, _pc: &ProducerConsumer<T>) {
+        (self.finish)(true);
+    }
+
+    fn on_finished(&self, _pc: &ProducerConsumer<T>) {
+        (self.finish)(false);
+    }
+}
+
+/// Starts a queue that runs `process` for every item and calls `finish` once the queue
+/// is done.
+fn start_stage<T: StaticTaskItem>(
+    options: ProducerConsumerOptions,
+    completion: &Completion,
+    process: impl Fn(&T) -> Result<()> + Send + Sync + 'static,
+    finish: impl Fn(bool) + Send + Sync + 'static,
+) -> ProducerConsumer<T> {
+    let queue = ProducerConsumer::with_options(options);
+    let stage = Stage {
+        process: Arc::new(process),
+        finish: Arc::new(finish),
+        completion: completion.clone(),
+    };
+    // A new queue is neither started, completed nor cancelled, so this cannot fail.
+    let _ = queue.start(&stage);
+    queue
+}
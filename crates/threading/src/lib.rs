@@ -1,14 +1,44 @@
+mod async_cond;
+pub use self::async_cond::*;
+mod barrier;
+pub use self::barrier::*;
+mod bulkhead;
+pub use self::bulkhead::*;
+mod cancellation;
+pub use self::cancellation::*;
+mod circuit_breaker;
+pub use self::circuit_breaker::*;
 mod cond;
 pub use crate::cond::*;
 pub mod constants;
 pub mod consumer;
+pub mod dataflow;
+pub mod parallel;
+mod progress_group;
+pub use self::progress_group::*;
+mod resilient_handler;
+pub use self::resilient_handler::*;
+mod rw_lock;
+pub use self::rw_lock::*;
+mod scheduler;
+pub use self::scheduler::*;
+mod semaphore;
+pub use self::semaphore::*;
+mod shutdown;
+pub use self::shutdown::*;
 mod signal;
 pub use self::signal::*;
 mod spinner;
 pub use self::spinner::*;
+mod task_group;
+pub use self::task_group::*;
+mod task_handle;
+pub use self::task_handle::*;
+#[cfg(feature = "test-support")]
+pub mod test_support;
 
 use futures::Future;
-use std::{fmt, pin::Pin, sync::Arc, thread};
+use std::{fmt, hash::Hash, pin::Pin, sync::Arc};
 use tokio::{
     sync::Notify,
     time::{self, Duration},
@@ -61,6 +91,14 @@ impl<T: Clone + Send + Sync + fmt::Debug> TaskItem for T {}
 pub trait StaticTaskItem: TaskItem + 'static {}
 impl<T: TaskItem + 'static> StaticTaskItem for T {}
 
+/// An item that belongs to an entity. Items with the same key are never processed
+/// concurrently. Equality is used to coalesce duplicates.
+pub trait KeyedTaskItem: StaticTaskItem + PartialEq {
+    type Key: Clone + Eq + Hash + Send + Sync + fmt::Debug + 'static;
+
+    fn key(&self) -> Self::Key;
+}
+
 pub trait TaskDelegation<TPC: AwaitableConsumer<T>, T: StaticTaskItem>: StaticTaskItem {
     fn on_started(&self, pc: &TPC);
     fn process(&self, pc: &TPC, item: &T) -> Result<TaskResult>;
@@ -69,11 +107,36 @@ pub trait TaskDelegation<TPC: AwaitableConsumer<T>, T: StaticTaskItem>: StaticTa
     fn on_finished(&self, pc: &TPC);
 }
 
+/// Handles the items of a consumer that delivers them in batches.
+pub trait BatchTaskDelegation<TPC: AwaitableConsumer<T>, T: StaticTaskItem>:
+    StaticTaskItem
+{
+    fn on_started(&self, pc: &TPC);
+    /// Processes a batch and returns one result per item, in the same order. Items left
+    /// without a result are reported as `TaskResult::None`. An error fails every item of
+    /// the batch.
+    fn process(&self, pc: &TPC, items: &[T]) -> Result<Vec<TaskResult>>;
+    fn on_completed(&self, pc: &TPC, item: &T, result: &TaskResult) -> bool;
+    fn on_cancelled(&self, pc: &TPC);
+    fn on_finished(&self, pc: &TPC);
+}
+
 pub trait AwaitableConsumer<T: TaskItem>: StaticTaskItem {
     fn is_cancelled(&self) -> bool;
     fn is_finished(&self) -> bool;
 }
 
+/// A queue that accepts items from other components, such as the `Scheduler`.
+pub trait TaskQueue<T: StaticTaskItem>: StaticTaskItem {
+    fn enqueue(&self, item: T) -> Result<()>;
+    /// Enqueues an item without waiting for room, failing with `Error::Exceeded` if the
+    /// queue is full.
+    fn try_enqueue(&self, item: T) -> Result<()>;
+    /// Returns the most items the queue holds, or `None` if it is unbounded. Zero means an
+    /// item is only accepted while a worker is waiting for it.
+    fn capacity(&self) -> Option<usize>;
+}
+
 pub fn wait<TPC: AwaitableConsumer<T>, T: StaticTaskItem>(
     this: &TPC,
     finished: &Arc<ManualResetCond>,
@@ -94,12 +157,16 @@ pub async fn wait_async<TPC: AwaitableConsumer<T>, T: StaticTaskItem>(
     this: &TPC,
     finished: &Arc<Notify>,
 ) -> Result<()> {
-    let mut notified = false;
+    loop {
+        let notified = finished.notified();
+        tokio::pin!(notified);
+        notified.as_mut().enable();
 
-    while !notified && !this.is_finished() && !this.is_cancelled() {
-        thread::sleep(Duration::ZERO);
-        finished.notified().await;
-        notified = true;
+        if this.is_finished() || this.is_cancelled() {
+            break;
+        }
+
+        notified.await;
     }
 
     if this.is_cancelled() {
@@ -186,7 +253,20 @@ pub async fn wait_for_async<TPC: AwaitableConsumer<T>, T: StaticTaskItem>(
         return Err(Error::Timeout);
     }
 
-    let result = time::timeout(timeout, finished.notified()).await;
+    let result = time::timeout(timeout, async {
+        loop {
+            let notified = finished.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            if this.is_finished() {
+                break;
+            }
+
+            notified.await;
+        }
+    })
+    .await;
     match result {
         Ok(_) => {
             if this.is_cancelled() {
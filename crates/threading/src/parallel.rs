@@ -0,0 +1,213 @@
+use std::{
+    fmt,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
+    thread,
+};
+
+use crate::{CancellationToken, Error, Result};
+
+type ProgressFn = Arc<dyn Fn(usize, Option<usize>) + Send + Sync>;
+
+#[derive(Clone)]
+#[must_use]
+pub struct ParallelOptions {
+    /// The number of threads to run on.
+    pub degree: usize,
+    /// Whether results keep the order of their items.
+    pub ordered: bool,
+    pub token: Option<CancellationToken>,
+    progress: Option<ProgressFn>,
+}
+
+impl fmt::Debug for ParallelOptions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ParallelOptions")
+            .field("degree", &self.degree)
+            .field("ordered", &self.ordered)
+            .field("token", &self.token)
+            .field("progress", &self.progress.is_some())
+            .finish()
+    }
+}
+
+impl Default for ParallelOptions {
+    fn default() -> Self {
+        ParallelOptions {
+            degree: emixcore::system::num_cpus().max(1),
+            ordered: true,
+            token: None,
+            progress: None,
+        }
+    }
+}
+
+impl ParallelOptions {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn with_degree(&self, degree: usize) -> Self {
+        ParallelOptions {
+            degree: degree.max(1),
+            ..self.clone()
+        }
+    }
+
+    pub fn with_ordered(&self, ordered: bool) -> Self {
+        ParallelOptions {
+            ordered,
+            ..self.clone()
+        }
+    }
+
+    /// Stops handing out items once `token` is cancelled.
+    pub fn with_token(&self, token: &CancellationToken) -> Self {
+        ParallelOptions {
+            token: Some(token.clone()),
+            ..self.clone()
+        }
+    }
+
+    /// Calls `progress` with the number of processed items and, when the iterator knows
+    /// its length, the total after each item.
+    pub fn with_progress(
+        &self,
+        progress: impl Fn(usize, Option<usize>) + Send + Sync + 'static,
+    ) -> Self {
+        ParallelOptions {
+            progress: Some(Arc::new(progress)),
+            ..self.clone()
+        }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.token
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+    }
+}
+
+/// Runs `f` for every item. Returns `Error::Canceled` if the token was cancelled before
+/// every item was processed.
+pub fn for_each<I, F>(items: I, options: &ParallelOptions, f: F) -> Result<()>
+where
+    I: IntoIterator,
+    I::IntoIter: Send,
+    I::Item: Send,
+    F: Fn(I::Item) + Sync,
+{
+    run(items, options, |item| {
+        f(item);
+        Ok(None::<()>)
+    })
+    .map(|_| ())
+}
+
+/// Runs `f` for every item and stops handing out items after the first error, which
+/// is returned.
+pub fn try_for_each<I, F>(items: I, options: &ParallelOptions, f: F) -> Result<()>
+where
+    I: IntoIterator,
+    I::IntoIter: Send,
+    I::Item: Send,
+    F: Fn(I::Item) -> Result<()> + Sync,
+{
+    run(items, options, |item| f(item).map(|_| None::<()>)).map(|_| ())
+}
+
+/// Maps every item with `f`. The results are in item order unless the options say
+/// otherwise, in which case they are in the order they finished.
+pub fn map<I, R, F>(items: I, options: &ParallelOptions, f: F) -> Result<Vec<R>>
+where
+    I: IntoIterator,
+    I::IntoIter: Send,
+    I::Item: Send,
+    R: Send,
+    F: Fn(I::Item) -> R + Sync,
+{
+    run(items, options, |item| Ok(Some(f(item))))
+}
+
+/// Maps every item with `f` and keeps the `Some` results.
+pub fn filter_map<I, R, F>(items: I, options: &ParallelOptions, f: F) -> Result<Vec<R>>
+where
+    I: IntoIterator,
+    I::IntoIter: Send,
+    I::Item: Send,
+    R: Send,
+    F: Fn(I::Item) -> Option<R> + Sync,
+{
+    run(items, options, |item| Ok(f(item)))
+}
+
+fn run<I, R, F>(items: I, options: &ParallelOptions, f: F) -> Result<Vec<R>>
+where
+    I: IntoIterator,
+    I::IntoIter: Send,
+    I::Item: Send,
+    R: Send,
+    F: Fn(I::Item) -> Result<Option<R>> + Sync,
+{
+    let iter = items.into_iter().enumerate();
+    let total = match iter.size_hint() {
+        (lower, Some(upper)) if lower == upper => Some(upper),
+        _ => None,
+    };
+    let iter = Mutex::new(iter);
+    let results = Mutex::new(Vec::new());
+    let error = Mutex::new(None);
+    let stopped = AtomicBool::new(false);
+    let done = AtomicUsize::new(0);
+
+    thread::scope(|scope| {
+        for _ in 0..options.degree {
+            scope.spawn(|| {
+                loop {
+                    if stopped.load(Ordering::SeqCst) || options.is_cancelled() {
+                        break;
+                    }
+
+                    let Some((index, item)) = iter.lock().unwrap().next() else {
+                        break;
+                    };
+
+                    match f(item) {
+                        Ok(Some(result)) => results.lock().unwrap().push((index, result)),
+                        Ok(None) => {}
+                        Err(e) => {
+                            stopped.store(true, Ordering::SeqCst);
+                            error.lock().unwrap().get_or_insert(e);
+                            break;
+                        }
+                    }
+
+                    let done = done.fetch_add(1, Ordering::SeqCst) + 1;
+
+                    if let Some(progress) = &options.progress {
+                        progress(done, total);
+                    }
+                }
+            });
+        }
+    });
+
+    if let Some(e) = error.into_inner().unwrap() {
+        return Err(e);
+    }
+
+    // Items may all have been handed out just before the token was cancelled.
+    if options.is_cancelled() && iter.into_inner().unwrap().next().is_some() {
+        return Err(Error::Canceled);
+    }
+
+    let mut results = results.into_inner().unwrap();
+
+    if options.ordered {
+        results.sort_unstable_by_key(|(index, _)| *index);
+    }
+
+    Ok(results.into_iter().map(|(_, result)| result).collect())
+}
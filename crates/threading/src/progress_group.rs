@@ -0,0 +1,485 @@
+use indicatif::*;
+use std::{
+    borrow::Cow,
+    fmt,
+    io::IsTerminal,
+    sync::{Arc, Mutex, Weak},
+    thread,
+    time::Duration,
+};
+
+use crate::{Error, Result, constants::*};
+
+const ERR_FINISHED: &str = "Progress task is already finished.";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum TaskKind {
+    Spinner,
+    Bar,
+    Bytes,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[must_use]
+pub struct ProgressGroupOptions {
+    /// Forces plain log lines on or off. When unset, plain output is used if stdout is
+    /// not a terminal.
+    pub plain: Option<bool>,
+    /// How often plain mode logs the state of unfinished tasks.
+    pub log_interval: Duration,
+}
+
+impl Default for ProgressGroupOptions {
+    fn default() -> Self {
+        ProgressGroupOptions {
+            plain: None,
+            log_interval: PROGRESS_LOG_INTERVAL_DEF,
+        }
+    }
+}
+
+impl ProgressGroupOptions {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn with_plain(&self, plain: bool) -> Self {
+        ProgressGroupOptions {
+            plain: Some(plain),
+            ..self.clone()
+        }
+    }
+
+    pub fn with_log_interval(&self, log_interval: Duration) -> Self {
+        ProgressGroupOptions {
+            log_interval,
+            ..self.clone()
+        }
+    }
+}
+
+#[derive(Debug)]
+struct TaskEntry {
+    name: String,
+    depth: usize,
+    kind: TaskKind,
+    bar: ProgressBar,
+}
+
+impl TaskEntry {
+    fn status_line(&self) -> String {
+        let mut line = self.name.clone();
+        let position = self.bar.position();
+
+        match (self.kind, self.bar.length()) {
+            (TaskKind::Spinner, _) | (_, None) => {}
+            (TaskKind::Bar, Some(length)) => {
+                line.push_str(&format!(" {}/{}", position, length));
+            }
+            (TaskKind::Bytes, Some(length)) => {
+                line.push_str(&format!(
+                    " {}/{} ({}/s)",
+                    HumanBytes(position),
+                    HumanBytes(length),
+                    HumanBytes(self.bar.per_sec() as u64)
+                ));
+            }
+        }
+
+        if let (TaskKind::Bar | TaskKind::Bytes, Some(length)) = (self.kind, self.bar.length()) {
+            let percent = (position.min(length) * 100)
+                .checked_div(length)
+                .unwrap_or(100);
+            line.push_str(&format!(" {}%", percent));
+
+            if !self.bar.is_finished() && position < length {
+                line.push_str(&format!(", eta {}", HumanDuration(self.bar.eta())));
+            }
+        }
+
+        if self.bar.is_finished() {
+            line.push_str(" done");
+        }
+
+        let message = self.bar.message();
+
+        if !message.is_empty() {
+            line.push_str(&format!(" - {}", message));
+        }
+
+        line
+    }
+}
+
+#[derive(Debug)]
+struct GroupInner {
+    multi: MultiProgress,
+    plain: bool,
+    tasks: Mutex<Vec<Arc<TaskEntry>>>,
+}
+
+impl GroupInner {
+    fn add(
+        this: &Arc<Self>,
+        parent: Option<&Arc<TaskEntry>>,
+        name: &str,
+        kind: TaskKind,
+        length: Option<u64>,
+    ) -> ProgressTask {
+        let (name, depth) = match parent {
+            Some(parent) => (format!("{}/{}", parent.name, name), parent.depth + 1),
+            None => (name.to_string(), 0),
+        };
+        let bar = match length {
+            Some(length) => ProgressBar::new(length),
+            None => ProgressBar::new_spinner(),
+        };
+        bar.set_style(Self::style(kind));
+        bar.set_prefix(format!(
+            "{}{}",
+            "  ".repeat(depth),
+            name.rsplit('/').next().unwrap()
+        ));
+        let mut tasks = this.tasks.lock().unwrap();
+        // Children go after the last task of their parent's subtree.
+        let index = match parent {
+            Some(parent) => {
+                let start = tasks
+                    .iter()
+                    .position(|it| Arc::ptr_eq(it, parent))
+                    .map_or(tasks.len(), |i| i + 1);
+                tasks[start..]
+                    .iter()
+                    .position(|it| it.depth <= parent.depth)
+                    .map_or(tasks.len(), |i| start + i)
+            }
+            None => tasks.len(),
+        };
+        let bar = this.multi.insert(index, bar);
+
+        if kind == TaskKind::Spinner && !this.plain {
+            bar.enable_steady_tick(Duration::from_millis(INTERVAL));
+        }
+
+        let entry = Arc::new(TaskEntry {
+            name,
+            depth,
+            kind,
+            bar,
+        });
+        tasks.insert(index, entry.clone());
+        ProgressTask {
+            group: this.clone(),
+            entry,
+        }
+    }
+
+    fn style(kind: TaskKind) -> ProgressStyle {
+        match kind {
+            TaskKind::Spinner => ProgressStyle::with_template("{spinner:.green} {prefix} {msg}")
+                .unwrap()
+                .tick_chars("⣾⣽⣻⢿⡿⣟⣯⣷"),
+            TaskKind::Bar => ProgressStyle::with_template(
+                "{prefix} [{bar:40.cyan/blue}] {pos}/{len} ({eta}) {msg}",
+            )
+            .unwrap()
+            .progress_chars("=> "),
+            TaskKind::Bytes => ProgressStyle::with_template(
+                "{prefix} [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({bytes_per_sec}, {eta}) {msg}",
+            )
+            .unwrap()
+            .progress_chars("=> "),
+        }
+    }
+
+    fn println(&self, line: &str) {
+        if self.plain {
+            println!("{}", line);
+        } else {
+            let _ = self.multi.println(line);
+        }
+    }
+
+    fn status_lines(&self, unfinished_only: bool) -> Vec<String> {
+        self.tasks
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|it| !unfinished_only || !it.bar.is_finished())
+            .map(|it| it.status_line())
+            .collect()
+    }
+}
+
+/// Shows several spinners and progress bars on one terminal.
+///
+/// Tasks can have children, which are drawn indented under their parent and named after
+/// it, as in `build/compile`. When stdout is not a terminal, such as under CI or when
+/// piped to a file, nothing is drawn; instead the group logs a line for every unfinished
+/// task each `log_interval` and one when a task finishes.
+#[derive(Clone)]
+#[must_use]
+pub struct ProgressGroup {
+    inner: Arc<GroupInner>,
+}
+
+impl fmt::Debug for ProgressGroup {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ProgressGroup")
+            .field("plain", &self.inner.plain)
+            .field("tasks", &self.inner.tasks)
+            .finish()
+    }
+}
+
+impl Default for ProgressGroup {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProgressGroup {
+    pub fn new() -> Self {
+        Self::with_options(ProgressGroupOptions::default())
+    }
+
+    pub fn with_options(options: ProgressGroupOptions) -> Self {
+        let plain = options
+            .plain
+            .unwrap_or_else(|| !std::io::stdout().is_terminal());
+        let target = if plain {
+            ProgressDrawTarget::hidden()
+        } else {
+            ProgressDrawTarget::stdout()
+        };
+        let inner = Arc::new(GroupInner {
+            multi: MultiProgress::with_draw_target(target),
+            plain,
+            tasks: Mutex::new(Vec::new()),
+        });
+
+        if plain && !options.log_interval.is_zero() {
+            Self::spawn_logger(Arc::downgrade(&inner), options.log_interval);
+        }
+
+        Self { inner }
+    }
+
+    fn spawn_logger(inner: Weak<GroupInner>, interval: Duration) {
+        thread::spawn(move || {
+            loop {
+                thread::sleep(interval);
+
+                let Some(inner) = inner.upgrade() else {
+                    break;
+                };
+
+                for line in inner.status_lines(true) {
+                    inner.println(&line);
+                }
+            }
+        });
+    }
+
+    /// Gets whether the group logs plain lines instead of drawing.
+    pub fn is_plain(&self) -> bool {
+        self.inner.plain
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.tasks.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Adds a spinner for a task of unknown length.
+    pub fn add_spinner(&self, name: &str) -> ProgressTask {
+        GroupInner::add(&self.inner, None, name, TaskKind::Spinner, None)
+    }
+
+    /// Adds a bar that counts items up to `length`.
+    pub fn add_bar(&self, name: &str, length: u64) -> ProgressTask {
+        GroupInner::add(&self.inner, None, name, TaskKind::Bar, Some(length))
+    }
+
+    /// Adds a bar that counts `length` bytes and shows the transfer rate.
+    pub fn add_bytes(&self, name: &str, length: u64) -> ProgressTask {
+        GroupInner::add(&self.inner, None, name, TaskKind::Bytes, Some(length))
+    }
+
+    /// Gets a line describing each task, in display order.
+    pub fn status_lines(&self) -> Vec<String> {
+        self.inner.status_lines(false)
+    }
+
+    /// Prints a line above the bars, or to stdout in plain mode.
+    pub fn println(&self, line: &str) {
+        self.inner.println(line);
+    }
+
+    pub fn suspend<F: FnOnce() -> R, R>(&self, f: F) -> R {
+        self.inner.multi.suspend(f)
+    }
+
+    pub fn clear(&self) -> Result<()> {
+        self.inner.multi.clear().map_err(Error::from_std_error)
+    }
+}
+
+/// A spinner or bar in a `ProgressGroup`.
+#[derive(Clone)]
+#[must_use]
+pub struct ProgressTask {
+    group: Arc<GroupInner>,
+    entry: Arc<TaskEntry>,
+}
+
+impl fmt::Debug for ProgressTask {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ProgressTask")
+            .field("entry", &self.entry)
+            .finish()
+    }
+}
+
+impl ProgressTask {
+    /// Gets the full name of the task, including its parents' names.
+    pub fn name(&self) -> &str {
+        &self.entry.name
+    }
+
+    /// Gets how deep the task is nested, starting at zero for top-level tasks.
+    pub fn depth(&self) -> usize {
+        self.entry.depth
+    }
+
+    pub fn add_spinner(&self, name: &str) -> ProgressTask {
+        GroupInner::add(
+            &self.group,
+            Some(&self.entry),
+            name,
+            TaskKind::Spinner,
+            None,
+        )
+    }
+
+    pub fn add_bar(&self, name: &str, length: u64) -> ProgressTask {
+        GroupInner::add(
+            &self.group,
+            Some(&self.entry),
+            name,
+            TaskKind::Bar,
+            Some(length),
+        )
+    }
+
+    pub fn add_bytes(&self, name: &str, length: u64) -> ProgressTask {
+        GroupInner::add(
+            &self.group,
+            Some(&self.entry),
+            name,
+            TaskKind::Bytes,
+            Some(length),
+        )
+    }
+
+    pub fn message(&self) -> String {
+        self.entry.bar.message()
+    }
+
+    pub fn set_message(&self, message: impl Into<Cow<'static, str>>) {
+        self.entry.bar.set_message(message);
+    }
+
+    pub fn position(&self) -> u64 {
+        self.entry.bar.position()
+    }
+
+    pub fn set_position(&self, position: u64) {
+        self.entry.bar.set_position(position);
+    }
+
+    pub fn inc(&self, delta: u64) {
+        self.entry.bar.inc(delta);
+    }
+
+    pub fn length(&self) -> Option<u64> {
+        self.entry.bar.length()
+    }
+
+    pub fn set_length(&self, length: u64) {
+        self.entry.bar.set_length(length);
+    }
+
+    pub fn tick(&self) {
+        self.entry.bar.tick();
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.entry.bar.elapsed()
+    }
+
+    pub fn eta(&self) -> Duration {
+        self.entry.bar.eta()
+    }
+
+    /// Gets the average number of steps, or bytes, per second.
+    pub fn per_sec(&self) -> f64 {
+        self.entry.bar.per_sec()
+    }
+
+    /// Gets a line describing the task, as logged in plain mode.
+    pub fn status_line(&self) -> String {
+        self.entry.status_line()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.entry.bar.is_finished()
+    }
+
+    fn check_finished(&self) -> Result<()> {
+        if self.is_finished() {
+            return Err(Error::InvalidOperation(ERR_FINISHED.into()));
+        }
+
+        Ok(())
+    }
+
+    fn log_finished(&self) {
+        if self.group.plain {
+            self.group.println(&self.status_line());
+        }
+    }
+
+    /// Marks the task as finished. A bar is filled up to its length.
+    pub fn finish(&self) -> Result<()> {
+        self.check_finished()?;
+        self.entry.bar.finish();
+        self.log_finished();
+        Ok(())
+    }
+
+    pub fn finish_with_message(&self, message: impl Into<Cow<'static, str>>) -> Result<()> {
+        self.check_finished()?;
+        self.entry.bar.finish_with_message(message);
+        self.log_finished();
+        Ok(())
+    }
+
+    /// Marks the task as finished, leaving a bar where it stopped.
+    pub fn abandon(&self) -> Result<()> {
+        self.check_finished()?;
+        self.entry.bar.abandon();
+        self.log_finished();
+        Ok(())
+    }
+
+    pub fn abandon_with_message(&self, message: impl Into<Cow<'static, str>>) -> Result<()> {
+        self.check_finished()?;
+        self.entry.bar.abandon_with_message(message);
+        self.log_finished();
+        Ok(())
+    }
+}
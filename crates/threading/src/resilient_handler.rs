@@ -0,0 +1,213 @@
+use std::{cell::Cell, fmt, sync::Arc, thread, time::Duration};
+
+use crate::{constants::*, dataflow::DataflowTarget, *};
+
+thread_local! {
+    /// Set by `process` when an item was requeued or dead-lettered instead of processed,
+    /// so the following `on_completed` on the same worker thread skips the inner handler.
+    static DIVERTED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// What a `ResilientHandler` does with an item while its circuit is open.
+pub enum OpenCircuitAction<T> {
+    /// Puts the item back on the queue it came from after waiting up to the requeue
+    /// delay. The worker does not wait for room, so if the queue is full the item fails
+    /// with `Error::CircuitOpen`. Rendezvous queues, with a capacity of zero, are rejected.
+    Requeue,
+    /// Posts the item to another target, such as a dead-letter queue.
+    DeadLetter(Arc<dyn DataflowTarget<T>>),
+}
+
+impl<T> Clone for OpenCircuitAction<T> {
+    fn clone(&self) -> Self {
+        match self {
+            OpenCircuitAction::Requeue => OpenCircuitAction::Requeue,
+            OpenCircuitAction::DeadLetter(target) => OpenCircuitAction::DeadLetter(target.clone()),
+        }
+    }
+}
+
+impl<T> fmt::Debug for OpenCircuitAction<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OpenCircuitAction::Requeue => write!(f, "Requeue"),
+            OpenCircuitAction::DeadLetter(target) => {
+                f.debug_tuple("DeadLetter").field(target).finish()
+            }
+        }
+    }
+}
+
+/// Wraps a handler with a `CircuitBreaker`, a `Bulkhead`, or both.
+///
+/// While the circuit is open, `process` is not called: the item is requeued or
+/// dead-lettered and the inner handler's `on_completed` is skipped for it. Errors and
+/// `TaskResult::Error` or `TaskResult::TimedOut` results count as failures.
+#[must_use]
+pub struct ResilientHandler<H, T> {
+    handler: H,
+    breaker: Option<(CircuitBreaker, OpenCircuitAction<T>)>,
+    bulkhead: Option<Bulkhead>,
+    requeue_delay: Duration,
+}
+
+impl<H: Clone, T> Clone for ResilientHandler<H, T> {
+    fn clone(&self) -> Self {
+        ResilientHandler {
+            handler: self.handler.clone(),
+            breaker: self.breaker.clone(),
+            bulkhead: self.bulkhead.clone(),
+            requeue_delay: self.requeue_delay,
+        }
+    }
+}
+
+impl<H: fmt::Debug, T> fmt::Debug for ResilientHandler<H, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ResilientHandler")
+            .field("handler", &self.handler)
+            .field("breaker", &self.breaker)
+            .field("bulkhead", &self.bulkhead)
+            .field("requeue_delay", &self.requeue_delay)
+            .finish()
+    }
+}
+
+impl<H: Clone, T> ResilientHandler<H, T> {
+    pub fn new(handler: H) -> Self {
+        ResilientHandler {
+            handler,
+            breaker: None,
+            bulkhead: None,
+            requeue_delay: PAUSE_TIMEOUT_DEF,
+        }
+    }
+
+    pub fn with_circuit_breaker(
+        &self,
+        breaker: &CircuitBreaker,
+        action: OpenCircuitAction<T>,
+    ) -> Self {
+        ResilientHandler {
+            breaker: Some((breaker.clone(), action)),
+            ..self.clone()
+        }
+    }
+
+    pub fn with_bulkhead(&self, bulkhead: &Bulkhead) -> Self {
+        ResilientHandler {
+            bulkhead: Some(bulkhead.clone()),
+            ..self.clone()
+        }
+    }
+
+    /// Sets the longest a worker waits for the cooldown before requeueing an item, so an
+    /// open circuit does not spin through the queue.
+    pub fn with_requeue_delay(&self, requeue_delay: Duration) -> Self {
+        ResilientHandler {
+            requeue_delay,
+            ..self.clone()
+        }
+    }
+}
+
+impl<H, T> ResilientHandler<H, T> {
+    fn enter_bulkhead(&self) -> Result<Option<BulkheadPermit>> {
+        match &self.bulkhead {
+            Some(bulkhead) => Ok(Some(bulkhead.enter()?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Requeues or dead-letters an item the open circuit rejected. Returns false if the
+    /// item could not be requeued because the queue is full or no longer accepts items.
+    fn divert<TPC: TaskQueue<T>>(
+        &self,
+        pc: &TPC,
+        breaker: &CircuitBreaker,
+        action: &OpenCircuitAction<T>,
+        item: &T,
+    ) -> Result<bool>
+    where
+        T: StaticTaskItem,
+    {
+        match action {
+            OpenCircuitAction::Requeue => {
+                // The worker would wait on its own queue for a taker that never comes.
+                if pc.capacity() == Some(0) {
+                    return Err(Error::InvalidOperation(
+                        "Cannot requeue to a queue with no capacity".to_string(),
+                    ));
+                }
+
+                thread::sleep(breaker.remaining_cooldown()?.min(self.requeue_delay));
+
+                if pc.try_enqueue(item.clone()).is_err() {
+                    return Ok(false);
+                }
+            }
+            OpenCircuitAction::DeadLetter(target) => target.post(item.clone())?,
+        }
+
+        DIVERTED.set(true);
+        Ok(true)
+    }
+}
+
+impl<TPC, T, H> TaskDelegation<TPC, T> for ResilientHandler<H, T>
+where
+    TPC: AwaitableConsumer<T> + TaskQueue<T>,
+    T: StaticTaskItem,
+    H: TaskDelegation<TPC, T>,
+{
+    fn on_started(&self, pc: &TPC) {
+        self.handler.on_started(pc);
+    }
+
+    fn process(&self, pc: &TPC, item: &T) -> Result<TaskResult> {
+        let Some((breaker, action)) = &self.breaker else {
+            let _slot = self.enter_bulkhead()?;
+            return self.handler.process(pc, item);
+        };
+
+        let permit = match breaker.try_acquire() {
+            Ok(permit) => permit,
+            Err(Error::CircuitOpen) => {
+                if self.divert(pc, breaker, action, item)? {
+                    return Ok(TaskResult::None);
+                }
+
+                return Ok(TaskResult::Error(Error::CircuitOpen.to_string()));
+            }
+            Err(e) => return Err(e),
+        };
+
+        // Taken only once the circuit lets the item through, so a diverted item does not
+        // hold a slot while it waits out the requeue delay.
+        let _slot = self.enter_bulkhead()?;
+        let result = self.handler.process(pc, item);
+
+        match &result {
+            Ok(TaskResult::Error(_)) | Ok(TaskResult::TimedOut) | Err(_) => permit.failure()?,
+            Ok(_) => permit.success()?,
+        }
+
+        result
+    }
+
+    fn on_completed(&self, pc: &TPC, item: &T, result: &TaskResult) -> bool {
+        if DIVERTED.replace(false) {
+            return true;
+        }
+
+        self.handler.on_completed(pc, item, result)
+    }
+
+    fn on_cancelled(&self, pc: &TPC) {
+        self.handler.on_cancelled(pc);
+    }
+
+    fn on_finished(&self, pc: &TPC) {
+        self.handler.on_finished(pc);
+    }
+}
@@ -0,0 +1,115 @@
+use parking_lot::RwLock;
+use std::time::Duration;
+
+pub use parking_lot::{RwLockReadGuard, RwLockUpgradableReadGuard, RwLockWriteGuard};
+
+use crate::{Error, Result};
+
+/// A reader-writer lock with timeouts and upgradeable reads.
+///
+/// Any number of readers can hold the lock at once, or a single writer. One reader at a
+/// time can take an upgradeable read, which coexists with plain readers and can later be
+/// turned into a write lock without letting another writer in between. The lock is fair
+/// to writers, so a steady stream of readers cannot starve them.
+#[derive(Debug, Default)]
+pub struct ReaderWriterLock<T: ?Sized> {
+    inner: RwLock<T>,
+}
+
+impl<T> ReaderWriterLock<T> {
+    pub fn new(value: T) -> Self {
+        Self {
+            inner: RwLock::new(value),
+        }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner.into_inner()
+    }
+}
+
+impl<T: ?Sized> ReaderWriterLock<T> {
+    /// Blocks until a shared read lock is acquired.
+    pub fn read(&self) -> RwLockReadGuard<'_, T> {
+        self.inner.read()
+    }
+
+    /// Acquires a shared read lock if it is available without blocking.
+    pub fn try_read(&self) -> Option<RwLockReadGuard<'_, T>> {
+        self.inner.try_read()
+    }
+
+    /// Blocks until a shared read lock is acquired or the timeout expires.
+    pub fn read_timeout(&self, timeout: Duration) -> Result<RwLockReadGuard<'_, T>> {
+        self.inner.try_read_for(timeout).ok_or(Error::Timeout)
+    }
+
+    /// Blocks until the exclusive write lock is acquired.
+    pub fn write(&self) -> RwLockWriteGuard<'_, T> {
+        self.inner.write()
+    }
+
+    /// Acquires the exclusive write lock if it is available without blocking.
+    pub fn try_write(&self) -> Option<RwLockWriteGuard<'_, T>> {
+        self.inner.try_write()
+    }
+
+    /// Blocks until the exclusive write lock is acquired or the timeout expires.
+    pub fn write_timeout(&self, timeout: Duration) -> Result<RwLockWriteGuard<'_, T>> {
+        self.inner.try_write_for(timeout).ok_or(Error::Timeout)
+    }
+
+    /// Blocks until an upgradeable read lock is acquired.
+    pub fn upgradeable_read(&self) -> RwLockUpgradableReadGuard<'_, T> {
+        self.inner.upgradable_read()
+    }
+
+    /// Acquires an upgradeable read lock if it is available without blocking.
+    pub fn try_upgradeable_read(&self) -> Option<RwLockUpgradableReadGuard<'_, T>> {
+        self.inner.try_upgradable_read()
+    }
+
+    /// Blocks until an upgradeable read lock is acquired or the timeout expires.
+    pub fn upgradeable_read_timeout(
+        &self,
+        timeout: Duration,
+    ) -> Result<RwLockUpgradableReadGuard<'_, T>> {
+        self.inner
+            .try_upgradable_read_for(timeout)
+            .ok_or(Error::Timeout)
+    }
+
+    /// Turns an upgradeable read into a write lock, blocking until the other readers
+    /// have released theirs.
+    pub fn upgrade<'a>(guard: RwLockUpgradableReadGuard<'a, T>) -> RwLockWriteGuard<'a, T> {
+        RwLockUpgradableReadGuard::upgrade(guard)
+    }
+
+    /// Like `upgrade`, but gives the upgradeable read back if the other readers do not
+    /// release their locks before the timeout expires.
+    pub fn upgrade_timeout<'a>(
+        guard: RwLockUpgradableReadGuard<'a, T>,
+        timeout: Duration,
+    ) -> std::result::Result<RwLockWriteGuard<'a, T>, RwLockUpgradableReadGuard<'a, T>> {
+        RwLockUpgradableReadGuard::try_upgrade_for(guard, timeout)
+    }
+
+    /// Turns a write lock back into an upgradeable read without releasing it.
+    pub fn downgrade_to_upgradeable<'a>(
+        guard: RwLockWriteGuard<'a, T>,
+    ) -> RwLockUpgradableReadGuard<'a, T> {
+        RwLockWriteGuard::downgrade_to_upgradable(guard)
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.inner.is_locked()
+    }
+
+    pub fn is_locked_exclusive(&self) -> bool {
+        self.inner.is_locked_exclusive()
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        self.inner.get_mut()
+    }
+}
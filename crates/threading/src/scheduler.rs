@@ -0,0 +1,618 @@
+use chrono::{DateTime, TimeDelta, TimeZone, Utc};
+use std::{
+    collections::BTreeMap,
+    fmt,
+    panic::{self, AssertUnwindSafe},
+    str::FromStr,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    AutoResetCond, CancellationToken, Error, GracefulShutdown, Result, StaticTaskItem, TaskQueue,
+    constants::*,
+};
+
+/// A source of wall-clock time for the `Scheduler`.
+pub trait Clock: Send + Sync + fmt::Debug {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The system clock.
+#[derive(Default, Clone, Copy, Debug)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock that only moves when told to. Clones share the same time, so a test can keep
+/// one handle and give another to the scheduler, then `advance` and `tick`.
+#[derive(Clone, Debug)]
+pub struct TestClock {
+    now: Arc<Mutex<DateTime<Utc>>>,
+}
+
+impl Default for TestClock {
+    fn default() -> Self {
+        Self::new(DateTime::UNIX_EPOCH)
+    }
+}
+
+impl TestClock {
+    pub fn new(start: DateTime<Utc>) -> Self {
+        TestClock {
+            now: Arc::new(Mutex::new(start)),
+        }
+    }
+
+    pub fn set(&self, now: DateTime<Utc>) {
+        *self.now.lock().unwrap() = now;
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        let mut now = self.now.lock().unwrap();
+        *now = add_duration(*now, duration).unwrap_or(DateTime::<Utc>::MAX_UTC);
+    }
+}
+
+impl Clock for TestClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.lock().unwrap()
+    }
+}
+
+/// What to do when a job's scheduled time passed without it running, e.g. because the
+/// runner was busy, stopped, or the job was paused.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MissedRunPolicy {
+    /// Run once and continue from the next occurrence after now.
+    #[default]
+    Skip,
+    /// Run once for every missed occurrence, up to `MISSED_RUNS_MAX`.
+    CatchUp,
+}
+
+impl fmt::Display for MissedRunPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MissedRunPolicy::Skip => write!(f, "Skip"),
+            MissedRunPolicy::CatchUp => write!(f, "CatchUp"),
+        }
+    }
+}
+
+type CronNext = Arc<dyn Fn(&DateTime<Utc>) -> Option<DateTime<Utc>> + Send + Sync>;
+
+#[derive(Clone)]
+enum TriggerKind {
+    Delay(Duration),
+    Interval(Duration),
+    Cron { source: String, next: CronNext },
+}
+
+/// When a job runs.
+#[derive(Clone)]
+pub struct Trigger {
+    kind: TriggerKind,
+}
+
+impl fmt::Debug for Trigger {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.kind {
+            TriggerKind::Delay(delay) => f.debug_tuple("Delay").field(delay).finish(),
+            TriggerKind::Interval(every) => f.debug_tuple("Interval").field(every).finish(),
+            TriggerKind::Cron { source, .. } => f.debug_tuple("Cron").field(source).finish(),
+        }
+    }
+}
+
+impl Trigger {
+    /// Runs once, `delay` after the job is scheduled.
+    pub fn delay(delay: Duration) -> Self {
+        Trigger {
+            kind: TriggerKind::Delay(delay),
+        }
+    }
+
+    /// Runs every `every`, starting `every` after the job is scheduled.
+    pub fn interval(every: Duration) -> Self {
+        Trigger {
+            kind: TriggerKind::Interval(every),
+        }
+    }
+
+    /// Runs on a cron expression evaluated in UTC. The expression includes a seconds
+    /// field, e.g. `"0 30 9 * * Mon-Fri"`.
+    pub fn cron(expression: &str) -> Result<Self> {
+        Self::cron_in(expression, Utc)
+    }
+
+    /// Runs on a cron expression evaluated in the given time zone.
+    pub fn cron_in<Tz>(expression: &str, tz: Tz) -> Result<Self>
+    where
+        Tz: TimeZone + Send + Sync + 'static,
+    {
+        let schedule =
+            cron::Schedule::from_str(expression).map_err(|e| Error::Parse(e.to_string()))?;
+        let next = move |after: &DateTime<Utc>| {
+            schedule
+                .after(&after.with_timezone(&tz))
+                .next()
+                .map(|t| t.with_timezone(&Utc))
+        };
+        Ok(Trigger {
+            kind: TriggerKind::Cron {
+                source: expression.to_string(),
+                next: Arc::new(next),
+            },
+        })
+    }
+
+    fn first(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match &self.kind {
+            TriggerKind::Delay(delay) => add_duration(now, *delay),
+            TriggerKind::Interval(every) => add_duration(now, *every),
+            TriggerKind::Cron { next, .. } => next(&now),
+        }
+    }
+
+    /// The occurrence that follows `prev`.
+    fn following(&self, prev: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match &self.kind {
+            TriggerKind::Delay(_) => None,
+            TriggerKind::Interval(every) => add_duration(prev, *every),
+            TriggerKind::Cron { next, .. } => next(&prev),
+        }
+    }
+
+    /// The first occurrence after `now`, keeping interval jobs aligned with `prev`.
+    fn after(&self, prev: DateTime<Utc>, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match &self.kind {
+            TriggerKind::Delay(_) => None,
+            TriggerKind::Interval(every) => {
+                let every = every.as_nanos().max(1);
+                let elapsed = (now - prev).num_nanoseconds().unwrap_or(i64::MAX).max(0) as u128;
+                let steps = elapsed / every + 1;
+                let nanos = u64::try_from(steps * every).ok()?;
+                add_duration(prev, Duration::from_nanos(nanos))
+            }
+            TriggerKind::Cron { next, .. } => next(&now),
+        }
+    }
+}
+
+/// Per-job settings.
+#[derive(Default, Clone, Debug, PartialEq, Eq)]
+#[must_use]
+pub struct JobOptions {
+    /// Each run is delayed by a random amount up to this value.
+    pub jitter: Duration,
+    pub missed_runs: MissedRunPolicy,
+    /// Adds the job in the paused state.
+    pub paused: bool,
+}
+
+impl JobOptions {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn with_jitter(&self, jitter: Duration) -> Self {
+        JobOptions {
+            jitter,
+            ..self.clone()
+        }
+    }
+
+    pub fn with_missed_runs(&self, missed_runs: MissedRunPolicy) -> Self {
+        JobOptions {
+            missed_runs,
+            ..self.clone()
+        }
+    }
+
+    pub fn with_paused(&self, paused: bool) -> Self {
+        JobOptions {
+            paused,
+            ..self.clone()
+        }
+    }
+}
+
+/// Identifies a job added to a `Scheduler`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct JobId(u64);
+
+type JobAction = Arc<dyn Fn() -> Result<()> + Send + Sync>;
+
+struct Job {
+    trigger: Trigger,
+    options: JobOptions,
+    action: JobAction,
+    /// The next occurrence without jitter.
+    next: Option<DateTime<Utc>>,
+    /// When the job actually runs next; `next` plus jitter.
+    due: DateTime<Utc>,
+    paused: bool,
+    running: bool,
+}
+
+/// xorshift64*; good enough to spread jitter and cheap to seed for tests.
+#[derive(Debug)]
+struct Rng(u64);
+
+impl Rng {
+    fn from_time() -> Self {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or_default();
+        Self::new(seed)
+    }
+
+    fn new(seed: u64) -> Self {
+        Rng(seed ^ 0x9E37_79B9_7F4A_7C15 | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    fn jitter(&mut self, max: Duration) -> Duration {
+        let max = max.as_nanos().min(u64::MAX as u128) as u64;
+
+        if max == 0 {
+            return Duration::ZERO;
+        }
+
+        Duration::from_nanos(self.next() % max.saturating_add(1))
+    }
+}
+
+/// Runs closures, or enqueues items into a consumer, on delays, intervals or cron
+/// expressions.
+///
+/// Jobs are run by `tick`, which executes everything that is due according to the
+/// scheduler's `Clock`. `start` spawns a thread that calls `tick` as jobs become due;
+/// tests can instead drive a `TestClock` and call `tick` directly. Jobs run on the
+/// ticking thread, so long work should be handed to a consumer with `schedule_enqueue`.
+/// A job that returns an error or panics is removed.
+#[derive(Clone)]
+pub struct Scheduler {
+    clock: Arc<dyn Clock>,
+    jobs: Arc<Mutex<BTreeMap<u64, Job>>>,
+    next_id: Arc<AtomicU64>,
+    rng: Arc<Mutex<Rng>>,
+    runner: Arc<Mutex<Option<CancellationToken>>>,
+    wake: AutoResetCond,
+}
+
+impl fmt::Debug for Scheduler {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Scheduler")
+            .field("clock", &self.clock)
+            .field("jobs", &self.len())
+            .field("running", &self.is_running())
+            .finish()
+    }
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        Self::with_clock(SystemClock)
+    }
+
+    pub fn with_clock(clock: impl Clock + 'static) -> Self {
+        Scheduler {
+            clock: Arc::new(clock),
+            jobs: Arc::new(Mutex::new(BTreeMap::new())),
+            next_id: Arc::new(AtomicU64::new(1)),
+            rng: Arc::new(Mutex::new(Rng::from_time())),
+            runner: Arc::new(Mutex::new(None)),
+            wake: AutoResetCond::new_unset(),
+        }
+    }
+
+    /// Reseeds the jitter generator so runs are reproducible.
+    pub fn set_seed(&self, seed: u64) {
+        *self.rng.lock().unwrap() = Rng::new(seed);
+    }
+
+    pub fn now(&self) -> DateTime<Utc> {
+        self.clock.now()
+    }
+
+    pub fn len(&self) -> usize {
+        self.jobs.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn contains(&self, id: JobId) -> bool {
+        self.jobs.lock().unwrap().contains_key(&id.0)
+    }
+
+    /// Adds a job. Returning an error from `action` removes the job.
+    pub fn schedule<F>(&self, trigger: Trigger, options: JobOptions, action: F) -> Result<JobId>
+    where
+        F: Fn() -> Result<()> + Send + Sync + 'static,
+    {
+        if let TriggerKind::Interval(every) = trigger.kind
+            && every.is_zero()
+        {
+            return Err(Error::InvalidInput(
+                "Interval must be greater than zero".to_string(),
+            ));
+        }
+
+        let Some(next) = trigger.first(self.now()) else {
+            return Err(Error::InvalidInput(format!(
+                "{:?} has no upcoming runs",
+                trigger
+            )));
+        };
+
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let due = self.jittered(next, options.jitter);
+        let job = Job {
+            trigger,
+            paused: options.paused,
+            options,
+            action: Arc::new(action),
+            next: Some(next),
+            due,
+            running: false,
+        };
+        self.jobs.lock().unwrap().insert(id, job);
+        let _ = self.wake.set();
+        Ok(JobId(id))
+    }
+
+    /// Adds a job that enqueues a clone of `item` into `queue` on every run. The job is
+    /// removed once the queue stops accepting items.
+    pub fn schedule_enqueue<Q, T>(
+        &self,
+        trigger: Trigger,
+        options: JobOptions,
+        queue: &Q,
+        item: T,
+    ) -> Result<JobId>
+    where
+        Q: TaskQueue<T>,
+        T: StaticTaskItem,
+    {
+        let queue = queue.clone();
+        self.schedule(trigger, options, move || queue.enqueue(item.clone()))
+    }
+
+    /// Removes a job. Returns false if it does not exist.
+    pub fn unschedule(&self, id: JobId) -> bool {
+        self.jobs.lock().unwrap().remove(&id.0).is_some()
+    }
+
+    pub fn clear(&self) {
+        self.jobs.lock().unwrap().clear();
+    }
+
+    pub fn pause(&self, id: JobId) -> bool {
+        let mut jobs = self.jobs.lock().unwrap();
+        let Some(job) = jobs.get_mut(&id.0) else {
+            return false;
+        };
+        job.paused = true;
+        true
+    }
+
+    /// Resumes a paused job. Occurrences that passed while it was paused are handled by
+    /// the job's `MissedRunPolicy` on the next tick.
+    pub fn resume(&self, id: JobId) -> bool {
+        let resumed = {
+            let mut jobs = self.jobs.lock().unwrap();
+            let Some(job) = jobs.get_mut(&id.0) else {
+                return false;
+            };
+            job.paused = false;
+            true
+        };
+        let _ = self.wake.set();
+        resumed
+    }
+
+    pub fn is_paused(&self, id: JobId) -> Option<bool> {
+        self.jobs.lock().unwrap().get(&id.0).map(|job| job.paused)
+    }
+
+    /// Returns when the job will run next, including jitter.
+    pub fn next_run(&self, id: JobId) -> Option<DateTime<Utc>> {
+        self.jobs.lock().unwrap().get(&id.0).map(|job| job.due)
+    }
+
+    /// Runs every job that is due and returns the number of runs.
+    pub fn tick(&self) -> usize {
+        let now = self.now();
+        let due = self.take_due(now);
+        let mut runs = 0;
+
+        for (id, action, count) in due {
+            let mut result = Ok(());
+
+            for _ in 0..count {
+                runs += 1;
+                result = panic::catch_unwind(AssertUnwindSafe(|| action()))
+                    .unwrap_or_else(|_| Err(Error::Other("Job panicked".to_string())));
+
+                if result.is_err() {
+                    break;
+                }
+            }
+
+            let mut jobs = self.jobs.lock().unwrap();
+            let Some(job) = jobs.get_mut(&id) else {
+                continue;
+            };
+            job.running = false;
+
+            if result.is_err() || job.next.is_none() {
+                jobs.remove(&id);
+            }
+        }
+
+        runs
+    }
+
+    /// Marks the due jobs as running, advances their schedules and returns how many
+    /// times each should run.
+    fn take_due(&self, now: DateTime<Utc>) -> Vec<(u64, JobAction, usize)> {
+        let mut jobs = self.jobs.lock().unwrap();
+        let mut due = Vec::new();
+
+        for (id, job) in jobs.iter_mut() {
+            if job.paused || job.running || job.due > now {
+                continue;
+            }
+
+            let Some(mut next) = job.next else {
+                continue;
+            };
+            let mut count = 1;
+
+            match job.options.missed_runs {
+                MissedRunPolicy::Skip => job.next = job.trigger.after(next, now),
+                MissedRunPolicy::CatchUp => {
+                    job.next = job.trigger.following(next);
+
+                    while let Some(following) = job.next
+                        && following <= now
+                        && count < MISSED_RUNS_MAX
+                    {
+                        count += 1;
+                        next = following;
+                        job.next = job.trigger.following(next);
+                    }
+
+                    if job.next.is_some_and(|following| following <= now) {
+                        job.next = job.trigger.after(next, now);
+                    }
+                }
+            }
+
+            if let Some(next) = job.next {
+                job.due = self.jittered(next, job.options.jitter);
+            }
+
+            job.running = true;
+            due.push((*id, job.action.clone(), count));
+        }
+
+        due
+    }
+
+    fn jittered(&self, at: DateTime<Utc>, jitter: Duration) -> DateTime<Utc> {
+        if jitter.is_zero() {
+            return at;
+        }
+
+        let jitter = self.rng.lock().unwrap().jitter(jitter);
+        add_duration(at, jitter).unwrap_or(at)
+    }
+
+    /// How long the runner can sleep before the next job is due.
+    fn idle_time(&self) -> Duration {
+        let now = self.now();
+        let jobs = self.jobs.lock().unwrap();
+        jobs.values()
+            .filter(|job| !job.paused && !job.running)
+            .map(|job| (job.due - now).to_std().unwrap_or(Duration::ZERO))
+            .min()
+            .unwrap_or(SCHEDULER_POLL_MAX)
+            .min(SCHEDULER_POLL_MAX)
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.runner.lock().unwrap().is_some()
+    }
+
+    /// Spawns a thread that runs jobs as they become due.
+    pub fn start(&self) -> Result<()> {
+        let token = {
+            let mut runner = self.runner.lock().unwrap();
+
+            if runner.is_some() {
+                return Err(Error::InvalidOperation(
+                    "Scheduler is already running".to_string(),
+                ));
+            }
+
+            let token = CancellationToken::new();
+            *runner = Some(token.clone());
+            token
+        };
+        let wake = self.wake.clone();
+        token.on_cancel(move |_| {
+            let _ = wake.set();
+        });
+
+        let this = self.clone();
+        thread::spawn(move || {
+            while !token.is_cancelled() {
+                this.tick();
+
+                if token.is_cancelled() {
+                    break;
+                }
+
+                let _ = this.wake.wait_timeout(this.idle_time());
+            }
+        });
+        Ok(())
+    }
+
+    /// Stops the runner thread. Jobs are kept and run again after `start`.
+    pub fn stop(&self) {
+        if let Some(token) = self.runner.lock().unwrap().take() {
+            token.cancel();
+        }
+    }
+
+    /// Stops the runner when `token` is cancelled.
+    pub fn stop_on(&self, token: &CancellationToken) {
+        let this = self.clone();
+        token.on_cancel(move |_| this.stop());
+    }
+}
+
+impl GracefulShutdown for Scheduler {
+    fn drain(&self) {
+        self.stop();
+    }
+
+    fn cancel(&self) {
+        self.stop();
+    }
+
+    fn is_finished(&self) -> bool {
+        !self.is_running()
+    }
+}
+
+fn add_duration(at: DateTime<Utc>, duration: Duration) -> Option<DateTime<Utc>> {
+    at.checked_add_signed(TimeDelta::from_std(duration).ok()?)
+}
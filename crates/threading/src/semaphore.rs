@@ -0,0 +1,156 @@
+use std::{
+    sync::{Arc, Condvar, Mutex},
+    time::{Duration, Instant},
+};
+use tokio::{sync::Notify, time};
+
+use crate::{Error, Result};
+
+#[derive(Debug)]
+struct SemaphoreInner {
+    count: Mutex<usize>,
+    cvar: Condvar,
+    notify: Notify,
+}
+
+/// A counting semaphore that can be waited on from both threads and async tasks.
+///
+/// Modeled after .NET's `SemaphoreSlim`: `wait` takes one slot, `release` returns slots
+/// and fails if that would exceed `max_count`.
+#[derive(Clone, Debug)]
+pub struct SemaphoreSlim {
+    inner: Arc<SemaphoreInner>,
+    max_count: usize,
+}
+
+impl SemaphoreSlim {
+    /// Creates a semaphore with `initial_count` free slots and no upper bound.
+    pub fn new(initial_count: usize) -> Self {
+        Self::with_max(initial_count, usize::MAX)
+    }
+
+    /// Creates a semaphore with `initial_count` free slots that can never hold more than
+    /// `max_count`.
+    pub fn with_max(initial_count: usize, max_count: usize) -> Self {
+        Self {
+            inner: Arc::new(SemaphoreInner {
+                count: Mutex::new(initial_count.min(max_count)),
+                cvar: Condvar::new(),
+                notify: Notify::new(),
+            }),
+            max_count,
+        }
+    }
+
+    /// Gets the number of free slots.
+    pub fn current_count(&self) -> Result<usize> {
+        let guard = Error::handle_poison_error(self.inner.count.lock())?;
+        Ok(*guard)
+    }
+
+    /// Gets the maximum number of free slots.
+    pub fn max_count(&self) -> usize {
+        self.max_count
+    }
+
+    /// Takes a slot if one is free without waiting.
+    pub fn try_wait(&self) -> Result<bool> {
+        let mut guard = Error::handle_poison_error(self.inner.count.lock())?;
+
+        if *guard == 0 {
+            return Ok(false);
+        }
+
+        *guard -= 1;
+        Ok(true)
+    }
+
+    /// Blocks until a slot is free and takes it.
+    pub fn wait(&self) -> Result<()> {
+        let mut guard = Error::handle_poison_error(self.inner.count.lock())?;
+
+        while *guard == 0 {
+            guard = Error::handle_poison_error(self.inner.cvar.wait(guard))?;
+        }
+
+        *guard -= 1;
+        Ok(())
+    }
+
+    /// Blocks until a slot is free or the timeout expires.
+    /// Returns Ok(true) if a slot was taken, Ok(false) if the timeout expired.
+    pub fn wait_timeout(&self, timeout: Duration) -> Result<bool> {
+        let mut guard = Error::handle_poison_error(self.inner.count.lock())?;
+        let start = Instant::now();
+
+        while *guard == 0 {
+            let Some(remaining) = timeout.checked_sub(start.elapsed()) else {
+                return Ok(false);
+            };
+            let (new_guard, _) =
+                Error::handle_poison_error(self.inner.cvar.wait_timeout(guard, remaining))?;
+            guard = new_guard;
+        }
+
+        *guard -= 1;
+        Ok(true)
+    }
+
+    /// Waits for a free slot and takes it. Dropping the future before it completes does
+    /// not take a slot.
+    pub async fn wait_async(&self) -> Result<()> {
+        loop {
+            let notified = self.inner.notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            if self.try_wait()? {
+                return Ok(());
+            }
+
+            notified.await;
+        }
+    }
+
+    /// Waits for a free slot or the timeout to expire.
+    /// Returns Ok(true) if a slot was taken, Ok(false) if the timeout expired.
+    pub async fn wait_timeout_async(&self, timeout: Duration) -> Result<bool> {
+        match time::timeout(timeout, self.wait_async()).await {
+            Ok(result) => result.map(|_| true),
+            Err(_) => Ok(false),
+        }
+    }
+
+    /// Returns one slot. Returns the count before the release.
+    pub fn release(&self) -> Result<usize> {
+        self.release_n(1)
+    }
+
+    /// Returns `release_count` slots. Returns the count before the release, or
+    /// `Error::Exceeded` if the count would go over `max_count`.
+    pub fn release_n(&self, release_count: usize) -> Result<usize> {
+        let mut guard = Error::handle_poison_error(self.inner.count.lock())?;
+        let previous = *guard;
+
+        match previous.checked_add(release_count) {
+            Some(count) if count <= self.max_count => *guard = count,
+            _ => {
+                return Err(Error::Exceeded(format!(
+                    "Semaphore count cannot exceed {}",
+                    self.max_count
+                )));
+            }
+        }
+
+        drop(guard);
+
+        if release_count == 1 {
+            self.inner.cvar.notify_one();
+        } else {
+            self.inner.cvar.notify_all();
+        }
+
+        self.inner.notify.notify_waiters();
+        Ok(previous)
+    }
+}
@@ -0,0 +1,498 @@
+use std::{
+    fmt,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread,
+};
+use tokio::time::{self, Duration, Instant};
+
+use crate::{CancelReason, CancellationToken, Error, Result, Signal, constants::*};
+
+/// A component the `ShutdownCoordinator` can stop.
+pub trait GracefulShutdown: Send + Sync + fmt::Debug {
+    /// Asks the component to finish its pending work and stop taking new work.
+    fn drain(&self);
+    /// Stops the component without finishing its pending work.
+    fn cancel(&self);
+    fn is_finished(&self) -> bool;
+}
+
+type HookFn = Arc<dyn Fn() + Send + Sync>;
+type FinishedFn = Arc<dyn Fn() -> bool + Send + Sync>;
+
+/// Adapts anything that is not a consumer or `Scheduler`, such as a server, from
+/// closures.
+#[derive(Clone)]
+#[must_use]
+pub struct ShutdownHook {
+    drain: HookFn,
+    cancel: Option<HookFn>,
+    is_finished: FinishedFn,
+}
+
+impl fmt::Debug for ShutdownHook {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ShutdownHook")
+            .field("cancel", &self.cancel.is_some())
+            .finish()
+    }
+}
+
+impl ShutdownHook {
+    pub fn new(
+        drain: impl Fn() + Send + Sync + 'static,
+        is_finished: impl Fn() -> bool + Send + Sync + 'static,
+    ) -> Self {
+        ShutdownHook {
+            drain: Arc::new(drain),
+            cancel: None,
+            is_finished: Arc::new(is_finished),
+        }
+    }
+
+    /// Sets what to do when the component misses the deadline. Without it, nothing is
+    /// done and the component is reported as abandoned if it never finishes.
+    pub fn with_cancel(&self, cancel: impl Fn() + Send + Sync + 'static) -> Self {
+        ShutdownHook {
+            cancel: Some(Arc::new(cancel)),
+            ..self.clone()
+        }
+    }
+}
+
+impl GracefulShutdown for ShutdownHook {
+    fn drain(&self) {
+        (self.drain)();
+    }
+
+    fn cancel(&self) {
+        if let Some(cancel) = &self.cancel {
+            cancel();
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        (self.is_finished)()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[must_use]
+pub struct ShutdownOptions {
+    /// How long components get to drain before they are cancelled.
+    pub deadline: Duration,
+    /// How long cancelled components get to stop before they are abandoned.
+    pub cancel_timeout: Duration,
+    pub poll_interval: Duration,
+}
+
+impl Default for ShutdownOptions {
+    fn default() -> Self {
+        ShutdownOptions {
+            deadline: SHUTDOWN_DEADLINE_DEF,
+            cancel_timeout: SHUTDOWN_CANCEL_TIMEOUT_DEF,
+            poll_interval: PEEK_TIMEOUT_DEF,
+        }
+    }
+}
+
+impl ShutdownOptions {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn with_deadline(&self, deadline: Duration) -> Self {
+        ShutdownOptions {
+            deadline,
+            ..self.clone()
+        }
+    }
+
+    pub fn with_cancel_timeout(&self, cancel_timeout: Duration) -> Self {
+        ShutdownOptions {
+            cancel_timeout,
+            ..self.clone()
+        }
+    }
+
+    pub fn with_poll_interval(&self, poll_interval: Duration) -> Self {
+        ShutdownOptions {
+            poll_interval: poll_interval.clamp(PEEK_TIMEOUT_MIN, PEEK_TIMEOUT_MAX),
+            ..self.clone()
+        }
+    }
+}
+
+/// What happened to each registered component during a shutdown.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ShutdownReport {
+    /// Components that drained before the deadline.
+    pub completed: Vec<String>,
+    /// Components that missed the deadline and stopped once cancelled.
+    pub cancelled: Vec<String>,
+    /// Components that were still running after being cancelled.
+    pub abandoned: Vec<String>,
+    pub elapsed: Duration,
+}
+
+impl ShutdownReport {
+    /// Gets the components that did not finish before the deadline.
+    pub fn timed_out(&self) -> impl Iterator<Item = &str> {
+        self.cancelled
+            .iter()
+            .chain(self.abandoned.iter())
+            .map(String::as_str)
+    }
+
+    /// Whether every component drained before the deadline.
+    pub fn is_clean(&self) -> bool {
+        self.cancelled.is_empty() && self.abandoned.is_empty()
+    }
+}
+
+type Component = (String, Arc<dyn GracefulShutdown>);
+
+/// Stops an application's consumers, schedulers, and servers together.
+///
+/// Shutdown starts on SIGINT or SIGTERM once `listen` is called, or on `request`.
+/// Every registered component is drained, the ones that miss the deadline are
+/// cancelled, and the returned `ShutdownReport` names the ones that did not finish in
+/// time. Components are drained one at a time in the order they were registered, each
+/// finishing before the next is drained, so register producers before the consumers
+/// they feed. Once the deadline passes, the rest are drained without waiting.
+#[derive(Clone)]
+#[must_use]
+pub struct ShutdownCoordinator {
+    pub options: ShutdownOptions,
+    components: Arc<Mutex<Vec<Component>>>,
+    token: CancellationToken,
+    requested: Signal,
+    running: Arc<AtomicBool>,
+    report: Arc<Mutex<Option<ShutdownReport>>>,
+}
+
+impl fmt::Debug for ShutdownCoordinator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let components = self.components.lock().unwrap();
+        f.debug_struct("ShutdownCoordinator")
+            .field("options", &self.options)
+            .field(
+                "components",
+                &components.iter().map(|(name, _)| name).collect::<Vec<_>>(),
+            )
+            .field("token", &self.token)
+            .finish()
+    }
+}
+
+impl Default for ShutdownCoordinator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ShutdownCoordinator {
+    pub fn new() -> Self {
+        Self::with_options(ShutdownOptions::default())
+    }
+
+    pub fn with_options(options: ShutdownOptions) -> Self {
+        ShutdownCoordinator {
+            options,
+            components: Arc::new(Mutex::new(Vec::new())),
+            token: CancellationToken::new(),
+            requested: Signal::new(),
+            running: Arc::new(AtomicBool::new(false)),
+            report: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn register(&self, name: &str, component: impl GracefulShutdown + 'static) -> Result<()> {
+        if self.is_shutting_down() {
+            return Err(Error::InvalidOperation(
+                "Shutdown has already started".to_string(),
+            ));
+        }
+
+        let mut components = Error::handle_poison_error(self.components.lock())?;
+
+        // The report names components, so each name must tell them apart.
+        if components.iter().any(|(existing, _)| existing == name) {
+            return Err(Error::Argument(format!(
+                "A component named '{}' is already registered",
+                name
+            )));
+        }
+
+        components.push((name.to_string(), Arc::new(component)));
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.components.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Gets a token that is cancelled with `CancelReason::Shutdown` when shutdown
+    /// starts, for work that should notice it without being registered.
+    pub fn token(&self) -> CancellationToken {
+        self.token.clone()
+    }
+
+    pub fn is_shutting_down(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    /// Starts the shutdown and wakes whoever is in `wait`.
+    pub fn request(&self) {
+        self.token.cancel_with(CancelReason::Shutdown);
+        self.requested.set();
+    }
+
+    /// Spawns a thread that calls `request` on SIGINT, SIGTERM, or Ctrl+C. The thread
+    /// exits once shutdown starts for any reason. Fails if the signal handlers cannot be
+    /// installed.
+    pub fn listen(&self) -> Result<()> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        let signals = {
+            let _runtime = runtime.enter();
+            SignalListener::new()?
+        };
+        let this = self.clone();
+        thread::spawn(move || runtime.block_on(this.listen_with(signals)));
+        Ok(())
+    }
+
+    /// Calls `request` on SIGINT, SIGTERM, or Ctrl+C. Returns once shutdown starts for
+    /// any reason, or right away if the signal handlers cannot be installed.
+    pub async fn listen_async(&self) -> Result<()> {
+        let signals = SignalListener::new()?;
+        self.listen_with(signals).await;
+        Ok(())
+    }
+
+    async fn listen_with(&self, mut signals: SignalListener) {
+        tokio::select! {
+            _ = signals.recv() => self.request(),
+            _ = self.token.cancelled() => {}
+        }
+    }
+
+    /// Blocks until shutdown is requested, then shuts down.
+    pub fn wait(&self) -> Result<ShutdownReport> {
+        while !self.is_shutting_down() {
+            self.requested.wait();
+        }
+
+        self.shutdown()
+    }
+
+    pub async fn wait_async(&self) -> Result<ShutdownReport> {
+        self.token.cancelled().await;
+        self.shutdown_async().await
+    }
+
+    /// Listens for OS signals and blocks until shutdown finishes.
+    pub fn run_until_signal(&self) -> Result<ShutdownReport> {
+        self.listen()?;
+        self.wait()
+    }
+
+    pub async fn run_until_signal_async(&self) -> Result<ShutdownReport> {
+        self.listen_async().await?;
+        self.shutdown_async().await
+    }
+
+    /// Drains every component, cancels the ones that miss the deadline, and reports
+    /// what happened. Only the first call shuts down; later calls wait for its report.
+    pub fn shutdown(&self) -> Result<ShutdownReport> {
+        self.request();
+
+        if !self.begin() {
+            loop {
+                if let Some(report) = self.report()? {
+                    return Ok(report);
+                }
+
+                thread::sleep(self.options.poll_interval);
+            }
+        }
+
+        let started = Instant::now();
+        let components = self.components()?;
+        let deadline = started + self.options.deadline;
+
+        for (_, component) in &components {
+            component.drain();
+
+            while !component.is_finished() && Instant::now() < deadline {
+                thread::sleep(self.options.poll_interval);
+            }
+        }
+
+        let mut pending = components.clone();
+        Self::sweep(&mut pending);
+        let cancelled = Self::cancel(&pending);
+        let deadline = Instant::now() + self.options.cancel_timeout;
+
+        while !Self::sweep(&mut pending) && Instant::now() < deadline {
+            thread::sleep(self.options.poll_interval);
+        }
+
+        self.finish(&components, cancelled, &pending, started.elapsed())
+    }
+
+    pub async fn shutdown_async(&self) -> Result<ShutdownReport> {
+        self.request();
+
+        if !self.begin() {
+            loop {
+                if let Some(report) = self.report()? {
+                    return Ok(report);
+                }
+
+                time::sleep(self.options.poll_interval).await;
+            }
+        }
+
+        let started = Instant::now();
+        let components = self.components()?;
+        let deadline = started + self.options.deadline;
+
+        for (_, component) in &components {
+            component.drain();
+
+            while !component.is_finished() && Instant::now() < deadline {
+                time::sleep(self.options.poll_interval).await;
+            }
+        }
+
+        let mut pending = components.clone();
+        Self::sweep(&mut pending);
+        let cancelled = Self::cancel(&pending);
+        let deadline = Instant::now() + self.options.cancel_timeout;
+
+        while !Self::sweep(&mut pending) && Instant::now() < deadline {
+            time::sleep(self.options.poll_interval).await;
+        }
+
+        self.finish(&components, cancelled, &pending, started.elapsed())
+    }
+
+    /// Gets the report of a finished shutdown.
+    pub fn report(&self) -> Result<Option<ShutdownReport>> {
+        Ok(Error::handle_poison_error(self.report.lock())?.clone())
+    }
+
+    fn begin(&self) -> bool {
+        self.running
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .is_ok()
+    }
+
+    fn components(&self) -> Result<Vec<Component>> {
+        Ok(Error::handle_poison_error(self.components.lock())?.clone())
+    }
+
+    fn cancel(pending: &[Component]) -> Vec<String> {
+        pending
+            .iter()
+            .map(|(name, component)| {
+                component.cancel();
+                name.clone()
+            })
+            .collect()
+    }
+
+    /// Drops finished components from `pending` and returns whether none are left.
+    fn sweep(pending: &mut Vec<Component>) -> bool {
+        pending.retain(|(_, component)| !component.is_finished());
+        pending.is_empty()
+    }
+
+    fn finish(
+        &self,
+        components: &[Component],
+        cancelled: Vec<String>,
+        abandoned: &[Component],
+        elapsed: Duration,
+    ) -> Result<ShutdownReport> {
+        let abandoned = abandoned
+            .iter()
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<_>>();
+        let completed = components
+            .iter()
+            .map(|(name, _)| name)
+            .filter(|name| !cancelled.contains(name))
+            .cloned()
+            .collect();
+        let cancelled = cancelled
+            .into_iter()
+            .filter(|name| !abandoned.contains(name))
+            .collect();
+        let report = ShutdownReport {
+            completed,
+            cancelled,
+            abandoned,
+            elapsed,
+        };
+        *Error::handle_poison_error(self.report.lock())? = Some(report.clone());
+        Ok(report)
+    }
+}
+
+/// The handlers for SIGINT, SIGTERM, and Ctrl+C, which stay installed until it is
+/// dropped.
+struct SignalListener {
+    #[cfg(unix)]
+    interrupt: tokio::signal::unix::Signal,
+    #[cfg(unix)]
+    terminate: tokio::signal::unix::Signal,
+    #[cfg(not(unix))]
+    ctrl_c: tokio::signal::windows::CtrlC,
+}
+
+impl SignalListener {
+    /// Installs the handlers. Must be called within a tokio runtime.
+    fn new() -> Result<Self> {
+        #[cfg(unix)]
+        {
+            use tokio::signal::unix::{SignalKind, signal};
+
+            Ok(SignalListener {
+                interrupt: signal(SignalKind::interrupt())?,
+                terminate: signal(SignalKind::terminate())?,
+            })
+        }
+
+        #[cfg(not(unix))]
+        Ok(SignalListener {
+            ctrl_c: tokio::signal::windows::ctrl_c()?,
+        })
+    }
+
+    async fn recv(&mut self) {
+        #[cfg(unix)]
+        tokio::select! {
+            _ = self.interrupt.recv() => {}
+            _ = self.terminate.recv() => {}
+        }
+
+        #[cfg(not(unix))]
+        self.ctrl_c.recv().await;
+    }
+}
+
+/// Resolves on the first SIGINT, SIGTERM, or Ctrl+C.
+pub async fn wait_for_signal() -> Result<()> {
+    SignalListener::new()?.recv().await;
+    Ok(())
+}
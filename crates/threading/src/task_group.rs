@@ -0,0 +1,268 @@
+use futures::FutureExt;
+use std::{
+    fmt,
+    future::Future,
+    mem,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    thread,
+};
+use tokio::{
+    sync::Notify,
+    time::{self, Duration},
+};
+
+use crate::{AwaitableConsumer, CancelReason, CancellationToken, Error, ManualResetCond, Result};
+
+/// How a `TaskGroup` reacts when one of its jobs fails.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TaskGroupMode {
+    /// Cancel the remaining jobs on the first error.
+    #[default]
+    FailFast,
+    /// Let every job run to completion and keep all errors.
+    CollectAll,
+}
+
+impl fmt::Display for TaskGroupMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TaskGroupMode::FailFast => write!(f, "FailFast"),
+            TaskGroupMode::CollectAll => write!(f, "CollectAll"),
+        }
+    }
+}
+
+/// The errors of a `TaskGroup`'s jobs.
+#[derive(Debug, Default)]
+struct Errors {
+    pending: Vec<Error>,
+    /// The message of the first error, once a wait returned it.
+    reported: Option<String>,
+}
+
+/// A set of related jobs that share a cancellation token.
+///
+/// Each job receives a child of the group's token. `wait` blocks until every job has
+/// returned and yields the first error as it was returned, or its message as
+/// `Error::Other` on later waits. The other errors are available from `take_errors`.
+#[derive(Clone, Debug)]
+#[must_use]
+pub struct TaskGroup {
+    mode: TaskGroupMode,
+    token: CancellationToken,
+    running: Arc<AtomicUsize>,
+    errors: Arc<Mutex<Errors>>,
+    finished_cond: Arc<ManualResetCond>,
+    finished_noti: Arc<Notify>,
+}
+
+impl Default for TaskGroup {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TaskGroup {
+    pub fn new() -> Self {
+        Self::with_mode(TaskGroupMode::default())
+    }
+
+    pub fn with_mode(mode: TaskGroupMode) -> Self {
+        Self::with_token(mode, CancellationToken::new())
+    }
+
+    /// Creates a group whose jobs are cancelled along with `token`.
+    pub fn with_parent(mode: TaskGroupMode, token: &CancellationToken) -> Self {
+        Self::with_token(mode, token.child_token())
+    }
+
+    fn with_token(mode: TaskGroupMode, token: CancellationToken) -> Self {
+        TaskGroup {
+            mode,
+            token,
+            running: Arc::new(AtomicUsize::new(0)),
+            errors: Arc::new(Mutex::new(Errors::default())),
+            finished_cond: Arc::new(ManualResetCond::new_set()),
+            finished_noti: Arc::new(Notify::new()),
+        }
+    }
+
+    pub fn mode(&self) -> TaskGroupMode {
+        self.mode
+    }
+
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+
+    pub fn running(&self) -> usize {
+        self.running.load(Ordering::SeqCst)
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.running() == 0
+    }
+
+    pub fn cancel(&self) {
+        self.token.cancel();
+    }
+
+    /// Runs a job on a new thread.
+    pub fn spawn<F>(&self, job: F) -> Result<()>
+    where
+        F: FnOnce(CancellationToken) -> Result<()> + Send + 'static,
+    {
+        let token = self.begin()?;
+        let this = self.clone();
+        thread::spawn(move || {
+            let result = panic::catch_unwind(AssertUnwindSafe(|| job(token)))
+                .unwrap_or_else(|_| Err(Error::Other("Task panicked".to_string())));
+            this.end(result);
+        });
+        Ok(())
+    }
+
+    /// Runs a job on the current tokio runtime.
+    pub fn spawn_async<F, Fut>(&self, job: F) -> Result<()>
+    where
+        F: FnOnce(CancellationToken) -> Fut,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        let token = self.begin()?;
+        let this = self.clone();
+        let future = job(token);
+        tokio::spawn(async move {
+            let result = AssertUnwindSafe(future)
+                .catch_unwind()
+                .await
+                .unwrap_or_else(|_| Err(Error::Other("Task panicked".to_string())));
+            this.end(result);
+        });
+        Ok(())
+    }
+
+    fn begin(&self) -> Result<CancellationToken> {
+        self.token.check()?;
+        self.running.fetch_add(1, Ordering::SeqCst);
+        Ok(self.token.child_token())
+    }
+
+    fn end(&self, result: Result<()>) {
+        if let Err(e) = result {
+            let reason = e.to_string();
+            let first = {
+                let mut errors = self.errors.lock().unwrap();
+                let first = errors.pending.is_empty() && errors.reported.is_none();
+                errors.pending.push(e);
+                first
+            };
+
+            if first && self.mode == TaskGroupMode::FailFast {
+                self.token.cancel_with(CancelReason::Failed(reason));
+            }
+        }
+
+        if self.running.fetch_sub(1, Ordering::SeqCst) == 1 {
+            // Waiters re-check `is_finished`; reset first so `set` always notifies them.
+            let _ = self.finished_cond.reset();
+            let _ = self.finished_cond.set();
+            self.finished_noti.notify_waiters();
+        }
+    }
+
+    /// Removes and returns every error recorded so far, except one already returned by
+    /// `wait`, after which `wait` no longer reports them.
+    pub fn take_errors(&self) -> Vec<Error> {
+        let mut errors = self.errors.lock().unwrap();
+        errors.reported = None;
+        mem::take(&mut errors.pending)
+    }
+
+    fn result(&self) -> Result<()> {
+        let mut errors = self.errors.lock().unwrap();
+
+        if let Some(message) = &errors.reported {
+            return Err(Error::Other(message.clone()));
+        }
+
+        if !errors.pending.is_empty() {
+            let e = errors.pending.remove(0);
+            errors.reported = Some(e.to_string());
+            return Err(e);
+        }
+
+        drop(errors);
+
+        if self.is_cancelled() {
+            return Err(Error::Canceled);
+        }
+
+        Ok(())
+    }
+
+    /// Blocks until every job has returned.
+    pub fn wait(&self) -> Result<()> {
+        self.finished_cond.wait_while(|| !self.is_finished())?;
+        self.result()
+    }
+
+    pub async fn wait_async(&self) -> Result<()> {
+        loop {
+            let notified = self.finished_noti.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            if self.is_finished() {
+                break;
+            }
+
+            notified.await;
+        }
+
+        self.result()
+    }
+
+    pub fn wait_for(&self, timeout: Duration) -> Result<()> {
+        if timeout.is_zero() {
+            return Err(Error::Timeout);
+        }
+
+        if !self
+            .finished_cond
+            .wait_timeout_while(|| !self.is_finished(), timeout)?
+        {
+            return Err(Error::Timeout);
+        }
+
+        self.result()
+    }
+
+    pub async fn wait_for_async(&self, timeout: Duration) -> Result<()> {
+        if timeout.is_zero() {
+            return Err(Error::Timeout);
+        }
+
+        match time::timeout(timeout, self.wait_async()).await {
+            Ok(result) => result,
+            Err(_) => Err(Error::Timeout),
+        }
+    }
+}
+
+impl AwaitableConsumer<()> for TaskGroup {
+    fn is_cancelled(&self) -> bool {
+        TaskGroup::is_cancelled(self)
+    }
+
+    fn is_finished(&self) -> bool {
+        TaskGroup::is_finished(self)
+    }
+}
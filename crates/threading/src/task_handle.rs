@@ -0,0 +1,297 @@
+use std::{
+    fmt,
+    future::{Future, IntoFuture},
+    pin::Pin,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+use tokio::{sync::Notify, time::Duration};
+
+use crate::*;
+
+struct HandleState<R> {
+    result: Mutex<Option<Result<R>>>,
+    finished: AtomicBool,
+    cancelled: AtomicBool,
+    finished_cond: Arc<ManualResetCond>,
+    finished_noti: Arc<Notify>,
+}
+
+impl<R> HandleState<R> {
+    /// Stores the result unless the task already has one. Returns false if it did.
+    fn resolve(&self, result: Result<R>) -> bool {
+        let mut guard = self.result.lock().unwrap();
+
+        if self.finished.load(Ordering::SeqCst) {
+            return false;
+        }
+
+        *guard = Some(result);
+        self.finished.store(true, Ordering::SeqCst);
+        drop(guard);
+        let _ = self.finished_cond.set();
+        self.finished_noti.notify_waiters();
+        true
+    }
+}
+
+/// The result of an item given to a consumer with `submit`.
+///
+/// The handle can be waited on from a thread, awaited directly, or cancelled. Cancelling
+/// an item that has not been picked up yet makes the worker skip it. The result can be
+/// taken once; later waits return `Error::InvalidOperation`.
+pub struct TaskHandle<R> {
+    state: Arc<HandleState<R>>,
+}
+
+impl<R> Clone for TaskHandle<R> {
+    fn clone(&self) -> Self {
+        TaskHandle {
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<R> fmt::Debug for TaskHandle<R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TaskHandle")
+            .field("finished", &self.state.finished)
+            .field("cancelled", &self.state.cancelled)
+            .finish()
+    }
+}
+
+impl<R: Send + 'static> TaskHandle<R> {
+    fn new() -> Self {
+        TaskHandle {
+            state: Arc::new(HandleState {
+                result: Mutex::new(None),
+                finished: AtomicBool::new(false),
+                cancelled: AtomicBool::new(false),
+                finished_cond: Arc::new(ManualResetCond::new_unset()),
+                finished_noti: Arc::new(Notify::new()),
+            }),
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.state.finished.load(Ordering::SeqCst)
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.state.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Cancels the task. Returns false if it already has a result.
+    pub fn cancel(&self) -> bool {
+        if !self.state.resolve(Err(Error::Canceled)) {
+            return false;
+        }
+
+        self.state.cancelled.store(true, Ordering::SeqCst);
+        true
+    }
+
+    fn take(&self) -> Result<R> {
+        self.state.result.lock().unwrap().take().unwrap_or_else(|| {
+            Err(Error::InvalidOperation(
+                "Task result was already taken".to_string(),
+            ))
+        })
+    }
+
+    /// Blocks until the task has a result and takes it.
+    pub fn wait(&self) -> Result<R> {
+        wait(self, &self.state.finished_cond)?;
+        self.take()
+    }
+
+    pub async fn wait_async(&self) -> Result<R> {
+        wait_async(self, &self.state.finished_noti).await?;
+        self.take()
+    }
+
+    /// Blocks until the task has a result or the timeout expires. The task keeps
+    /// running after a timeout.
+    pub fn wait_for(&self, timeout: Duration) -> Result<R> {
+        wait_for(self, timeout, &self.state.finished_cond)?;
+        self.take()
+    }
+
+    pub async fn wait_for_async(&self, timeout: Duration) -> Result<R> {
+        wait_for_async(self, timeout, &self.state.finished_noti).await?;
+        self.take()
+    }
+}
+
+impl<R: Send + 'static> AwaitableConsumer<()> for TaskHandle<R> {
+    fn is_cancelled(&self) -> bool {
+        TaskHandle::is_cancelled(self)
+    }
+
+    fn is_finished(&self) -> bool {
+        TaskHandle::is_finished(self)
+    }
+}
+
+impl<R: Send + 'static> IntoFuture for TaskHandle<R> {
+    type Output = Result<R>;
+    type IntoFuture = Pin<Box<dyn Future<Output = Result<R>> + Send>>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        Box::pin(async move { self.wait_async().await })
+    }
+}
+
+/// Resolves the handle as cancelled when the last copy of its submission is dropped
+/// without a result.
+struct SubmissionGuard<R> {
+    state: Arc<HandleState<R>>,
+}
+
+impl<R> Drop for SubmissionGuard<R> {
+    fn drop(&mut self) {
+        if self.state.resolve(Err(Error::Canceled)) {
+            self.state.cancelled.store(true, Ordering::SeqCst);
+        }
+    }
+}
+
+/// An item queued with `submit`, carrying the handle its result is delivered to.
+pub struct Submission<T, R> {
+    item: T,
+    handle: TaskHandle<R>,
+    _guard: Arc<SubmissionGuard<R>>,
+}
+
+impl<T: Clone, R> Clone for Submission<T, R> {
+    fn clone(&self) -> Self {
+        Submission {
+            item: self.item.clone(),
+            handle: self.handle.clone(),
+            _guard: self._guard.clone(),
+        }
+    }
+}
+
+impl<T: fmt::Debug, R> fmt::Debug for Submission<T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Submission")
+            .field("item", &self.item)
+            .field("handle", &self.handle)
+            .finish()
+    }
+}
+
+impl<T, R: Send + 'static> Submission<T, R> {
+    /// Creates a submission for `item` and the handle that receives its result.
+    pub fn new(item: T) -> (Self, TaskHandle<R>) {
+        let handle = TaskHandle::new();
+        let this = Submission {
+            item,
+            handle: handle.clone(),
+            _guard: Arc::new(SubmissionGuard {
+                state: handle.state.clone(),
+            }),
+        };
+        (this, handle)
+    }
+
+    pub fn item(&self) -> &T {
+        &self.item
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.handle.is_cancelled()
+    }
+
+    /// Delivers the result to the handle. Returns false if it already has one.
+    pub fn complete(&self, result: Result<R>) -> bool {
+        self.handle.state.resolve(result)
+    }
+}
+
+/// Adds `submit` to every queue of `Submission`s.
+pub trait SubmitQueue<T: StaticTaskItem, R: Send + 'static>: TaskQueue<Submission<T, R>> {
+    /// Queues `item` and returns the handle its result is delivered to.
+    fn submit(&self, item: T) -> Result<TaskHandle<R>> {
+        let (submission, handle) = Submission::new(item);
+        self.enqueue(submission)?;
+        Ok(handle)
+    }
+}
+
+impl<Q, T, R> SubmitQueue<T, R> for Q
+where
+    Q: TaskQueue<Submission<T, R>>,
+    T: StaticTaskItem,
+    R: Send + 'static,
+{
+}
+
+type SubmitFn<T, R> = Arc<dyn Fn(&T) -> Result<R> + Send + Sync>;
+
+/// A handler that runs a function for every submitted item and delivers its output to
+/// the item's handle. Cancelled items are skipped.
+pub struct SubmitHandler<T, R> {
+    process: SubmitFn<T, R>,
+}
+
+impl<T, R> Clone for SubmitHandler<T, R> {
+    fn clone(&self) -> Self {
+        SubmitHandler {
+            process: self.process.clone(),
+        }
+    }
+}
+
+impl<T, R> fmt::Debug for SubmitHandler<T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SubmitHandler").finish()
+    }
+}
+
+impl<T, R> SubmitHandler<T, R> {
+    pub fn new(process: impl Fn(&T) -> Result<R> + Send + Sync + 'static) -> Self {
+        SubmitHandler {
+            process: Arc::new(process),
+        }
+    }
+}
+
+impl<TPC, T, R> TaskDelegation<TPC, Submission<T, R>> for SubmitHandler<T, R>
+where
+    TPC: AwaitableConsumer<Submission<T, R>>,
+    T: StaticTaskItem,
+    R: Send + 'static,
+{
+    fn on_started(&self, _pc: &TPC) {}
+
+    fn process(&self, _pc: &TPC, item: &Submission<T, R>) -> Result<TaskResult> {
+        if item.is_cancelled() {
+            return Ok(TaskResult::Cancelled);
+        }
+
+        match (self.process)(item.item()) {
+            Ok(value) => {
+                item.complete(Ok(value));
+                Ok(TaskResult::Success)
+            }
+            Err(e) => {
+                let message = e.to_string();
+                item.complete(Err(e));
+                Ok(TaskResult::Error(message))
+            }
+        }
+    }
+
+    fn on_completed(&self, _pc: &TPC, _item: &Submission<T, R>, _result: &TaskResult) -> bool {
+        true
+    }
+
+    fn on_cancelled(&self, _pc: &TPC) {}
+
+    fn on_finished(&self, _pc: &TPC) {}
+}
@@ -0,0 +1,466 @@
+use std::{
+    fmt,
+    marker::PhantomData,
+    sync::{Arc, Condvar, Mutex, MutexGuard},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{AwaitableConsumer, Error, Result, StaticTaskItem, TaskDelegation, TaskResult};
+
+/// How often a wait on a `VirtualClock` checks the primitive it waits on in real time.
+const POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+#[derive(Debug, Default)]
+struct ClockState {
+    elapsed: Duration,
+    sleepers: usize,
+}
+
+/// A clock that only moves when it is advanced. Consumers created `with_clock` run their
+/// threshold, peek, pause, and sleep-after-send timeouts, as well as their batch latency
+/// and dedup window, on it, so a test decides when they expire instead of sleeping
+/// through them.
+#[derive(Clone, Debug)]
+pub struct VirtualClock {
+    origin: Instant,
+    state: Arc<Mutex<ClockState>>,
+    cond: Arc<Condvar>,
+}
+
+impl Default for VirtualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VirtualClock {
+    pub fn new() -> Self {
+        VirtualClock {
+            origin: Instant::now(),
+            state: Arc::new(Mutex::new(ClockState::default())),
+            cond: Arc::new(Condvar::new()),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, ClockState> {
+        self.state.lock().unwrap()
+    }
+
+    /// Gets the virtual time that passed since the clock was created.
+    pub fn elapsed(&self) -> Duration {
+        self.state().elapsed
+    }
+
+    /// Gets the current time on the clock, which starts at its creation and only moves
+    /// when it is advanced.
+    pub fn now(&self) -> Instant {
+        self.origin + self.elapsed()
+    }
+
+    /// Gets the number of threads waiting for the clock to move.
+    pub fn sleepers(&self) -> usize {
+        self.state().sleepers
+    }
+
+    /// Moves the clock forward and wakes the threads whose timeout expired.
+    pub fn advance(&self, duration: Duration) {
+        self.state().elapsed += duration;
+        self.cond.notify_all();
+    }
+
+    /// Blocks until at least `count` threads wait for the clock. Returns false if that
+    /// did not happen within `timeout` of real time.
+    pub fn wait_for_sleepers(&self, count: usize, timeout: Duration) -> bool {
+        let state = self.state();
+        let (_state, result) = self
+            .cond
+            .wait_timeout_while(state, timeout, |it| it.sleepers < count)
+            .unwrap();
+        !result.timed_out()
+    }
+
+    /// Advances the clock by `step` until `condition` holds. Returns false if it did not
+    /// hold within `timeout` of real time.
+    pub fn advance_until(
+        &self,
+        step: Duration,
+        timeout: Duration,
+        condition: impl Fn() -> bool,
+    ) -> bool {
+        let deadline = Instant::now() + timeout;
+
+        while !condition() {
+            if Instant::now() >= deadline {
+                return false;
+            }
+
+            self.advance(step);
+            thread::sleep(POLL_INTERVAL);
+        }
+
+        true
+    }
+
+    /// Blocks the calling thread until the clock moved by `duration`.
+    pub fn sleep(&self, duration: Duration) {
+        let mut state = self.state();
+        let deadline = state.elapsed + duration;
+        state.sleepers += 1;
+        self.cond.notify_all();
+
+        while state.elapsed < deadline {
+            state = self.cond.wait(state).unwrap();
+        }
+
+        state.sleepers -= 1;
+    }
+
+    /// Calls `wait` with a short real timeout until it returns a value or the clock moved
+    /// by `timeout`.
+    pub(crate) fn wait_timeout<R>(
+        &self,
+        timeout: Duration,
+        mut wait: impl FnMut(Duration) -> Option<R>,
+    ) -> Option<R> {
+        let deadline = {
+            let mut state = self.state();
+            state.sleepers += 1;
+            self.cond.notify_all();
+            state.elapsed + timeout
+        };
+
+        let result = loop {
+            if let Some(it) = wait(POLL_INTERVAL) {
+                break Some(it);
+            }
+
+            if self.elapsed() >= deadline {
+                break None;
+            }
+        };
+
+        self.state().sleepers -= 1;
+        result
+    }
+}
+
+/// What one `InlineDriver::step` did.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Step {
+    /// An item was processed.
+    Processed,
+    /// The consumer is paused, so nothing was taken.
+    Paused,
+    /// The queue is empty but not completed.
+    Idle,
+    /// The handler stopped the worker before the consumer was completed or cancelled,
+    /// so the consumer will not finish.
+    Stopped,
+    /// The consumer finished or was cancelled and its last callback ran.
+    Finished,
+}
+
+impl Step {
+    pub(crate) fn stopped(finished: bool) -> Self {
+        if finished {
+            Step::Finished
+        } else {
+            Step::Stopped
+        }
+    }
+}
+
+/// A consumer whose worker loop an `InlineDriver` can run on the calling thread.
+pub trait InlineConsumer<T: StaticTaskItem>: AwaitableConsumer<T> {
+    /// The state of the single inline worker.
+    type Worker;
+
+    /// Starts the consumer with one worker that is never spawned and calls `on_started`.
+    fn start_inline<H: TaskDelegation<Self, T>>(&self, handler: &H) -> Result<Self::Worker>;
+
+    /// Runs one turn of the worker loop without waiting for items, a pause, or the
+    /// threshold. Once the loop ends, the worker leaves the pool as a spawned one would.
+    fn step_inline<H: TaskDelegation<Self, T>>(
+        &self,
+        handler: &H,
+        worker: &mut Self::Worker,
+    ) -> Step;
+}
+
+/// Runs a consumer's handler on the calling thread, one item per `step`.
+///
+/// The consumer is started with a single worker that runs the same loop as a spawned
+/// one, so the callbacks come in a fixed order: `on_started`, then `process` and
+/// `on_completed` for each item in queue order, then `on_finished` or `on_cancelled`.
+/// Nothing waits in real time: a step on an empty or paused queue returns instead, and
+/// the threshold between items is skipped. A bounded queue must have room for every item
+/// enqueued between steps.
+pub struct InlineDriver<C: InlineConsumer<T>, T: StaticTaskItem, H> {
+    consumer: C,
+    handler: H,
+    worker: Mutex<C::Worker>,
+    item: PhantomData<fn() -> T>,
+}
+
+impl<C, T, H> fmt::Debug for InlineDriver<C, T, H>
+where
+    C: InlineConsumer<T> + fmt::Debug,
+    T: StaticTaskItem,
+    H: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("InlineDriver")
+            .field("consumer", &self.consumer)
+            .field("handler", &self.handler)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<C, T, H> InlineDriver<C, T, H>
+where
+    C: InlineConsumer<T>,
+    T: StaticTaskItem,
+    H: TaskDelegation<C, T>,
+{
+    /// Starts `consumer` and calls `on_started` on the calling thread.
+    pub fn start(consumer: &C, handler: &H) -> Result<Self> {
+        let worker = consumer.start_inline(handler)?;
+        Ok(InlineDriver {
+            consumer: consumer.clone(),
+            handler: handler.clone(),
+            worker: Mutex::new(worker),
+            item: PhantomData,
+        })
+    }
+
+    pub fn consumer(&self) -> &C {
+        &self.consumer
+    }
+
+    /// Processes the next item, or ends the worker loop if the consumer is done.
+    pub fn step(&self) -> Step {
+        let mut worker = self.worker.lock().unwrap();
+        self.consumer.step_inline(&self.handler, &mut worker)
+    }
+
+    /// Steps until the queue is empty or paused, or the consumer is done. Returns the
+    /// number of items processed.
+    pub fn run_until_idle(&self) -> usize {
+        let mut processed = 0;
+
+        while self.step() == Step::Processed {
+            processed += 1;
+        }
+
+        processed
+    }
+
+    /// Steps until the consumer finishes. Fails if it runs out of items or is paused
+    /// before it is completed, since nothing else would let it finish, and with
+    /// `Error::Canceled` if it was cancelled.
+    pub fn run(&self) -> Result<()> {
+        loop {
+            match self.step() {
+                Step::Processed => {}
+                Step::Finished if self.consumer.is_cancelled() => return Err(Error::Canceled),
+                Step::Finished => return Ok(()),
+                step => {
+                    return Err(Error::InvalidOperation(format!(
+                        "Consumer cannot finish inline: {:?}",
+                        step
+                    )));
+                }
+            }
+        }
+    }
+}
+
+/// Polls `condition` until it holds, for asserting on what other threads do without
+/// guessing how long they take. Returns false if it did not hold within `timeout`.
+pub fn eventually(timeout: Duration, condition: impl Fn() -> bool) -> bool {
+    let deadline = Instant::now() + timeout;
+
+    while !condition() {
+        if Instant::now() >= deadline {
+            return false;
+        }
+
+        thread::sleep(POLL_INTERVAL);
+    }
+
+    true
+}
+
+type ProcessFn<T> = Arc<dyn Fn(&T) -> Result<TaskResult> + Send + Sync>;
+
+/// A handler for any consumer that processes items with a closure and never stops the
+/// worker.
+#[derive(Clone)]
+pub struct FnHandler<T> {
+    process: ProcessFn<T>,
+}
+
+impl<T> fmt::Debug for FnHandler<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FnHandler").finish_non_exhaustive()
+    }
+}
+
+impl<T> FnHandler<T> {
+    pub fn new(process: impl Fn(&T) -> Result<TaskResult> + Send + Sync + 'static) -> Self {
+        FnHandler {
+            process: Arc::new(process),
+        }
+    }
+}
+
+impl<TPC: AwaitableConsumer<T>, T: StaticTaskItem> TaskDelegation<TPC, T> for FnHandler<T> {
+    fn on_started(&self, _pc: &TPC) {}
+
+    fn process(&self, _pc: &TPC, item: &T) -> Result<TaskResult> {
+        (self.process)(item)
+    }
+
+    fn on_completed(&self, _pc: &TPC, _item: &T, _result: &TaskResult) -> bool {
+        true
+    }
+
+    fn on_cancelled(&self, _pc: &TPC) {}
+
+    fn on_finished(&self, _pc: &TPC) {}
+}
+
+/// A `TaskDelegation` callback as seen by a `CallbackRecorder`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Callback<T> {
+    Started,
+    Process(T),
+    Completed(T, TaskResult),
+    Cancelled,
+    Finished,
+}
+
+/// Wraps a handler and records every callback it receives, for asserting their order.
+pub struct CallbackRecorder<H, T> {
+    handler: H,
+    events: Arc<Mutex<Vec<Callback<T>>>>,
+}
+
+impl<H: Clone, T> Clone for CallbackRecorder<H, T> {
+    fn clone(&self) -> Self {
+        CallbackRecorder {
+            handler: self.handler.clone(),
+            events: self.events.clone(),
+        }
+    }
+}
+
+impl<H: fmt::Debug, T: fmt::Debug> fmt::Debug for CallbackRecorder<H, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CallbackRecorder")
+            .field("handler", &self.handler)
+            .field("events", &self.events)
+            .finish()
+    }
+}
+
+impl<H, T: Clone + PartialEq + fmt::Debug> CallbackRecorder<H, T> {
+    pub fn new(handler: H) -> Self {
+        CallbackRecorder {
+            handler,
+            events: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    pub fn events(&self) -> Vec<Callback<T>> {
+        self.events.lock().unwrap().clone()
+    }
+
+    pub fn clear(&self) {
+        self.events.lock().unwrap().clear();
+    }
+
+    fn record(&self, callback: Callback<T>) {
+        self.events.lock().unwrap().push(callback);
+    }
+
+    /// Asserts that the recorded callbacks are exactly `expected`, in order.
+    #[track_caller]
+    pub fn assert_events(&self, expected: &[Callback<T>]) {
+        assert_eq!(self.events(), expected, "unexpected callback sequence");
+    }
+
+    /// Asserts the ordering every consumer guarantees whatever its threading: one
+    /// `Started` first, every `Completed` after the `Process` of an equal item, and at
+    /// most one `Cancelled` or `Finished`, which comes last.
+    #[track_caller]
+    pub fn assert_well_formed(&self) {
+        let events = self.events();
+        let mut in_flight = Vec::new();
+
+        assert_eq!(
+            events.first(),
+            Some(&Callback::Started),
+            "callbacks must begin with Started: {:?}",
+            events
+        );
+
+        for (index, event) in events.iter().enumerate().skip(1) {
+            match event {
+                Callback::Started => panic!("Started repeated at {}: {:?}", index, events),
+                Callback::Process(item) => in_flight.push(item),
+                Callback::Completed(item, _) => {
+                    let Some(position) = in_flight.iter().position(|it| *it == item) else {
+                        panic!(
+                            "Completed({:?}) at {} without a pending Process: {:?}",
+                            item, index, events
+                        );
+                    };
+                    in_flight.swap_remove(position);
+                }
+                Callback::Cancelled | Callback::Finished => {
+                    assert_eq!(
+                        index,
+                        events.len() - 1,
+                        "{:?} must be the last callback: {:?}",
+                        event,
+                        events
+                    );
+                }
+            }
+        }
+    }
+}
+
+impl<TPC, T, H> TaskDelegation<TPC, T> for CallbackRecorder<H, T>
+where
+    TPC: AwaitableConsumer<T>,
+    T: StaticTaskItem + PartialEq,
+    H: TaskDelegation<TPC, T>,
+{
+    fn on_started(&self, pc: &TPC) {
+        self.record(Callback::Started);
+        self.handler.on_started(pc);
+    }
+
+    fn process(&self, pc: &TPC, item: &T) -> Result<TaskResult> {
+        self.record(Callback::Process(item.clone()));
+        self.handler.process(pc, item)
+    }
+
+    fn on_completed(&self, pc: &TPC, item: &T, result: &TaskResult) -> bool {
+        self.record(Callback::Completed(item.clone(), result.clone()));
+        self.handler.on_completed(pc, item, result)
+    }
+
+    fn on_cancelled(&self, pc: &TPC) {
+        self.record(Callback::Cancelled);
+        self.handler.on_cancelled(pc);
+    }
+
+    fn on_finished(&self, pc: &TPC) {
+        self.record(Callback::Finished);
+        self.handler.on_finished(pc);
+    }
+}
@@ -0,0 +1,115 @@
+#[cfg(test)]
+mod tests {
+    use emixthreading::{AsyncAutoResetCond, AsyncCountdownCond, AsyncManualResetCond};
+    use std::{
+        sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+        },
+        time::Duration,
+    };
+    use tokio::time;
+
+    #[tokio::test]
+    async fn test_async_autoreset_wait() {
+        let cond = AsyncAutoResetCond::new_unset();
+        let waiter = {
+            let cond = cond.clone();
+            tokio::spawn(async move { cond.wait().await })
+        };
+
+        time::sleep(Duration::from_millis(20)).await;
+        cond.set().unwrap();
+        waiter.await.unwrap().unwrap();
+        assert!(!cond.is_set().unwrap(), "Should auto-reset after release");
+    }
+
+    #[tokio::test]
+    async fn test_async_autoreset_releases_one() {
+        let cond = AsyncAutoResetCond::new_unset();
+        let released = Arc::new(AtomicUsize::new(0));
+        let mut waiters = Vec::new();
+
+        for _ in 0..3 {
+            let cond = cond.clone();
+            let released = released.clone();
+            waiters.push(tokio::spawn(async move {
+                cond.wait().await.unwrap();
+                released.fetch_add(1, Ordering::SeqCst);
+            }));
+        }
+
+        time::sleep(Duration::from_millis(20)).await;
+        cond.set().unwrap();
+        time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(released.load(Ordering::SeqCst), 1);
+
+        cond.set().unwrap();
+        time::sleep(Duration::from_millis(20)).await;
+        cond.set().unwrap();
+
+        for waiter in waiters {
+            waiter.await.unwrap();
+        }
+
+        assert_eq!(released.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_async_autoreset_timeout() {
+        let cond = AsyncAutoResetCond::new_unset();
+        assert!(!cond.wait_timeout(Duration::from_millis(20)).await.unwrap());
+
+        cond.set().unwrap();
+        assert!(cond.wait_timeout(Duration::from_millis(20)).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_async_autoreset_dropped_wait_keeps_signal() {
+        let cond = AsyncAutoResetCond::new_unset();
+
+        // A wait abandoned by a timeout must not consume a later signal.
+        assert!(!cond.wait_timeout(Duration::from_millis(10)).await.unwrap());
+        cond.set().unwrap();
+        assert!(cond.is_set().unwrap());
+        assert!(cond.try_wait().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_async_manualreset_releases_all() {
+        let cond = AsyncManualResetCond::new_unset();
+        let mut waiters = Vec::new();
+
+        for _ in 0..5 {
+            let cond = cond.clone();
+            waiters.push(tokio::spawn(async move { cond.wait().await }));
+        }
+
+        time::sleep(Duration::from_millis(20)).await;
+        cond.set().unwrap();
+
+        for waiter in waiters {
+            waiter.await.unwrap().unwrap();
+        }
+
+        assert!(cond.is_set().unwrap(), "Should stay set");
+        cond.reset().unwrap();
+        assert!(!cond.wait_timeout(Duration::from_millis(10)).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_async_countdown() {
+        let cond = AsyncCountdownCond::new(3);
+        let waiter = {
+            let cond = cond.clone();
+            tokio::spawn(async move { cond.wait_timeout(Duration::from_secs(5)).await })
+        };
+
+        assert_eq!(cond.signal().unwrap(), 2);
+        assert_eq!(cond.add_count(1).unwrap(), 3);
+        assert_eq!(cond.signal_n(3).unwrap(), 0);
+        assert!(waiter.await.unwrap().unwrap());
+        assert_eq!(cond.try_add_count(1).unwrap(), None);
+        assert_eq!(cond.initial_count(), 3);
+    }
+}
@@ -0,0 +1,105 @@
+#[cfg(test)]
+mod tests {
+    use emixthreading::Barrier;
+    use std::{
+        sync::{Arc, Mutex},
+        thread,
+        time::Duration,
+    };
+
+    #[test]
+    fn test_barrier_phases_with_callback() {
+        let phases = Arc::new(Mutex::new(Vec::new()));
+        let barrier = {
+            let phases = phases.clone();
+            Barrier::with_callback(3, move |phase| phases.lock().unwrap().push(phase))
+        };
+
+        let handles: Vec<_> = (0..3)
+            .map(|_| {
+                let barrier = barrier.clone();
+                thread::spawn(move || {
+                    for expected in 0..4 {
+                        assert_eq!(barrier.signal_and_wait().unwrap(), expected);
+                    }
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(*phases.lock().unwrap(), vec![0, 1, 2, 3]);
+        assert_eq!(barrier.current_phase().unwrap(), 4);
+    }
+
+    #[test]
+    fn test_barrier_timeout_withdraws_signal() {
+        let barrier = Barrier::new(2);
+        assert!(
+            !barrier
+                .signal_and_wait_timeout(Duration::from_millis(20))
+                .unwrap()
+        );
+        assert_eq!(barrier.participants_remaining().unwrap(), 2);
+        assert_eq!(barrier.current_phase().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_barrier_remove_participants_completes_phase() {
+        let barrier = Barrier::new(2);
+        let waiter = {
+            let barrier = barrier.clone();
+            thread::spawn(move || barrier.signal_and_wait())
+        };
+
+        thread::sleep(Duration::from_millis(20));
+        barrier.remove_participants(1).unwrap();
+        assert_eq!(waiter.join().unwrap().unwrap(), 0);
+        assert_eq!(barrier.participant_count().unwrap(), 1);
+        assert!(barrier.remove_participants(2).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_barrier_async() {
+        let barrier = Barrier::new(3);
+        barrier.add_participants(1).unwrap();
+        let tasks: Vec<_> = (0..4)
+            .map(|_| {
+                let barrier = barrier.clone();
+                tokio::spawn(async move { barrier.signal_and_wait_async().await })
+            })
+            .collect();
+
+        for task in tasks {
+            assert_eq!(task.await.unwrap().unwrap(), 0);
+        }
+
+        assert!(
+            !barrier
+                .signal_and_wait_timeout_async(Duration::from_millis(20))
+                .await
+                .unwrap()
+        );
+        assert_eq!(barrier.participants_remaining().unwrap(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_barrier_async_drop_withdraws_signal() {
+        let barrier = Barrier::new(2);
+        let task = tokio::spawn({
+            let barrier = barrier.clone();
+            async move { barrier.signal_and_wait_async().await }
+        });
+
+        while barrier.participants_remaining().unwrap() == 2 {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+
+        task.abort();
+        assert!(task.await.unwrap_err().is_cancelled());
+        assert_eq!(barrier.participants_remaining().unwrap(), 2);
+        assert_eq!(barrier.current_phase().unwrap(), 0);
+    }
+}
@@ -0,0 +1,211 @@
+#[cfg(test)]
+mod tests {
+    use emixcore::{Error, Result};
+    use emixthreading::{
+        BatchTaskDelegation, TaskResult,
+        consumer::{BatchConsumer, BatchConsumerOptions},
+        test_support::{VirtualClock, eventually},
+    };
+    use std::{
+        sync::{
+            Arc, Mutex,
+            atomic::{AtomicUsize, Ordering},
+        },
+        time::Duration,
+    };
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+    const MAX_LATENCY: Duration = Duration::from_secs(60);
+
+    #[derive(Clone, Debug)]
+    struct BatchHandler {
+        batches: Arc<Mutex<Vec<Vec<usize>>>>,
+        succeeded: Arc<AtomicUsize>,
+        failed: Arc<AtomicUsize>,
+        fail_batches: bool,
+    }
+
+    impl BatchHandler {
+        fn new() -> Self {
+            BatchHandler {
+                batches: Arc::new(Mutex::new(Vec::new())),
+                succeeded: Arc::new(AtomicUsize::new(0)),
+                failed: Arc::new(AtomicUsize::new(0)),
+                fail_batches: false,
+            }
+        }
+
+        fn batches(&self) -> Vec<Vec<usize>> {
+            self.batches.lock().unwrap().clone()
+        }
+    }
+
+    impl BatchTaskDelegation<BatchConsumer<usize>, usize> for BatchHandler {
+        fn on_started(&self, _pc: &BatchConsumer<usize>) {}
+
+        fn process(&self, _pc: &BatchConsumer<usize>, items: &[usize]) -> Result<Vec<TaskResult>> {
+            self.batches.lock().unwrap().push(items.to_vec());
+
+            if self.fail_batches {
+                return Err(Error::Other("Batch rejected".to_string()));
+            }
+
+            Ok(items
+                .iter()
+                .map(|n| {
+                    if n.is_multiple_of(5) {
+                        TaskResult::Error(format!("Item {} rejected", n))
+                    } else {
+                        TaskResult::Success
+                    }
+                })
+                .collect())
+        }
+
+        fn on_completed(
+            &self,
+            _pc: &BatchConsumer<usize>,
+            _item: &usize,
+            result: &TaskResult,
+        ) -> bool {
+            match result {
+                TaskResult::Success => self.succeeded.fetch_add(1, Ordering::SeqCst),
+                _ => self.failed.fetch_add(1, Ordering::SeqCst),
+            };
+            true
+        }
+
+        fn on_cancelled(&self, _pc: &BatchConsumer<usize>) {}
+
+        fn on_finished(&self, _pc: &BatchConsumer<usize>) {}
+    }
+
+    #[test]
+    fn test_flushes_full_batches_and_remainder_on_complete() -> Result<()> {
+        let handler = BatchHandler::new();
+        let consumer = BatchConsumer::with_options(
+            BatchConsumerOptions::new()
+                .with_batch_size(4)
+                .with_max_latency(TIMEOUT),
+        );
+        consumer.start(&handler)?;
+
+        for i in 1..=10 {
+            consumer.enqueue(i)?;
+        }
+
+        consumer.complete();
+        consumer.wait_for(TIMEOUT)?;
+
+        assert_eq!(
+            handler.batches(),
+            vec![vec![1, 2, 3, 4], vec![5, 6, 7, 8], vec![9, 10]]
+        );
+        assert!(consumer.is_finished());
+        assert_eq!(consumer.running(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_flushes_after_max_latency() -> Result<()> {
+        let clock = VirtualClock::new();
+        let handler = BatchHandler::new();
+        let consumer = BatchConsumer::with_options(
+            BatchConsumerOptions::new()
+                .with_threads(1)
+                .with_batch_size(100)
+                .with_max_latency(MAX_LATENCY),
+        )
+        .with_clock(&clock);
+        consumer.start(&handler)?;
+        consumer.enqueue(1)?;
+        consumer.enqueue(2)?;
+
+        assert!(eventually(TIMEOUT, || consumer.running() == 2));
+        assert!(clock.wait_for_sleepers(1, TIMEOUT));
+        assert!(
+            handler.batches().is_empty(),
+            "A partial batch should wait for max_latency on the clock"
+        );
+
+        clock.advance(MAX_LATENCY);
+        assert!(
+            eventually(TIMEOUT, || handler.batches() == vec![vec![1, 2]]),
+            "A partial batch should flush once its oldest item waited max_latency"
+        );
+
+        consumer.enqueue(3)?;
+        consumer.complete();
+        assert!(clock.advance_until(MAX_LATENCY, TIMEOUT, || consumer.is_finished()));
+        assert_eq!(handler.batches(), vec![vec![1, 2], vec![3]]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_reports_results_per_item() -> Result<()> {
+        let handler = BatchHandler::new();
+        let consumer = BatchConsumer::with_options(
+            BatchConsumerOptions::new()
+                .with_threads(2)
+                .with_batch_size(3),
+        );
+        consumer.start(&handler)?;
+
+        for i in 1..=20 {
+            consumer.enqueue(i)?;
+        }
+
+        consumer.complete();
+        consumer.wait_for(TIMEOUT)?;
+
+        assert_eq!(handler.succeeded.load(Ordering::SeqCst), 16);
+        assert_eq!(handler.failed.load(Ordering::SeqCst), 4);
+        let mut items = handler.batches().concat();
+        items.sort();
+        assert_eq!(items, (1..=20).collect::<Vec<_>>());
+        Ok(())
+    }
+
+    #[test]
+    fn test_failed_batch_fails_every_item() -> Result<()> {
+        let mut handler = BatchHandler::new();
+        handler.fail_batches = true;
+        let consumer = BatchConsumer::with_options(BatchConsumerOptions::new().with_batch_size(3));
+        consumer.start(&handler)?;
+
+        for i in 1..=7 {
+            consumer.enqueue(i)?;
+        }
+
+        consumer.complete();
+        consumer.wait_for(TIMEOUT)?;
+
+        assert_eq!(handler.batches().len(), 3);
+        assert_eq!(handler.succeeded.load(Ordering::SeqCst), 0);
+        assert_eq!(handler.failed.load(Ordering::SeqCst), 7);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_cancel_drops_partial_batch() -> Result<()> {
+        let handler = BatchHandler::new();
+        let consumer = BatchConsumer::with_options(
+            BatchConsumerOptions::new()
+                .with_batch_size(10)
+                .with_max_latency(TIMEOUT),
+        );
+        consumer.start(&handler)?;
+        consumer.enqueue(1)?;
+        consumer.enqueue(2)?;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        consumer.cancel();
+
+        assert!(matches!(
+            consumer.wait_for_async(TIMEOUT).await,
+            Err(Error::Canceled)
+        ));
+        assert!(handler.batches().is_empty());
+        assert!(consumer.enqueue(3).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,68 @@
+#[cfg(test)]
+mod tests {
+    use emixcore::{Error, Result};
+    use emixthreading::Bulkhead;
+    use std::{
+        sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+        },
+        thread,
+        time::Duration,
+    };
+
+    #[test]
+    fn test_limits_concurrent_calls() -> Result<()> {
+        let bulkhead = Bulkhead::new(3);
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let handles = (0..12)
+            .map(|_| {
+                let bulkhead = bulkhead.clone();
+                let running = running.clone();
+                let peak = peak.clone();
+                thread::spawn(move || {
+                    bulkhead.call(|| {
+                        let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                        peak.fetch_max(now, Ordering::SeqCst);
+                        thread::sleep(Duration::from_millis(20));
+                        running.fetch_sub(1, Ordering::SeqCst);
+                        Ok(())
+                    })
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for handle in handles {
+            handle.join().unwrap()?;
+        }
+
+        assert_eq!(peak.load(Ordering::SeqCst), 3);
+        assert_eq!(bulkhead.available()?, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rejects_after_max_wait() -> Result<()> {
+        let bulkhead = Bulkhead::with_max_wait(1, Duration::from_millis(30));
+        let permit = bulkhead.enter()?;
+        assert!(bulkhead.try_enter()?.is_none());
+        assert!(matches!(bulkhead.enter(), Err(Error::Exceeded(_))));
+        drop(permit);
+        assert!(bulkhead.try_enter()?.is_some());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_call_async() -> Result<()> {
+        let bulkhead = Bulkhead::with_max_wait(1, Duration::from_millis(30));
+        let permit = bulkhead.enter_async().await?;
+        assert!(matches!(
+            bulkhead.call_async(async { Ok(1) }).await,
+            Err(Error::Exceeded(_))
+        ));
+        drop(permit);
+        assert_eq!(bulkhead.call_async(async { Ok(2) }).await?, 2);
+        Ok(())
+    }
+}
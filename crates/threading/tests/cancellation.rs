@@ -0,0 +1,171 @@
+#[cfg(test)]
+mod tests {
+    use emixcore::{Error, Result};
+    use emixthreading::{
+        CancelReason, CancellationToken, TaskDelegation, TaskResult, consumer::Consumer,
+    };
+    use std::{
+        sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+        },
+        thread,
+        time::Duration,
+    };
+
+    #[test]
+    fn test_token_cancel() {
+        let token = CancellationToken::new();
+        assert!(!token.is_cancelled());
+        assert!(token.check().is_ok());
+        assert_eq!(token.reason(), None);
+
+        token.cancel();
+        assert!(token.is_cancelled());
+        assert_eq!(token.reason(), Some(CancelReason::Requested));
+        assert!(matches!(token.check(), Err(Error::Canceled)));
+    }
+
+    #[test]
+    fn test_token_first_reason_wins() {
+        let token = CancellationToken::new();
+        token.cancel_with(CancelReason::Shutdown);
+        token.cancel_with(CancelReason::TimedOut);
+        assert_eq!(token.reason(), Some(CancelReason::Shutdown));
+    }
+
+    #[test]
+    fn test_child_token_follows_parent() {
+        let parent = CancellationToken::new();
+        let child = parent.child_token();
+        let grandchild = child.child_token();
+
+        parent.cancel_with(CancelReason::Failed("boom".to_string()));
+        assert!(child.is_cancelled());
+        assert!(grandchild.is_cancelled());
+        assert_eq!(
+            grandchild.reason(),
+            Some(CancelReason::Failed("boom".to_string())),
+            "Children should inherit the parent's reason"
+        );
+    }
+
+    #[test]
+    fn test_child_token_does_not_cancel_parent() {
+        let parent = CancellationToken::new();
+        let child = parent.child_token();
+        child.cancel();
+        assert!(child.is_cancelled());
+        assert!(!parent.is_cancelled());
+    }
+
+    #[test]
+    fn test_child_of_cancelled_token_is_cancelled() {
+        let parent = CancellationToken::new();
+        parent.cancel();
+        assert!(parent.child_token().is_cancelled());
+    }
+
+    #[test]
+    fn test_on_cancel_callbacks() {
+        let token = CancellationToken::new();
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        let c = calls.clone();
+        token.on_cancel(move |reason| {
+            assert_eq!(*reason, CancelReason::Requested);
+            c.fetch_add(1, Ordering::SeqCst);
+        });
+        let c = calls.clone();
+        let registration = token.on_cancel(move |_| {
+            c.fetch_add(10, Ordering::SeqCst);
+        });
+        assert!(token.unregister(registration));
+
+        token.cancel();
+        token.cancel();
+        assert_eq!(calls.load(Ordering::SeqCst), 1, "Callback should run once");
+
+        // Registering after cancellation runs the callback immediately.
+        let c = calls.clone();
+        token.on_cancel(move |_| {
+            c.fetch_add(1, Ordering::SeqCst);
+        });
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_token_wait() -> Result<()> {
+        let token = CancellationToken::new();
+        assert!(!token.wait_timeout(Duration::from_millis(20))?);
+
+        let t = token.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            t.cancel();
+        });
+
+        token.wait()?;
+        assert!(token.is_cancelled());
+        Ok(())
+    }
+
+    #[test]
+    fn test_token_cancel_after() -> Result<()> {
+        let token = CancellationToken::new();
+        token.cancel_after(Duration::from_millis(20));
+        assert!(token.wait_timeout(Duration::from_secs(5))?);
+        assert_eq!(token.reason(), Some(CancelReason::TimedOut));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_token_cancelled_async() {
+        let token = CancellationToken::new();
+        let child = token.child_token();
+
+        let t = token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            t.cancel();
+        });
+
+        tokio::time::timeout(Duration::from_secs(5), child.cancelled())
+            .await
+            .expect("Child should be cancelled");
+    }
+
+    #[derive(Clone, Debug)]
+    struct IdleHandler;
+
+    impl TaskDelegation<Consumer<usize>, usize> for IdleHandler {
+        fn on_started(&self, _pc: &Consumer<usize>) {}
+
+        fn process(&self, _pc: &Consumer<usize>, _item: &usize) -> Result<TaskResult> {
+            Ok(TaskResult::Success)
+        }
+
+        fn on_completed(&self, _pc: &Consumer<usize>, _item: &usize, _result: &TaskResult) -> bool {
+            true
+        }
+
+        fn on_cancelled(&self, _pc: &Consumer<usize>) {}
+
+        fn on_finished(&self, _pc: &Consumer<usize>) {}
+    }
+
+    #[tokio::test]
+    async fn test_consumer_cancel_on_token() -> Result<()> {
+        let token = CancellationToken::new();
+        let consumer = Consumer::<usize>::new();
+        consumer.cancel_on(&token.child_token());
+        consumer.start(&IdleHandler)?;
+
+        token.cancel();
+        assert!(consumer.is_cancelled(), "Consumer should follow the token");
+
+        let result = consumer.wait_for_async(Duration::from_secs(5)).await;
+        assert!(matches!(result, Err(Error::Canceled)));
+        Ok(())
+    }
+}
@@ -0,0 +1,314 @@
+#[cfg(test)]
+mod tests {
+    use emixcore::{Error, Result};
+    use emixthreading::{
+        Bulkhead, CircuitBreaker, CircuitBreakerOptions, CircuitState, OpenCircuitAction,
+        ResilientHandler, TaskDelegation, TaskResult,
+        consumer::{ProducerConsumer, ProducerConsumerOptions},
+        dataflow::DataflowTarget,
+    };
+    use std::{
+        sync::{
+            Arc, Mutex,
+            atomic::{AtomicBool, AtomicUsize, Ordering},
+        },
+        thread,
+        time::Duration,
+    };
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    fn fail() -> Result<()> {
+        Err(Error::Other("Dependency is down".to_string()))
+    }
+
+    #[test]
+    fn test_opens_after_consecutive_failures() -> Result<()> {
+        let breaker = CircuitBreaker::with_options(
+            CircuitBreakerOptions::new()
+                .with_failure_threshold(3)
+                .with_failure_rate(0.0, 1)
+                .with_cooldown(Duration::from_millis(100)),
+        );
+
+        for _ in 0..2 {
+            assert!(breaker.call(fail).is_err());
+        }
+
+        breaker.call(|| Ok(()))?;
+
+        for _ in 0..3 {
+            assert!(breaker.call(fail).is_err());
+        }
+
+        assert_eq!(breaker.state()?, CircuitState::Open);
+        assert!(breaker.remaining_cooldown()? > Duration::ZERO);
+        assert!(matches!(breaker.call(|| Ok(())), Err(Error::CircuitOpen)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_opens_on_failure_rate() -> Result<()> {
+        let breaker = CircuitBreaker::with_options(
+            CircuitBreakerOptions::new()
+                .with_failure_threshold(0)
+                .with_failure_rate(0.5, 4),
+        );
+
+        assert!(breaker.call(fail).is_err());
+        breaker.call(|| Ok(()))?;
+        breaker.call(|| Ok(()))?;
+        assert_eq!(breaker.state()?, CircuitState::Closed);
+        assert!(breaker.call(fail).is_err());
+        assert_eq!(breaker.state()?, CircuitState::Open);
+        Ok(())
+    }
+
+    #[test]
+    fn test_half_open_closes_or_reopens() -> Result<()> {
+        let breaker = CircuitBreaker::with_options(
+            CircuitBreakerOptions::new()
+                .with_cooldown(Duration::from_millis(50))
+                .with_half_open_calls(2),
+        );
+        breaker.trip()?;
+        thread::sleep(Duration::from_millis(80));
+        assert_eq!(breaker.state()?, CircuitState::HalfOpen);
+
+        let first = breaker.try_acquire()?;
+        let second = breaker.try_acquire()?;
+        assert!(matches!(breaker.try_acquire(), Err(Error::CircuitOpen)));
+        first.success()?;
+        second.failure()?;
+        assert_eq!(breaker.state()?, CircuitState::Open);
+
+        thread::sleep(Duration::from_millis(80));
+        breaker.call(|| Ok(()))?;
+        breaker.call(|| Ok(()))?;
+        assert_eq!(breaker.state()?, CircuitState::Closed);
+        Ok(())
+    }
+
+    #[test]
+    fn test_stale_permits_do_not_decide_half_open() -> Result<()> {
+        let breaker = CircuitBreaker::with_options(
+            CircuitBreakerOptions::new()
+                .with_cooldown(Duration::from_millis(50))
+                .with_half_open_calls(1),
+        );
+        let late_success = breaker.try_acquire()?;
+        let late_failure = breaker.try_acquire()?;
+        breaker.trip()?;
+        thread::sleep(Duration::from_millis(80));
+        assert_eq!(breaker.state()?, CircuitState::HalfOpen);
+
+        // Both calls started while the circuit was closed, so neither is a trial.
+        late_success.success()?;
+        assert_eq!(breaker.state()?, CircuitState::HalfOpen);
+        late_failure.failure()?;
+        assert_eq!(breaker.state()?, CircuitState::HalfOpen);
+
+        breaker.call(|| Ok(()))?;
+        assert_eq!(breaker.state()?, CircuitState::Closed);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_call_async() -> Result<()> {
+        let breaker =
+            CircuitBreaker::with_options(CircuitBreakerOptions::new().with_failure_threshold(1));
+        assert_eq!(breaker.call_async(async { Ok(5) }).await?, 5);
+        assert!(breaker.call_async(async { fail() }).await.is_err());
+        assert!(matches!(
+            breaker.call_async(async { Ok(5) }).await,
+            Err(Error::CircuitOpen)
+        ));
+        breaker.reset()?;
+        assert_eq!(breaker.call_async(async { Ok(6) }).await?, 6);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_dropped_trial_gives_slot_back() -> Result<()> {
+        let breaker = CircuitBreaker::with_options(
+            CircuitBreakerOptions::new()
+                .with_cooldown(Duration::from_millis(20))
+                .with_half_open_calls(1),
+        );
+        breaker.trip()?;
+        tokio::time::sleep(Duration::from_millis(40)).await;
+
+        let abandoned = tokio::time::timeout(
+            Duration::from_millis(20),
+            breaker.call_async(std::future::pending::<Result<()>>()),
+        )
+        .await;
+        assert!(abandoned.is_err(), "The trial call should time out");
+        assert_eq!(breaker.state()?, CircuitState::HalfOpen);
+
+        assert_eq!(breaker.call_async(async { Ok(7) }).await?, 7);
+        assert_eq!(breaker.state()?, CircuitState::Closed);
+        Ok(())
+    }
+
+    #[derive(Clone, Debug, Default)]
+    struct FlakyHandler {
+        down: Arc<AtomicBool>,
+        processed: Arc<AtomicUsize>,
+        completed: Arc<AtomicUsize>,
+    }
+
+    impl TaskDelegation<ProducerConsumer<usize>, usize> for FlakyHandler {
+        fn on_started(&self, _pc: &ProducerConsumer<usize>) {}
+
+        fn process(&self, _pc: &ProducerConsumer<usize>, _item: &usize) -> Result<TaskResult> {
+            if self.down.load(Ordering::SeqCst) {
+                return Ok(TaskResult::Error("Dependency is down".to_string()));
+            }
+
+            self.processed.fetch_add(1, Ordering::SeqCst);
+            Ok(TaskResult::Success)
+        }
+
+        fn on_completed(
+            &self,
+            _pc: &ProducerConsumer<usize>,
+            _item: &usize,
+            _result: &TaskResult,
+        ) -> bool {
+            self.completed.fetch_add(1, Ordering::SeqCst);
+            true
+        }
+
+        fn on_cancelled(&self, _pc: &ProducerConsumer<usize>) {}
+
+        fn on_finished(&self, _pc: &ProducerConsumer<usize>) {}
+    }
+
+    #[derive(Debug, Default)]
+    struct DeadLetters(Mutex<Vec<usize>>);
+
+    impl DataflowTarget<usize> for DeadLetters {
+        fn post(&self, item: usize) -> Result<()> {
+            self.0.lock().unwrap().push(item);
+            Ok(())
+        }
+
+        fn complete(&self) {}
+
+        fn cancel(&self) {}
+    }
+
+    #[test]
+    fn test_handler_dead_letters_while_open() -> Result<()> {
+        let inner = FlakyHandler::default();
+        inner.down.store(true, Ordering::SeqCst);
+        let dead_letters = Arc::new(DeadLetters::default());
+        let breaker =
+            CircuitBreaker::with_options(CircuitBreakerOptions::new().with_failure_threshold(2));
+        let handler = ResilientHandler::new(inner.clone())
+            .with_circuit_breaker(
+                &breaker,
+                OpenCircuitAction::DeadLetter(dead_letters.clone()),
+            )
+            .with_bulkhead(&Bulkhead::new(1));
+        let queue = ProducerConsumer::<usize>::with_options(
+            ProducerConsumerOptions::new().with_capacity(10),
+        );
+
+        for i in 0..10 {
+            queue.enqueue(i)?;
+        }
+
+        queue.complete();
+        queue.start(&handler)?;
+        queue.wait_for(TIMEOUT)?;
+
+        assert_eq!(breaker.state()?, CircuitState::Open);
+        assert_eq!(inner.completed.load(Ordering::SeqCst), 2);
+        assert_eq!(dead_letters.0.lock().unwrap().len(), 8);
+        Ok(())
+    }
+
+    #[test]
+    fn test_handler_requeues_until_closed() -> Result<()> {
+        let inner = FlakyHandler::default();
+        let breaker = CircuitBreaker::with_options(
+            CircuitBreakerOptions::new().with_cooldown(Duration::from_millis(100)),
+        );
+        breaker.trip()?;
+        let handler = ResilientHandler::new(inner.clone())
+            .with_circuit_breaker(&breaker, OpenCircuitAction::Requeue)
+            .with_requeue_delay(Duration::from_millis(10));
+        let queue = ProducerConsumer::<usize>::with_options(
+            ProducerConsumerOptions::new()
+                .with_capacity(20)
+                .with_threads(2),
+        );
+        queue.start(&handler)?;
+
+        for i in 0..10 {
+            queue.enqueue(i)?;
+        }
+
+        while inner.processed.load(Ordering::SeqCst) < 10 {
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        queue.complete();
+        queue.wait_for(TIMEOUT)?;
+
+        assert_eq!(breaker.state()?, CircuitState::Closed);
+        assert_eq!(inner.processed.load(Ordering::SeqCst), 10);
+        assert_eq!(inner.completed.load(Ordering::SeqCst), 10);
+        Ok(())
+    }
+
+    #[test]
+    fn test_handler_requeue_to_full_queue_fails_the_item() -> Result<()> {
+        let inner = FlakyHandler::default();
+        let breaker = CircuitBreaker::new();
+        breaker.trip()?;
+        let handler = ResilientHandler::new(inner.clone())
+            .with_circuit_breaker(&breaker, OpenCircuitAction::Requeue)
+            .with_requeue_delay(Duration::from_millis(50));
+        let queue = ProducerConsumer::<usize>::with_options(
+            ProducerConsumerOptions::new().with_capacity(1),
+        );
+        queue.start(&handler)?;
+        // The lone worker takes the first item, so the second fills the queue before the
+        // worker tries to put the first one back.
+        queue.enqueue(0)?;
+        queue.enqueue(1)?;
+
+        while inner.completed.load(Ordering::SeqCst) < 1 {
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        queue.complete();
+        queue.wait_for(TIMEOUT)?;
+
+        assert_eq!(inner.processed.load(Ordering::SeqCst), 0);
+        assert_eq!(inner.completed.load(Ordering::SeqCst), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_handler_rejects_requeue_to_rendezvous_queue() -> Result<()> {
+        let inner = FlakyHandler::default();
+        let breaker = CircuitBreaker::new();
+        breaker.trip()?;
+        let handler = ResilientHandler::new(inner.clone())
+            .with_circuit_breaker(&breaker, OpenCircuitAction::Requeue)
+            .with_bulkhead(&Bulkhead::new(1));
+        let queue = ProducerConsumer::<usize>::new();
+        queue.start(&handler)?;
+        queue.enqueue(0)?;
+        queue.complete();
+        queue.wait_for(TIMEOUT)?;
+
+        assert_eq!(inner.processed.load(Ordering::SeqCst), 0);
+        assert_eq!(inner.completed.load(Ordering::SeqCst), 1);
+        Ok(())
+    }
+}
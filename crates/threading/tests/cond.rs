@@ -1,6 +1,6 @@
 #[cfg(test)]
 mod tests {
-    use emixthreading::{AutoResetCond, CountdownCond, ManualResetCond};
+    use emixthreading::{AutoResetCond, CountdownCond, ManualResetCond, test_support::eventually};
     use std::sync::{
         Arc,
         atomic::{AtomicUsize, Ordering},
@@ -8,6 +8,9 @@ mod tests {
     use std::thread;
     use std::time::Duration;
 
+    /// How long a waiter may take to notice a signal before a test fails.
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
     // ============================================================================
     // AutoResetCond Tests
     // ============================================================================
@@ -191,7 +194,7 @@ mod tests {
 
         // Set once - should wake exactly one waiter
         cond.set().unwrap();
-        thread::sleep(Duration::from_millis(50));
+        assert!(eventually(TIMEOUT, || woke_count.load(Ordering::SeqCst) >= 1));
         assert_eq!(
             woke_count.load(Ordering::SeqCst),
             1,
@@ -200,17 +203,20 @@ mod tests {
 
         // Set again - should wake another waiter
         cond.set().unwrap();
-        thread::sleep(Duration::from_millis(50));
+        assert!(eventually(TIMEOUT, || woke_count.load(Ordering::SeqCst) >= 2));
         assert_eq!(
             woke_count.load(Ordering::SeqCst),
             2,
             "Exactly two waiters should have woken"
         );
 
-        // Set remaining times to wake all waiters
-        for _ in 0..3 {
+        // Set remaining times to wake all waiters, one at a time so no set is lost on a
+        // flag that is still set
+        for woken in 3..=5 {
             cond.set().unwrap();
-            thread::sleep(Duration::from_millis(50));
+            assert!(eventually(TIMEOUT, || {
+                woke_count.load(Ordering::SeqCst) >= woken
+            }));
         }
 
         // All waiters should wake up
@@ -270,10 +276,7 @@ mod tests {
         });
 
         let flag_ref = flag.clone();
-        let result = cond.wait_timeout_while(
-            || flag_ref.load(Ordering::SeqCst) < 5,
-            Duration::from_millis(200),
-        );
+        let result = cond.wait_timeout_while(|| flag_ref.load(Ordering::SeqCst) < 5, TIMEOUT);
         assert!(result.is_ok(), "wait_timeout_while should succeed");
         assert!(
             result.unwrap(),
@@ -567,9 +570,9 @@ mod tests {
 
         // Set to wake all waiters
         cond.set().unwrap();
-        thread::sleep(Duration::from_millis(50));
+        assert!(eventually(TIMEOUT, || woke_count.load(Ordering::SeqCst) == 5));
 
-        // Reset while waiters are processing
+        // Reset once the waiters were released
         cond.reset().unwrap();
         assert!(!cond.is_set().unwrap(), "Should be reset");
 
@@ -631,10 +634,7 @@ mod tests {
         });
 
         let flag_ref = flag.clone();
-        let result = cond.wait_timeout_while(
-            || flag_ref.load(Ordering::SeqCst) < 5,
-            Duration::from_millis(200),
-        );
+        let result = cond.wait_timeout_while(|| flag_ref.load(Ordering::SeqCst) < 5, TIMEOUT);
         assert!(result.is_ok(), "wait_timeout_while should succeed");
         assert!(
             result.unwrap(),
@@ -1017,7 +1017,7 @@ mod tests {
         });
 
         // Wait with timeout - should succeed when signal arrives
-        let result = cond.wait_timeout(Duration::from_millis(200));
+        let result = cond.wait_timeout(TIMEOUT);
         assert!(result.is_ok(), "wait_timeout should succeed");
         assert!(result.unwrap(), "Should not timeout, signal should arrive");
         assert_eq!(cond.current_count().unwrap(), 0);
@@ -1120,7 +1120,7 @@ mod tests {
         });
 
         // Wait with timeout - should succeed when set
-        let result = cond.wait_timeout(Duration::from_millis(200));
+        let result = cond.wait_timeout(TIMEOUT);
         assert!(result.is_ok(), "wait_timeout should succeed");
         assert!(result.unwrap(), "Should not timeout, should be set");
 
@@ -1142,7 +1142,7 @@ mod tests {
             });
             handles.push(handle);
 
-            let result = cond.wait_timeout(Duration::from_millis(500));
+            let result = cond.wait_timeout(TIMEOUT);
             assert!(result.is_ok(), "wait_timeout should succeed");
             assert!(result.unwrap(), "Should be set in cycle {}", i);
             assert!(!cond.is_set().unwrap(), "Should be reset after wait");
@@ -1207,7 +1207,7 @@ mod tests {
         });
 
         // Wait with timeout - should succeed when set
-        let result = cond.wait_timeout(Duration::from_millis(200));
+        let result = cond.wait_timeout(TIMEOUT);
         assert!(result.is_ok(), "wait_timeout should succeed");
         assert!(result.unwrap(), "Should not timeout, should be set");
 
@@ -1279,9 +1279,9 @@ mod tests {
 
         // Set to wake all
         cond.set().unwrap();
-        thread::sleep(Duration::from_millis(50));
+        assert!(eventually(TIMEOUT, || woke_count.load(Ordering::SeqCst) == 5));
 
-        // Reset while some may still be processing
+        // Reset once the waiters were released
         cond.reset().unwrap();
         assert!(!cond.is_set().unwrap(), "Should be reset");
 
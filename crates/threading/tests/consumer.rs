@@ -1,12 +1,13 @@
 #[cfg(test)]
 mod tests {
-    use emixcore::Result;
+    use emixcore::{Error, Result};
     use emixthreading::{
         QueueBehavior, TaskDelegation, TaskResult,
         consumer::{
             Consumer, ConsumerOptions, InjectorWorker, InjectorWorkerOptions, ProducerConsumer,
-            ProducerConsumerOptions,
+            ProducerConsumerOptions, ScalingOptions,
         },
+        test_support::{InlineDriver, Step, VirtualClock},
     };
     use std::{
         sync::{
@@ -19,6 +20,7 @@ mod tests {
 
     const THREADS: usize = 2; // Reduced for faster tests
     const TEST_SIZE: usize = 100; // Reduced for faster tests
+    const TIMEOUT: Duration = Duration::from_secs(5);
 
     #[derive(Clone, Debug)]
     pub struct TestTaskHandler {
@@ -405,57 +407,55 @@ mod tests {
         Ok(())
     }
 
-    #[tokio::test]
-    async fn test_consumer_pause_resume() -> Result<()> {
+    #[test]
+    fn test_consumer_pause_resume() -> Result<()> {
         let handler = TestTaskHandler::new();
         let consumer = Consumer::<usize>::new();
-        consumer.start(&handler.clone())?;
-
-        // Enqueue some items
-        for i in 1..=50 {
-            consumer.enqueue(i)?;
-        }
 
-        // Pause the consumer
+        // Pause the consumer before it sees any item
         consumer.pause();
         assert!(consumer.is_paused(), "Consumer should be paused");
+        let driver = InlineDriver::start(&consumer, &handler)?;
 
-        // Give it a bit of time to process some items while paused
-        tokio::time::sleep(Duration::from_millis(100)).await;
+        for i in 1..=50 {
+            consumer.enqueue(i)?;
+        }
 
-        let tasks_before_resume = handler.tasks();
+        assert_eq!(driver.step(), Step::Paused);
+        assert_eq!(handler.tasks(), 0, "Should not process tasks while paused");
 
         // Resume the consumer
         consumer.resume();
         assert!(!consumer.is_paused(), "Consumer should be resumed");
 
-        // Complete and wait for processing
+        // Complete and run to the end
         consumer.complete();
-        consumer.wait_async().await?;
-
-        let tasks_after = handler.tasks();
-        assert!(
-            tasks_after > tasks_before_resume,
-            "Should have processed more tasks after resume"
+        driver.run()?;
+        consumer.wait()?;
+        assert_eq!(
+            handler.tasks(),
+            50,
+            "Should process every task after resume"
         );
-        assert!(tasks_after > 0, "Should have processed some tasks");
 
         Ok(())
     }
 
-    #[tokio::test]
-    async fn test_consumer_cancel() -> Result<()> {
+    #[test]
+    fn test_consumer_cancel() -> Result<()> {
         let handler = TestTaskHandler::new();
         let consumer = Consumer::<usize>::new();
-        consumer.start(&handler.clone())?;
+        let driver = InlineDriver::start(&consumer, &handler)?;
 
         // Enqueue many items
         for i in 1..=1000 {
             consumer.enqueue(i)?;
         }
 
-        // Give it some time to process
-        tokio::time::sleep(Duration::from_millis(50)).await;
+        // Process some of them
+        for _ in 0..10 {
+            assert_eq!(driver.step(), Step::Processed);
+        }
 
         // Cancel the consumer
         consumer.cancel();
@@ -467,8 +467,11 @@ mod tests {
             "Should not be able to enqueue after cancel"
         );
 
-        // Wait for cancellation
-        let result = consumer.wait_async().await;
+        // The worker stops before the next item
+        assert_eq!(driver.step(), Step::Finished);
+        assert_eq!(handler.tasks(), 10, "Should stop processing once cancelled");
+
+        let result = consumer.wait();
         assert!(result.is_err(), "Wait should return error when cancelled");
 
         Ok(())
@@ -509,62 +512,53 @@ mod tests {
     #[test]
     fn test_producer_consumer_pause_resume() -> Result<()> {
         let handler = TestTaskHandler::new();
-        let options = ProducerConsumerOptions::new().with_threads(THREADS);
+        let options = ProducerConsumerOptions::new().with_capacity(50);
         let prodcon = ProducerConsumer::<usize>::with_options(options);
-        prodcon.start(&handler)?;
-
-        // Enqueue some items
-        for i in 1..=50 {
-            prodcon.enqueue(i)?;
-        }
 
-        // Give it a bit of time to process some items
-        thread::sleep(Duration::from_millis(100));
-
-        // Pause the producer-consumer
+        // Pause the producer-consumer before it sees any item. The capacity holds every
+        // item, since the paused worker takes none.
         prodcon.pause();
         assert!(prodcon.is_paused(), "ProducerConsumer should be paused");
+        let driver = InlineDriver::start(&prodcon, &handler)?;
 
-        // Give it a bit more time while paused
-        thread::sleep(Duration::from_millis(100));
+        for i in 1..=50 {
+            prodcon.enqueue(i)?;
+        }
 
-        let tasks_before_resume = handler.tasks();
+        assert_eq!(driver.step(), Step::Paused);
+        assert_eq!(handler.tasks(), 0, "Should not process tasks while paused");
 
         // Resume
         prodcon.resume();
         assert!(!prodcon.is_paused(), "ProducerConsumer should be resumed");
-
         prodcon.complete();
-        prodcon.wait()?;
 
-        let tasks_after = handler.tasks();
-        assert!(
-            tasks_after >= tasks_before_resume,
-            "Should not have processed fewer tasks after resume"
+        driver.run()?;
+        prodcon.wait()?;
+        assert_eq!(
+            handler.tasks(),
+            50,
+            "Should process every task after resume"
         );
-        assert!(tasks_after > 0, "Should have processed some tasks");
 
         Ok(())
     }
 
-    #[tokio::test]
-    async fn test_producer_consumer_cancel() -> Result<()> {
+    #[test]
+    fn test_producer_consumer_cancel() -> Result<()> {
         let handler = TestTaskHandler::new();
-        let options = ProducerConsumerOptions::new().with_threads(THREADS);
+        let options = ProducerConsumerOptions::new().with_capacity(100);
         let prodcon = ProducerConsumer::<usize>::with_options(options);
-        prodcon.start(&handler)?;
+        let driver = InlineDriver::start(&prodcon, &handler)?;
 
         // Enqueue items
-        let pc = prodcon.clone();
-        tokio::spawn(async move {
-            for i in 1..=1000 {
-                if let Err(_) = pc.enqueue(i) {
-                    break;
-                }
-            }
-        });
+        for i in 1..=100 {
+            prodcon.enqueue(i)?;
+        }
 
-        tokio::time::sleep(Duration::from_millis(50)).await;
+        for _ in 0..10 {
+            assert_eq!(driver.step(), Step::Processed);
+        }
 
         // Cancel
         prodcon.cancel();
@@ -573,66 +567,71 @@ mod tests {
             "ProducerConsumer should be cancelled"
         );
 
-        let result = prodcon.wait_async().await;
+        assert_eq!(driver.step(), Step::Finished);
+        assert_eq!(handler.tasks(), 10, "Should stop processing once cancelled");
+
+        let result = prodcon.wait();
         assert!(result.is_err(), "Wait should return error when cancelled");
 
         Ok(())
     }
 
-    #[tokio::test]
-    async fn test_injector_worker_pause_resume() -> Result<()> {
+    #[test]
+    fn test_injector_worker_pause_resume() -> Result<()> {
         let handler = TestTaskHandler::new();
-        let options = InjectorWorkerOptions::new().with_threads(THREADS);
-        let injwork = InjectorWorker::<usize>::with_options(options);
-        injwork.start(&handler)?;
+        let injwork = InjectorWorker::<usize>::new();
+
+        // Pause before any item is seen
+        injwork.pause();
+        assert!(injwork.is_paused(), "InjectorWorker should be paused");
+        let driver = InlineDriver::start(&injwork, &handler)?;
 
-        // Enqueue items
         for i in 1..=50 {
             injwork.enqueue(i)?;
         }
 
-        // Pause
-        injwork.pause();
-        assert!(injwork.is_paused(), "InjectorWorker should be paused");
-
-        tokio::time::sleep(Duration::from_millis(100)).await;
-        let tasks_before = handler.tasks();
+        assert_eq!(driver.step(), Step::Paused);
+        assert_eq!(handler.tasks(), 0, "Should not process tasks while paused");
 
         // Resume
         injwork.resume();
         assert!(!injwork.is_paused(), "InjectorWorker should be resumed");
 
         injwork.complete();
-        injwork.wait_async().await?;
-
-        let tasks_after = handler.tasks();
-        assert!(
-            tasks_after > tasks_before,
-            "Should have processed more tasks after resume"
+        driver.run()?;
+        injwork.wait()?;
+        assert_eq!(
+            handler.tasks(),
+            50,
+            "Should process every task after resume"
         );
 
         Ok(())
     }
 
-    #[tokio::test]
-    async fn test_injector_worker_cancel() -> Result<()> {
+    #[test]
+    fn test_injector_worker_cancel() -> Result<()> {
         let handler = TestTaskHandler::new();
-        let options = InjectorWorkerOptions::new().with_threads(THREADS);
-        let injwork = InjectorWorker::<usize>::with_options(options);
-        injwork.start(&handler)?;
+        let injwork = InjectorWorker::<usize>::new();
+        let driver = InlineDriver::start(&injwork, &handler)?;
 
         // Enqueue many items
         for i in 1..=1000 {
             injwork.enqueue(i)?;
         }
 
-        tokio::time::sleep(Duration::from_millis(50)).await;
+        for _ in 0..10 {
+            assert_eq!(driver.step(), Step::Processed);
+        }
 
         // Cancel
         injwork.cancel();
         assert!(injwork.is_cancelled(), "InjectorWorker should be cancelled");
 
-        let result = injwork.wait_async().await;
+        assert_eq!(driver.step(), Step::Finished);
+        assert_eq!(handler.tasks(), 10, "Should stop processing once cancelled");
+
+        let result = injwork.wait();
         assert!(result.is_err(), "Wait should return error when cancelled");
 
         Ok(())
@@ -682,11 +681,11 @@ mod tests {
         Ok(())
     }
 
-    #[tokio::test]
-    async fn test_consumer_stop_enforce_cancel() -> Result<()> {
+    #[test]
+    fn test_consumer_stop_enforce_cancel() -> Result<()> {
         let handler = TestTaskHandler::new();
         let consumer = Consumer::<usize>::new();
-        consumer.start(&handler.clone())?;
+        let driver = InlineDriver::start(&consumer, &handler)?;
 
         // Enqueue items
         for i in 1..=100 {
@@ -697,17 +696,23 @@ mod tests {
         consumer.stop(true);
         assert!(consumer.is_cancelled(), "Stop with enforce should cancel");
 
-        let result = consumer.wait_async().await;
+        assert!(
+            matches!(driver.run(), Err(Error::Canceled)),
+            "Should not process tasks once cancelled"
+        );
+        assert_eq!(handler.tasks(), 0, "Should drop the queued tasks");
+
+        let result = consumer.wait();
         assert!(result.is_err(), "Wait should return error when cancelled");
 
         Ok(())
     }
 
-    #[tokio::test]
-    async fn test_consumer_stop_enforce_complete() -> Result<()> {
+    #[test]
+    fn test_consumer_stop_enforce_complete() -> Result<()> {
         let handler = TestTaskHandler::new();
         let consumer = Consumer::<usize>::new();
-        consumer.start(&handler.clone())?;
+        let driver = InlineDriver::start(&consumer, &handler)?;
 
         // Enqueue items
         for i in 1..=50 {
@@ -722,8 +727,9 @@ mod tests {
         );
         assert!(!consumer.is_cancelled(), "Should not be cancelled");
 
-        consumer.wait_async().await?;
-        assert!(handler.tasks() > 0, "Should have processed tasks");
+        driver.run()?;
+        consumer.wait()?;
+        assert_eq!(handler.tasks(), 50, "Should process every queued task");
 
         Ok(())
     }
@@ -817,4 +823,287 @@ mod tests {
 
         Ok(())
     }
+
+    #[derive(Clone, Debug)]
+    pub struct SlowTaskHandler {
+        pub delay: Duration,
+        pub done: Arc<AtomicUsize>,
+        pub active: Arc<AtomicUsize>,
+        pub peak: Arc<AtomicUsize>,
+    }
+
+    impl SlowTaskHandler {
+        pub fn new(delay: Duration) -> Self {
+            SlowTaskHandler {
+                delay,
+                done: Arc::new(AtomicUsize::new(0)),
+                active: Arc::new(AtomicUsize::new(0)),
+                peak: Arc::new(AtomicUsize::new(0)),
+            }
+        }
+
+        pub fn done(&self) -> usize {
+            self.done.load(Ordering::SeqCst)
+        }
+
+        /// The most items processed at the same time.
+        pub fn peak(&self) -> usize {
+            self.peak.load(Ordering::SeqCst)
+        }
+
+        fn work(&self) {
+            let active = self.active.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(active, Ordering::SeqCst);
+            thread::sleep(self.delay);
+            self.active.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+
+    impl TaskDelegation<ProducerConsumer<usize>, usize> for SlowTaskHandler {
+        fn on_started(&self, _pc: &ProducerConsumer<usize>) {}
+
+        fn process(&self, _pc: &ProducerConsumer<usize>, _item: &usize) -> Result<TaskResult> {
+            self.work();
+            Ok(TaskResult::Success)
+        }
+
+        fn on_completed(
+            &self,
+            _pc: &ProducerConsumer<usize>,
+            _item: &usize,
+            _result: &TaskResult,
+        ) -> bool {
+            self.done.fetch_add(1, Ordering::SeqCst);
+            true
+        }
+
+        fn on_cancelled(&self, _pc: &ProducerConsumer<usize>) {}
+
+        fn on_finished(&self, _pc: &ProducerConsumer<usize>) {}
+    }
+
+    impl TaskDelegation<InjectorWorker<usize>, usize> for SlowTaskHandler {
+        fn on_started(&self, _pc: &InjectorWorker<usize>) {}
+
+        fn process(&self, _pc: &InjectorWorker<usize>, _item: &usize) -> Result<TaskResult> {
+            self.work();
+            Ok(TaskResult::Success)
+        }
+
+        fn on_completed(
+            &self,
+            _pc: &InjectorWorker<usize>,
+            _item: &usize,
+            _result: &TaskResult,
+        ) -> bool {
+            self.done.fetch_add(1, Ordering::SeqCst);
+            true
+        }
+
+        fn on_cancelled(&self, _pc: &InjectorWorker<usize>) {}
+
+        fn on_finished(&self, _pc: &InjectorWorker<usize>) {}
+    }
+
+    fn wait_for_workers(workers: impl Fn() -> usize, expected: usize) -> bool {
+        for _ in 0..200 {
+            if workers() == expected {
+                return true;
+            }
+
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        false
+    }
+
+    #[test]
+    fn test_consumer_set_workers_not_started() {
+        let consumer = Consumer::<usize>::new();
+        assert!(
+            consumer.set_workers(4).is_err(),
+            "Should not resize before start"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_consumer_set_workers() -> Result<()> {
+        let handler = TestTaskHandler::new();
+        let consumer = Consumer::<usize>::new();
+        consumer.start(&handler)?;
+        assert_eq!(consumer.consumers(), 1);
+
+        consumer.set_workers(4)?;
+        assert_eq!(consumer.consumers(), 4, "Should spawn workers immediately");
+        assert_eq!(consumer.target_workers(), 4);
+
+        consumer.set_workers(2)?;
+        assert!(
+            wait_for_workers(|| consumer.consumers(), 2),
+            "Surplus workers should retire"
+        );
+
+        for i in 1..=TEST_SIZE {
+            consumer.enqueue(i)?;
+        }
+
+        consumer.complete();
+        consumer.wait_async().await?;
+        assert_eq!(handler.done(), TEST_SIZE, "Should process every item");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_producer_consumer_autoscale() -> Result<()> {
+        let handler = SlowTaskHandler::new(Duration::from_millis(10));
+        let scaling = ScalingOptions::new()
+            .with_max_workers(4)
+            .with_backlog_threshold(1)
+            .with_idle_timeout(Duration::from_millis(100))
+            .with_interval(Duration::from_millis(10));
+        let options = ProducerConsumerOptions::new()
+            .with_capacity(TEST_SIZE)
+            .with_scaling(scaling);
+        let prodcon = ProducerConsumer::<usize>::with_options(options);
+        prodcon.start(&handler)?;
+
+        for i in 1..=TEST_SIZE {
+            prodcon.enqueue(i)?;
+        }
+
+        assert!(
+            wait_for_workers(|| prodcon.consumers(), 4),
+            "Should scale up to max workers under backlog"
+        );
+        assert!(
+            wait_for_workers(|| prodcon.consumers(), 1),
+            "Idle workers should retire down to min workers"
+        );
+        assert_eq!(handler.done(), TEST_SIZE, "Should process every item");
+
+        prodcon.complete();
+        prodcon.wait_for_async(Duration::from_secs(5)).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_injector_worker_set_workers() -> Result<()> {
+        let handler = SlowTaskHandler::new(Duration::from_millis(1));
+        let options = InjectorWorkerOptions::new().with_threads(4);
+        let injwork = InjectorWorker::<usize>::with_options(options);
+        injwork.start(&handler)?;
+
+        for i in 1..=TEST_SIZE {
+            injwork.enqueue(i)?;
+        }
+
+        injwork.set_workers(1)?;
+        assert!(
+            wait_for_workers(|| injwork.workers(), 1),
+            "Surplus workers should retire"
+        );
+
+        injwork.complete();
+        injwork.wait_async().await?;
+        assert_eq!(
+            handler.done(),
+            TEST_SIZE,
+            "Retired workers should hand back their local items"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_injector_worker_steals_from_siblings() -> Result<()> {
+        let handler = SlowTaskHandler::new(Duration::from_millis(20));
+        let options = InjectorWorkerOptions::new().with_threads(4);
+        let injwork = InjectorWorker::<usize>::with_options(options);
+        injwork.start(&handler)?;
+
+        for i in 1..=8 {
+            injwork.enqueue(i)?;
+        }
+
+        injwork.complete();
+        injwork.wait_for(Duration::from_secs(5))?;
+        assert_eq!(handler.done(), 8, "Should process every item");
+        assert!(
+            handler.peak() > 1,
+            "Idle workers should steal batches taken by a busy one"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_injector_worker_wakes_parked_workers() -> Result<()> {
+        let handler = SlowTaskHandler::new(Duration::ZERO);
+        let options = InjectorWorkerOptions::new().with_threads(THREADS);
+        let clock = VirtualClock::new();
+        let injwork = InjectorWorker::<usize>::with_options(options).with_clock(&clock);
+        injwork.start(&handler)?;
+        assert!(
+            clock.wait_for_sleepers(THREADS, TIMEOUT),
+            "Workers should park"
+        );
+
+        for i in 1..=TEST_SIZE {
+            injwork.enqueue(i)?;
+        }
+
+        injwork.complete();
+        injwork.wait_for(Duration::from_secs(5))?;
+        assert_eq!(handler.done(), TEST_SIZE, "Parked workers should wake up");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_injector_worker_without_work_stealing() -> Result<()> {
+        let handler = SlowTaskHandler::new(Duration::ZERO);
+        let options = InjectorWorkerOptions::new()
+            .with_threads(THREADS)
+            .with_work_stealing(false);
+        let injwork = InjectorWorker::<usize>::with_options(options);
+        injwork.start(&handler)?;
+
+        for i in 1..=TEST_SIZE {
+            injwork.enqueue(i)?;
+        }
+
+        injwork.complete();
+        injwork.wait_for(Duration::from_secs(5))?;
+        assert_eq!(handler.done(), TEST_SIZE, "Should process every item");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_scaling_options_should_scale_up() {
+        let scaling = ScalingOptions::new()
+            .with_max_workers(4)
+            .with_backlog_threshold(2)
+            .with_latency_threshold(Duration::from_millis(100));
+
+        assert!(
+            !scaling.should_scale_up(1, 0, Duration::ZERO),
+            "Empty queue"
+        );
+        assert!(
+            !scaling.should_scale_up(1, 2, Duration::ZERO),
+            "Within backlog"
+        );
+        assert!(
+            scaling.should_scale_up(1, 3, Duration::ZERO),
+            "Backlog grew"
+        );
+        assert!(
+            scaling.should_scale_up(1, 1, Duration::from_millis(200)),
+            "Latency grew"
+        );
+        assert!(!scaling.should_scale_up(4, 100, Duration::ZERO), "At max");
+    }
 }
@@ -0,0 +1,239 @@
+#[cfg(test)]
+mod tests {
+    use emixcore::{Error, Result};
+    use emixthreading::{
+        consumer::ProducerConsumerOptions,
+        dataflow::{
+            ActionBlock, BatchBlock, BroadcastBlock, DataflowTarget, JoinBlock, TransformBlock,
+            TransformManyBlock,
+        },
+    };
+    use std::{
+        sync::{
+            Arc, Mutex,
+            atomic::{AtomicUsize, Ordering},
+        },
+        thread,
+        time::{Duration, Instant},
+    };
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    fn collector<T: Clone + Send + Sync + std::fmt::Debug + 'static>()
+    -> (Arc<Mutex<Vec<T>>>, ActionBlock<T>) {
+        let items = Arc::new(Mutex::new(Vec::new()));
+        let sink = items.clone();
+        let block = ActionBlock::new(move |item: &T| {
+            sink.lock().unwrap().push(item.clone());
+            Ok(())
+        });
+        (items, block)
+    }
+
+    #[tokio::test]
+    async fn test_transform_pipeline() -> Result<()> {
+        let sum = Arc::new(AtomicUsize::new(0));
+        let total = sum.clone();
+        let square = TransformBlock::with_options(
+            ProducerConsumerOptions::new()
+                .with_capacity(4)
+                .with_threads(4),
+            |n: &usize| Ok(n * n),
+        );
+        let add = ActionBlock::new(move |n: &usize| {
+            total.fetch_add(*n, Ordering::SeqCst);
+            Ok(())
+        });
+        square.link_to(&add);
+
+        for i in 1..=10 {
+            square.post(i)?;
+        }
+
+        square.complete();
+        add.completion().wait_for_async(TIMEOUT).await?;
+        assert!(square.completion().is_finished());
+        assert_eq!(sum.load(Ordering::SeqCst), 385);
+        Ok(())
+    }
+
+    #[test]
+    fn test_transform_many() -> Result<()> {
+        let split = TransformManyBlock::new(|s: &String| {
+            Ok(s.split(' ').map(str::to_string).collect::<Vec<_>>())
+        });
+        let (words, sink) = collector::<String>();
+        split.link_to(&sink);
+
+        split.post("a b c".to_string())?;
+        split.post("d e".to_string())?;
+        split.complete();
+        sink.completion().wait_for(TIMEOUT)?;
+
+        assert_eq!(*words.lock().unwrap(), vec!["a", "b", "c", "d", "e"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_batch_flushes_partial_batch() -> Result<()> {
+        let batch = BatchBlock::new(3);
+        let (batches, sink) = collector::<Vec<usize>>();
+        batch.link_to(&sink);
+
+        for i in 1..=7 {
+            batch.post(i)?;
+        }
+
+        batch.complete();
+        sink.completion().wait_for(TIMEOUT)?;
+
+        assert_eq!(
+            *batches.lock().unwrap(),
+            vec![vec![1, 2, 3], vec![4, 5, 6], vec![7]]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_broadcast_to_every_target() -> Result<()> {
+        let broadcast = BroadcastBlock::new();
+        let (left, left_sink) = collector::<usize>();
+        let (right, right_sink) = collector::<usize>();
+        broadcast.link_to(&left_sink);
+        broadcast.link_to(&right_sink);
+
+        for i in 1..=3 {
+            broadcast.post(i)?;
+        }
+
+        broadcast.complete();
+        left_sink.completion().wait_for(TIMEOUT)?;
+        right_sink.completion().wait_for(TIMEOUT)?;
+
+        assert_eq!(*left.lock().unwrap(), vec![1, 2, 3]);
+        assert_eq!(*right.lock().unwrap(), vec![1, 2, 3]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_join_pairs_inputs() -> Result<()> {
+        let join = JoinBlock::<usize, String>::new();
+        let (pairs, sink) = collector::<(usize, String)>();
+        join.link_to(&sink);
+
+        let first = join.first().clone();
+        let producer = thread::spawn(move || {
+            for i in 1..=3 {
+                first.enqueue(i).unwrap();
+            }
+        });
+
+        for s in ["a", "b", "c", "unpaired"] {
+            join.second().enqueue(s.to_string())?;
+        }
+
+        producer.join().unwrap();
+        join.complete();
+        sink.completion().wait_for(TIMEOUT)?;
+
+        assert_eq!(
+            *pairs.lock().unwrap(),
+            vec![
+                (1, "a".to_string()),
+                (2, "b".to_string()),
+                (3, "c".to_string())
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_cancellation_propagates() -> Result<()> {
+        let double = TransformBlock::new(|n: &usize| Ok(n * 2));
+        let batch = BatchBlock::new(10);
+        let (batches, sink) = collector::<Vec<usize>>();
+        double.link_to(&batch);
+        batch.link_to(&sink);
+
+        double.post(1)?;
+        double.cancel();
+
+        assert!(matches!(
+            sink.completion().wait_for(TIMEOUT),
+            Err(Error::Canceled)
+        ));
+        assert!(batch.completion().is_cancelled());
+        assert!(
+            batches.lock().unwrap().is_empty(),
+            "Cancelled blocks should not flush"
+        );
+        assert!(double.post(2).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_bounded_buffers_apply_backpressure() -> Result<()> {
+        let slow =
+            ActionBlock::with_options(ProducerConsumerOptions::new().with_capacity(1), |_| {
+                thread::sleep(Duration::from_millis(30));
+                Ok(())
+            });
+        let time = Instant::now();
+
+        for i in 0..6 {
+            slow.post(i)?;
+        }
+
+        assert!(
+            time.elapsed() >= Duration::from_millis(90),
+            "Posting should wait for the slow block"
+        );
+        slow.complete();
+        slow.completion().wait_for(TIMEOUT)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_errors_are_counted() -> Result<()> {
+        let check = TransformBlock::new(|n: &usize| {
+            if n.is_multiple_of(2) {
+                Err(Error::Other("even".to_string()))
+            } else {
+                Ok(*n)
+            }
+        });
+        let (odds, sink) = collector::<usize>();
+        check.link_to(&sink);
+
+        for i in 1..=6 {
+            check.post(i)?;
+        }
+
+        check.complete();
+        sink.completion().wait_for(TIMEOUT)?;
+
+        assert_eq!(check.completion().errors(), 3);
+        assert_eq!(*odds.lock().unwrap(), vec![1, 3, 5]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_outputs_wait_for_a_link() -> Result<()> {
+        let double = TransformBlock::new(|n: &usize| Ok(n * 2));
+
+        for i in 1..=3 {
+            double.post(i)?;
+        }
+
+        double.complete();
+        double.completion().wait_for(TIMEOUT)?;
+        assert_eq!(double.completion().errors(), 0);
+
+        let (results, sink) = collector::<usize>();
+        double.link_to(&sink);
+        sink.completion().wait_for(TIMEOUT)?;
+
+        assert_eq!(*results.lock().unwrap(), vec![2, 4, 6]);
+        Ok(())
+    }
+}
@@ -0,0 +1,132 @@
+#[cfg(test)]
+mod tests {
+    // Compares `InjectorWorker` with and without work stealing. Run with
+    // `cargo test --release --test injector_benchmark -- --ignored --nocapture`.
+    use emixcore::Result;
+    use emixthreading::{
+        TaskDelegation, TaskResult,
+        consumer::{InjectorWorker, InjectorWorkerOptions},
+    };
+    use std::{
+        sync::{
+            Arc, Mutex,
+            atomic::{AtomicUsize, Ordering},
+        },
+        thread,
+        time::{Duration, Instant},
+    };
+
+    const THREADS: usize = 4;
+    const ITEMS: usize = 200_000;
+    const SAMPLES: usize = 200;
+
+    #[derive(Clone, Debug)]
+    struct BenchHandler {
+        done: Arc<AtomicUsize>,
+        latencies: Arc<Mutex<Vec<Duration>>>,
+    }
+
+    impl BenchHandler {
+        fn new() -> Self {
+            BenchHandler {
+                done: Arc::new(AtomicUsize::new(0)),
+                latencies: Arc::new(Mutex::new(Vec::new())),
+            }
+        }
+    }
+
+    impl TaskDelegation<InjectorWorker<Instant>, Instant> for BenchHandler {
+        fn on_started(&self, _pc: &InjectorWorker<Instant>) {}
+
+        fn process(&self, _pc: &InjectorWorker<Instant>, item: &Instant) -> Result<TaskResult> {
+            self.latencies.lock().unwrap().push(item.elapsed());
+            Ok(TaskResult::Success)
+        }
+
+        fn on_completed(
+            &self,
+            _pc: &InjectorWorker<Instant>,
+            _item: &Instant,
+            _result: &TaskResult,
+        ) -> bool {
+            self.done.fetch_add(1, Ordering::Relaxed);
+            true
+        }
+
+        fn on_cancelled(&self, _pc: &InjectorWorker<Instant>) {}
+
+        fn on_finished(&self, _pc: &InjectorWorker<Instant>) {}
+    }
+
+    fn options(work_stealing: bool) -> InjectorWorkerOptions {
+        InjectorWorkerOptions::new()
+            .with_threads(THREADS)
+            .with_work_stealing(work_stealing)
+    }
+
+    fn throughput(work_stealing: bool) -> Result<f64> {
+        let handler = BenchHandler::new();
+        let injwork = InjectorWorker::with_options(options(work_stealing));
+        injwork.start(&handler)?;
+        let time = Instant::now();
+
+        for _ in 0..ITEMS {
+            injwork.enqueue(Instant::now())?;
+        }
+
+        injwork.complete();
+        injwork.wait()?;
+        assert_eq!(handler.done.load(Ordering::SeqCst), ITEMS);
+        Ok(ITEMS as f64 / time.elapsed().as_secs_f64())
+    }
+
+    /// Enqueues items one at a time with idle gaps and returns the median and 99th
+    /// percentile time from enqueue to processing.
+    fn latency(work_stealing: bool) -> Result<(Duration, Duration)> {
+        let handler = BenchHandler::new();
+        let injwork = InjectorWorker::with_options(options(work_stealing));
+        injwork.start(&handler)?;
+
+        for _ in 0..SAMPLES {
+            thread::sleep(Duration::from_millis(2));
+            injwork.enqueue(Instant::now())?;
+        }
+
+        injwork.complete();
+        injwork.wait()?;
+        let mut latencies = handler.latencies.lock().unwrap().clone();
+        latencies.sort();
+        Ok((
+            latencies[latencies.len() / 2],
+            latencies[latencies.len() * 99 / 100],
+        ))
+    }
+
+    #[test]
+    #[ignore]
+    fn bench_injector_worker_throughput() -> Result<()> {
+        for work_stealing in [false, true] {
+            println!(
+                "work_stealing={}: {:.0} items/s",
+                work_stealing,
+                throughput(work_stealing)?
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    #[ignore]
+    fn bench_injector_worker_latency() -> Result<()> {
+        for work_stealing in [false, true] {
+            let (median, p99) = latency(work_stealing)?;
+            println!(
+                "work_stealing={}: median {:?}, p99 {:?}",
+                work_stealing, median, p99
+            );
+        }
+
+        Ok(())
+    }
+}
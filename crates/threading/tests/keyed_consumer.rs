@@ -0,0 +1,275 @@
+#[cfg(test)]
+mod tests {
+    use emixcore::{Error, Result};
+    use emixthreading::{
+        KeyedTaskItem, TaskDelegation, TaskResult,
+        consumer::{KeyedConsumer, KeyedConsumerOptions},
+        test_support::VirtualClock,
+    };
+    use std::{
+        collections::HashMap,
+        sync::{
+            Arc, Mutex,
+            atomic::{AtomicUsize, Ordering},
+        },
+        thread,
+        time::Duration,
+    };
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Job {
+        account: u32,
+        seq: usize,
+    }
+
+    impl KeyedTaskItem for Job {
+        type Key = u32;
+
+        fn key(&self) -> u32 {
+            self.account
+        }
+    }
+
+    #[derive(Clone, Debug, Default)]
+    struct JobHandler {
+        delay: Duration,
+        seen: Arc<Mutex<HashMap<u32, Vec<usize>>>>,
+        active: Arc<Mutex<HashMap<u32, usize>>>,
+        overlaps: Arc<AtomicUsize>,
+        running: Arc<AtomicUsize>,
+        peak: Arc<AtomicUsize>,
+    }
+
+    impl JobHandler {
+        fn with_delay(delay: Duration) -> Self {
+            JobHandler {
+                delay,
+                ..Default::default()
+            }
+        }
+
+        fn seen(&self, account: u32) -> Vec<usize> {
+            self.seen
+                .lock()
+                .unwrap()
+                .get(&account)
+                .cloned()
+                .unwrap_or_default()
+        }
+    }
+
+    impl TaskDelegation<KeyedConsumer<Job>, Job> for JobHandler {
+        fn on_started(&self, _pc: &KeyedConsumer<Job>) {}
+
+        fn process(&self, _pc: &KeyedConsumer<Job>, item: &Job) -> Result<TaskResult> {
+            {
+                let mut active = self.active.lock().unwrap();
+                let count = active.entry(item.account).or_default();
+
+                if *count > 0 {
+                    self.overlaps.fetch_add(1, Ordering::SeqCst);
+                }
+
+                *count += 1;
+            }
+
+            let now = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(now, Ordering::SeqCst);
+            thread::sleep(self.delay);
+            self.running.fetch_sub(1, Ordering::SeqCst);
+            *self.active.lock().unwrap().get_mut(&item.account).unwrap() -= 1;
+            self.seen
+                .lock()
+                .unwrap()
+                .entry(item.account)
+                .or_default()
+                .push(item.seq);
+            Ok(TaskResult::Success)
+        }
+
+        fn on_completed(
+            &self,
+            _pc: &KeyedConsumer<Job>,
+            _item: &Job,
+            _result: &TaskResult,
+        ) -> bool {
+            true
+        }
+
+        fn on_cancelled(&self, _pc: &KeyedConsumer<Job>) {}
+
+        fn on_finished(&self, _pc: &KeyedConsumer<Job>) {}
+    }
+
+    #[test]
+    fn test_serializes_items_by_key() -> Result<()> {
+        let handler = JobHandler::with_delay(Duration::from_millis(2));
+        let consumer =
+            KeyedConsumer::<Job>::with_options(KeyedConsumerOptions::new().with_threads(4));
+        consumer.start(&handler)?;
+
+        for seq in 0..20 {
+            for account in 0..4 {
+                consumer.enqueue(Job { account, seq })?;
+            }
+        }
+
+        consumer.complete();
+        consumer.wait_for(TIMEOUT)?;
+
+        assert_eq!(handler.overlaps.load(Ordering::SeqCst), 0);
+        assert!(handler.peak.load(Ordering::SeqCst) > 1);
+
+        for account in 0..4 {
+            assert_eq!(handler.seen(account), (0..20).collect::<Vec<_>>());
+        }
+
+        assert_eq!(consumer.keys(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_one_hot_key_does_not_block_others() -> Result<()> {
+        let handler = JobHandler::with_delay(Duration::from_millis(20));
+        let consumer =
+            KeyedConsumer::<Job>::with_options(KeyedConsumerOptions::new().with_threads(2));
+
+        for seq in 0..10 {
+            consumer.enqueue(Job { account: 1, seq })?;
+        }
+
+        consumer.enqueue(Job { account: 2, seq: 0 })?;
+        consumer.start(&handler)?;
+        thread::sleep(Duration::from_millis(80));
+
+        assert_eq!(handler.seen(2), vec![0]);
+        assert!(handler.seen(1).len() < 10);
+        consumer.complete();
+        consumer.wait_for(TIMEOUT)?;
+        assert_eq!(handler.seen(1), (0..10).collect::<Vec<_>>());
+        Ok(())
+    }
+
+    /// Gets the CPU time this process has used so far.
+    #[cfg(target_os = "linux")]
+    fn cpu_time() -> Duration {
+        // utime and stime are the 14th and 15th fields, in clock ticks of 10ms.
+        let stat = std::fs::read_to_string("/proc/self/stat").unwrap();
+        let fields: Vec<&str> = stat[stat.rfind(')').unwrap() + 2..].split(' ').collect();
+        let ticks: u64 = fields[11].parse::<u64>().unwrap() + fields[12].parse::<u64>().unwrap();
+        Duration::from_millis(ticks * 10)
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_idle_workers_wait_for_a_busy_key_after_complete() -> Result<()> {
+        let handler = JobHandler::with_delay(Duration::from_millis(50));
+        let consumer =
+            KeyedConsumer::<Job>::with_options(KeyedConsumerOptions::new().with_threads(8));
+
+        for seq in 0..6 {
+            consumer.enqueue(Job { account: 1, seq })?;
+        }
+
+        consumer.complete();
+        let cpu = cpu_time();
+        let time = std::time::Instant::now();
+        consumer.start(&handler)?;
+        consumer.wait_for(TIMEOUT)?;
+        let used = cpu_time() - cpu;
+
+        assert_eq!(handler.seen(1), (0..6).collect::<Vec<_>>());
+        assert!(
+            used < time.elapsed() / 2,
+            "Idle workers should wait instead of spinning, used {:?} of CPU in {:?}",
+            used,
+            time.elapsed()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_coalesces_pending_duplicates() -> Result<()> {
+        let handler = JobHandler::default();
+        let consumer = KeyedConsumer::<Job>::with_options(
+            KeyedConsumerOptions::new().with_dedup_window(Duration::from_secs(60)),
+        );
+
+        for _ in 0..3 {
+            consumer.enqueue(Job { account: 1, seq: 0 })?;
+            consumer.enqueue(Job { account: 1, seq: 1 })?;
+            consumer.enqueue(Job { account: 2, seq: 0 })?;
+        }
+
+        assert_eq!(consumer.len(), 3);
+        assert_eq!(consumer.coalesced(), 6);
+
+        consumer.start(&handler)?;
+        consumer.complete();
+        consumer.wait_for(TIMEOUT)?;
+        assert_eq!(handler.seen(1), vec![0, 1]);
+        assert_eq!(handler.seen(2), vec![0]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_dedup_window_expires() -> Result<()> {
+        let clock = VirtualClock::new();
+        let window = Duration::from_secs(60);
+        let handler = JobHandler::default();
+        let consumer = KeyedConsumer::<Job>::with_options(
+            KeyedConsumerOptions::new().with_dedup_window(window),
+        )
+        .with_clock(&clock);
+        consumer.enqueue(Job { account: 1, seq: 0 })?;
+        clock.advance(window);
+        consumer.enqueue(Job { account: 1, seq: 0 })?;
+        assert_eq!(
+            consumer.coalesced(),
+            1,
+            "The window includes its last instant"
+        );
+
+        clock.advance(Duration::from_secs(1));
+        consumer.enqueue(Job { account: 1, seq: 0 })?;
+        assert_eq!(consumer.coalesced(), 1);
+        assert_eq!(consumer.len(), 2);
+
+        consumer.start(&handler)?;
+        consumer.complete();
+        assert!(clock.advance_until(window, TIMEOUT, || consumer.is_finished()));
+        assert_eq!(handler.seen(1), vec![0, 0]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_cancel_drops_pending_items() -> Result<()> {
+        let handler = JobHandler::with_delay(Duration::from_millis(20));
+        let consumer =
+            KeyedConsumer::<Job>::with_options(KeyedConsumerOptions::new().with_capacity(100));
+
+        for seq in 0..50 {
+            consumer.enqueue(Job { account: 1, seq })?;
+        }
+
+        consumer.start(&handler)?;
+        thread::sleep(Duration::from_millis(50));
+        consumer.cancel();
+
+        assert!(matches!(consumer.wait_for(TIMEOUT), Err(Error::Canceled)));
+
+        while !consumer.is_finished() {
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        assert!(handler.seen(1).len() < 50);
+        assert!(consumer.is_empty());
+        assert!(matches!(
+            consumer.enqueue(Job { account: 1, seq: 0 }),
+            Err(Error::Canceled)
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,80 @@
+// Model tests that explore every interleaving of the conditions' lock and wait calls.
+// Run with `RUSTFLAGS="--cfg emixthreading_loom" cargo test --release --test loom_cond`.
+#[cfg(all(test, emixthreading_loom))]
+mod tests {
+    use emixthreading::{AutoResetCond, CountdownCond, ManualResetCond};
+    use loom::{sync::Arc, thread};
+
+    #[test]
+    fn test_manual_reset_releases_every_waiter() {
+        loom::model(|| {
+            let cond = Arc::new(ManualResetCond::new_unset());
+            let waiters = (0..2)
+                .map(|_| {
+                    let cond = cond.clone();
+                    thread::spawn(move || cond.wait().unwrap())
+                })
+                .collect::<Vec<_>>();
+
+            cond.set().unwrap();
+
+            for waiter in waiters {
+                waiter.join().unwrap();
+            }
+
+            assert!(cond.is_set().unwrap());
+        });
+    }
+
+    #[test]
+    fn test_auto_reset_releases_one_waiter_per_set() {
+        loom::model(|| {
+            let cond = Arc::new(AutoResetCond::new_unset());
+            let waiter = {
+                let cond = cond.clone();
+                thread::spawn(move || cond.wait().unwrap())
+            };
+
+            cond.set().unwrap();
+            waiter.join().unwrap();
+            assert!(!cond.is_set().unwrap());
+        });
+    }
+
+    #[test]
+    fn test_auto_reset_does_not_lose_a_set() {
+        loom::model(|| {
+            let cond = Arc::new(AutoResetCond::new_unset());
+            let setter = {
+                let cond = cond.clone();
+                thread::spawn(move || cond.set().unwrap())
+            };
+
+            cond.wait().unwrap();
+            setter.join().unwrap();
+            assert!(!cond.is_set().unwrap());
+        });
+    }
+
+    #[test]
+    fn test_countdown_releases_after_last_signal() {
+        loom::model(|| {
+            let cond = Arc::new(CountdownCond::new(2));
+            let signalers = (0..2)
+                .map(|_| {
+                    let cond = cond.clone();
+                    thread::spawn(move || {
+                        cond.signal().unwrap();
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            cond.wait().unwrap();
+            assert_eq!(cond.current_count().unwrap(), 0);
+
+            for signaler in signalers {
+                signaler.join().unwrap();
+            }
+        });
+    }
+}
@@ -0,0 +1,109 @@
+#[cfg(test)]
+mod tests {
+    use emixcore::{Error, Result};
+    use emixthreading::{
+        CancellationToken,
+        parallel::{self, ParallelOptions},
+    };
+    use std::{
+        sync::{
+            Arc, Mutex,
+            atomic::{AtomicUsize, Ordering},
+        },
+        thread,
+        time::Duration,
+    };
+
+    fn options() -> ParallelOptions {
+        ParallelOptions::new().with_degree(4)
+    }
+
+    #[test]
+    fn test_map_keeps_order() -> Result<()> {
+        let squares = parallel::map(0..100usize, &options(), |n| {
+            // Make later items finish first.
+            thread::sleep(Duration::from_micros((100 - n as u64) * 10));
+            n * n
+        })?;
+        assert_eq!(squares, (0..100).map(|n| n * n).collect::<Vec<_>>());
+        Ok(())
+    }
+
+    #[test]
+    fn test_unordered_map_has_every_result() -> Result<()> {
+        let mut values = parallel::map(1..=50usize, &options().with_ordered(false), |n| n * 2)?;
+        values.sort();
+        assert_eq!(values, (1..=50).map(|n| n * 2).collect::<Vec<_>>());
+        Ok(())
+    }
+
+    #[test]
+    fn test_filter_map_and_for_each() -> Result<()> {
+        let evens = parallel::filter_map(1..=10usize, &options(), |n| {
+            n.is_multiple_of(2).then_some(n)
+        })?;
+        assert_eq!(evens, vec![2, 4, 6, 8, 10]);
+
+        let sum = AtomicUsize::new(0);
+        parallel::for_each(vec![1usize, 2, 3, 4], &options(), |n| {
+            sum.fetch_add(n, Ordering::SeqCst);
+        })?;
+        assert_eq!(sum.load(Ordering::SeqCst), 10);
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_for_each_stops_on_first_error() {
+        let processed = AtomicUsize::new(0);
+        let result = parallel::try_for_each(0..1000usize, &options(), |n| {
+            processed.fetch_add(1, Ordering::SeqCst);
+
+            if n == 10 {
+                return Err(Error::InvalidInput(format!("Bad item {}", n)));
+            }
+
+            thread::sleep(Duration::from_millis(1));
+            Ok(())
+        });
+
+        assert!(matches!(result, Err(Error::InvalidInput(_))));
+        assert!(
+            processed.load(Ordering::SeqCst) < 100,
+            "Items after the error should not be handed out"
+        );
+    }
+
+    #[test]
+    fn test_cancellation_stops_processing() {
+        let token = CancellationToken::new();
+        let processed = AtomicUsize::new(0);
+        let result = parallel::for_each(0..1000usize, &options().with_token(&token), |n| {
+            if n == 5 {
+                token.cancel();
+            }
+
+            processed.fetch_add(1, Ordering::SeqCst);
+        });
+
+        assert!(matches!(result, Err(Error::Canceled)));
+        assert!(processed.load(Ordering::SeqCst) < 1000);
+    }
+
+    #[test]
+    fn test_progress_reports_every_item() -> Result<()> {
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let sink = reports.clone();
+        let options = options().with_progress(move |done, total| {
+            sink.lock().unwrap().push((done, total));
+        });
+        parallel::for_each(0..20usize, &options, |_| {})?;
+
+        let mut reports = reports.lock().unwrap().clone();
+        reports.sort();
+        assert_eq!(
+            reports,
+            (1..=20).map(|done| (done, Some(20))).collect::<Vec<_>>()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,91 @@
+#[cfg(test)]
+mod tests {
+    use emixthreading::{ProgressGroup, ProgressGroupOptions};
+    use std::time::Duration;
+
+    fn plain_group() -> ProgressGroup {
+        ProgressGroup::with_options(
+            ProgressGroupOptions::new()
+                .with_plain(true)
+                .with_log_interval(Duration::ZERO),
+        )
+    }
+
+    #[test]
+    fn test_children_follow_their_parent() {
+        let group = plain_group();
+        let build = group.add_spinner("build");
+        let deploy = group.add_spinner("deploy");
+        let compile = build.add_bar("compile", 10);
+        let link = build.add_spinner("link");
+        let objects = compile.add_bar("objects", 3);
+
+        assert_eq!(objects.name(), "build/compile/objects");
+        assert_eq!(objects.depth(), 2);
+        assert_eq!(link.depth(), 1);
+        assert_eq!(deploy.depth(), 0);
+        assert_eq!(group.len(), 5);
+
+        let names = group
+            .status_lines()
+            .into_iter()
+            .map(|line| line.split(' ').next().unwrap().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![
+                "build",
+                "build/compile",
+                "build/compile/objects",
+                "build/link",
+                "deploy"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bar_tracks_position_and_status() {
+        let group = plain_group();
+        assert!(group.is_plain());
+        let bar = group.add_bar("files", 4);
+        bar.inc(1);
+        bar.set_message("copying");
+
+        assert_eq!(bar.position(), 1);
+        assert_eq!(bar.length(), Some(4));
+        let line = bar.status_line();
+        assert!(line.starts_with("files 1/4 25%"), "Unexpected line: {line}");
+        assert!(line.ends_with("- copying"), "Unexpected line: {line}");
+
+        bar.set_position(4);
+        bar.finish_with_message("copied").unwrap();
+        assert!(bar.is_finished());
+        assert_eq!(bar.status_line(), "files 4/4 100% done - copied");
+        assert!(bar.finish().is_err(), "A task can only finish once");
+    }
+
+    #[test]
+    fn test_bytes_bar_reports_sizes() {
+        let group = plain_group();
+        let download = group.add_bytes("download", 2048);
+        download.set_position(1024);
+
+        let line = download.status_line();
+        assert!(
+            line.starts_with("download 1.00 KiB/2.00 KiB"),
+            "Unexpected line: {line}"
+        );
+        assert!(line.contains("50%"), "Unexpected line: {line}");
+    }
+
+    #[test]
+    fn test_abandon_keeps_position() {
+        let group = plain_group();
+        let bar = group.add_bar("upload", 10);
+        bar.set_position(3);
+        bar.abandon().unwrap();
+
+        assert!(bar.is_finished());
+        assert_eq!(bar.position(), 3);
+    }
+}
@@ -0,0 +1,338 @@
+#[cfg(test)]
+mod tests {
+    use emixcore::{Error, Result};
+    use emixthreading::{
+        TaskDelegation, TaskResult,
+        consumer::{FileQueueStore, ProducerConsumer, ProducerConsumerOptions, QueueStore},
+    };
+    use std::{
+        fs,
+        io::Write,
+        path::PathBuf,
+        sync::{
+            Arc, Mutex,
+            atomic::{AtomicUsize, Ordering},
+            mpsc,
+        },
+        thread,
+        time::Duration,
+    };
+
+    fn log_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join("emixthreading").join(format!(
+            "{}-{}.log",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct RecordingHandler {
+        pub items: Arc<Mutex<Vec<usize>>>,
+        pub done: Arc<AtomicUsize>,
+        pub delay: Duration,
+        pub stop_after: Option<usize>,
+    }
+
+    impl RecordingHandler {
+        pub fn new() -> Self {
+            RecordingHandler {
+                items: Arc::new(Mutex::new(Vec::new())),
+                done: Arc::new(AtomicUsize::new(0)),
+                delay: Duration::ZERO,
+                stop_after: None,
+            }
+        }
+
+        pub fn items(&self) -> Vec<usize> {
+            let mut items = self.items.lock().unwrap().clone();
+            items.sort();
+            items
+        }
+    }
+
+    impl TaskDelegation<ProducerConsumer<usize>, usize> for RecordingHandler {
+        fn on_started(&self, _pc: &ProducerConsumer<usize>) {}
+
+        fn process(&self, _pc: &ProducerConsumer<usize>, item: &usize) -> Result<TaskResult> {
+            self.items.lock().unwrap().push(*item);
+            thread::sleep(self.delay);
+            Ok(TaskResult::Success)
+        }
+
+        fn on_completed(
+            &self,
+            _pc: &ProducerConsumer<usize>,
+            _item: &usize,
+            _result: &TaskResult,
+        ) -> bool {
+            let done = self.done.fetch_add(1, Ordering::SeqCst) + 1;
+            self.stop_after.is_none_or(|limit| done < limit)
+        }
+
+        fn on_cancelled(&self, _pc: &ProducerConsumer<usize>) {}
+
+        fn on_finished(&self, _pc: &ProducerConsumer<usize>) {}
+    }
+
+    #[test]
+    fn test_file_store_append_ack_pending() -> Result<()> {
+        let path = log_path("append_ack");
+        let store = FileQueueStore::<String>::open(&path)?;
+
+        let a = store.append(&"a".to_string())?;
+        let b = store.append(&"b".to_string())?;
+        let c = store.append(&"c".to_string())?;
+        store.ack(b)?;
+
+        let pending = store.pending()?;
+        assert_eq!(
+            pending,
+            vec![(a, "a".to_string()), (c, "c".to_string())],
+            "Acked items should not be pending"
+        );
+        assert_eq!(store.len(), 2);
+
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_store_survives_reopen() -> Result<()> {
+        let path = log_path("reopen");
+        {
+            let store = FileQueueStore::<usize>::open(&path)?;
+            for i in 1..=5 {
+                store.append(&i)?;
+            }
+            store.ack(1)?;
+            store.ack(3)?;
+        }
+
+        let store = FileQueueStore::<usize>::open(&path)?;
+        let items: Vec<usize> = store.pending()?.into_iter().map(|(_, it)| it).collect();
+        assert_eq!(items, vec![2, 4, 5], "Unacked items should be replayed");
+
+        let id = store.append(&6)?;
+        assert!(id > 5, "Ids should not be reused after reopen");
+
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_store_ignores_torn_line() -> Result<()> {
+        let path = log_path("torn");
+        {
+            let store = FileQueueStore::<usize>::open(&path)?;
+            store.append(&1)?;
+            store.append(&2)?;
+        }
+
+        let mut file = fs::OpenOptions::new().append(true).open(&path)?;
+        file.write_all(br#"{"op":"enqueue","id":3,"ite"#)?;
+        drop(file);
+
+        let store = FileQueueStore::<usize>::open(&path)?;
+        let items: Vec<usize> = store.pending()?.into_iter().map(|(_, it)| it).collect();
+        assert_eq!(items, vec![1, 2], "Torn line should be skipped");
+
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_store_rejects_corrupt_line() -> Result<()> {
+        let path = log_path("corrupt");
+        fs::create_dir_all(path.parent().unwrap())?;
+        fs::write(
+            &path,
+            concat!(
+                r#"{"op":"enqueue","id":1,"item":1}"#,
+                "\n",
+                r#"{"op":"enq"#,
+                "\n",
+                r#"{"op":"enqueue","id":2,"item":2}"#,
+                "\n",
+            ),
+        )?;
+
+        let result = FileQueueStore::<usize>::open(&path);
+        assert!(
+            matches!(result, Err(Error::Parse(_))),
+            "A bad line before the last should fail the open"
+        );
+
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_store_truncates_when_drained() -> Result<()> {
+        let path = log_path("truncate");
+        let store = FileQueueStore::<usize>::open(&path)?;
+        let a = store.append(&1)?;
+        let b = store.append(&2)?;
+        store.ack(a)?;
+        store.ack(b)?;
+
+        assert!(store.is_empty());
+        assert_eq!(fs::metadata(&path)?.len(), 0, "Log should be truncated");
+
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_store_compaction_keeps_sibling_files() -> Result<()> {
+        let path = log_path("sibling");
+        let sibling = path.with_extension("compact");
+        fs::write(&sibling, "keep")?;
+        let store = FileQueueStore::<usize>::open(&path)?;
+        store.append(&1)?;
+        store.compact()?;
+
+        assert_eq!(fs::read_to_string(&sibling)?, "keep");
+        assert_eq!(store.len(), 1);
+
+        fs::remove_file(&sibling)?;
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_producer_consumer_replays_pending_items() -> Result<()> {
+        let path = log_path("replay");
+        let options = ProducerConsumerOptions::new().with_capacity(16);
+
+        // Items enqueued but never processed, as if the process crashed.
+        {
+            let store = FileQueueStore::<usize>::open(&path)?;
+            let prodcon = ProducerConsumer::with_store(options.clone(), store)?;
+
+            for i in 1..=10 {
+                prodcon.enqueue(i)?;
+            }
+
+            prodcon.cancel();
+        }
+
+        let handler = RecordingHandler::new();
+        let store = FileQueueStore::<usize>::open(&path)?;
+        let prodcon = ProducerConsumer::with_store(options, store)?;
+        prodcon.start(&handler)?;
+
+        for i in 11..=15 {
+            prodcon.enqueue(i)?;
+        }
+
+        prodcon.complete();
+        prodcon.wait_for_async(Duration::from_secs(5)).await?;
+
+        assert_eq!(
+            handler.items(),
+            (1..=15).collect::<Vec<_>>(),
+            "Replayed and new items should be processed"
+        );
+
+        let store = FileQueueStore::<usize>::open(&path)?;
+        assert!(store.is_empty(), "Processed items should be acknowledged");
+
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_producer_consumer_replays_after_early_complete() -> Result<()> {
+        let path = log_path("replay_completed");
+        {
+            let store = FileQueueStore::<usize>::open(&path)?;
+
+            for i in 1..=5 {
+                store.append(&i)?;
+            }
+        }
+
+        let handler = RecordingHandler::new();
+        let store = FileQueueStore::<usize>::open(&path)?;
+        let prodcon = ProducerConsumer::with_store(ProducerConsumerOptions::new(), store)?;
+        assert_eq!(prodcon.len(), 5, "Replayed items should count as queued");
+        prodcon.complete();
+        prodcon.start(&handler)?;
+        prodcon.wait_for_async(Duration::from_secs(5)).await?;
+
+        assert_eq!(
+            handler.items(),
+            vec![1, 2, 3, 4, 5],
+            "Replayed items should be processed after an early complete"
+        );
+
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    /// Starts a consumer with a replay backlog larger than its capacity on another thread
+    /// and returns what `start` returned, or `None` if it did not return in time.
+    fn start_with_backlog(
+        name: &str,
+        handler: RecordingHandler,
+        stop: impl FnOnce(&ProducerConsumer<usize>) + Send + 'static,
+    ) -> Result<Option<Result<()>>> {
+        let path = log_path(name);
+        {
+            let store = FileQueueStore::<usize>::open(&path)?;
+
+            for i in 1..=20 {
+                store.append(&i)?;
+            }
+        }
+
+        let store = FileQueueStore::<usize>::open(&path)?;
+        let options = ProducerConsumerOptions::new()
+            .with_capacity(2)
+            .with_threads(1);
+        let prodcon = ProducerConsumer::with_store(options, store)?;
+        let (tx, rx) = mpsc::channel();
+        let starter = prodcon.clone();
+        thread::spawn(move || {
+            let _ = tx.send(starter.start(&handler));
+        });
+        stop(&prodcon);
+
+        let result = rx.recv_timeout(Duration::from_secs(5)).ok();
+        prodcon.cancel();
+        let _ = fs::remove_file(&path);
+        Ok(result)
+    }
+
+    #[test]
+    fn test_start_returns_when_cancelled_during_replay() -> Result<()> {
+        let mut handler = RecordingHandler::new();
+        handler.delay = Duration::from_millis(20);
+        let result = start_with_backlog("replay_cancel", handler, |prodcon| {
+            thread::sleep(Duration::from_millis(30));
+            prodcon.cancel();
+        })?;
+        assert!(
+            matches!(result, Some(Err(Error::Canceled))),
+            "start should give up on the replay when cancelled, got {:?}",
+            result
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_start_returns_when_workers_stop_during_replay() -> Result<()> {
+        let mut handler = RecordingHandler::new();
+        handler.stop_after = Some(1);
+        let result = start_with_backlog("replay_stopped", handler, |_| {})?;
+        assert!(
+            matches!(result, Some(Err(Error::InvalidOperation(_)))),
+            "start should give up on the replay when no worker is left, got {:?}",
+            result
+        );
+        Ok(())
+    }
+}
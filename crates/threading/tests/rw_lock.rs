@@ -0,0 +1,85 @@
+#[cfg(test)]
+mod tests {
+    use emixcore::Error;
+    use emixthreading::ReaderWriterLock;
+    use std::{sync::Arc, thread, time::Duration};
+
+    #[test]
+    fn test_rw_lock_shared_reads() {
+        let lock = ReaderWriterLock::new(5);
+        let a = lock.read();
+        let b = lock.read();
+        assert_eq!(*a + *b, 10);
+        assert!(lock.try_write().is_none(), "Readers should block writers");
+        assert!(matches!(
+            lock.write_timeout(Duration::from_millis(10)),
+            Err(Error::Timeout)
+        ));
+    }
+
+    #[test]
+    fn test_rw_lock_upgradeable_read() {
+        let lock = ReaderWriterLock::new(vec![1, 2, 3]);
+        let upgradeable = lock.upgradeable_read();
+        assert!(
+            lock.try_read().is_some(),
+            "Readers can share with an upgradeable read"
+        );
+        assert!(
+            lock.try_upgradeable_read().is_none(),
+            "Only one upgradeable read at a time"
+        );
+
+        let mut write = ReaderWriterLock::upgrade(upgradeable);
+        write.push(4);
+        let upgradeable = ReaderWriterLock::downgrade_to_upgradeable(write);
+        assert_eq!(upgradeable.len(), 4);
+    }
+
+    #[test]
+    fn test_rw_lock_upgrade_timeout() {
+        let lock = Arc::new(ReaderWriterLock::new(0));
+        let upgradeable = lock.upgradeable_read();
+        let reader = {
+            let lock = lock.clone();
+            thread::spawn(move || {
+                let _read = lock.read();
+                thread::sleep(Duration::from_millis(100));
+            })
+        };
+
+        thread::sleep(Duration::from_millis(20));
+        let upgradeable =
+            match ReaderWriterLock::upgrade_timeout(upgradeable, Duration::from_millis(10)) {
+                Ok(_) => panic!("Upgrade should wait for the reader"),
+                Err(guard) => guard,
+            };
+
+        let mut write = ReaderWriterLock::upgrade(upgradeable);
+        *write += 1;
+        drop(write);
+        reader.join().unwrap();
+        assert_eq!(*lock.read(), 1);
+    }
+
+    #[test]
+    fn test_rw_lock_writers_exclusive() {
+        let lock = Arc::new(ReaderWriterLock::new(0usize));
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let lock = lock.clone();
+                thread::spawn(move || {
+                    for _ in 0..1000 {
+                        *lock.write() += 1;
+                    }
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(Arc::try_unwrap(lock).unwrap().into_inner(), 4000);
+    }
+}
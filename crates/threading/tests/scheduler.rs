@@ -0,0 +1,286 @@
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Duration as TimeDelta, FixedOffset, TimeZone, Utc};
+    use emixcore::{Error, Result};
+    use emixthreading::{
+        JobOptions, MissedRunPolicy, Scheduler, TaskDelegation, TaskResult, TestClock, Trigger,
+        consumer::Consumer,
+    };
+    use std::{
+        sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+        },
+        thread,
+        time::Duration,
+    };
+
+    fn counter() -> (
+        Arc<AtomicUsize>,
+        impl Fn() -> Result<()> + Send + Sync + 'static,
+    ) {
+        let count = Arc::new(AtomicUsize::new(0));
+        let inner = count.clone();
+        (count, move || {
+            inner.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        })
+    }
+
+    fn test_scheduler() -> (TestClock, Scheduler) {
+        let clock = TestClock::default();
+        let scheduler = Scheduler::with_clock(clock.clone());
+        (clock, scheduler)
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct CountingHandler {
+        pub items: Arc<AtomicUsize>,
+    }
+
+    impl TaskDelegation<Consumer<usize>, usize> for CountingHandler {
+        fn on_started(&self, _pc: &Consumer<usize>) {}
+
+        fn process(&self, _pc: &Consumer<usize>, item: &usize) -> Result<TaskResult> {
+            self.items.fetch_add(*item, Ordering::SeqCst);
+            Ok(TaskResult::Success)
+        }
+
+        fn on_completed(&self, _pc: &Consumer<usize>, _item: &usize, _result: &TaskResult) -> bool {
+            true
+        }
+
+        fn on_cancelled(&self, _pc: &Consumer<usize>) {}
+
+        fn on_finished(&self, _pc: &Consumer<usize>) {}
+    }
+
+    #[test]
+    fn test_scheduler_interval() -> Result<()> {
+        let (clock, scheduler) = test_scheduler();
+        let (count, action) = counter();
+        scheduler.schedule(
+            Trigger::interval(Duration::from_secs(10)),
+            JobOptions::new(),
+            action,
+        )?;
+
+        clock.advance(Duration::from_secs(9));
+        assert_eq!(scheduler.tick(), 0, "Should not run before the interval");
+
+        for _ in 0..3 {
+            clock.advance(Duration::from_secs(10));
+            assert_eq!(scheduler.tick(), 1);
+        }
+
+        assert_eq!(count.load(Ordering::SeqCst), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_scheduler_delay_runs_once() -> Result<()> {
+        let (clock, scheduler) = test_scheduler();
+        let (count, action) = counter();
+        let id = scheduler.schedule(
+            Trigger::delay(Duration::from_secs(5)),
+            JobOptions::new(),
+            action,
+        )?;
+
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(scheduler.tick(), 1);
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(scheduler.tick(), 0);
+
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+        assert!(!scheduler.contains(id), "One-shot jobs should be removed");
+        Ok(())
+    }
+
+    #[test]
+    fn test_scheduler_cron_with_seconds() -> Result<()> {
+        let (clock, scheduler) = test_scheduler();
+        let (count, action) = counter();
+        let id = scheduler.schedule(Trigger::cron("*/15 * * * * *")?, JobOptions::new(), action)?;
+
+        assert_eq!(
+            scheduler.next_run(id),
+            Some(DateTime::UNIX_EPOCH + TimeDelta::seconds(15))
+        );
+
+        for _ in 0..4 {
+            clock.advance(Duration::from_secs(15));
+            scheduler.tick();
+        }
+
+        assert_eq!(count.load(Ordering::SeqCst), 4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_scheduler_cron_in_time_zone() -> Result<()> {
+        let (_clock, scheduler) = test_scheduler();
+        let tz = FixedOffset::east_opt(2 * 3600).unwrap();
+        let id = scheduler.schedule(
+            Trigger::cron_in("0 0 9 * * *", tz)?,
+            JobOptions::new(),
+            || Ok(()),
+        )?;
+
+        assert_eq!(
+            scheduler.next_run(id),
+            Some(Utc.with_ymd_and_hms(1970, 1, 1, 7, 0, 0).unwrap()),
+            "09:00 at UTC+2 is 07:00 UTC"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_scheduler_invalid_cron() {
+        assert!(matches!(Trigger::cron("not a cron"), Err(Error::Parse(_))));
+    }
+
+    #[test]
+    fn test_scheduler_missed_runs() -> Result<()> {
+        let (clock, scheduler) = test_scheduler();
+        let (skipped, skip_action) = counter();
+        let (caught_up, catch_up_action) = counter();
+        let every = Trigger::interval(Duration::from_secs(1));
+        let skip = scheduler.schedule(every.clone(), JobOptions::new(), skip_action)?;
+        let catch_up = scheduler.schedule(
+            every,
+            JobOptions::new().with_missed_runs(MissedRunPolicy::CatchUp),
+            catch_up_action,
+        )?;
+
+        clock.advance(Duration::from_millis(5500));
+        scheduler.tick();
+
+        assert_eq!(skipped.load(Ordering::SeqCst), 1);
+        assert_eq!(caught_up.load(Ordering::SeqCst), 5);
+
+        let next = Some(DateTime::UNIX_EPOCH + TimeDelta::seconds(6));
+        assert_eq!(scheduler.next_run(skip), next, "Skip should stay aligned");
+        assert_eq!(scheduler.next_run(catch_up), next);
+        Ok(())
+    }
+
+    #[test]
+    fn test_scheduler_pause_resume() -> Result<()> {
+        let (clock, scheduler) = test_scheduler();
+        let (count, action) = counter();
+        let id = scheduler.schedule(
+            Trigger::interval(Duration::from_secs(1)),
+            JobOptions::new(),
+            action,
+        )?;
+
+        assert!(scheduler.pause(id));
+        assert_eq!(scheduler.is_paused(id), Some(true));
+        clock.advance(Duration::from_secs(3));
+        assert_eq!(scheduler.tick(), 0, "Paused jobs should not run");
+
+        assert!(scheduler.resume(id));
+        assert_eq!(scheduler.tick(), 1, "Missed runs should be skipped");
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(scheduler.tick(), 1);
+
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+        assert!(scheduler.unschedule(id));
+        assert!(!scheduler.pause(id));
+        Ok(())
+    }
+
+    #[test]
+    fn test_scheduler_jitter() -> Result<()> {
+        let (clock, scheduler) = test_scheduler();
+        scheduler.set_seed(42);
+        let jitter = Duration::from_secs(2);
+        let id = scheduler.schedule(
+            Trigger::interval(Duration::from_secs(10)),
+            JobOptions::new().with_jitter(jitter),
+            || Ok(()),
+        )?;
+
+        for i in 1..=20 {
+            let base = DateTime::UNIX_EPOCH + TimeDelta::seconds(10 * i);
+            let due = scheduler.next_run(id).unwrap();
+            assert!(due >= base && due <= base + TimeDelta::seconds(2));
+
+            clock.set(due);
+            assert_eq!(scheduler.tick(), 1);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_scheduler_error_removes_job() -> Result<()> {
+        let (clock, scheduler) = test_scheduler();
+        let id = scheduler.schedule(
+            Trigger::interval(Duration::from_secs(1)),
+            JobOptions::new(),
+            || Err(Error::Other("failed".to_string())),
+        )?;
+
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(scheduler.tick(), 1);
+        assert!(!scheduler.contains(id));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_scheduler_enqueue_into_consumer() -> Result<()> {
+        let (clock, scheduler) = test_scheduler();
+        let handler = CountingHandler {
+            items: Arc::new(AtomicUsize::new(0)),
+        };
+        let consumer = Consumer::<usize>::new();
+        consumer.start(&handler)?;
+        let id = scheduler.schedule_enqueue(
+            Trigger::interval(Duration::from_secs(1)),
+            JobOptions::new(),
+            &consumer,
+            7,
+        )?;
+
+        for _ in 0..3 {
+            clock.advance(Duration::from_secs(1));
+            scheduler.tick();
+        }
+
+        consumer.complete();
+        consumer.wait_async().await?;
+        assert_eq!(handler.items.load(Ordering::SeqCst), 21);
+
+        clock.advance(Duration::from_secs(1));
+        scheduler.tick();
+        assert!(
+            !scheduler.contains(id),
+            "Job should be removed once the queue is completed"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_scheduler_runner() -> Result<()> {
+        let scheduler = Scheduler::new();
+        let (count, action) = counter();
+        scheduler.schedule(
+            Trigger::interval(Duration::from_millis(20)),
+            JobOptions::new(),
+            action,
+        )?;
+
+        scheduler.start()?;
+        assert!(scheduler.start().is_err(), "Should not start twice");
+        thread::sleep(Duration::from_millis(250));
+        scheduler.stop();
+        let runs = count.load(Ordering::SeqCst);
+        assert!(runs >= 3, "Runner should run the job, ran {} times", runs);
+
+        thread::sleep(Duration::from_millis(100));
+        assert!(count.load(Ordering::SeqCst) <= runs + 1);
+        Ok(())
+    }
+}
@@ -0,0 +1,98 @@
+#[cfg(test)]
+mod tests {
+    use emixcore::Error;
+    use emixthreading::SemaphoreSlim;
+    use std::{
+        sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+        },
+        thread,
+        time::Duration,
+    };
+
+    #[test]
+    fn test_semaphore_try_wait_release() {
+        let sem = SemaphoreSlim::with_max(2, 2);
+        assert!(sem.try_wait().unwrap());
+        assert!(sem.try_wait().unwrap());
+        assert!(!sem.try_wait().unwrap());
+        assert_eq!(sem.release().unwrap(), 0);
+        assert_eq!(sem.current_count().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_semaphore_release_exceeds_max() {
+        let sem = SemaphoreSlim::with_max(1, 2);
+        assert!(matches!(sem.release_n(2), Err(Error::Exceeded(_))));
+        assert_eq!(sem.current_count().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_semaphore_wait_timeout() {
+        let sem = SemaphoreSlim::new(0);
+        assert!(!sem.wait_timeout(Duration::from_millis(20)).unwrap());
+
+        let releaser = sem.clone();
+        let handle = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            releaser.release().unwrap();
+        });
+
+        assert!(sem.wait_timeout(Duration::from_secs(5)).unwrap());
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_semaphore_limits_concurrency() {
+        let sem = SemaphoreSlim::new(2);
+        let active = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let sem = sem.clone();
+                let active = active.clone();
+                let peak = peak.clone();
+                thread::spawn(move || {
+                    sem.wait().unwrap();
+                    let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    thread::sleep(Duration::from_millis(10));
+                    active.fetch_sub(1, Ordering::SeqCst);
+                    sem.release().unwrap();
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert!(peak.load(Ordering::SeqCst) <= 2);
+        assert_eq!(sem.current_count().unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_semaphore_async() {
+        let sem = SemaphoreSlim::new(1);
+        sem.wait_async().await.unwrap();
+        assert!(
+            !sem.wait_timeout_async(Duration::from_millis(20))
+                .await
+                .unwrap()
+        );
+
+        let waiter = {
+            let sem = sem.clone();
+            tokio::spawn(async move { sem.wait_async().await })
+        };
+
+        sem.release().unwrap();
+        waiter.await.unwrap().unwrap();
+        assert_eq!(
+            sem.current_count().unwrap(),
+            0,
+            "A timed out wait must not take a slot"
+        );
+    }
+}
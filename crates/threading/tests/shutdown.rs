@@ -0,0 +1,253 @@
+#[cfg(test)]
+mod tests {
+    use emixcore::{Error, Result};
+    use emixthreading::{
+        CancelReason, Scheduler, ShutdownCoordinator, ShutdownHook, ShutdownOptions,
+        TaskDelegation, TaskResult,
+        consumer::{ProducerConsumer, ProducerConsumerOptions},
+    };
+    use std::{
+        sync::{
+            Arc,
+            atomic::{AtomicBool, AtomicUsize, Ordering},
+        },
+        thread,
+        time::Duration,
+    };
+
+    #[derive(Clone, Debug, Default)]
+    struct CountingHandler {
+        done: Arc<AtomicUsize>,
+    }
+
+    impl TaskDelegation<ProducerConsumer<usize>, usize> for CountingHandler {
+        fn on_started(&self, _pc: &ProducerConsumer<usize>) {}
+
+        fn process(&self, _pc: &ProducerConsumer<usize>, _item: &usize) -> Result<TaskResult> {
+            thread::sleep(Duration::from_millis(5));
+            Ok(TaskResult::Success)
+        }
+
+        fn on_completed(
+            &self,
+            _pc: &ProducerConsumer<usize>,
+            _item: &usize,
+            _result: &TaskResult,
+        ) -> bool {
+            self.done.fetch_add(1, Ordering::SeqCst);
+            true
+        }
+
+        fn on_cancelled(&self, _pc: &ProducerConsumer<usize>) {}
+
+        fn on_finished(&self, _pc: &ProducerConsumer<usize>) {}
+    }
+
+    fn options() -> ShutdownOptions {
+        ShutdownOptions::new()
+            .with_deadline(Duration::from_millis(300))
+            .with_cancel_timeout(Duration::from_millis(100))
+            .with_poll_interval(Duration::from_millis(10))
+    }
+
+    #[test]
+    fn test_drains_registered_components() -> Result<()> {
+        let handler = CountingHandler::default();
+        let queue = ProducerConsumer::<usize>::with_options(
+            ProducerConsumerOptions::new().with_capacity(20),
+        );
+        queue.start(&handler)?;
+
+        for i in 0..20 {
+            queue.enqueue(i)?;
+        }
+
+        let scheduler = Scheduler::new();
+        scheduler.start()?;
+
+        let coordinator = ShutdownCoordinator::with_options(options());
+        coordinator.register("scheduler", scheduler.clone())?;
+        coordinator.register("queue", queue.clone())?;
+        let report = coordinator.shutdown()?;
+
+        assert!(report.is_clean());
+        assert_eq!(report.completed, vec!["scheduler", "queue"]);
+        assert_eq!(handler.done.load(Ordering::SeqCst), 20);
+        assert!(!scheduler.is_running());
+        assert_eq!(coordinator.shutdown()?, report);
+        Ok(())
+    }
+
+    #[test]
+    fn test_reports_components_that_miss_the_deadline() -> Result<()> {
+        let stopped = Arc::new(AtomicBool::new(false));
+        let coordinator = ShutdownCoordinator::with_options(options());
+        coordinator.register("fast", ShutdownHook::new(|| {}, || true))?;
+        coordinator.register(
+            "slow",
+            ShutdownHook::new(|| {}, {
+                let stopped = stopped.clone();
+                move || stopped.load(Ordering::SeqCst)
+            })
+            .with_cancel({
+                let stopped = stopped.clone();
+                move || stopped.store(true, Ordering::SeqCst)
+            }),
+        )?;
+        coordinator.register("stuck", ShutdownHook::new(|| {}, || false))?;
+        let report = coordinator.shutdown()?;
+
+        assert!(!report.is_clean());
+        assert_eq!(report.completed, vec!["fast"]);
+        assert_eq!(report.cancelled, vec!["slow"]);
+        assert_eq!(report.abandoned, vec!["stuck"]);
+        assert_eq!(
+            report.timed_out().collect::<Vec<_>>(),
+            vec!["slow", "stuck"]
+        );
+        assert!(report.elapsed >= Duration::from_millis(300));
+        Ok(())
+    }
+
+    #[test]
+    fn test_drains_components_one_at_a_time() -> Result<()> {
+        let producer_done = Arc::new(AtomicBool::new(false));
+        let saw_producer_done = Arc::new(AtomicBool::new(false));
+        let coordinator = ShutdownCoordinator::with_options(options());
+        coordinator.register(
+            "producer",
+            ShutdownHook::new(
+                {
+                    let producer_done = producer_done.clone();
+                    move || {
+                        let producer_done = producer_done.clone();
+                        thread::spawn(move || {
+                            thread::sleep(Duration::from_millis(50));
+                            producer_done.store(true, Ordering::SeqCst);
+                        });
+                    }
+                },
+                {
+                    let producer_done = producer_done.clone();
+                    move || producer_done.load(Ordering::SeqCst)
+                },
+            ),
+        )?;
+        coordinator.register(
+            "consumer",
+            ShutdownHook::new(
+                {
+                    let producer_done = producer_done.clone();
+                    let saw_producer_done = saw_producer_done.clone();
+                    move || {
+                        saw_producer_done
+                            .store(producer_done.load(Ordering::SeqCst), Ordering::SeqCst)
+                    }
+                },
+                || true,
+            ),
+        )?;
+        let report = coordinator.shutdown()?;
+
+        assert!(report.is_clean());
+        assert!(
+            saw_producer_done.load(Ordering::SeqCst),
+            "The consumer is drained only after the producer finished"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_rejects_duplicate_names() -> Result<()> {
+        let coordinator = ShutdownCoordinator::new();
+        coordinator.register("queue", ShutdownHook::new(|| {}, || true))?;
+
+        assert!(matches!(
+            coordinator.register("queue", ShutdownHook::new(|| {}, || true)),
+            Err(Error::Argument(_))
+        ));
+        assert_eq!(coordinator.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_wait_returns_after_request() -> Result<()> {
+        let coordinator = ShutdownCoordinator::with_options(options());
+        let token = coordinator.token();
+        let requester = coordinator.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            requester.request();
+        });
+
+        let report = coordinator.wait()?;
+        assert!(report.is_clean());
+        assert!(coordinator.is_shutting_down());
+        assert_eq!(token.reason(), Some(CancelReason::Shutdown));
+        assert!(matches!(
+            coordinator.register("late", ShutdownHook::new(|| {}, || true)),
+            Err(Error::InvalidOperation(_))
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_async() -> Result<()> {
+        let drained = Arc::new(AtomicBool::new(false));
+        let coordinator = ShutdownCoordinator::with_options(options());
+        coordinator.register(
+            "server",
+            ShutdownHook::new(
+                {
+                    let drained = drained.clone();
+                    move || drained.store(true, Ordering::SeqCst)
+                },
+                {
+                    let drained = drained.clone();
+                    move || drained.load(Ordering::SeqCst)
+                },
+            ),
+        )?;
+
+        let (first, second) = tokio::join!(coordinator.shutdown_async(), coordinator.wait_async());
+        assert_eq!(first?, second?);
+        assert!(drained.load(Ordering::SeqCst));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_listen_returns_once_shutdown_starts() -> Result<()> {
+        let coordinator = ShutdownCoordinator::with_options(options());
+        coordinator.listen()?;
+        let listener = coordinator.clone();
+        let listening = tokio::spawn(async move { listener.listen_async().await });
+
+        coordinator.request();
+        tokio::time::timeout(Duration::from_secs(5), listening)
+            .await
+            .map_err(|_| Error::Timeout)?
+            .map_err(Error::from_std_error)??;
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_listens_for_sigterm() -> Result<()> {
+        let coordinator = ShutdownCoordinator::with_options(options());
+        let listener = coordinator.clone();
+        let listening = tokio::spawn(async move { listener.run_until_signal_async().await });
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        std::process::Command::new("kill")
+            .args(["-TERM", &std::process::id().to_string()])
+            .status()?;
+
+        let report = tokio::time::timeout(Duration::from_secs(5), listening)
+            .await
+            .map_err(|_| Error::Timeout)?
+            .map_err(Error::from_std_error)??;
+        assert!(report.is_clean());
+        assert!(coordinator.is_shutting_down());
+        Ok(())
+    }
+}
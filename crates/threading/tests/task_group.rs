@@ -0,0 +1,221 @@
+#[cfg(test)]
+mod tests {
+    use emixcore::{Error, Result};
+    use emixthreading::{CancelReason, TaskGroup, TaskGroupMode};
+    use std::{
+        sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+        },
+        thread,
+        time::Duration,
+    };
+
+    fn wait_until_cancelled(token: &emixthreading::CancellationToken) -> Result<()> {
+        if token.wait_timeout(Duration::from_secs(5))? {
+            Err(Error::Canceled)
+        } else {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_task_group_success() -> Result<()> {
+        let group = TaskGroup::new();
+        let count = Arc::new(AtomicUsize::new(0));
+
+        for _ in 0..8 {
+            let count = count.clone();
+            group.spawn(move |_| {
+                count.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            })?;
+        }
+
+        group.wait()?;
+        assert_eq!(count.load(Ordering::SeqCst), 8);
+        assert!(group.is_finished());
+        assert!(!group.is_cancelled());
+        Ok(())
+    }
+
+    #[test]
+    fn test_task_group_fail_fast_cancels_siblings() -> Result<()> {
+        let group = TaskGroup::with_mode(TaskGroupMode::FailFast);
+        let cancelled = Arc::new(AtomicUsize::new(0));
+
+        for _ in 0..3 {
+            let cancelled = cancelled.clone();
+            group.spawn(move |token| {
+                let result = wait_until_cancelled(&token);
+                if result.is_err() {
+                    cancelled.fetch_add(1, Ordering::SeqCst);
+                }
+                result
+            })?;
+        }
+
+        group.spawn(|_| Err(Error::InvalidInput("bad item".to_string())))?;
+
+        let result = group.wait();
+        assert!(
+            matches!(result, Err(Error::InvalidInput(ref message)) if message == "bad item"),
+            "First error should propagate as it was returned"
+        );
+        assert!(
+            matches!(group.wait(), Err(Error::Other(message)) if message.contains("bad item")),
+            "Waiting again should report its message"
+        );
+        assert_eq!(
+            cancelled.load(Ordering::SeqCst),
+            3,
+            "Siblings should be cancelled"
+        );
+        assert!(matches!(
+            group.token().reason(),
+            Some(CancelReason::Failed(_))
+        ));
+        assert!(
+            group.spawn(|_| Ok(())).is_err(),
+            "Cancelled group rejects jobs"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_task_group_collect_all() -> Result<()> {
+        let group = TaskGroup::with_mode(TaskGroupMode::CollectAll);
+        let finished = Arc::new(AtomicUsize::new(0));
+
+        for i in 0..6 {
+            let finished = finished.clone();
+            group.spawn(move |_| {
+                thread::sleep(Duration::from_millis(10));
+                finished.fetch_add(1, Ordering::SeqCst);
+                if i % 2 == 0 {
+                    Err(Error::Other(format!("job {}", i)))
+                } else {
+                    Ok(())
+                }
+            })?;
+        }
+
+        assert!(matches!(group.wait(), Err(Error::Other(_))));
+        assert_eq!(finished.load(Ordering::SeqCst), 6, "Every job should run");
+        assert!(!group.is_cancelled(), "CollectAll should not cancel");
+        assert!(
+            matches!(group.wait(), Err(Error::Other(_))),
+            "Waiting again should still report the failure"
+        );
+        assert_eq!(
+            group.take_errors().len(),
+            2,
+            "Every error but the one wait returned is kept"
+        );
+        assert!(group.wait().is_ok(), "Taken errors are no longer reported");
+        Ok(())
+    }
+
+    #[test]
+    fn test_task_group_keeps_first_error_variant() -> Result<()> {
+        let group = TaskGroup::with_mode(TaskGroupMode::CollectAll);
+        group.spawn(|_| Err(Error::Timeout))?;
+
+        assert!(matches!(group.wait(), Err(Error::Timeout)));
+        assert!(matches!(
+            group.wait_for(Duration::from_secs(5)),
+            Err(Error::Other(message)) if message == Error::Timeout.to_string()
+        ));
+        assert!(group.take_errors().is_empty());
+        assert!(group.wait().is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn test_task_group_take_errors_while_failing() -> Result<()> {
+        let group = TaskGroup::with_mode(TaskGroupMode::CollectAll);
+
+        for i in 0..32 {
+            group.spawn(move |_| Err(Error::Other(format!("job {}", i))))?;
+        }
+
+        // Taking the errors while jobs record theirs must not stop the group finishing.
+        while !group.is_finished() {
+            group.take_errors();
+        }
+
+        assert!(!matches!(
+            group.wait_for(Duration::from_secs(5)),
+            Err(Error::Timeout)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_task_group_panic_is_error() {
+        let group = TaskGroup::new();
+        group.spawn(|_| panic!("boom")).unwrap();
+        assert!(matches!(group.wait(), Err(Error::Other(_))));
+    }
+
+    #[test]
+    fn test_task_group_wait_for_timeout() -> Result<()> {
+        let group = TaskGroup::new();
+        group.spawn(|token| {
+            let _ = wait_until_cancelled(&token);
+            Ok(())
+        })?;
+
+        assert!(matches!(
+            group.wait_for(Duration::from_millis(20)),
+            Err(Error::Timeout)
+        ));
+
+        group.cancel();
+        assert!(matches!(
+            group.wait_for(Duration::from_secs(5)),
+            Err(Error::Canceled)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_task_group_parent_token() -> Result<()> {
+        let parent = emixthreading::CancellationToken::new();
+        let group = TaskGroup::with_parent(TaskGroupMode::CollectAll, &parent);
+        group.spawn(|token| wait_until_cancelled(&token))?;
+
+        parent.cancel_with(CancelReason::Shutdown);
+        assert!(group.wait().is_err());
+        assert_eq!(group.token().reason(), Some(CancelReason::Shutdown));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_task_group_async() -> Result<()> {
+        let group = TaskGroup::new();
+        let count = Arc::new(AtomicUsize::new(0));
+
+        for _ in 0..4 {
+            let count = count.clone();
+            group.spawn_async(|_| async move {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                count.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            })?;
+        }
+
+        group.spawn_async(|token| async move {
+            token.cancelled().await;
+            Err(Error::Canceled)
+        })?;
+        group.spawn_async(|_| async { Err(Error::NotSupported) })?;
+
+        let result = group.wait_for_async(Duration::from_secs(5)).await;
+        assert!(
+            matches!(result, Err(Error::NotSupported)),
+            "First error should propagate"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,130 @@
+#[cfg(test)]
+mod tests {
+    use emixcore::{Error, Result};
+    use emixthreading::{
+        Submission, SubmitHandler, SubmitQueue,
+        consumer::{
+            Consumer, InjectorWorker, InjectorWorkerOptions, ProducerConsumer,
+            ProducerConsumerOptions,
+        },
+    };
+    use std::{
+        sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+        },
+        thread,
+        time::Duration,
+    };
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    fn square() -> SubmitHandler<usize, usize> {
+        SubmitHandler::new(|n: &usize| {
+            if *n == 0 {
+                return Err(Error::InvalidInput("Zero is not allowed".to_string()));
+            }
+
+            Ok(n * n)
+        })
+    }
+
+    #[test]
+    fn test_submit_returns_results() -> Result<()> {
+        let queue = ProducerConsumer::<Submission<usize, usize>>::with_options(
+            ProducerConsumerOptions::new().with_threads(4),
+        );
+        queue.start(&square())?;
+        let handles = (1..=20)
+            .map(|n| queue.submit(n))
+            .collect::<Result<Vec<_>>>()?;
+
+        for (n, handle) in (1..=20).zip(&handles) {
+            assert_eq!(handle.wait_for(TIMEOUT)?, n * n);
+        }
+
+        assert!(matches!(handles[0].wait(), Err(Error::InvalidOperation(_))));
+        queue.complete();
+        queue.wait_for(TIMEOUT)
+    }
+
+    #[tokio::test]
+    async fn test_handles_can_be_awaited() -> Result<()> {
+        let queue = InjectorWorker::<Submission<usize, usize>>::with_options(
+            InjectorWorkerOptions::new().with_threads(2),
+        );
+        queue.start(&square())?;
+        let value = queue.submit(7)?.await?;
+        assert_eq!(value, 49);
+        assert!(matches!(
+            queue.submit(0)?.wait_for_async(TIMEOUT).await,
+            Err(Error::InvalidInput(_))
+        ));
+        queue.complete();
+        queue.wait_for_async(TIMEOUT).await
+    }
+
+    #[test]
+    fn test_wait_for_times_out() -> Result<()> {
+        let queue = Consumer::<Submission<u64, u64>>::new();
+        queue.start(&SubmitHandler::new(|ms: &u64| {
+            thread::sleep(Duration::from_millis(*ms));
+            Ok(*ms)
+        }))?;
+        let handle = queue.submit(300)?;
+
+        assert!(matches!(
+            handle.wait_for(Duration::from_millis(20)),
+            Err(Error::Timeout)
+        ));
+        assert_eq!(handle.wait_for(TIMEOUT)?, 300);
+        queue.complete();
+        queue.wait_for(TIMEOUT)
+    }
+
+    #[test]
+    fn test_cancelled_handle_is_skipped() -> Result<()> {
+        let processed = Arc::new(AtomicUsize::new(0));
+        let counter = processed.clone();
+        let queue = ProducerConsumer::<Submission<usize, usize>>::with_options(
+            ProducerConsumerOptions::new().with_capacity(4),
+        );
+        let first = queue.submit(1)?;
+        let second = queue.submit(2)?;
+
+        assert!(second.cancel());
+        assert!(second.is_cancelled());
+        queue.start(&SubmitHandler::new(move |n: &usize| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Ok(*n)
+        }))?;
+
+        assert_eq!(first.wait_for(TIMEOUT)?, 1);
+        assert!(matches!(second.wait_for(TIMEOUT), Err(Error::Canceled)));
+        assert!(!first.cancel(), "A finished task cannot be cancelled");
+        queue.complete();
+        queue.wait_for(TIMEOUT)?;
+        assert_eq!(processed.load(Ordering::SeqCst), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_cancelling_the_queue_resolves_pending_handles() -> Result<()> {
+        let queue = ProducerConsumer::<Submission<u64, u64>>::with_options(
+            ProducerConsumerOptions::new().with_capacity(4),
+        );
+        queue.start(&SubmitHandler::new(|ms: &u64| {
+            thread::sleep(Duration::from_millis(*ms));
+            Ok(*ms)
+        }))?;
+        let running = queue.submit(100)?;
+        let pending = queue.submit(100)?;
+        thread::sleep(Duration::from_millis(20));
+        queue.cancel();
+
+        assert_eq!(running.wait_for(TIMEOUT)?, 100);
+        assert!(matches!(pending.wait_for(TIMEOUT), Err(Error::Canceled)));
+        assert!(matches!(queue.submit(1), Err(Error::Canceled)));
+        Ok(())
+    }
+}
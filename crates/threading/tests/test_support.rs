@@ -0,0 +1,353 @@
+#[cfg(test)]
+mod tests {
+    use emixcore::{Error, Result};
+    use emixthreading::{
+        TaskDelegation, TaskQueue, TaskResult,
+        consumer::{
+            Consumer, ConsumerOptions, InjectorWorker, ProducerConsumer, ProducerConsumerOptions,
+        },
+        test_support::{
+            Callback, CallbackRecorder, FnHandler, InlineConsumer, InlineDriver, Step,
+            VirtualClock, eventually,
+        },
+    };
+    use std::{thread, time::Duration};
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+    const THRESHOLD: Duration = Duration::from_secs(60);
+
+    fn handler() -> FnHandler<usize> {
+        FnHandler::new(|n: &usize| {
+            if n.is_multiple_of(3) {
+                return Err(Error::InvalidInput(format!("Item {}", n)));
+            }
+
+            Ok(TaskResult::Success)
+        })
+    }
+
+    fn processed(recorder: &CallbackRecorder<FnHandler<usize>, usize>) -> usize {
+        recorder
+            .events()
+            .iter()
+            .filter(|it| matches!(it, Callback::Completed(..)))
+            .count()
+    }
+
+    #[test]
+    fn test_records_callbacks_in_order() -> Result<()> {
+        let recorder = CallbackRecorder::new(handler());
+        let consumer = Consumer::<usize>::with_options(ConsumerOptions::new().with_threads(1));
+        consumer.enqueue(1)?;
+        consumer.enqueue(3)?;
+        consumer.start(&recorder)?;
+        consumer.complete();
+        consumer.wait_for(TIMEOUT)?;
+
+        recorder.assert_events(&[
+            Callback::Started,
+            Callback::Process(1),
+            Callback::Completed(1, TaskResult::Success),
+            Callback::Process(3),
+            Callback::Completed(3, TaskResult::Error("Invalid input. Item 3".to_string())),
+            Callback::Finished,
+        ]);
+        recorder.assert_well_formed();
+        Ok(())
+    }
+
+    fn expected_inline_events(finished: Callback<usize>) -> Vec<Callback<usize>> {
+        vec![
+            Callback::Started,
+            Callback::Process(1),
+            Callback::Completed(1, TaskResult::Success),
+            Callback::Process(3),
+            Callback::Completed(3, TaskResult::Error("Invalid input. Item 3".to_string())),
+            Callback::Process(4),
+            Callback::Completed(4, TaskResult::Success),
+            finished,
+        ]
+    }
+
+    fn assert_inline_order<C: InlineConsumer<usize> + TaskQueue<usize>>(
+        consumer: C,
+        complete: impl Fn(&C),
+    ) -> Result<()> {
+        let recorder = CallbackRecorder::new(handler());
+        let driver = InlineDriver::start(&consumer, &recorder)?;
+        assert_eq!(driver.step(), Step::Idle);
+
+        for n in [1, 3, 4] {
+            consumer.enqueue(n)?;
+        }
+
+        assert_eq!(driver.step(), Step::Processed);
+        complete(&consumer);
+        driver.run()?;
+
+        assert_eq!(driver.step(), Step::Finished);
+        assert!(consumer.is_finished());
+        recorder.assert_events(&expected_inline_events(Callback::Finished));
+        Ok(())
+    }
+
+    #[test]
+    fn test_inline_driver_runs_callbacks_in_order() -> Result<()> {
+        assert_inline_order(Consumer::<usize>::new(), Consumer::complete)?;
+        assert_inline_order(
+            ProducerConsumer::<usize>::with_options(
+                ProducerConsumerOptions::new().with_capacity(3),
+            ),
+            ProducerConsumer::complete,
+        )?;
+        assert_inline_order(InjectorWorker::<usize>::new(), InjectorWorker::complete)
+    }
+
+    #[test]
+    fn test_inline_driver_skips_threshold() -> Result<()> {
+        let clock = VirtualClock::new();
+        let recorder = CallbackRecorder::new(handler());
+        let consumer =
+            Consumer::<usize>::with_options(ConsumerOptions::new().with_threshold(THRESHOLD))
+                .with_clock(&clock);
+        let driver = InlineDriver::start(&consumer, &recorder)?;
+
+        for n in [1, 3, 4] {
+            consumer.enqueue(n)?;
+        }
+
+        assert_eq!(driver.run_until_idle(), 3);
+        assert_eq!(clock.elapsed(), Duration::ZERO);
+        Ok(())
+    }
+
+    #[test]
+    fn test_inline_driver_pause() -> Result<()> {
+        let recorder = CallbackRecorder::new(handler());
+        let consumer = Consumer::<usize>::new();
+        let driver = InlineDriver::start(&consumer, &recorder)?;
+        consumer.enqueue(1)?;
+        consumer.pause();
+
+        assert_eq!(driver.step(), Step::Paused);
+        assert_eq!(driver.run_until_idle(), 0);
+        assert_eq!(consumer.len(), 1);
+
+        consumer.resume();
+        assert_eq!(driver.run_until_idle(), 1);
+        assert_eq!(driver.step(), Step::Idle);
+        Ok(())
+    }
+
+    #[test]
+    fn test_inline_driver_cancel() -> Result<()> {
+        let recorder = CallbackRecorder::new(handler());
+        let consumer = Consumer::<usize>::new();
+        let driver = InlineDriver::start(&consumer, &recorder)?;
+
+        for n in [1, 3, 4, 5] {
+            consumer.enqueue(n)?;
+        }
+
+        assert_eq!(driver.step(), Step::Processed);
+        assert_eq!(driver.step(), Step::Processed);
+        assert_eq!(driver.step(), Step::Processed);
+        consumer.cancel();
+
+        assert_eq!(driver.step(), Step::Finished);
+        assert!(matches!(driver.run(), Err(Error::Canceled)));
+        assert!(consumer.wait_for(TIMEOUT).is_err());
+        recorder.assert_events(&expected_inline_events(Callback::Cancelled));
+        Ok(())
+    }
+
+    #[test]
+    fn test_inline_driver_run_needs_completion() -> Result<()> {
+        let recorder = CallbackRecorder::new(handler());
+        let consumer = Consumer::<usize>::new();
+        let driver = InlineDriver::start(&consumer, &recorder)?;
+        consumer.enqueue(1)?;
+
+        assert!(matches!(driver.run(), Err(Error::InvalidOperation(_))));
+        assert_eq!(processed(&recorder), 1);
+        assert!(!consumer.is_finished());
+
+        consumer.complete();
+        driver.run()?;
+        recorder.assert_well_formed();
+        Ok(())
+    }
+
+    #[test]
+    fn test_inline_driver_handler_stops_worker() -> Result<()> {
+        let recorder = CallbackRecorder::new(handler());
+        let consumer = Consumer::<usize>::new();
+        let driver = InlineDriver::start(&consumer, &StopAfterOne(recorder.clone()))?;
+        consumer.enqueue(1)?;
+        consumer.enqueue(2)?;
+
+        assert_eq!(driver.step(), Step::Stopped);
+        assert_eq!(driver.step(), Step::Stopped);
+        assert!(!consumer.is_finished());
+        assert_eq!(consumer.len(), 1);
+        assert_eq!(processed(&recorder), 1);
+        Ok(())
+    }
+
+    /// Stops the worker after its first item.
+    #[derive(Clone, Debug)]
+    struct StopAfterOne(CallbackRecorder<FnHandler<usize>, usize>);
+
+    impl TaskDelegation<Consumer<usize>, usize> for StopAfterOne {
+        fn on_started(&self, pc: &Consumer<usize>) {
+            self.0.on_started(pc);
+        }
+
+        fn process(&self, pc: &Consumer<usize>, item: &usize) -> Result<TaskResult> {
+            self.0.process(pc, item)
+        }
+
+        fn on_completed(&self, pc: &Consumer<usize>, item: &usize, result: &TaskResult) -> bool {
+            self.0.on_completed(pc, item, result);
+            false
+        }
+
+        fn on_cancelled(&self, pc: &Consumer<usize>) {
+            self.0.on_cancelled(pc);
+        }
+
+        fn on_finished(&self, pc: &Consumer<usize>) {
+            self.0.on_finished(pc);
+        }
+    }
+
+    #[test]
+    fn test_virtual_clock_sleep() {
+        let clock = VirtualClock::new();
+        let sleeper = clock.clone();
+        let handle = thread::spawn(move || sleeper.sleep(Duration::from_secs(10)));
+
+        assert!(clock.wait_for_sleepers(1, TIMEOUT));
+        clock.advance(Duration::from_secs(4));
+        assert_eq!(clock.elapsed(), Duration::from_secs(4));
+        assert!(!handle.is_finished());
+
+        clock.advance(Duration::from_secs(6));
+        handle.join().unwrap();
+        assert_eq!(clock.sleepers(), 0);
+    }
+
+    #[test]
+    fn test_threshold_waits_for_virtual_clock() -> Result<()> {
+        let clock = VirtualClock::new();
+        let recorder = CallbackRecorder::new(handler());
+        let consumer = Consumer::<usize>::with_options(
+            ConsumerOptions::new()
+                .with_threads(1)
+                .with_threshold(THRESHOLD),
+        )
+        .with_clock(&clock);
+
+        for n in [1, 2, 4] {
+            consumer.enqueue(n)?;
+        }
+
+        consumer.start(&recorder)?;
+
+        for n in 1..=3 {
+            assert!(eventually(TIMEOUT, || processed(&recorder) == n));
+            // The worker waits out the threshold on the clock, so nothing else is
+            // processed until the clock moves.
+            assert!(clock.wait_for_sleepers(1, TIMEOUT));
+            assert_eq!(processed(&recorder), n);
+            clock.advance(THRESHOLD);
+        }
+
+        consumer.complete();
+        consumer.wait_for(TIMEOUT)?;
+        assert_eq!(clock.elapsed(), THRESHOLD * 3);
+        recorder.assert_well_formed();
+        Ok(())
+    }
+
+    #[test]
+    fn test_failed_items_skip_threshold() -> Result<()> {
+        let clock = VirtualClock::new();
+        let recorder = CallbackRecorder::new(handler());
+        let consumer = Consumer::<usize>::with_options(
+            ConsumerOptions::new()
+                .with_threads(1)
+                .with_threshold(THRESHOLD),
+        )
+        .with_clock(&clock);
+
+        for n in [3, 6, 9] {
+            consumer.enqueue(n)?;
+        }
+
+        consumer.start(&recorder)?;
+        consumer.complete();
+        consumer.wait_for(TIMEOUT)?;
+        assert_eq!(processed(&recorder), 3);
+        assert_eq!(clock.elapsed(), Duration::ZERO);
+        Ok(())
+    }
+
+    #[test]
+    fn test_cancel_drops_items_and_reports_cancelled() -> Result<()> {
+        let clock = VirtualClock::new();
+        let recorder = CallbackRecorder::new(handler());
+        let consumer = Consumer::<usize>::with_options(
+            ConsumerOptions::new()
+                .with_threads(1)
+                .with_threshold(THRESHOLD),
+        )
+        .with_clock(&clock);
+
+        for n in [1, 2, 4] {
+            consumer.enqueue(n)?;
+        }
+
+        consumer.start(&recorder)?;
+        assert!(clock.wait_for_sleepers(1, TIMEOUT));
+        consumer.cancel();
+        clock.advance(THRESHOLD);
+        assert!(consumer.wait_for(TIMEOUT).is_err());
+
+        assert!(consumer.is_empty());
+        assert_eq!(processed(&recorder), 1);
+        assert_eq!(
+            recorder.events().last(),
+            Some(&Callback::<usize>::Cancelled)
+        );
+        recorder.assert_well_formed();
+        Ok(())
+    }
+
+    #[test]
+    fn test_recorder_checks_threaded_consumers() -> Result<()> {
+        let recorder = CallbackRecorder::new(handler());
+        let consumer = Consumer::<usize>::with_options(ConsumerOptions::new().with_threads(4));
+        consumer.start(&recorder)?;
+
+        for n in 1..=50 {
+            consumer.enqueue(n)?;
+        }
+
+        consumer.complete();
+        consumer.wait_for(TIMEOUT)?;
+        recorder.assert_well_formed();
+        assert_eq!(recorder.events().len(), 102);
+        Ok(())
+    }
+
+    #[test]
+    #[should_panic(expected = "without a pending Process")]
+    fn test_recorder_rejects_completed_without_process() {
+        let recorder = CallbackRecorder::<FnHandler<usize>, usize>::new(handler());
+        let consumer = Consumer::<usize>::new();
+        TaskDelegation::on_started(&recorder, &consumer);
+        TaskDelegation::on_completed(&recorder, &consumer, &7, &TaskResult::Success);
+        recorder.assert_well_formed();
+    }
+}